@@ -0,0 +1,23 @@
+// What the About screen shows: the version and credits from the package
+// metadata, then the changelog. It lives with the model so the About state
+// knows how far it can scroll.
+
+use crate::i18n::tf;
+
+const CHANGELOG: &str = include_str!("../CHANGELOG.md");
+
+// The About screen, top to bottom
+pub fn lines() -> Vec<String> {
+    let mut lines = vec![
+        format!("RustDoku6 {}", env!("CARGO_PKG_VERSION")),
+        env!("CARGO_PKG_DESCRIPTION").to_string(),
+        String::new(),
+        tf("about.author", &[&env!("CARGO_PKG_AUTHORS").replace(':', ", ")]),
+        tf("about.license", &[&env!("CARGO_PKG_LICENSE")]),
+        tf("about.source", &[&env!("CARGO_PKG_REPOSITORY")]),
+        tf("about.built_with", &[&"Rust, Ratatui, Antigravity"]),
+        String::new(),
+    ];
+    lines.extend(CHANGELOG.lines().map(str::to_string));
+    lines
+}
@@ -0,0 +1,155 @@
+// Adaptive difficulty: `--adaptive` picks the puzzle's star rating from how
+// recent games at each rating went. A game counts as a success when it was
+// solved within par with few mistakes. The pick climbs while the player keeps
+// succeeding at a rating and drops back once they fall below the target rate.
+
+use std::{io, time::Duration};
+
+use rand::Rng;
+
+use crate::{
+    endless,
+    model::{Game, MAX_STARS},
+    screen::Screen,
+    storage,
+};
+
+const HISTORY_FILE: &str = "adaptive.csv";
+// Results per rating that count toward its success rate
+const RECENT: usize = 10;
+// Results needed at a rating before moving past it
+const MIN_RESULTS: usize = 3;
+// Success rate to keep the player at
+const TARGET_RATE: f64 = 0.7;
+// A success allows this many mistakes and this much time per star
+const MAX_MISTAKES: u32 = 2;
+const PAR_PER_STAR: Duration = Duration::from_secs(240);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Outcome {
+    pub stars: u8,
+    pub solved: bool,
+    pub time: Duration,
+    pub mistakes: u32,
+}
+
+impl Outcome {
+    pub fn is_success(&self) -> bool {
+        self.solved && self.mistakes <= MAX_MISTAKES && self.time <= PAR_PER_STAR * self.stars as u32
+    }
+
+    // e.g. `2,1,312000,1`: stars, solved, milliseconds, mistakes
+    fn encode(&self) -> String {
+        format!("{},{},{},{}", self.stars, self.solved as u8, self.time.as_millis(), self.mistakes)
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let parts: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [stars, solved, time, mistakes] = parts[..] else {
+            return None;
+        };
+        Some(Self {
+            stars: stars.parse().ok().filter(|s| (1..=MAX_STARS).contains(s))?,
+            solved: solved == "1",
+            time: Duration::from_millis(time.parse().ok()?),
+            mistakes: mistakes.parse().ok()?,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Adaptive {
+    // Oldest first
+    pub history: Vec<Outcome>,
+}
+
+impl Adaptive {
+    pub fn load() -> Self {
+        let history = storage::read(HISTORY_FILE).map_or_else(Vec::new, |text| text.lines().filter_map(Outcome::parse).collect());
+        Self { history }
+    }
+
+    // The most recent results at a rating, newest first
+    fn recent(&self, stars: u8) -> impl Iterator<Item = &Outcome> {
+        self.history.iter().rev().filter(move |outcome| outcome.stars == stars).take(RECENT)
+    }
+
+    // Share of recent games at a rating that were successes, and how many there were
+    pub fn success_rate(&self, stars: u8) -> (f64, usize) {
+        let (successes, count) = self.recent(stars).fold((0, 0), |(s, n), outcome| (s + outcome.is_success() as usize, n + 1));
+        if count == 0 {
+            return (0.0, 0);
+        }
+        (successes as f64 / count as f64, count)
+    }
+
+    // The lowest rating the player hasn't yet shown they can handle
+    pub fn pick_stars(&self) -> u8 {
+        let mut stars = 1;
+        while stars < MAX_STARS {
+            let (rate, count) = self.success_rate(stars);
+            if count < MIN_RESULTS || rate < TARGET_RATE {
+                break;
+            }
+            stars += 1;
+        }
+        stars
+    }
+
+    pub fn new_game<R: Rng + ?Sized>(&self, rng: &mut R) -> Game {
+        let stars = self.pick_stars();
+        log::info!("adaptive difficulty picked {} stars", stars);
+        endless::next_puzzle(stars, rng)
+    }
+
+    // Add how the game went to the history. Games quit before entering
+    // anything aren't counted.
+    pub fn record(&mut self, game: &Game) -> io::Result<Option<Outcome>> {
+        let solved = game.screen() == Screen::Won;
+        let touched = game.mistakes > 0 || game.grid.cells.iter().flatten().any(|cell| !cell.is_fixed() && cell.value().is_some());
+        if !solved && !touched {
+            return Ok(None);
+        }
+        let outcome = Outcome { stars: game.stars(), solved, time: game.timer.elapsed(), mistakes: game.mistakes };
+        self.history.push(outcome);
+        storage::append_line(HISTORY_FILE, &outcome.encode())?;
+        Ok(Some(outcome))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Adaptive, Outcome};
+    use std::time::Duration;
+
+    fn outcome(stars: u8, solved: bool, secs: u64, mistakes: u32) -> Outcome {
+        Outcome { stars, solved, time: Duration::from_secs(secs), mistakes }
+    }
+
+    #[test]
+    fn outcomes_round_trip() {
+        let result = outcome(3, true, 500, 1);
+        assert_eq!(Outcome::parse(&result.encode()), Some(result));
+        assert!(result.is_success());
+        assert!(!outcome(1, true, 500, 0).is_success()); // over par
+        assert!(!outcome(2, false, 60, 0).is_success());
+        assert_eq!(Outcome::parse("9,1,0,0"), None);
+        assert_eq!(Outcome::parse("garbage"), None);
+    }
+
+    #[test]
+    fn picks_climb_with_successes_and_fall_back_after_failures() {
+        let mut adaptive = Adaptive::default();
+        assert_eq!(adaptive.pick_stars(), 1);
+
+        adaptive.history.extend([outcome(1, true, 100, 0); 3]);
+        assert_eq!(adaptive.pick_stars(), 2);
+        adaptive.history.extend([outcome(2, true, 200, 1); 3]);
+        assert_eq!(adaptive.pick_stars(), 3);
+
+        // A run of failures at 2 stars brings the pick back down
+        adaptive.history.extend([outcome(2, false, 900, 4); 3]);
+        assert_eq!(adaptive.success_rate(2), (0.5, 6));
+        assert_eq!(adaptive.pick_stars(), 2);
+    }
+}
@@ -0,0 +1,226 @@
+// Whether the terminal's background is light or dark. The board brings its
+// own colors, but text drawn on the terminal's background is picked for a
+// dark one, so a light terminal gets darker text instead. Config
+// `background` names it, or with "auto" the terminal is asked (OSC 11), with
+// `COLORFGBG` to go by when it doesn't say. A terminal that doesn't answer
+// at all only holds startup up for TIMEOUT, and keys typed while it's asked
+// are handed on to the game.
+
+use std::{
+    io::{self, Read, Write},
+    time::{Duration, Instant},
+};
+
+use crate::events::{Key, KeyCode};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Background {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl Background {
+    // Config `background`: "dark" or "light"; None for "auto"
+    pub fn from_setting(value: &str) -> Option<Self> {
+        match value {
+            "dark" => Some(Background::Dark),
+            "light" => Some(Background::Light),
+            _ => None,
+        }
+    }
+
+    // `COLORFGBG`, "fg;bg" in the 16 ANSI colors, set by some terminals.
+    // White (7) and the bright colors but dark grey (8) are light.
+    pub fn from_colorfgbg(value: &str) -> Option<Self> {
+        let bg: u8 = value.rsplit(';').next()?.parse().ok()?;
+        match bg {
+            7 | 9..=15 => Some(Background::Light),
+            0..=6 | 8 => Some(Background::Dark),
+            _ => None,
+        }
+    }
+
+    // Light past half the relative luminance
+    fn of_rgb(red: f64, green: f64, blue: f64) -> Self {
+        match 0.2126 * red + 0.7152 * green + 0.0722 * blue > 0.5 {
+            true => Background::Light,
+            false => Background::Dark,
+        }
+    }
+}
+
+// Longest reply read before giving up on it
+const MAX_REPLY: usize = 256;
+// Longest wait for the terminal's answers. Terminals answer at once, so a
+// bare few frames; some multiplexers and serial consoles never do.
+pub const TIMEOUT: Duration = Duration::from_millis(150);
+
+// What came back from asking the terminal
+#[derive(Debug, Default, PartialEq)]
+pub struct Probe {
+    pub background: Option<Background>,
+    // Keys typed while it was asked, around its answers
+    pub typed: Vec<Key>,
+}
+
+// Ask the terminal, in raw mode, for its background color. The query is
+// followed by one for the device attributes (DA1), which every terminal
+// answers, so reading stops at that answer whether or not the color came
+// before it, or once `ready`, waiting up to the time it's given for input,
+// finds none before TIMEOUT is up.
+pub fn query(input: &mut impl Read, output: &mut impl Write, mut ready: impl FnMut(Duration) -> io::Result<bool>) -> io::Result<Probe> {
+    output.write_all(b"\x1b]11;?\x1b\\\x1b[c")?;
+    output.flush()?;
+    let deadline = Instant::now() + TIMEOUT;
+    let mut reply = Vec::new();
+    let mut byte = [0u8];
+    while reply.len() < MAX_REPLY && !answered(&reply) {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() || !ready(left)? || input.read(&mut byte)? == 0 {
+            break;
+        }
+        reply.push(byte[0]);
+    }
+    Ok(Probe { background: parse_reply(&reply), typed: typed_keys(&reply) })
+}
+
+// The terminal on the controlling tty, read and written directly so no
+// buffered input is left behind for the backend
+#[cfg(unix)]
+pub fn detect() -> Probe {
+    use rustix::event::{poll, PollFd, PollFlags, Timespec};
+
+    let Ok(mut tty) = std::fs::OpenOptions::new().read(true).write(true).open("/dev/tty") else {
+        return Probe::default();
+    };
+    let Ok(mut output) = tty.try_clone() else {
+        return Probe::default();
+    };
+    let waiting = output.try_clone();
+    let ready = |wait: Duration| {
+        let waiting = waiting.as_ref().map_err(|err| io::Error::new(err.kind(), err.to_string()))?;
+        let timeout = Timespec { tv_sec: wait.as_secs() as _, tv_nsec: wait.subsec_nanos() as _ };
+        Ok(poll(&mut [PollFd::new(waiting, PollFlags::IN)], Some(&timeout))? > 0)
+    };
+    query(&mut tty, &mut output, ready).unwrap_or_else(|err| {
+        log::warn!("background color query failed: {}", err);
+        Probe::default()
+    })
+}
+
+#[cfg(not(unix))]
+pub fn detect() -> Probe {
+    Probe::default()
+}
+
+// Whether the DA1 answer, `ESC [ ? ... c`, has arrived
+fn answered(reply: &[u8]) -> bool {
+    reply.windows(3).position(|w| w == b"\x1b[?").is_some_and(|at| reply[at..].ends_with(b"c"))
+}
+
+// The keys among the bytes read that aren't part of the terminal's answers
+// or other escape sequences: printable characters, Enter and Backspace
+fn typed_keys(reply: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let mut bytes = reply.iter().copied().peekable();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            // OSC, up to BEL or ST
+            0x1b if bytes.next_if_eq(&b']').is_some() => {
+                while let Some(byte) = bytes.next() {
+                    if byte == 0x07 || (byte == 0x1b && bytes.next_if_eq(&b'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            // CSI, such as the DA1 answer or an arrow key, up to its final byte
+            0x1b if bytes.next_if_eq(&b'[').is_some() => while bytes.next().is_some_and(|byte| !(0x40..=0x7e).contains(&byte)) {},
+            0x1b => {
+                bytes.next();
+            }
+            b'\r' | b'\n' => keys.push(Key::new(KeyCode::Enter)),
+            0x7f | 0x08 => keys.push(Key::new(KeyCode::Backspace)),
+            0x20..=0x7e => keys.push(Key::new(KeyCode::Char(byte as char))),
+            _ => {}
+        }
+    }
+    keys
+}
+
+// The color in `ESC ] 11 ; rgb:RRRR/GGGG/BBBB`, each part one to four hex digits
+fn parse_reply(reply: &[u8]) -> Option<Background> {
+    let text = String::from_utf8_lossy(reply);
+    let start = text.find("\x1b]11;rgb:")? + "\x1b]11;rgb:".len();
+    let end = text[start..].find(['\x07', '\x1b']).map_or(text.len(), |end| start + end);
+    let parts: Vec<f64> = text[start..end]
+        .split('/')
+        .map(|part| u32::from_str_radix(part, 16).ok().filter(|_| (1..=4).contains(&part.len())).map(|v| v as f64 / ((1u32 << (4 * part.len())) - 1) as f64))
+        .collect::<Option<_>>()?;
+    let [red, green, blue] = parts[..] else {
+        return None;
+    };
+    Some(Background::of_rgb(red, green, blue))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{self, Read},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use super::{query, Background, TIMEOUT};
+    use crate::events::{Key, KeyCode};
+
+    #[test]
+    fn background_is_read_from_the_reply_or_the_environment() {
+        let ask = |reply: &[u8]| {
+            let mut sent = Vec::new();
+            let detected = query(&mut &reply[..], &mut sent, |_| Ok(true)).unwrap().background;
+            assert_eq!(sent, b"\x1b]11;?\x1b\\\x1b[c");
+            detected
+        };
+        assert_eq!(ask(b"\x1b]11;rgb:ffff/ffff/ffff\x1b\\\x1b[?62;22c"), Some(Background::Light));
+        assert_eq!(ask(b"\x1b]11;rgb:1e/1e/2e\x07\x1b[?1;2c"), Some(Background::Dark));
+        // No color, only the attributes
+        assert_eq!(ask(b"\x1b[?6c"), None);
+        // Reading stops at the attributes, leaving what comes after
+        let mut input: &[u8] = b"\x1b[?6cq";
+        query(&mut input, &mut Vec::new(), |_| Ok(true)).unwrap();
+        assert_eq!(input, b"q");
+        // Keys typed before the answers came are kept
+        let mut input: &[u8] = b"4\x1b[A\x1b]11;rgb:0/0/0\x07\r\x1b[?6c";
+        let probe = query(&mut input, &mut Vec::new(), |_| Ok(true)).unwrap();
+        assert_eq!((probe.background, probe.typed), (Some(Background::Dark), vec![Key::new(KeyCode::Char('4')), Key::new(KeyCode::Enter)]));
+
+        assert_eq!(Background::from_colorfgbg("0;15"), Some(Background::Light));
+        assert_eq!(Background::from_colorfgbg("15;default;0"), Some(Background::Dark));
+        assert_eq!(Background::from_colorfgbg("default"), None);
+        assert_eq!(Background::from_setting("light"), Some(Background::Light));
+        assert_eq!(Background::from_setting("auto"), None);
+    }
+
+    // Blocks on every read, as a tty that's never written to does
+    struct Silent;
+
+    impl Read for Silent {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            thread::sleep(Duration::from_secs(3600));
+            Ok(0)
+        }
+    }
+
+    #[test]
+    fn a_terminal_that_never_answers_is_given_up_on() {
+        let started = Instant::now();
+        let probe = query(&mut Silent, &mut Vec::new(), |wait| {
+            thread::sleep(wait);
+            Ok(false)
+        })
+        .unwrap();
+        assert_eq!((probe.background, probe.typed), (None, Vec::new()));
+        assert!(started.elapsed() >= TIMEOUT && started.elapsed() < Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,141 @@
+// Backups of everything kept for the player in one file, to move to a new
+// machine or keep in case the data dir is lost: the config file and each file
+// of the data dir (stats, best solves, records, replays...). Logs are left
+// out, as the cache dir only holds what can go. A backup is text, each file
+// after a line giving its name and length in bytes:
+//
+//   rustdoku6 backup 1
+//   file data/stats.csv 32
+//   6,classic,2,1,1,90000,90000,0,0
+//
+// Restoring writes each file back over what's there now, replacing it whole;
+// files the backup doesn't have are left as they are.
+
+use std::{fs, io, path::Path};
+
+use rustdoku6::storage;
+
+use crate::config;
+
+const HEADER: &str = "rustdoku6 backup 1";
+const CONFIG_NAME: &str = "config.toml";
+const DATA_PREFIX: &str = "data/";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Backup {
+    // Names as written in the backup, `config.toml` or `data/<file>`, with
+    // their contents
+    pub files: Vec<(String, String)>,
+}
+
+impl Backup {
+    // The config file and data files as they are now
+    pub fn collect() -> io::Result<Self> {
+        let mut files = Vec::new();
+        if let Some(text) = config::config_path().and_then(|path| read_if_there(&path).transpose()) {
+            files.push((CONFIG_NAME.to_string(), text?));
+        }
+        let Some(dir) = storage::data_dir().filter(|dir| dir.is_dir()) else {
+            return Ok(Self { files });
+        };
+        let mut names: Vec<String> = fs::read_dir(&dir)?
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            // Temporary files of a save under way
+            .filter(|name| !name.starts_with('.'))
+            .collect();
+        names.sort();
+        for name in names {
+            let text = fs::read_to_string(dir.join(&name)).map_err(|err| io::Error::new(err.kind(), format!("{}: {}", name, err)))?;
+            files.push((format!("{}{}", DATA_PREFIX, name), text));
+        }
+        Ok(Self { files })
+    }
+
+    pub fn encode(&self) -> String {
+        let mut text = format!("{}\n", HEADER);
+        for (name, contents) in &self.files {
+            text += &format!("file {} {}\n{}\n", name, contents.len(), contents);
+        }
+        text
+    }
+
+    // A backup as `encode` writes it. Names that would point outside the
+    // config or data dir are refused.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut rest = text.strip_prefix(HEADER).and_then(|rest| rest.strip_prefix('\n')).ok_or("not a rustdoku6 backup")?;
+        let mut files = Vec::new();
+        while !rest.is_empty() {
+            let (line, after) = rest.split_once('\n').ok_or("the backup is cut short")?;
+            let mut parts = line.split(' ');
+            let (Some("file"), Some(name), Some(len), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+                return Err(format!("unexpected line in the backup: {}", line));
+            };
+            let data = name.strip_prefix(DATA_PREFIX);
+            if name != CONFIG_NAME && !data.is_some_and(|file| !file.is_empty() && !file.starts_with('.') && !file.contains(['/', '\\'])) {
+                return Err(format!("unexpected file in the backup: {}", name));
+            }
+            let len: usize = len.parse().map_err(|_| format!("bad length for {}", name))?;
+            let contents = after.get(..len).ok_or_else(|| format!("{} is cut short", name))?;
+            rest = after[len..].strip_prefix('\n').ok_or_else(|| format!("{} doesn't end where its length says", name))?;
+            files.push((name.to_string(), contents.to_string()));
+        }
+        Ok(Self { files })
+    }
+
+    // Write the files back. Returns their names.
+    pub fn restore(&self) -> io::Result<Vec<&str>> {
+        for (name, contents) in &self.files {
+            match name.strip_prefix(DATA_PREFIX) {
+                Some(file) => {
+                    storage::write(file, contents)?;
+                }
+                None => {
+                    let path = config::config_path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory"))?;
+                    if let Some(dir) = path.parent() {
+                        fs::create_dir_all(dir)?;
+                    }
+                    fs::write(path, contents)?;
+                }
+            }
+        }
+        Ok(self.files.iter().map(|(name, _)| name.as_str()).collect())
+    }
+}
+
+// Contents of `path`, or None if there's no such file
+fn read_if_there(path: &Path) -> io::Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Backup;
+
+    #[test]
+    fn backups_read_back_whatever_the_files_hold() {
+        let backup = Backup {
+            files: vec![
+                ("config.toml".to_string(), "mouse = false\n".to_string()),
+                // Lines that look like the backup's own, and no newline at the end
+                ("data/notes.txt".to_string(), "file data/x 3\nrustdoku6 backup 1".to_string()),
+                ("data/empty.csv".to_string(), String::new()),
+            ],
+        };
+        let text = backup.encode();
+        assert!(text.starts_with("rustdoku6 backup 1\nfile config.toml 14\nmouse = false\n\n"));
+        assert_eq!(Backup::parse(&text), Ok(backup));
+        assert_eq!(Backup::parse("rustdoku6 backup 1\n"), Ok(Backup::default()));
+
+        assert!(Backup::parse("hello\n").is_err());
+        assert!(Backup::parse(&text[..text.len() - 3]).is_err());
+        for name in ["data/../config.toml", "data/", "../stats.csv", "data/.stats.csv.1.tmp"] {
+            assert!(Backup::parse(&format!("rustdoku6 backup 1\nfile {} 0\n\n", name)).is_err(), "{}", name);
+        }
+    }
+}
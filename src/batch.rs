@@ -0,0 +1,167 @@
+// Grading puzzle collections in bulk (`rustdoku6 rate --dir <dir>`), to sort
+// through a big import before playing it: every puzzle of every file, read as
+// `view` reads them, gets its clue count, whether it has a single solution,
+// and the difficulty the built-in techniques give it. Puzzles are graded on
+// all cores at once; the rows come out in file order all the same. Each row
+// also gives the puzzle's canonical hash, and the first puzzle it repeats, the
+// same or turned, mirrored, shuffled or relabeled, if any, and what solving
+// it took: see `solver::SolveStats`.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::{
+    model::Grid,
+    solver::{SolveStats, TechniqueRegistry},
+    symmetry,
+};
+
+pub const CSV_HEADER: &str = "file,puzzle,clues,unique,difficulty,techniques,canonical,duplicate_of,nodes,backtracks,passes,applied";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Graded {
+    pub clues: usize,
+    // Solutions found, looking no further than two
+    pub solutions: usize,
+    // The hardest technique difficulty needed, None if the techniques get stuck
+    pub difficulty: Option<u8>,
+    // The techniques of that difficulty
+    pub hardest: Vec<&'static str>,
+    // See `symmetry::canonical_hash`
+    pub canonical: u64,
+    // What finding the solutions and the difficulty took
+    pub stats: SolveStats,
+}
+
+impl Graded {
+    pub fn of(givens: &Grid, techniques: &TechniqueRegistry) -> Self {
+        let (solutions, search) = givens.solutions_with_stats(2);
+        let (difficulty, logic) = techniques.grade_with_stats(givens);
+        Self {
+            clues: givens.clue_count(),
+            solutions: solutions.len(),
+            difficulty,
+            hardest: techniques.hardest_applied(difficulty, &logic).unwrap_or_default(),
+            canonical: symmetry::canonical_hash(givens),
+            stats: SolveStats { passes: logic.passes, techniques: logic.techniques, ..search },
+        }
+    }
+
+    // A line of the CSV, for puzzle `puzzle` (from 1) of `file`, repeating
+    // `duplicate_of` if given. Uniqueness is `yes`, `no` or `none` for a
+    // puzzle without a solution, and puzzles the techniques can't finish have
+    // no difficulty. The hash is written in hex and a repeat as `file#puzzle`,
+    // and the steps each technique made as `id=count`, apart by semicolons.
+    pub fn csv_row(&self, file: &str, puzzle: usize, duplicate_of: Option<(&str, usize)>) -> String {
+        let unique = match self.solutions {
+            0 => "none",
+            1 => "yes",
+            _ => "no",
+        };
+        let difficulty = self.difficulty.map_or(String::new(), |difficulty| difficulty.to_string());
+        let duplicate_of = duplicate_of.map_or(String::new(), |(file, puzzle)| csv_field(&format!("{}#{}", file, puzzle)));
+        let hardest = csv_field(&self.hardest.join("; "));
+        let stats = &self.stats;
+        let applied: Vec<String> = stats.techniques.iter().map(|(id, count)| format!("{}={}", id, count)).collect();
+        format!(
+            "{},{},{},{},{},{},{:016x},{},{},{},{},{}",
+            csv_field(file),
+            puzzle,
+            self.clues,
+            unique,
+            difficulty,
+            hardest,
+            self.canonical,
+            duplicate_of,
+            stats.nodes,
+            stats.backtracks,
+            stats.passes,
+            applied.join("; ")
+        )
+    }
+}
+
+// For each puzzle, the first one before it with the same canonical form
+pub fn duplicates(graded: &[Graded]) -> Vec<Option<usize>> {
+    let mut first = HashMap::new();
+    graded
+        .iter()
+        .enumerate()
+        .map(|(i, puzzle)| {
+            let first = *first.entry(puzzle.canonical).or_insert(i);
+            (first != i).then_some(first)
+        })
+        .collect()
+}
+
+// Grade each puzzle, on as many threads as there are cores
+pub fn grade_all(puzzles: &[Grid]) -> Vec<Graded> {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get()).min(puzzles.len().max(1));
+    let next = AtomicUsize::new(0);
+    let graded = Mutex::new(vec![None; puzzles.len()]);
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                let techniques = TechniqueRegistry::builtin();
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(puzzle) = puzzles.get(i) else {
+                        break;
+                    };
+                    let result = Graded::of(puzzle, &techniques);
+                    graded.lock().unwrap_or_else(|poisoned| poisoned.into_inner())[i] = Some(result);
+                }
+            });
+        }
+    });
+    graded.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner()).into_iter().flatten().collect()
+}
+
+// Quoted when it holds a comma, quote or line break
+fn csv_field(text: &str) -> String {
+    match text.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", text.replace('"', "\"\"")),
+        false => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{duplicates, grade_all, Graded};
+    use crate::{
+        model::Game,
+        solver::{SolveStats, TechniqueRegistry},
+        symmetry::Transform,
+    };
+
+    #[test]
+    fn puzzles_are_graded_in_order() {
+        let games: Vec<Game> = (1..=6).map(Game::with_seed).collect();
+        let mut puzzles: Vec<_> = games.iter().map(Game::givens).collect();
+        // The second puzzle again, in disguise
+        puzzles.push(Transform { turns: 2, mirror: false, digits: [6, 5, 4, 3, 2, 1] }.apply(&puzzles[1]).unwrap());
+        let graded = grade_all(&puzzles);
+        let techniques = TechniqueRegistry::builtin();
+        assert_eq!(graded, puzzles.iter().map(|puzzle| Graded::of(puzzle, &techniques)).collect::<Vec<_>>());
+        assert_eq!(graded[0].clues, puzzles[0].clue_count());
+        assert_eq!(duplicates(&graded), [None, None, None, None, None, None, Some(1)]);
+
+        // Every empty cell is filled by a technique step in the end
+        let stats = &graded[0].stats;
+        assert!(stats.nodes >= 36 - graded[0].clues && stats.passes >= stats.steps());
+        assert!(stats.techniques.iter().any(|&(id, _)| matches!(id, "naked_single" | "hidden_single")));
+
+        let stuck = Graded { clues: 8, solutions: 2, difficulty: None, hardest: Vec::new(), canonical: 0xab, stats: SolveStats::default() };
+        assert_eq!(stuck.csv_row("a,b.txt", 3, None), "\"a,b.txt\",3,8,no,,,00000000000000ab,,0,0,0,");
+        let stats = SolveStats { nodes: 40, backtracks: 2, passes: 30, techniques: vec![("naked_single", 20), ("naked_pair", 1)] };
+        let pairs = Graded { clues: 12, solutions: 1, difficulty: Some(3), hardest: vec!["Naked Pair", "X-Wing"], canonical: 1, stats };
+        assert_eq!(pairs.csv_row("pack.txt", 2, Some(("old.txt", 4))), "pack.txt,2,12,yes,3,Naked Pair; X-Wing,0000000000000001,old.txt#4,40,2,30,naked_single=20; naked_pair=1");
+        assert!(grade_all(&[]).is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+// How fast the engine runs on this machine (`rustdoku6 bench`), so a slower
+// solver or generator shows up as numbers anyone can compare: puzzles
+// generated, solved, graded and checked for a single solution per second, for
+// each board size the engine handles. Only 6x6 puzzles are played, so other
+// sizes carve theirs from a filled board, a clue at a time while the
+// solution stays unique, down to no fewer than CARVE_FLOOR of the cells.
+// Puzzles come from a fixed seed, so runs on one machine compare; each task
+// runs over them again and again for its time budget, on one thread.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use rand::{prelude::*, rngs::StdRng};
+
+use crate::{
+    generator::{Generator, PuzzleSpec},
+    model::{Board, Cell},
+    solver::TechniqueRegistry,
+};
+
+// Puzzles made before timing the other tasks, and gone through in turn
+const PUZZLES: usize = 8;
+const SEED: u64 = 6;
+// The share of cells left as clues at the least when carving, as the plain
+// backtracking solver slows down fast on big boards with few clues
+const CARVE_FLOOR: f64 = 0.4;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Measure {
+    pub task: &'static str,
+    pub runs: usize,
+    pub total: Duration,
+}
+
+impl Measure {
+    pub fn per_second(&self) -> f64 {
+        self.runs as f64 / self.total.as_secs_f64().max(f64::EPSILON)
+    }
+
+    pub fn mean(&self) -> Duration {
+        self.total / self.runs.max(1) as u32
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+    // e.g. 6x6
+    pub board: String,
+    pub measures: Vec<Measure>,
+}
+
+// Measure every board size, giving each task `budget`, and hand over each
+// size's report once it's done
+pub fn run(budget: Duration, mut done: impl FnMut(&Report)) {
+    done(&board::<4, 2, 2>(budget, carved));
+    done(&board::<6, 2, 3>(budget, |rng| {
+        Generator::generate(rng.random::<u32>() as u64, &PuzzleSpec::default()).expect("classic puzzles generate from any seed").grid
+    }));
+    done(&board::<8, 2, 4>(budget, carved));
+    done(&board::<9, 3, 3>(budget, carved));
+}
+
+// The report's lines: a header, then a line per task
+pub fn table(report: &Report) -> Vec<String> {
+    let mut lines = vec![format!("{} board", report.board)];
+    for measure in &report.measures {
+        lines.push(format!("  {:<10} {:>10.1}/s {:>12.3} ms each ({} runs)", measure.task, measure.per_second(), measure.mean().as_secs_f64() * 1000.0, measure.runs));
+    }
+    lines
+}
+
+fn board<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(budget: Duration, mut generate: impl FnMut(&mut StdRng) -> Board<N, BOX_ROWS, BOX_COLS>) -> Report {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let generated = measure("generate", budget, |_| {
+        black_box(generate(&mut rng));
+    });
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let puzzles: Vec<_> = (0..PUZZLES).map(|_| generate(&mut rng)).collect();
+    let puzzle = |n: usize| &puzzles[n % puzzles.len()];
+    let techniques = TechniqueRegistry::builtin();
+    let measures = vec![
+        generated,
+        measure("solve", budget, |n| {
+            black_box(puzzle(n).solutions(1));
+        }),
+        measure("grade", budget, |n| {
+            black_box(techniques.grade(puzzle(n)));
+        }),
+        measure("unique", budget, |n| {
+            black_box(puzzle(n).solutions(2));
+        }),
+    ];
+    Report { board: format!("{}x{}", N, N), measures }
+}
+
+// Run `task` with the run's number until `budget` is spent, and at least once
+fn measure(task: &'static str, budget: Duration, mut run: impl FnMut(usize)) -> Measure {
+    let started = Instant::now();
+    let mut runs = 0;
+    while runs == 0 || started.elapsed() < budget {
+        run(runs);
+        runs += 1;
+    }
+    Measure { task, runs, total: started.elapsed() }
+}
+
+// A filled board with clues taken out in random order for as long as the
+// solution stays unique, down to CARVE_FLOOR
+fn carved<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(rng: &mut StdRng) -> Board<N, BOX_ROWS, BOX_COLS> {
+    let mut board = Board::new();
+    assert!(board.fill_randomly(rng), "an empty board can be filled");
+    board.cells.iter_mut().flatten().for_each(|cell| cell.set_fixed(true));
+    let mut order: Vec<(usize, usize)> = (0..N * N).map(|i| (i / N, i % N)).collect();
+    order.shuffle(rng);
+    let floor = (CARVE_FLOOR * (N * N) as f64) as usize;
+    for (r, c) in order {
+        if board.clue_count() <= floor {
+            break;
+        }
+        let clue = board.cells[r][c];
+        board.cells[r][c] = Cell::default();
+        if board.solutions(2).len() > 1 {
+            board.cells[r][c] = clue;
+        }
+    }
+    board
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{board, carved, table};
+
+    #[test]
+    fn every_task_is_run_and_reported() {
+        let puzzle = carved::<4, 2, 2>(&mut StdRng::seed_from_u64(1));
+        assert_eq!(puzzle.solutions(2).len(), 1);
+        assert!(puzzle.clue_count() >= 6);
+
+        let report = board::<4, 2, 2>(Duration::ZERO, carved);
+        let tasks: Vec<_> = report.measures.iter().map(|measure| (measure.task, measure.runs)).collect();
+        assert_eq!(tasks, [("generate", 1), ("solve", 1), ("grade", 1), ("unique", 1)]);
+        let lines = table(&report);
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "4x4 board");
+        assert!(lines[2].trim_start().starts_with("solve") && lines[2].ends_with("(1 runs)"), "{}", lines[2]);
+    }
+}
@@ -0,0 +1,100 @@
+// A practice opponent for racing alone: the enabled techniques solve the same
+// puzzle a placement at a time on the solve clock, taking longer over the
+// harder techniques, and its progress shows in a bar under the board beside
+// the player's. `--bot <level>` picks how quickly it works.
+
+use std::time::Duration;
+
+use crate::{model::Grid, solver::TechniqueRegistry};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Level {
+    Relaxed,
+    #[default]
+    Steady,
+    Quick,
+}
+
+impl Level {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "relaxed" => Some(Level::Relaxed),
+            "steady" => Some(Level::Steady),
+            "quick" => Some(Level::Quick),
+            _ => None,
+        }
+    }
+
+    // Time taken over a placement, per point of its technique's difficulty
+    fn step(self) -> Duration {
+        match self {
+            Level::Relaxed => Duration::from_secs(15),
+            Level::Steady => Duration::from_secs(9),
+            Level::Quick => Duration::from_secs(5),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bot {
+    pub level: Level,
+    // The time on the clock of each of its placements
+    pub times: Vec<Duration>,
+    // Entries the puzzle needs; more than the placements when the techniques
+    // can't finish it, and the bot gets stuck
+    pub total: usize,
+}
+
+impl Bot {
+    pub fn new(level: Level, givens: &Grid, techniques: &TechniqueRegistry) -> Self {
+        let difficulty = |name: &str| techniques.enabled().find(|technique| technique.name() == name).map_or(1, |technique| technique.difficulty());
+        let mut at = Duration::ZERO;
+        let times = techniques
+            .solve_path(givens)
+            .iter()
+            .map(|step| {
+                at += level.step() * difficulty(step.technique) as u32;
+                at
+            })
+            .collect();
+        Self { level, times, total: 36 - givens.clue_count() }
+    }
+
+    // Placements made by `elapsed`
+    pub fn progress(&self, elapsed: Duration) -> usize {
+        self.times.iter().take_while(|&&at| at <= elapsed).count()
+    }
+
+    // When it solves the puzzle; None if it gets stuck
+    pub fn finish(&self) -> Option<Duration> {
+        self.times.last().copied().filter(|_| self.times.len() == self.total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bot, Level};
+    use crate::{model::Game, solver::TechniqueRegistry};
+    use std::time::Duration;
+
+    #[test]
+    fn bot_places_at_its_level_and_may_get_stuck() {
+        let techniques = TechniqueRegistry::builtin();
+        let game = (1..).map(Game::with_seed).find(|game| techniques.grade(&game.givens()).is_some()).unwrap();
+        let quick = Bot::new(Level::Quick, &game.givens(), &techniques);
+        let relaxed = Bot::new(Level::Relaxed, &game.givens(), &techniques);
+        assert_eq!(quick.times.len(), quick.total);
+        assert_eq!((quick.progress(Duration::ZERO), quick.progress(Duration::from_secs(5))), (0, 1));
+        assert!(quick.finish() < relaxed.finish());
+        assert_eq!(relaxed.progress(Duration::MAX), relaxed.total);
+
+        // Without the basic techniques it can't get anywhere
+        let mut none = TechniqueRegistry::builtin();
+        for id in ["naked_single", "hidden_single", "locked_candidates", "naked_pair"] {
+            none.set_enabled(id, false);
+        }
+        let stuck = Bot::new(Level::Quick, &game.givens(), &none);
+        assert_eq!((stuck.progress(Duration::MAX), stuck.finish()), (0, None));
+        assert_eq!(Level::parse("quick"), Some(Level::Quick));
+    }
+}
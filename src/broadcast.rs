@@ -0,0 +1,255 @@
+// Spectator mode: a game started with `--broadcast <port>` streams its board
+// read-only to anyone running `rustdoku6 watch host:port`, e.g. for streaming
+// or teaching over SSH. Each change is sent as one `frame` line holding the
+// whole visible state, so a spectator can join at any time and a lost line
+// costs nothing.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    model::{Game, InputMode, Timer},
+    puzzle::PuzzleMeta,
+    screen::{Screen, ScreenStack},
+};
+
+// A spectator that can't take a frame this quickly is dropped rather than stall the game
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    // Just the seed and variant, enough to generate the puzzle
+    pub puzzle: PuzzleMeta,
+    // Row by row; None for an empty cell
+    pub values: [Option<u8>; 36],
+    // Pencil marks as bit masks, bit 0 for 1
+    pub marks: [u8; 36],
+    pub cursor: (usize, usize),
+    pub state: Screen,
+    pub mode: InputMode,
+    pub elapsed: Duration,
+    pub mistakes: u32,
+}
+
+impl Frame {
+    pub fn capture(game: &Game) -> Self {
+        let cells: Vec<_> = game.grid.cells.iter().flatten().collect();
+        Self {
+            puzzle: PuzzleMeta { seed: game.meta.seed, variant: game.meta.variant.clone(), ..PuzzleMeta::default() },
+            values: std::array::from_fn(|i| cells[i].value()),
+            marks: std::array::from_fn(|i| (0..6).filter(|&m| cells[i].mark(m + 1)).map(|m| 1 << m).sum()),
+            cursor: game.cursor,
+            // Popups are the player's own business
+            state: game.screens.iter().rev().find(|screen| !matches!(screen, Screen::About | Screen::Share | Screen::Explain | Screen::History | Screen::Summary)).unwrap_or(Screen::Playing),
+            mode: game.mode,
+            // Whole seconds, as shown on the clock, so a running clock alone doesn't flood spectators
+            elapsed: Duration::from_secs(game.timer.elapsed().as_secs()),
+            mistakes: game.mistakes,
+        }
+    }
+
+    // e.g. `frame 42 1.3...(36) 00 04 ...(36) 2 3 Playing Normal 61000 1`
+    pub fn encode(&self) -> String {
+        let values: String = self.values.iter().map(|v| v.map_or('.', |v| (b'0' + v) as char)).collect();
+        let marks: Vec<String> = self.marks.iter().map(|m| format!("{:02x}", m)).collect();
+        format!(
+            "frame {} {} {} {} {} {:?} {:?} {} {}",
+            self.puzzle.key(),
+            values,
+            marks.concat(),
+            self.cursor.0,
+            self.cursor.1,
+            self.state,
+            self.mode,
+            self.elapsed.as_millis(),
+            self.mistakes
+        )
+    }
+
+    pub fn parse(line: &str) -> Option<Self> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() != 10 || parts[0] != "frame" || parts[2].len() != 36 || parts[3].len() != 72 {
+            return None;
+        }
+        let value_bytes = parts[2].as_bytes();
+        let mut values = [None; 36];
+        let mut marks = [0; 36];
+        for i in 0..36 {
+            values[i] = match value_bytes[i] {
+                b'.' => None,
+                b @ b'1'..=b'6' => Some(b - b'0'),
+                _ => return None,
+            };
+            marks[i] = u8::from_str_radix(parts[3].get(2 * i..2 * i + 2)?, 16).ok().filter(|&m| m < 64)?;
+        }
+        let index = |i: usize| parts[i].parse::<usize>().ok().filter(|&n| n < 6);
+        Some(Self {
+            puzzle: PuzzleMeta::from_key(parts[1])?,
+            values,
+            marks,
+            cursor: (index(4)?, index(5)?),
+            state: match parts[6] {
+                "Playing" => Screen::Playing,
+                "Won" => Screen::Won,
+                "TimeUp" => Screen::TimeUp,
+                "OutOfMistakes" => Screen::OutOfMistakes,
+                "Demo" => Screen::Demo,
+                "Paused" => Screen::Paused,
+                _ => return None,
+            },
+            mode: match parts[7] {
+                "Normal" => InputMode::Normal,
+                "Pencil" => InputMode::Pencil,
+                _ => return None,
+            },
+            elapsed: Duration::from_millis(parts[8].parse().ok()?),
+            mistakes: parts[9].parse().ok()?,
+        })
+    }
+
+    // Show the frame on a spectator's copy of the game, regenerating the puzzle
+    // when the player moved on to a new one
+    pub fn show(&self, game: &mut Option<Game>) {
+        let game = match game {
+            Some(game) if game.meta.key() == self.puzzle.key() => game,
+            _ => match self.puzzle.regenerate() {
+                Some(puzzle) => game.insert(puzzle),
+                None => return,
+            },
+        };
+        for (i, cell) in game.grid.cells.iter_mut().flatten().enumerate() {
+            if !cell.is_fixed() {
+                cell.set_value(self.values[i]);
+                cell.clear_marks();
+                (1..=6).filter(|&value| self.marks[i] & (1 << (value - 1)) != 0).for_each(|value| cell.set_mark(value, true));
+            }
+        }
+        game.cursor = self.cursor;
+        game.screens = ScreenStack::showing(self.state);
+        game.mode = self.mode;
+        game.timer = Timer::stopped_at(self.elapsed);
+        game.mistakes = self.mistakes;
+        game.mark_dirty();
+    }
+}
+
+// The player's side: accepts spectators in the background and sends them
+// each new frame
+pub struct Broadcast {
+    joined: Receiver<TcpStream>,
+    watchers: Vec<TcpStream>,
+    last: String,
+}
+
+impl Broadcast {
+    pub fn start(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        let (sender, joined) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if sender.send(stream).is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(Self { joined, watchers: Vec::new(), last: String::new() })
+    }
+
+    // Send the current frame to new spectators, and to everyone if it changed
+    fn publish(&mut self, game: &Game) {
+        let frame = Frame::capture(game).encode();
+        let changed = frame != self.last;
+        let line = format!("{}\n", frame);
+        let mut newcomers = Vec::new();
+        while let Ok(stream) = self.joined.try_recv() {
+            log::info!("spectator connected from {:?}", stream.peer_addr());
+            if stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_ok() {
+                newcomers.push(stream);
+            }
+        }
+        if changed {
+            self.watchers.retain_mut(|stream| (&*stream).write_all(line.as_bytes()).is_ok());
+        }
+        newcomers.retain_mut(|stream| (&*stream).write_all(line.as_bytes()).is_ok());
+        self.watchers.append(&mut newcomers);
+        self.last = frame;
+    }
+}
+
+impl crate::Link for Broadcast {
+    fn sync(&mut self, game: &mut Game) -> io::Result<()> {
+        self.publish(game);
+        Ok(())
+    }
+}
+
+// The spectator's side: frames read from the broadcasting game
+pub struct Spectator {
+    frames: Receiver<Frame>,
+}
+
+impl Spectator {
+    pub fn connect(addr: &str) -> io::Result<Self> {
+        let reader = BufReader::new(TcpStream::connect(addr)?);
+        let (sender, frames) = mpsc::channel();
+        thread::spawn(move || {
+            for line in reader.lines().map_while(Result::ok) {
+                if let Some(frame) = Frame::parse(&line) {
+                    if sender.send(frame).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        Ok(Self { frames })
+    }
+
+    // The newest frame since the last call; Err once the game has gone away
+    pub fn latest(&self) -> Result<Option<Frame>, mpsc::TryRecvError> {
+        let mut latest = None;
+        loop {
+            match self.frames.try_recv() {
+                Ok(frame) => latest = Some(frame),
+                Err(mpsc::TryRecvError::Empty) => return Ok(latest),
+                Err(err) if latest.is_none() => return Err(err),
+                Err(_) => return Ok(latest),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Frame;
+    use crate::{model::Game, screen::Screen};
+
+    #[test]
+    fn frames_round_trip_onto_a_spectators_game() {
+        let mut game = Game::with_seed(21);
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        game.cursor = (r, c);
+        game.handle_input(game.solution.unwrap()[r][c]);
+        let given = game.grid.cells[5][5].is_fixed();
+        game.grid.cells[5][5].set_mark(3, !given);
+        game.apply(crate::command::Command::Share);
+
+        let frame = Frame::parse(&Frame::capture(&game).encode()).unwrap();
+        assert_eq!(frame, Frame::capture(&game));
+        assert_eq!(frame.state, Screen::Playing);
+
+        let mut spectator = None;
+        frame.show(&mut spectator);
+        let spectator = spectator.unwrap();
+        assert_eq!(spectator.meta.seed, 21);
+        assert_eq!(spectator.cursor, (r, c));
+        assert_eq!(spectator.grid.cells[r][c].value(), Some(game.solution.unwrap()[r][c]));
+        assert_eq!(spectator.grid.cells[5][5].marks(), game.grid.cells[5][5].marks());
+
+        assert_eq!(Frame::parse("frame 1 short"), None);
+    }
+}
@@ -0,0 +1,86 @@
+// A saved solve as an asciinema recording (cast format v2), to share how it
+// went without screen-recording software: the board is drawn as the game
+// draws it, in color, each time an entry went in or came out, at the time it
+// did. `asciinema play` or the web player shows it; gaps over a few seconds
+// play shorter, as `asciinema play -i` can change.
+
+use std::time::Duration;
+
+use crate::{
+    config::Config,
+    model::{Game, Timer},
+    puzzle::PuzzleMeta,
+    replay::{json_string, Solve},
+    screen::{Screen, ScreenStack},
+    ui,
+};
+
+pub const SIZE: (u16, u16) = (80, 24);
+// Longest pause played back, in seconds
+const IDLE_TIME_LIMIT: f64 = 3.0;
+// How long the solved board shows at the end
+const LAST_FRAME: Duration = Duration::from_secs(2);
+
+// The cast of `solve`, at `cols` x `rows`. An error for a puzzle whose key
+// no longer generates it.
+pub fn cast(solve: &Solve, config: &Config, (cols, rows): (u16, u16)) -> Result<String, String> {
+    let mut game = PuzzleMeta::from_key(&solve.puzzle).and_then(|meta| meta.regenerate()).ok_or_else(|| format!("{} can't be generated again", solve.puzzle))?;
+    game.show_coordinates = config.coordinates;
+    game.subtle_regions = config.subtle_regions;
+    game.timer = Timer::stopped_at(Duration::ZERO);
+    let title = format!("RustDoku6 {}", solve.puzzle);
+    let mut cast = format!("{{\"version\":2,\"width\":{},\"height\":{},\"idle_time_limit\":{},\"title\":{}}}\n", cols, rows, IDLE_TIME_LIMIT, json_string(&title));
+    let mut frame = |at: Duration, game: &Game| cast.push_str(&format!("[{:.3},\"o\",{}]\n", at.as_secs_f64(), json_string(&format!("\x1b[H\x1b[2J{}", ui::ansi_snapshot(game, config, cols, rows)))));
+    frame(Duration::ZERO, &game);
+    for step in &solve.replay.moves {
+        let cell = &mut game.grid.cells[step.row][step.col];
+        cell.set_value(step.value);
+        cell.clear_marks();
+        game.cursor = (step.row, step.col);
+        game.mistakes += (step.value.is_some() && !step.correct) as u32;
+        game.timer = Timer::stopped_at(step.at);
+        frame(step.at, &game);
+    }
+    if game.is_won() {
+        game.screens = ScreenStack::new(Screen::Won);
+        game.timer = Timer::stopped_at(solve.time);
+        game.mistakes = solve.mistakes;
+        frame(solve.time + LAST_FRAME, &game);
+    }
+    Ok(cast)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::cast;
+    use crate::config::Config;
+    use rustdoku6::{
+        model::Game,
+        replay::{Replay, Solve},
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn casts_play_the_entries_over_in_time() {
+        let game = Game::with_seed(9);
+        let mut replay = Replay::default();
+        let solution = game.solution.unwrap();
+        let blanks = (0..36).map(|i| (i / 6, i % 6)).filter(|&(r, c)| !game.grid.cells[r][c].is_fixed());
+        for (i, (r, c)) in blanks.enumerate() {
+            replay.record(Duration::from_secs(i as u64 + 1), (r, c), Some(solution[r][c]), true);
+        }
+        let entries = replay.moves.len();
+        let solve = Solve { puzzle: "9".to_string(), time: Duration::from_secs(entries as u64), mistakes: 0, replay };
+        let text = cast(&solve, &Config::default(), (80, 24)).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].starts_with("{\"version\":2,\"width\":80,\"height\":24,"));
+        // The board before any entry, one frame per entry, then the solved board
+        assert_eq!(lines.len(), 1 + 1 + entries + 1);
+        assert!(lines[1].starts_with("[0.000,\"o\",\"\\u001b[H\\u001b[2J"));
+        assert!(lines[2].starts_with("[1.000,\"o\","));
+        assert!(lines.last().unwrap().contains("WON"));
+
+        let unknown = Solve { puzzle: "x".to_string(), ..solve };
+        assert!(cast(&unknown, &Config::default(), (80, 24)).is_err());
+    }
+}
@@ -0,0 +1,345 @@
+// Player intents, decoupled from the terminal: keys are translated to commands
+// by the keymap and applied with `Game::apply`, so game logic (and tests,
+// replays or scripts) never needs to know about the terminal backend. Besides
+// the default keymap there are built-in alternatives, picked with config
+// `keymap`, that bind a few keys differently and fall back to the default for
+// the rest.
+
+use std::sync::OnceLock;
+
+use crate::events::{Key, KeyCode};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    MoveCursor(i8, i8),
+    // Put the cursor on (row, col), e.g. from a mouse click
+    SelectCell(usize, usize),
+    // Start a go-to chord: the next two digits are the row and column to jump to
+    GoTo,
+    // Start recording a macro into the register of the next digit, or stop
+    RecordMacro,
+    // Replay the macro in the register of the next digit
+    PlayMacro,
+    // A value in normal mode, a pencil mark toggle in pencil mode
+    EnterDigit(u8),
+    // Shift+digit: the opposite of EnterDigit, so marks can go in without switching modes
+    EnterAlternate(u8),
+    ClearCell,
+    // Make the last entry, mark or clear again, on the cell under the cursor
+    Repeat,
+    // Lock the entry under the cursor, or unlock it
+    ToggleLock,
+    ToggleMode,
+    Undo,
+    Hint,
+    // Say why the wrong entry under the cursor can't be right
+    Explain,
+    // Count wrong entries without pointing them out
+    CheckProgress,
+    // Step through the solve so far, to resume from an earlier point
+    BrowseHistory,
+    // Enter: resume from the point picked in the history browser
+    Confirm,
+    // Open a what-if branch, or keep the entries made in the open one
+    Hypothesis,
+    // Throw away the open what-if branch
+    DiscardHypothesis,
+    // Start a fresh puzzle from the review of a finished one
+    NewGame,
+    // Type in a share code to play its puzzle
+    Import,
+    // The next or previous puzzle of the file opened with `view`
+    NextPuzzle,
+    PreviousPuzzle,
+    // Show where the wrong entries went, in the review of a finished puzzle
+    ToggleHeatmap,
+    // Show how many digits each empty cell can still take
+    ToggleCounts,
+    // Light up rows, columns and boxes with one empty cell left
+    ToggleLastCells,
+    // Label the rows A-F and the columns 1-6 around the board
+    ToggleCoordinates,
+    ToggleDemo,
+    ToggleAbout,
+    // Records of games played, per kind of puzzle
+    ToggleStats,
+    // Developer overlay with timings and layout
+    ToggleDebug,
+    Share,
+    // Leave a popup or watch mode; quits from the board
+    Back,
+    // Quits, except from a popup where it closes the popup
+    Quit,
+    // Any other key: closes a popup, otherwise does nothing
+    Dismiss,
+}
+
+impl Command {
+    // Whether holding the key down should keep applying the command
+    pub fn repeats(self) -> bool {
+        matches!(self, Command::MoveCursor(..) | Command::Undo)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Keymap {
+    #[default]
+    Default,
+    // Arrows to move and the numpad for the rest: its digits enter, 0 clears,
+    // . switches to pencil marks, * repeats, + hints and - undoes
+    Numpad,
+    // WASD to move, with digits on the number row under the same hand. Watch
+    // and share move to y and v, and z undoes.
+    LeftHanded,
+}
+
+impl Keymap {
+    // Config `keymap`: "default", "numpad" or "left-handed"
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Keymap::Default),
+            "numpad" => Some(Keymap::Numpad),
+            "left-handed" => Some(Keymap::LeftHanded),
+            _ => None,
+        }
+    }
+
+    // The keys this keymap binds otherwise than the default bindings
+    fn rebound(self) -> &'static [(KeyCode, Command)] {
+        match self {
+            Keymap::Default => &[],
+            Keymap::Numpad => &[
+                (KeyCode::Char('0'), Command::ClearCell),
+                (KeyCode::Char('.'), Command::ToggleMode),
+                (KeyCode::Char('*'), Command::Repeat),
+                (KeyCode::Char('+'), Command::Hint),
+                (KeyCode::Char('-'), Command::Undo),
+            ],
+            Keymap::LeftHanded => &[
+                (KeyCode::Char('w'), Command::MoveCursor(-1, 0)),
+                (KeyCode::Char('a'), Command::MoveCursor(0, -1)),
+                (KeyCode::Char('s'), Command::MoveCursor(1, 0)),
+                (KeyCode::Char('d'), Command::MoveCursor(0, 1)),
+                (KeyCode::Char('y'), Command::ToggleDemo),
+                (KeyCode::Char('v'), Command::Share),
+                (KeyCode::Char('z'), Command::Undo),
+            ],
+        }
+    }
+
+    pub fn command(self, key: Key) -> Command {
+        match self.rebound().iter().find(|&&(code, _)| code == key.code) {
+            Some(&(_, command)) => command,
+            None => default_command(key),
+        }
+    }
+
+    // The key bound to `command`, the keymap's own before the default ones
+    fn key_for(self, command: Command) -> Option<String> {
+        let own = self.rebound().iter().map(|&(code, _)| code);
+        own.chain(nameable_keys()).find(|&code| self.command(Key::new(code)) == command).map(key_name)
+    }
+
+    // The key this keymap binds to `command`, as shown in key hints, looked
+    // up in its bindings. Moves name the keys of their axis, and commands
+    // taking a digit name the whole range.
+    pub fn label(self, command: Command) -> String {
+        let keys = |commands: &[Command], apart: &str| commands.iter().filter_map(|&command| self.key_for(command)).collect::<Vec<_>>().join(apart);
+        match command {
+            Command::MoveCursor(0, _) => keys(&[Command::MoveCursor(0, -1), Command::MoveCursor(0, 1)], "/"),
+            Command::MoveCursor(_, 0) => keys(&[Command::MoveCursor(-1, 0), Command::MoveCursor(1, 0)], "/"),
+            Command::MoveCursor(..) => format!("{}/{}", self.label(Command::MoveCursor(-1, 0)), self.label(Command::MoveCursor(0, -1))),
+            Command::EnterDigit(_) => keys(&[Command::EnterDigit(1), Command::EnterDigit(6)], "-"),
+            Command::EnterAlternate(_) => format!("Shift+{}", self.label(Command::EnterDigit(1))),
+            Command::SelectCell(..) => "Click".to_string(),
+            // Every key left unbound
+            Command::Dismiss => "Any key".to_string(),
+            command => self.key_for(command).unwrap_or_default(),
+        }
+    }
+}
+
+// The keys a label can name, in the order one is picked when a command has
+// several: letters, digits, the other printable characters and then the
+// named keys
+fn nameable_keys() -> impl Iterator<Item = KeyCode> {
+    let others = ('!'..='~').filter(|ch| !ch.is_ascii_lowercase() && !ch.is_ascii_digit());
+    let named = [KeyCode::Enter, KeyCode::Esc, KeyCode::Backspace, KeyCode::Delete, KeyCode::Left, KeyCode::Right, KeyCode::Up, KeyCode::Down];
+    ('a'..='z').chain('0'..='9').chain(others).map(KeyCode::Char).chain(named).chain((1..=12).map(KeyCode::F))
+}
+
+fn key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(ch) => ch.to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Backspace => "BS".to_string(),
+        KeyCode::Delete => "Del".to_string(),
+        KeyCode::Left => "Left".to_string(),
+        KeyCode::Right => "Right".to_string(),
+        KeyCode::Up => "Up".to_string(),
+        KeyCode::Down => "Down".to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        KeyCode::Other => String::new(),
+    }
+}
+
+static KEYMAP: OnceLock<Keymap> = OnceLock::new();
+
+// Pick the keymap for the rest of the run; only the first call counts
+pub fn set_keymap(keymap: Keymap) {
+    let _ = KEYMAP.set(keymap);
+}
+
+pub fn keymap() -> Keymap {
+    KEYMAP.get().copied().unwrap_or_default()
+}
+
+// The command the keymap in use binds to `key`
+pub fn from_key(key: Key) -> Command {
+    keymap().command(key)
+}
+
+// The key the keymap in use binds to `command`
+pub fn key_label(command: Command) -> String {
+    keymap().label(command)
+}
+
+// What Shift+1..6 types on a US layout
+const SHIFTED_DIGITS: [char; 6] = ['!', '@', '#', '$', '%', '^'];
+
+// The default key bindings
+fn default_command(key: Key) -> Command {
+    match key.code {
+        KeyCode::Char(ch @ '1'..='6') if key.shift => Command::EnterAlternate(ch as u8 - b'0'),
+        KeyCode::Char(ch) if SHIFTED_DIGITS.contains(&ch) => {
+            let digit = SHIFTED_DIGITS.iter().position(|&shifted| shifted == ch).map_or(0, |i| i as u8 + 1);
+            Command::EnterAlternate(digit)
+        }
+        KeyCode::Char('q') => Command::Quit,
+        KeyCode::Esc => Command::Back,
+        KeyCode::Char('w') => Command::ToggleDemo,
+        KeyCode::Char('i') | KeyCode::Char('I') => Command::ToggleAbout,
+        KeyCode::Char('s') => Command::Share,
+        KeyCode::Char('t') => Command::ToggleStats,
+        KeyCode::F(12) => Command::ToggleDebug,
+        KeyCode::Char('h') => Command::Hint,
+        KeyCode::Char('e') => Command::Explain,
+        KeyCode::Char('c') => Command::CheckProgress,
+        KeyCode::Char('r') => Command::BrowseHistory,
+        KeyCode::Enter => Command::Confirm,
+        KeyCode::Char('b') => Command::Hypothesis,
+        KeyCode::Char('x') => Command::DiscardHypothesis,
+        KeyCode::Char('n') => Command::NewGame,
+        KeyCode::Char('o') => Command::Import,
+        KeyCode::Char(']') => Command::NextPuzzle,
+        KeyCode::Char('[') => Command::PreviousPuzzle,
+        KeyCode::Char('m') => Command::ToggleHeatmap,
+        KeyCode::Char('k') => Command::ToggleCounts,
+        KeyCode::Char('f') => Command::ToggleLastCells,
+        KeyCode::Char('C') => Command::ToggleCoordinates,
+        KeyCode::Char('p') => Command::ToggleMode,
+        KeyCode::Char('u') => Command::Undo,
+        KeyCode::Char('l') => Command::ToggleLock,
+        KeyCode::Char('g') => Command::GoTo,
+        KeyCode::Char('.') => Command::Repeat,
+        KeyCode::Char('Q') => Command::RecordMacro,
+        KeyCode::Char('P') => Command::PlayMacro,
+        KeyCode::Left => Command::MoveCursor(0, -1),
+        KeyCode::Right => Command::MoveCursor(0, 1),
+        KeyCode::Up => Command::MoveCursor(-1, 0),
+        KeyCode::Down => Command::MoveCursor(1, 0),
+        KeyCode::Char(ch @ '1'..='6') => Command::EnterDigit(ch as u8 - b'0'),
+        KeyCode::Backspace | KeyCode::Delete => Command::ClearCell,
+        _ => Command::Dismiss,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_key, key_label, Command, Keymap};
+    use crate::events::{Key, KeyCode};
+
+    fn key(code: KeyCode) -> Key {
+        Key::new(code)
+    }
+
+    #[test]
+    fn default_keymap() {
+        assert_eq!(from_key(key(KeyCode::Char('4'))), Command::EnterDigit(4));
+        assert_eq!(from_key(key(KeyCode::Char('7'))), Command::Dismiss);
+        assert_eq!(from_key(key(KeyCode::Up)), Command::MoveCursor(-1, 0));
+        assert_eq!(from_key(key(KeyCode::Delete)), Command::ClearCell);
+        assert_eq!(from_key(key(KeyCode::Esc)), Command::Back);
+        assert_eq!(from_key(key(KeyCode::F(12))), Command::ToggleDebug);
+        assert_eq!(from_key(key(KeyCode::Char('e'))), Command::Explain);
+        assert_eq!(from_key(key(KeyCode::Char('x'))), Command::DiscardHypothesis);
+        assert_eq!(from_key(key(KeyCode::Char('g'))), Command::GoTo);
+        assert_eq!(from_key(key(KeyCode::Char('Q'))), Command::RecordMacro);
+        assert_eq!(from_key(key(KeyCode::Char('#'))), Command::EnterAlternate(3));
+        assert_eq!(from_key(Key { shift: true, ..key(KeyCode::Char('6')) }), Command::EnterAlternate(6));
+    }
+
+    #[test]
+    fn key_labels_name_the_bound_keys() {
+        let commands = [
+            Command::ToggleLock,
+            Command::ToggleMode,
+            Command::Undo,
+            Command::Hint,
+            Command::Explain,
+            Command::CheckProgress,
+            Command::BrowseHistory,
+            Command::Hypothesis,
+            Command::DiscardHypothesis,
+            Command::NewGame,
+            Command::Import,
+            Command::NextPuzzle,
+            Command::PreviousPuzzle,
+            Command::ToggleHeatmap,
+            Command::ToggleCounts,
+            Command::ToggleLastCells,
+            Command::ToggleCoordinates,
+            Command::ToggleDemo,
+            Command::ToggleAbout,
+            Command::ToggleStats,
+            Command::Share,
+            Command::Quit,
+        ];
+        for command in commands {
+            let label: Vec<char> = key_label(command).chars().collect();
+            assert_eq!(from_key(key(KeyCode::Char(label[0]))), command, "{:?}", label);
+        }
+        assert_eq!(from_key(key(KeyCode::Char('3'))), Command::EnterDigit(3));
+        assert_eq!(key_label(Command::EnterDigit(3)), "1-6");
+        assert_eq!(key_label(Command::MoveCursor(0, -1)), "Left/Right");
+        assert_eq!((key_label(Command::EnterAlternate(2)), key_label(Command::ClearCell), key_label(Command::Back)), ("Shift+1-6".to_string(), "BS".to_string(), "Esc".to_string()));
+    }
+
+    #[test]
+    fn alternate_keymaps_rebind_a_few_keys() {
+        let numpad = Keymap::Numpad;
+        assert_eq!(numpad.command(key(KeyCode::Char('0'))), Command::ClearCell);
+        assert_eq!(numpad.command(key(KeyCode::Char('-'))), Command::Undo);
+        assert_eq!(numpad.command(key(KeyCode::Char('5'))), Command::EnterDigit(5));
+        assert_eq!(numpad.command(key(KeyCode::Left)), Command::MoveCursor(0, -1));
+        assert_eq!(numpad.label(Command::Hint), "+");
+
+        let left = Keymap::LeftHanded;
+        assert_eq!(left.command(key(KeyCode::Char('a'))), Command::MoveCursor(0, -1));
+        assert_eq!(left.command(key(KeyCode::Char('s'))), Command::MoveCursor(1, 0));
+        assert_eq!(left.command(key(KeyCode::Char('3'))), Command::EnterDigit(3));
+        assert_eq!(left.command(key(KeyCode::Char('q'))), Command::Quit);
+        // Every command keeps a key, and its label names it
+        for command in [Command::ToggleDemo, Command::Share, Command::Undo, Command::Hint, Command::ToggleMode, Command::GoTo, Command::Repeat] {
+            for keymap in [numpad, left] {
+                let label: Vec<char> = keymap.label(command).chars().collect();
+                assert_eq!(keymap.command(key(KeyCode::Char(label[0]))), command, "{:?} {:?}", keymap, command);
+            }
+        }
+        // Labels come from the bindings, the keymap's own first
+        assert_eq!((left.label(Command::MoveCursor(0, 1)), left.label(Command::Undo), numpad.label(Command::ToggleMode)), ("a/d".to_string(), "z".to_string(), ".".to_string()));
+        assert_eq!(Keymap::parse("left-handed"), Some(left));
+        assert_eq!(Keymap::parse("dvorak"), None);
+    }
+}
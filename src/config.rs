@@ -0,0 +1,255 @@
+// User settings, read from `$XDG_CONFIG_HOME/rustdoku6/config.toml`
+// (or `~/.config/rustdoku6/config.toml`). The file uses a small TOML subset:
+// `key = value` lines, quoted strings, `[a, b]` lists and `#` comments.
+// Unknown keys are ignored so older builds can read newer files.
+
+use std::{env, fs, path::PathBuf};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    // Technique ids (see `techniques.rs`) excluded from hints and grading
+    pub disabled_techniques: Vec<String>,
+    // Hide the board while the game is paused
+    pub blank_on_pause: bool,
+    // Minutes without input before the game pauses itself (0 disables)
+    pub idle_pause_minutes: u64,
+    // Base URL of a shared daily puzzle server, e.g. "http://example.com:8080"
+    pub daily_server: Option<String>,
+    // Name submitted with daily results
+    #[cfg_attr(not(feature = "net"), allow(dead_code))]
+    pub player_name: String,
+    // Raster board rendering: "off", "auto", "kitty" or "sixel"
+    pub graphics: String,
+    // How often the game loop wakes without input, in milliseconds
+    pub tick_ms: u64,
+    // Held keys: where the terminal doesn't mark repeats, the same key again
+    // within this is auto-repeat. Cursor movement repeats at most this often;
+    // other held keys don't repeat.
+    pub key_repeat_ms: u64,
+    // Refuse digits that clash with a peer already on the board, instead of entering them
+    pub block_conflicts: bool,
+    // Flash a wrong entry red, then clear it (it still counts as a mistake)
+    pub auto_clear_mistakes: bool,
+    // When a full board wins: "rules" if no rule is broken, "solution" only
+    // if it matches the stored solution
+    pub win_check: String,
+    // Ask before quitting a puzzle that has entries on it
+    pub confirm_quit: bool,
+    // The terminal's background: "auto" to ask it, "dark" or "light"
+    pub background: String,
+    // Language of the interface: "auto" to follow the locale, or "en" or "de"
+    pub language: String,
+    // Faint region shading, with the lines inside a region dimmed so its
+    // edges stand out, in place of the six saturated backgrounds
+    pub subtle_regions: bool,
+    // Put each generated puzzle through a random turn, mirror and relabeling
+    // of its digits, so a seed played again doesn't look familiar
+    pub transform_puzzles: bool,
+    // Open each random puzzle on a preview of its clues, symmetry and the
+    // techniques it needs, to start or reroll before the clock runs
+    pub preview: bool,
+    // Generation profiles, in file order: `profile.<name> = "<settings>"`
+    // lines, see `profile.rs`
+    pub profiles: Vec<(String, String)>,
+    // Keep a few puzzles of each profile made ahead of time in the cache dir
+    pub puzzle_cache: bool,
+    // Append each solve's timed entries to replays.jsonl in the data dir
+    pub replays: bool,
+    // Start with the rows and columns labeled around the board
+    pub coordinates: bool,
+    // Built-in key bindings: "default", "numpad" or "left-handed"
+    pub keymap: String,
+    // Capture the mouse; turning it off keeps the terminal's text selection working
+    pub mouse: bool,
+    // File logging level: "off", "error", "warn", "info", "debug" or "trace"
+    pub log_level: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            disabled_techniques: Vec::new(),
+            blank_on_pause: false,
+            idle_pause_minutes: 5,
+            daily_server: None,
+            player_name: "anonymous".to_string(),
+            graphics: "off".to_string(),
+            tick_ms: 250,
+            key_repeat_ms: 80,
+            block_conflicts: false,
+            auto_clear_mistakes: false,
+            win_check: "rules".to_string(),
+            confirm_quit: true,
+            background: "auto".to_string(),
+            language: "auto".to_string(),
+            subtle_regions: false,
+            transform_puzzles: false,
+            preview: false,
+            profiles: Vec::new(),
+            puzzle_cache: true,
+            coordinates: false,
+            replays: false,
+            keymap: "default".to_string(),
+            mouse: true,
+            log_level: "off".to_string(),
+        }
+    }
+}
+
+impl Config {
+    // Load the config file, falling back to defaults if it is missing or unreadable
+    pub fn load() -> Self {
+        config_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|text| Self::parse(&text))
+            .unwrap_or_default()
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let mut config = Self::default();
+        for line in text.lines() {
+            let line = strip_comment(line).trim();
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "disabled_techniques" => config.disabled_techniques = parse_list(value),
+                "blank_on_pause" => config.blank_on_pause = parse_bool(value, config.blank_on_pause),
+                "idle_pause_minutes" => config.idle_pause_minutes = value.parse().unwrap_or(config.idle_pause_minutes),
+                "daily_server" => config.daily_server = Some(parse_string(value)).filter(|url| !url.is_empty()),
+                "player_name" => config.player_name = parse_string(value),
+                "graphics" => config.graphics = parse_string(value),
+                // A zero tick would spin the input thread
+                "tick_ms" => config.tick_ms = value.parse().unwrap_or(config.tick_ms).max(10),
+                "key_repeat_ms" => config.key_repeat_ms = value.parse().unwrap_or(config.key_repeat_ms),
+                "block_conflicts" => config.block_conflicts = parse_bool(value, config.block_conflicts),
+                "auto_clear_mistakes" => config.auto_clear_mistakes = parse_bool(value, config.auto_clear_mistakes),
+                "win_check" => config.win_check = parse_string(value),
+                "confirm_quit" => config.confirm_quit = parse_bool(value, config.confirm_quit),
+                "background" => config.background = parse_string(value),
+                "language" => config.language = parse_string(value),
+                "subtle_regions" => config.subtle_regions = parse_bool(value, config.subtle_regions),
+                "transform_puzzles" => config.transform_puzzles = parse_bool(value, config.transform_puzzles),
+                "preview" => config.preview = parse_bool(value, config.preview),
+                "puzzle_cache" => config.puzzle_cache = parse_bool(value, config.puzzle_cache),
+                key if key.starts_with("profile.") => config.profiles.push((key["profile.".len()..].to_string(), parse_string(value))),
+                "replays" => config.replays = parse_bool(value, config.replays),
+                "coordinates" => config.coordinates = parse_bool(value, config.coordinates),
+                "keymap" => config.keymap = parse_string(value),
+                "mouse" => config.mouse = parse_bool(value, config.mouse),
+                "log_level" => config.log_level = parse_string(value),
+                _ => {}
+            }
+        }
+        config
+    }
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("rustdoku6").join("config.toml"))
+}
+
+// The line up to a `#` that isn't inside a quoted string
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, ch) in line.char_indices() {
+        match ch {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn parse_string(value: &str) -> String {
+    value.trim().trim_matches('"').to_string()
+}
+
+fn parse_bool(value: &str, default: bool) -> bool {
+    match value {
+        "true" => true,
+        "false" => false,
+        _ => default,
+    }
+}
+
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(parse_string)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Config;
+
+    #[test]
+    fn parse_reads_lists_and_ignores_noise() {
+        let config = Config::parse(
+            "# comment\n\
+             unknown_key = 3\n\
+             disabled_techniques = [\"naked_pair\", locked_candidates] # trailing\n\
+             blank_on_pause = true\n\
+             idle_pause_minutes = 2\n\
+             daily_server = \"http://localhost:8080\"\n\
+             tick_ms = 0\n\
+             key_repeat_ms = 300\n\
+             mouse = false\n\
+             keymap = \"left-handed\"\n\
+             transform_puzzles = true\n\
+             preview = true\n\
+             puzzle_cache = false\n\
+             profile.pairs = \"symmetry=half_turn require=naked_pair\"\n\
+             coordinates = true\n\
+             replays = true\n\
+             subtle_regions = true\n\
+             background = \"light\"\n\
+             block_conflicts = true\n\
+             auto_clear_mistakes = true\n\
+             win_check = \"solution\"\n",
+        );
+        assert_eq!(config.disabled_techniques, vec!["naked_pair", "locked_candidates"]);
+        assert!(config.blank_on_pause);
+        assert_eq!(config.idle_pause_minutes, 2);
+        assert_eq!(config.daily_server.as_deref(), Some("http://localhost:8080"));
+        assert_eq!((config.tick_ms, config.key_repeat_ms), (10, 300));
+        assert!(!config.mouse);
+        assert_eq!(config.keymap, "left-handed");
+        assert!(config.transform_puzzles);
+        assert!(config.preview);
+        assert!(!config.puzzle_cache);
+        assert_eq!(config.profiles, [("pairs".to_string(), "symmetry=half_turn require=naked_pair".to_string())]);
+        assert!(config.coordinates);
+        assert!(config.replays);
+        assert!(config.subtle_regions);
+        assert_eq!(config.background, "light");
+        assert!(config.block_conflicts);
+        assert!(config.auto_clear_mistakes);
+        assert_eq!(config.win_check, "solution");
+    }
+
+    #[test]
+    fn parse_keeps_a_hash_inside_quotes() {
+        let config = Config::parse(
+            "player_name = \"Team #1\" # the usual crew\n\
+             profile.hard = \"require=x_wing # and more\"\n",
+        );
+        assert_eq!(config.player_name, "Team #1");
+        assert_eq!(config.profiles, [("hard".to_string(), "require=x_wing # and more".to_string())]);
+    }
+
+    #[test]
+    fn parse_empty_gives_defaults() {
+        assert_eq!(Config::parse(""), Config::default());
+    }
+}
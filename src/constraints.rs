@@ -0,0 +1,342 @@
+// Rules a board's values must follow. Checking an entry asks every constraint
+// of the board, so generation, grading and the win check all follow the same
+// rules, and a variant adds its own constraints instead of changing the
+// checks. Constraints that keep a group of cells from repeating a value name
+// that group as a unit, so the solver's techniques and peer checks cover it.
+
+use crate::{regions::RegionMap, solver::Unit};
+
+// A clue per row or column, None where there's no clue
+pub type Clues = [Option<u32>];
+
+// What constraints see of a board, whatever its size
+pub trait BoardView {
+    fn value(&self, row: usize, col: usize) -> Option<u8>;
+    fn regions(&self) -> &RegionMap;
+}
+
+pub trait Constraint: Send + Sync {
+    // Shown where the rules are listed, e.g. "Diagonal"
+    fn name(&self) -> &'static str;
+    // Whether `value` can go at (row, col) given the board's other values
+    fn allows(&self, board: &dyn BoardView, row: usize, col: usize, value: u8) -> bool;
+    // A group of cells, besides rows, columns and regions, that can't repeat
+    // a value, tagged with its kind
+    fn units(&self, _size: usize) -> Vec<(&'static str, Unit)> {
+        Vec::new()
+    }
+    // What the rule asks, for the legend beside the board. The standard
+    // rules go without saying.
+    fn legend(&self) -> Option<&'static str> {
+        None
+    }
+    // The cells and target sum of a killer cage
+    fn cage(&self) -> Option<(&[(usize, usize)], u32)> {
+        None
+    }
+    // Clues shown outside the grid, for the rows and for the columns
+    fn outside_clues(&self) -> Option<(&Clues, &Clues)> {
+        None
+    }
+    // Cells joined by a line drawn across the board, in order
+    fn path(&self) -> Option<&[(usize, usize)]> {
+        None
+    }
+}
+
+// The rules every board follows: no value twice in a row, column or region
+pub fn standard() -> Vec<Box<dyn Constraint>> {
+    vec![Box::new(RowUnique), Box::new(ColUnique), Box::new(BoxUnique)]
+}
+
+// No other cell of `cells` holds `value`
+fn unique_in(board: &dyn BoardView, cells: impl IntoIterator<Item = (usize, usize)>, row: usize, col: usize, value: u8) -> bool {
+    cells.into_iter().all(|(r, c)| (r, c) == (row, col) || board.value(r, c) != Some(value))
+}
+
+pub struct RowUnique;
+
+impl Constraint for RowUnique {
+    fn name(&self) -> &'static str {
+        "Row"
+    }
+
+    fn allows(&self, board: &dyn BoardView, row: usize, col: usize, value: u8) -> bool {
+        unique_in(board, (0..board.regions().size).map(|c| (row, c)), row, col, value)
+    }
+}
+
+pub struct ColUnique;
+
+impl Constraint for ColUnique {
+    fn name(&self) -> &'static str {
+        "Column"
+    }
+
+    fn allows(&self, board: &dyn BoardView, row: usize, col: usize, value: u8) -> bool {
+        unique_in(board, (0..board.regions().size).map(|r| (r, col)), row, col, value)
+    }
+}
+
+// Unique within each region of the board's region map, boxes unless it's a jigsaw
+pub struct BoxUnique;
+
+impl Constraint for BoxUnique {
+    fn name(&self) -> &'static str {
+        "Box"
+    }
+
+    fn allows(&self, board: &dyn BoardView, row: usize, col: usize, value: u8) -> bool {
+        let regions = board.regions();
+        unique_in(board, regions.region_cells(regions.region_of(row, col)).iter().copied(), row, col, value)
+    }
+}
+
+// Both long diagonals hold each value once
+pub struct Diagonal;
+
+impl Constraint for Diagonal {
+    fn name(&self) -> &'static str {
+        "Diagonal"
+    }
+
+    fn allows(&self, board: &dyn BoardView, row: usize, col: usize, value: u8) -> bool {
+        let size = board.regions().size;
+        (row != col || unique_in(board, (0..size).map(|i| (i, i)), row, col, value))
+            && (row + col != size - 1 || unique_in(board, (0..size).map(|i| (i, size - 1 - i)), row, col, value))
+    }
+
+    fn legend(&self) -> Option<&'static str> {
+        Some("No digit twice on either long diagonal")
+    }
+
+    fn units(&self, size: usize) -> Vec<(&'static str, Unit)> {
+        vec![("Diagonal", (0..size).map(|i| (i, i)).collect()), ("Diagonal", (0..size).map(|i| (i, size - 1 - i)).collect())]
+    }
+}
+
+// A group of cells whose values are all different and add up to `sum`
+pub struct KillerCage {
+    pub cells: Unit,
+    pub sum: u32,
+}
+
+impl Constraint for KillerCage {
+    fn name(&self) -> &'static str {
+        "Cage"
+    }
+
+    fn allows(&self, board: &dyn BoardView, row: usize, col: usize, value: u8) -> bool {
+        if !self.cells.contains(&(row, col)) {
+            return true;
+        }
+        if !unique_in(board, self.cells.iter().copied(), row, col, value) {
+            return false;
+        }
+        let others: Vec<u32> = self.cells.iter().filter(|&&cell| cell != (row, col)).map(|&(r, c)| board.value(r, c)).map(|v| v.map_or(0, u32::from)).collect();
+        let empty = others.iter().filter(|&&v| v == 0).count() as u32;
+        let total = others.iter().sum::<u32>() + value as u32;
+        // Each empty cell still needs at least a 1
+        if empty == 0 {
+            total == self.sum
+        } else {
+            total + empty <= self.sum
+        }
+    }
+
+    fn legend(&self) -> Option<&'static str> {
+        Some("Digits in a cage differ and add up to its sum")
+    }
+
+    fn cage(&self) -> Option<(&[(usize, usize)], u32)> {
+        Some((&self.cells, self.sum))
+    }
+
+    fn units(&self, _size: usize) -> Vec<(&'static str, Unit)> {
+        vec![("Cage", self.cells.clone())]
+    }
+}
+
+// Two neighbouring cells, marked on the board, whose values differ by one
+pub struct Consecutive {
+    pub cells: [(usize, usize); 2],
+}
+
+impl Constraint for Consecutive {
+    fn name(&self) -> &'static str {
+        "Consecutive"
+    }
+
+    fn allows(&self, board: &dyn BoardView, row: usize, col: usize, value: u8) -> bool {
+        let other = match self.cells {
+            [a, b] if a == (row, col) => b,
+            [a, b] if b == (row, col) => a,
+            _ => return true,
+        };
+        board.value(other.0, other.1).is_none_or(|v| v.abs_diff(value) == 1)
+    }
+
+    fn legend(&self) -> Option<&'static str> {
+        Some("Marked neighbours differ by one")
+    }
+}
+
+// Sums outside the grid, each what the values between the 1 and the largest
+// value of its row or column add up to
+pub struct Sandwich {
+    pub rows: Vec<Option<u32>>,
+    pub cols: Vec<Option<u32>>,
+}
+
+impl Sandwich {
+    // Every clue the filled lines of a board give
+    pub fn of<const N: usize>(values: &[[u8; N]; N]) -> Self {
+        let rows = values.iter().map(|row| Some(sum_between(row))).collect();
+        let cols = (0..N).map(|c| Some(sum_between(&values.map(|row| row[c])))).collect();
+        Self { rows, cols }
+    }
+}
+
+// What the values between a full line's 1 and its largest value add up to
+pub fn sum_between(line: &[u8]) -> u32 {
+    let at = |v: usize| line.iter().position(|&value| value as usize == v).unwrap_or(0);
+    let (a, b) = (at(1), at(line.len()));
+    line[a.min(b) + 1..a.max(b)].iter().map(|&v| u32::from(v)).sum()
+}
+
+// Whether a line can still add up to `sum` between its 1 and its largest
+// value. Until both are placed anything can be between them.
+fn sandwich_fits(line: &[Option<u8>], sum: u32) -> bool {
+    let at = |v: usize| line.iter().position(|&value| value.is_some_and(|value| value as usize == v));
+    let (Some(a), Some(b)) = (at(1), at(line.len())) else {
+        return true;
+    };
+    let between = &line[a.min(b) + 1..a.max(b)];
+    let filled = between.iter().flatten().map(|&v| u32::from(v)).sum::<u32>();
+    let empty = between.iter().filter(|v| v.is_none()).count() as u32;
+    // Each empty cell between them takes a 2 at least and one less than the
+    // largest value at most
+    let largest = line.len() as u32 - 1;
+    match empty {
+        0 => filled == sum,
+        _ => filled + 2 * empty <= sum && sum <= filled + largest * empty,
+    }
+}
+
+impl Constraint for Sandwich {
+    fn name(&self) -> &'static str {
+        "Sandwich"
+    }
+
+    fn allows(&self, board: &dyn BoardView, row: usize, col: usize, value: u8) -> bool {
+        let size = board.regions().size;
+        let at = |r: usize, c: usize| if (r, c) == (row, col) { Some(value) } else { board.value(r, c) };
+        let row_fits = self.rows.get(row).copied().flatten().is_none_or(|sum| sandwich_fits(&(0..size).map(|c| at(row, c)).collect::<Vec<_>>(), sum));
+        let col_fits = self.cols.get(col).copied().flatten().is_none_or(|sum| sandwich_fits(&(0..size).map(|r| at(r, col)).collect::<Vec<_>>(), sum));
+        row_fits && col_fits
+    }
+
+    fn legend(&self) -> Option<&'static str> {
+        Some("A sum outside the grid adds up the digits between the 1 and the 6 of its row or column")
+    }
+
+    fn outside_clues(&self) -> Option<(&Clues, &Clues)> {
+        Some((&self.rows, &self.cols))
+    }
+}
+
+// A line of neighbouring cells whose values rise from the bulb, its first
+// cell, to the far end
+pub struct Thermometer {
+    pub cells: Unit,
+}
+
+impl Constraint for Thermometer {
+    fn name(&self) -> &'static str {
+        "Thermometer"
+    }
+
+    fn allows(&self, board: &dyn BoardView, row: usize, col: usize, value: u8) -> bool {
+        let Some(i) = self.cells.iter().position(|&cell| cell == (row, col)) else {
+            return true;
+        };
+        // Room for the steps below and above it, and for those to the values placed
+        let size = board.regions().size;
+        let (i, len, value) = (i as i32, self.cells.len() as i32, i32::from(value));
+        if value <= i || value > size as i32 - (len - 1 - i) {
+            return false;
+        }
+        self.cells.iter().enumerate().all(|(j, &(r, c))| {
+            let j = j as i32;
+            j == i || board.value(r, c).is_none_or(|v| (i32::from(v) - value) * (j - i).signum() >= (j - i).abs())
+        })
+    }
+
+    fn legend(&self) -> Option<&'static str> {
+        Some("Digits rise along each thermometer from its bulb, the brighter end")
+    }
+
+    fn path(&self) -> Option<&[(usize, usize)]> {
+        Some(&self.cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{Consecutive, Diagonal, KillerCage, Sandwich, Thermometer};
+    use crate::model::Board;
+
+    #[test]
+    fn variant_constraints_limit_entries() {
+        let mut board = Board::<4, 2, 2>::with_constraints(vec![
+            Box::new(Diagonal),
+            Box::new(KillerCage { cells: vec![(3, 0), (3, 1)], sum: 3 }),
+            Box::new(Consecutive { cells: [(0, 2), (0, 3)] }),
+        ]);
+        assert!(board.regions.peers(0, 0).contains(&(3, 3)));
+        board.cells[0][0].set_value(Some(1));
+        assert!(!board.is_valid_move(2, 2, 1)); // diagonal
+        assert!(board.is_valid_move(1, 3, 1)); // off the diagonals
+
+        assert!(!board.is_valid_move(3, 1, 3)); // leaves nothing for the other cage cell
+        board.cells[3][0].set_value(Some(2));
+        assert!(!board.is_valid_move(3, 1, 4));
+        assert!(board.is_valid_move(3, 1, 1));
+
+        board.cells[0][3].set_value(Some(4));
+        assert!(!board.is_valid_move(0, 2, 2));
+        assert!(board.is_valid_move(0, 2, 3));
+
+        // Generation follows every constraint
+        let mut board = Board::<6, 2, 3>::with_constraints(vec![Box::new(Diagonal)]);
+        assert!(board.fill_randomly(&mut StdRng::seed_from_u64(1)));
+        assert!(board.is_solved());
+        let diagonal: std::collections::HashSet<_> = (0..6).map(|i| board.cells[i][i].value()).collect();
+        assert_eq!(diagonal.len(), 6);
+
+        // Sandwich sums are checked once the 1 and the 6 are in
+        let sandwich = Sandwich::of(&[[1, 2, 3, 4, 5, 6], [4, 5, 6, 1, 2, 3], [2, 3, 1, 6, 4, 5], [6, 4, 5, 2, 3, 1], [3, 1, 2, 5, 6, 4], [5, 6, 4, 3, 1, 2]]);
+        assert_eq!(sandwich.rows[..3], [Some(14), Some(0), Some(0)]);
+        assert_eq!(sandwich.cols[0], Some(6));
+        let mut board = Board::<6, 2, 3>::with_constraints(vec![Box::new(sandwich)]);
+        board.cells[0][0].set_value(Some(1));
+        board.cells[0][2].set_value(Some(4));
+        assert!(!board.is_valid_move(0, 3, 6)); // 4 and one more between can't make 14
+        assert!(board.is_valid_move(0, 5, 6));
+        board.cells[1][2].set_value(Some(6));
+        assert!(!board.is_valid_move(1, 0, 1)); // a cell between, where the sum is 0
+        assert!(board.is_valid_move(1, 3, 1));
+
+        // Thermometers rise from the bulb, leaving room for every step
+        let mut board = Board::<6, 2, 3>::with_constraints(vec![Box::new(Thermometer { cells: vec![(0, 0), (0, 1), (1, 1), (1, 2)] })]);
+        assert!(!board.is_valid_move(0, 0, 4)); // no room for three more above it
+        assert!(!board.is_valid_move(1, 2, 3));
+        board.cells[0][1].set_value(Some(3));
+        assert!(!board.is_valid_move(0, 0, 3));
+        assert!(board.is_valid_move(0, 0, 2));
+        assert!(!board.is_valid_move(1, 2, 4)); // needs a step in between
+        assert!(board.is_valid_move(1, 2, 5));
+    }
+}
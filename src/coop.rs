@@ -0,0 +1,302 @@
+// Co-op over the network: several players solve one board together. One game
+// hosts (`--host <port>`) and the others join it (`--join host:port`) and
+// play the host's puzzle.
+//
+// Messages are single text lines over TCP. The host is the authority: players
+// send their edits to it, and it applies them in the order they arrive and
+// rebroadcasts each one to everyone, sender included. Edits show up locally
+// straight away and are overwritten if another player's edit to the same cell
+// reached the host later, so simultaneous edits settle on the last one the
+// host received and every board converges. Pencil marks stay private.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::model::Game;
+
+// The host is always player 0
+const HOST: u8 = 0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Message {
+    // First line to a joining player: its player number and the puzzle seed
+    Welcome { player: u8, seed: u64 },
+    Cursor { player: u8, row: usize, col: usize },
+    // A value entered or erased (None) on the shared board
+    Cell { player: u8, row: usize, col: usize, value: Option<u8> },
+    Leave { player: u8 },
+}
+
+impl Message {
+    pub fn encode(&self) -> String {
+        match *self {
+            Message::Welcome { player, seed } => format!("welcome {} {}", player, seed),
+            Message::Cursor { player, row, col } => format!("cursor {} {} {}", player, row, col),
+            Message::Cell { player, row, col, value } => {
+                let value = value.map_or("-".to_string(), |v| v.to_string());
+                format!("cell {} {} {} {}", player, row, col, value)
+            }
+            Message::Leave { player } => format!("leave {}", player),
+        }
+    }
+
+    // None for anything malformed or out of range
+    pub fn parse(line: &str) -> Option<Self> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let player = parts.get(1)?.parse().ok()?;
+        let index = |i: usize| parts.get(i)?.parse::<usize>().ok().filter(|&n| n < 6);
+        let message = match (parts[0], parts.len()) {
+            ("welcome", 3) => Message::Welcome { player, seed: parts[2].parse().ok()? },
+            ("cursor", 4) => Message::Cursor { player, row: index(2)?, col: index(3)? },
+            ("cell", 5) => {
+                let value = match parts[4] {
+                    "-" => None,
+                    v => Some(v.parse().ok().filter(|v| (1..=6).contains(v))?),
+                };
+                Message::Cell { player, row: index(2)?, col: index(3)?, value }
+            }
+            ("leave", 2) => Message::Leave { player },
+            _ => return None,
+        };
+        Some(message)
+    }
+}
+
+enum Event {
+    // A player connected to the host
+    Joined(TcpStream),
+    // A line from the connection of player `.0` (the host, for joined games)
+    Received(u8, Message),
+    Left(u8),
+}
+
+pub struct Coop {
+    pub player: u8,
+    is_host: bool,
+    // Open connections by player: every joined player for the host, the host otherwise
+    links: Vec<(u8, TcpStream)>,
+    events: Receiver<Event>,
+    sender: Sender<Event>,
+    next_player: u8,
+    // Board values as last sent or received, to spot local edits
+    shared: [[Option<u8>; 6]; 6],
+    sent_cursor: Option<(usize, usize)>,
+}
+
+impl Coop {
+    // Accept players on `port` in the background
+    pub fn host(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        let (sender, events) = mpsc::channel();
+        let accepted = sender.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if accepted.send(Event::Joined(stream)).is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(Self::new(HOST, true, Vec::new(), sender, events))
+    }
+
+    // Connect to a host, returning the link and the seed of the puzzle to play
+    pub fn join(addr: &str) -> io::Result<(Self, u64)> {
+        let stream = TcpStream::connect(addr)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let Some(Message::Welcome { player, seed }) = Message::parse(&line) else {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} is not a RustDoku6 host", addr)));
+        };
+        let (sender, events) = mpsc::channel();
+        spawn_reader(HOST, reader, sender.clone());
+        Ok((Self::new(player, false, vec![(HOST, stream)], sender, events), seed))
+    }
+
+    fn new(player: u8, is_host: bool, links: Vec<(u8, TcpStream)>, sender: Sender<Event>, events: Receiver<Event>) -> Self {
+        Self { player, is_host, links, events, sender, next_player: HOST + 1, shared: [[None; 6]; 6], sent_cursor: None }
+    }
+
+    // Apply what other players did and send out what changed here
+    fn exchange(&mut self, game: &mut Game) -> io::Result<()> {
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                Event::Joined(stream) => self.welcome(stream, game),
+                Event::Received(from, message) => self.receive(from, message, game),
+                Event::Left(HOST) if !self.is_host => {
+                    return Err(io::Error::new(io::ErrorKind::ConnectionAborted, "the host left"));
+                }
+                Event::Left(player) => self.drop_player(player, game),
+            }
+        }
+
+        for (r, c) in (0..36).map(|i| (i / 6, i % 6)) {
+            let value = game.grid.cells[r][c].value();
+            if value != self.shared[r][c] && !game.grid.cells[r][c].is_fixed() {
+                self.shared[r][c] = value;
+                self.send(Message::Cell { player: self.player, row: r, col: c, value }, None, game)?;
+            }
+        }
+        if self.sent_cursor != Some(game.cursor) {
+            self.sent_cursor = Some(game.cursor);
+            let (row, col) = game.cursor;
+            self.send(Message::Cursor { player: self.player, row, col }, None, game)?;
+        }
+        Ok(())
+    }
+
+    // Host: greet a new player with its number, the seed, the board and the cursors
+    fn welcome(&mut self, stream: TcpStream, game: &mut Game) {
+        let player = self.next_player;
+        self.next_player = self.next_player.wrapping_add(1).max(HOST + 1);
+        let mut lines = vec![Message::Welcome { player, seed: game.meta.seed }];
+        for (r, c) in (0..36).map(|i| (i / 6, i % 6)) {
+            if let (Some(value), false) = (self.shared[r][c], game.grid.cells[r][c].is_fixed()) {
+                lines.push(Message::Cell { player: HOST, row: r, col: c, value: Some(value) });
+            }
+        }
+        let (row, col) = game.cursor;
+        lines.push(Message::Cursor { player: HOST, row, col });
+        lines.extend(game.peer_cursors.iter().map(|&(player, (row, col))| Message::Cursor { player, row, col }));
+
+        let greeted = stream.try_clone().and_then(|reader| {
+            let mut writer = &stream;
+            for line in &lines {
+                writeln!(writer, "{}", line.encode())?;
+            }
+            spawn_reader(player, BufReader::new(reader), self.sender.clone());
+            Ok(())
+        });
+        match greeted {
+            Ok(()) => {
+                log::info!("co-op player {} joined from {:?}", player, stream.peer_addr());
+                self.links.push((player, stream));
+            }
+            Err(err) => log::warn!("co-op player {} could not join: {}", player, err),
+        }
+    }
+
+    fn receive(&mut self, from: u8, message: Message, game: &mut Game) {
+        match message {
+            Message::Cell { player, row, col, value } => {
+                // The host decides who made an edit; joined games trust the host
+                let player = if self.is_host { from } else { player };
+                if game.grid.cells[row][col].is_fixed() {
+                    return;
+                }
+                self.shared[row][col] = value;
+                game.set_shared_value(row, col, value);
+                if self.is_host {
+                    let _ = self.send(Message::Cell { player, row, col, value }, None, game);
+                }
+            }
+            Message::Cursor { player, row, col } => {
+                let player = if self.is_host { from } else { player };
+                if player == self.player {
+                    return;
+                }
+                game.peer_cursors.retain(|&(p, _)| p != player);
+                game.peer_cursors.push((player, (row, col)));
+                game.mark_dirty();
+                if self.is_host {
+                    let _ = self.send(Message::Cursor { player, row, col }, Some(player), game);
+                }
+            }
+            Message::Leave { player } if !self.is_host => {
+                game.peer_cursors.retain(|&(p, _)| p != player);
+                game.mark_dirty();
+            }
+            _ => {}
+        }
+    }
+
+    fn drop_player(&mut self, player: u8, game: &mut Game) {
+        log::info!("co-op player {} left", player);
+        self.links.retain(|&(p, _)| p != player);
+        game.peer_cursors.retain(|&(p, _)| p != player);
+        game.mark_dirty();
+        if self.is_host {
+            let _ = self.send(Message::Leave { player }, None, game);
+        }
+    }
+
+    // The host writes to every player except `skip`; joined games write to the host.
+    // A host drops players it can't reach; a joined game fails without its host.
+    fn send(&mut self, message: Message, skip: Option<u8>, game: &mut Game) -> io::Result<()> {
+        let line = message.encode();
+        let mut unreachable = Vec::new();
+        for (player, stream) in &mut self.links {
+            if Some(*player) == skip {
+                continue;
+            }
+            if let Err(err) = writeln!(stream, "{}", line) {
+                if !self.is_host {
+                    return Err(err);
+                }
+                unreachable.push(*player);
+            }
+        }
+        for player in unreachable {
+            self.drop_player(player, game);
+        }
+        Ok(())
+    }
+}
+
+// Fails when a joined game loses the host, which leaves it to carry on alone
+impl crate::Link for Coop {
+    fn sync(&mut self, game: &mut Game) -> io::Result<()> {
+        let result = self.exchange(game);
+        if result.is_err() {
+            game.coop_player = None;
+            game.peer_cursors.clear();
+            game.mark_dirty();
+        }
+        result
+    }
+}
+
+// Forward lines from one connection until it closes
+fn spawn_reader(player: u8, reader: BufReader<TcpStream>, sender: Sender<Event>) {
+    thread::spawn(move || {
+        for line in reader.lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if let Some(message) = Message::parse(&line) {
+                if sender.send(Event::Received(player, message)).is_err() {
+                    return;
+                }
+            }
+        }
+        let _ = sender.send(Event::Left(player));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Message;
+
+    #[test]
+    fn messages_round_trip_and_reject_garbage() {
+        let messages = [
+            Message::Welcome { player: 2, seed: 12345 },
+            Message::Cursor { player: 1, row: 5, col: 0 },
+            Message::Cell { player: 0, row: 2, col: 3, value: Some(6) },
+            Message::Cell { player: 3, row: 0, col: 1, value: None },
+            Message::Leave { player: 1 },
+        ];
+        for message in messages {
+            assert_eq!(Message::parse(&message.encode()), Some(message));
+        }
+        assert_eq!(Message::parse("cell 1 6 0 3"), None);
+        assert_eq!(Message::parse("cell 1 0 0 7"), None);
+        assert_eq!(Message::parse("cursor x 0 0"), None);
+        assert_eq!(Message::parse("GET / HTTP/1.0"), None);
+        assert_eq!(Message::parse(""), None);
+    }
+}
@@ -0,0 +1,101 @@
+// Crash reports: when the game loop fails or panics, the full game state and
+// the recent commands are written to a text file in the data dir, so a bug
+// report can include the exact board and move sequence.
+
+use std::{
+    fmt::Write as _,
+    io,
+    panic,
+    path::PathBuf,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{model::Game, storage};
+
+// The message of the last panic, captured by the hook because the default one
+// would print into the alternate screen where nobody can read it
+static LAST_PANIC: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn install_panic_hook() {
+    panic::set_hook(Box::new(|info| {
+        let location = info.location().map_or(String::new(), |loc| format!(" at {}:{}", loc.file(), loc.line()));
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        let text = format!("panic{}: {}", location, message);
+        log::error!("{}", text);
+        if let Ok(mut last) = LAST_PANIC.lock() {
+            *last = Some(text);
+        }
+    }));
+}
+
+pub fn take_panic_message() -> String {
+    LAST_PANIC.lock().ok().and_then(|mut last| last.take()).unwrap_or_else(|| "panic".to_string())
+}
+
+// Write the report for `reason` and return its path
+pub fn dump(game: &Game, reason: &str) -> io::Result<PathBuf> {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    storage::write(&format!("crash-{}.txt", secs), &report(game, reason))
+}
+
+fn report(game: &Game, reason: &str) -> String {
+    let mut out = String::new();
+    // Writing to a String can't fail
+    let _ = writeln!(out, "RustDoku6 {} crash report", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(out, "reason: {}", reason);
+    let meta = &game.meta;
+    let _ = writeln!(out, "seed: {} ({}, {:?})", meta.seed, meta.variant, meta.source);
+    let _ = writeln!(out, "puzzle: {}", game.grid.share_code());
+    let _ = writeln!(out, "difficulty: {:?}", meta.difficulty);
+    let _ = writeln!(out, "author: {}", meta.author.as_deref().unwrap_or("-"));
+    let _ = writeln!(out, "daily: {}", meta.date.as_deref().unwrap_or("-"));
+    let _ = writeln!(out, "screens: {:?}, mode: {:?}, cursor: r{}c{}", game.screens.iter().collect::<Vec<_>>(), game.mode, game.cursor.0 + 1, game.cursor.1 + 1);
+    let _ = writeln!(out, "time: {}ms, mistakes: {}", game.timer.elapsed().as_millis(), game.mistakes);
+
+    // Board as played ('.' for empty) next to the solution
+    let _ = writeln!(out, "\nboard   solution");
+    for r in 0..6 {
+        let row: String = game.grid.cells[r].iter().map(|cell| cell.value().map_or('.', |v| (b'0' + v) as char)).collect();
+        let solution: String = game.solution.map_or("-".repeat(6), |solution| solution[r].iter().map(|&v| (b'0' + v) as char).collect());
+        let _ = writeln!(out, "{}  {}", row, solution);
+    }
+    for (i, cell) in game.grid.cells.iter().flatten().enumerate() {
+        if cell.has_marks() {
+            let marks: Vec<String> = (1..=6).filter(|&v| cell.mark(v)).map(|v| v.to_string()).collect();
+            let _ = writeln!(out, "marks r{}c{}: {}", i / 6 + 1, i % 6 + 1, marks.join(","));
+        }
+    }
+
+    let _ = writeln!(out, "\nlast {} commands:", game.actions.len());
+    for (at, command) in &game.actions {
+        let _ = writeln!(out, "{:>8}ms {:?}", at.as_millis(), command);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::report;
+    use crate::{command::Command, model::Game};
+
+    #[test]
+    fn report_includes_board_and_commands() {
+        let mut game = Game::with_seed(42);
+        game.apply(Command::MoveCursor(1, 0));
+        game.apply(Command::ToggleMode);
+
+        let text = report(&game, "test failure");
+        assert!(text.contains("reason: test failure"));
+        assert!(text.contains("seed: 42"));
+        assert!(text.contains(&game.grid.share_code()));
+        assert!(text.contains("MoveCursor(1, 0)"));
+        assert!(text.contains("ToggleMode"));
+        assert_eq!(text.lines().filter(|line| line.len() == 14 && line.contains("  ")).count(), 6);
+    }
+}
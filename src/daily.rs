@@ -0,0 +1,143 @@
+// Daily puzzle: everyone playing on the same (UTC) date gets the same board.
+// The seed is derived from the date locally, or fetched from a configurable
+// server when built with the `net` feature so a group can share one canonical board.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Today's date in UTC as YYYY-MM-DD
+pub fn today() -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// The date of this week's Monday in UTC, naming the weekly challenge
+pub fn this_week() -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    week_of((secs / 86_400) as i64)
+}
+
+// 1970-01-01 was a Thursday, three days after a Monday
+fn week_of(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days - (days + 3).rem_euclid(7));
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Converts days since 1970-01-01 to a (year, month, day) proleptic Gregorian date
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// Stable seed for a date string (FNV-1a), used when no server is configured
+pub fn local_seed(date: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in date.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    // Keep daily seeds in the same short range as random ones
+    hash % (u32::MAX as u64 + 1)
+}
+
+// GET `<server>/daily/<date>`, expecting the seed as the response body
+#[cfg(feature = "net")]
+pub fn fetch_seed(server: &str, date: &str) -> std::io::Result<u64> {
+    let body = crate::http::get(&format!("{}/daily/{}", server.trim_end_matches('/'), date))?;
+    body.trim()
+        .parse()
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("bad daily seed: {}", body.trim())))
+}
+
+// POST the completion time and mistakes to `<server>/daily/<date>/results`
+#[cfg(feature = "net")]
+pub fn submit_result(server: &str, date: &str, player: &str, time: std::time::Duration, mistakes: u32) -> std::io::Result<()> {
+    let body = format!("player={}&time_ms={}&mistakes={}", form_encode(player), time.as_millis(), mistakes);
+    crate::http::post(&format!("{}/daily/{}/results", server.trim_end_matches('/'), form_encode(date)), &body).map(|_| ())
+}
+
+// Submissions on their way, so quitting right after a solve can wait for them
+#[cfg(feature = "net")]
+static SUBMITTING: std::sync::Mutex<Vec<std::thread::JoinHandle<()>>> = std::sync::Mutex::new(Vec::new());
+
+// `submit_result` on another thread, so the UI doesn't wait on the network
+#[cfg(feature = "net")]
+pub fn submit_in_background(server: String, date: String, player: String, time: std::time::Duration, mistakes: u32) {
+    let handle = std::thread::spawn(move || {
+        if let Err(err) = submit_result(&server, &date, &player, time, mistakes) {
+            log::warn!("daily result submission failed: {}", err);
+        }
+    });
+    SUBMITTING.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(handle);
+}
+
+// Wait up to `limit` for submissions still on their way. Returns false if
+// some didn't finish in time, and are given up on.
+#[cfg(feature = "net")]
+pub fn finish_submissions(limit: std::time::Duration) -> bool {
+    let deadline = std::time::Instant::now() + limit;
+    let handles = std::mem::take(&mut *SUBMITTING.lock().unwrap_or_else(|poisoned| poisoned.into_inner()));
+    while !handles.iter().all(|handle| handle.is_finished()) {
+        if std::time::Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    handles.into_iter().for_each(|handle| drop(handle.join()));
+    true
+}
+
+// `text` as a form field or path part: letters, digits and `-._~` as they
+// are, every other byte as %XX
+#[cfg(feature = "net")]
+fn form_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{civil_from_days, local_seed, week_of};
+
+    #[test]
+    fn civil_from_days_handles_epoch_and_leap_years() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(20_740), (2026, 10, 14));
+    }
+
+    #[test]
+    fn weeks_start_on_monday() {
+        assert_eq!(week_of(0), "1969-12-29");
+        assert_eq!(week_of(20_738), "2026-10-12");
+        assert_eq!(week_of(20_740), "2026-10-12");
+        assert_eq!(week_of(20_744), "2026-10-12");
+        assert_eq!(week_of(20_745), "2026-10-19");
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn submitted_fields_are_encoded() {
+        assert_eq!(super::form_encode("Ann & Bo=1 ü"), "Ann%20%26%20Bo%3D1%20%C3%BC");
+        assert_eq!(super::form_encode("2026-10-14"), "2026-10-14");
+    }
+
+    #[test]
+    fn local_seed_is_stable_per_date() {
+        assert_eq!(local_seed("2026-10-14"), local_seed("2026-10-14"));
+        assert_ne!(local_seed("2026-10-14"), local_seed("2026-10-15"));
+        assert!(local_seed("2026-10-14") <= u32::MAX as u64);
+    }
+}
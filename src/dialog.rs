@@ -0,0 +1,136 @@
+// Modal dialogs: a message, a yes/no question, a line of text or a pick from
+// a list. While one is open it gets every key; once it's answered the game
+// acts on the answer by what the dialog was opened for, so a new screen needs
+// a purpose and an answer handler rather than state of its own.
+
+use crate::events::{Key, KeyCode};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Kind {
+    Message,
+    Confirm,
+    // The text typed so far
+    TextInput(String),
+    // The choices and the highlighted one
+    ListPicker { items: Vec<String>, selected: usize },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Answer {
+    // A message was read
+    Ok,
+    Yes,
+    Text(String),
+    // Index into the list picker's items
+    Choice(usize),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    // Still waiting for an answer
+    Open,
+    // Closed with Esc, or "no" to a question
+    Cancelled,
+    Answered(Answer),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dialog {
+    pub title: String,
+    // Shown above the input, if any
+    pub text: String,
+    pub kind: Kind,
+}
+
+impl Dialog {
+    pub fn message(title: &str, text: &str) -> Self {
+        Self { title: title.to_string(), text: text.to_string(), kind: Kind::Message }
+    }
+
+    pub fn confirm(title: &str, text: &str) -> Self {
+        Self { title: title.to_string(), text: text.to_string(), kind: Kind::Confirm }
+    }
+
+    pub fn text_input(title: &str, text: &str) -> Self {
+        Self { title: title.to_string(), text: text.to_string(), kind: Kind::TextInput(String::new()) }
+    }
+
+    pub fn list_picker(title: &str, text: &str, items: Vec<String>) -> Self {
+        Self { title: title.to_string(), text: text.to_string(), kind: Kind::ListPicker { items, selected: 0 } }
+    }
+
+    // Take a key: Esc cancels any dialog, Enter answers it. A message closes on
+    // any key, a question also takes y or n, text input takes characters and
+    // Backspace, and the list picker moves with Up and Down.
+    pub fn handle(&mut self, key: Key) -> Outcome {
+        if key.code == KeyCode::Esc {
+            return Outcome::Cancelled;
+        }
+        match &mut self.kind {
+            Kind::Message => Outcome::Answered(Answer::Ok),
+            Kind::Confirm => match key.code {
+                KeyCode::Enter | KeyCode::Char('y') | KeyCode::Char('Y') => Outcome::Answered(Answer::Yes),
+                KeyCode::Char('n') | KeyCode::Char('N') => Outcome::Cancelled,
+                _ => Outcome::Open,
+            },
+            Kind::TextInput(value) => match key.code {
+                KeyCode::Enter => Outcome::Answered(Answer::Text(value.trim().to_string())),
+                KeyCode::Char(ch) => {
+                    value.push(ch);
+                    Outcome::Open
+                }
+                KeyCode::Backspace => {
+                    value.pop();
+                    Outcome::Open
+                }
+                _ => Outcome::Open,
+            },
+            Kind::ListPicker { items, selected } => match key.code {
+                KeyCode::Enter if !items.is_empty() => Outcome::Answered(Answer::Choice(*selected)),
+                KeyCode::Up => {
+                    *selected = selected.saturating_sub(1);
+                    Outcome::Open
+                }
+                KeyCode::Down => {
+                    *selected = (*selected + 1).min(items.len().saturating_sub(1));
+                    Outcome::Open
+                }
+                _ => Outcome::Open,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Answer, Dialog, Outcome};
+    use crate::events::{Key, KeyCode};
+
+    fn press(dialog: &mut Dialog, codes: &[KeyCode]) -> Outcome {
+        let mut outcome = Outcome::Open;
+        for &code in codes {
+            outcome = dialog.handle(Key::new(code));
+        }
+        outcome
+    }
+
+    #[test]
+    fn dialogs_answer_or_cancel() {
+        assert_eq!(press(&mut Dialog::message("Oops", "Bad code"), &[KeyCode::Char('x')]), Outcome::Answered(Answer::Ok));
+
+        let mut confirm = Dialog::confirm("Quit?", "");
+        assert_eq!(press(&mut confirm, &[KeyCode::Char('q')]), Outcome::Open);
+        assert_eq!(press(&mut confirm, &[KeyCode::Char('y')]), Outcome::Answered(Answer::Yes));
+        assert_eq!(press(&mut confirm, &[KeyCode::Char('n')]), Outcome::Cancelled);
+
+        let mut input = Dialog::text_input("Import", "");
+        let typed = press(&mut input, &[KeyCode::Char(' '), KeyCode::Char('R'), KeyCode::Char('x'), KeyCode::Backspace, KeyCode::Char('D'), KeyCode::Enter]);
+        assert_eq!(typed, Outcome::Answered(Answer::Text("RD".to_string())));
+        assert_eq!(press(&mut input, &[KeyCode::Esc]), Outcome::Cancelled);
+
+        let mut picker = Dialog::list_picker("Slot", "", vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(press(&mut picker, &[KeyCode::Down, KeyCode::Down, KeyCode::Enter]), Outcome::Answered(Answer::Choice(1)));
+        assert_eq!(press(&mut picker, &[KeyCode::Up, KeyCode::Up, KeyCode::Enter]), Outcome::Answered(Answer::Choice(0)));
+        assert_eq!(press(&mut Dialog::list_picker("Slot", "", Vec::new()), &[KeyCode::Enter]), Outcome::Open);
+    }
+}
@@ -0,0 +1,99 @@
+// Endless mode: solving a puzzle loads the next one straight away, at the same
+// difficulty or, when escalating, one star harder every few puzzles. Each solve
+// scores points for difficulty and speed, less a penalty per mistake.
+
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::{
+    generator::PuzzleSpec,
+    model::{Game, MAX_STARS},
+};
+
+// Puzzles solved at one difficulty before escalating to the next
+const PUZZLES_PER_STEP: u32 = 3;
+// Random puzzles tried when looking for the target difficulty
+const MAX_TRIES: usize = 40;
+
+#[derive(Debug)]
+pub struct Endless {
+    // Raise the difficulty as the session goes on
+    pub escalate: bool,
+    // Difficulty the next puzzle is picked for
+    pub stars: u8,
+    pub solved: u32,
+    pub score: u64,
+    // Points scored for the last solved puzzle
+    pub last_points: Option<u64>,
+}
+
+impl Endless {
+    pub fn new(stars: u8, escalate: bool) -> Self {
+        Self { escalate, stars, solved: 0, score: 0, last_points: None }
+    }
+
+    // Score a solved puzzle and pick the next difficulty
+    pub fn record(&mut self, stars: u8, mistakes: u32, time: Duration) {
+        let points = points(stars, mistakes, time);
+        self.solved += 1;
+        self.score += points;
+        self.last_points = Some(points);
+        if self.escalate && self.solved.is_multiple_of(PUZZLES_PER_STEP) {
+            self.stars = (self.stars + 1).min(MAX_STARS);
+        }
+    }
+}
+
+// 100 per star, up to 100 more for solving within five minutes, and 25 off for
+// each mistake; a solve is always worth at least 10
+pub fn points(stars: u8, mistakes: u32, time: Duration) -> u64 {
+    let speed = 100u64.saturating_sub(time.as_secs() / 3);
+    (stars as u64 * 100 + speed).saturating_sub(mistakes as u64 * 25).max(10)
+}
+
+// A new puzzle rated `stars`, or the closest a few tries with seeds from `rng`
+// find. The rating uses the built-in techniques, so it may shift once the
+// player's own technique set is applied.
+pub fn next_puzzle<R: Rng + ?Sized>(stars: u8, rng: &mut R) -> Game {
+    let mut roll = || Game::random_with(rng, &PuzzleSpec::default()).expect("classic puzzles generate from any seed");
+    let mut best = roll();
+    for _ in 1..MAX_TRIES {
+        if best.stars() == stars {
+            break;
+        }
+        let game = roll();
+        if game.stars().abs_diff(stars) < best.stars().abs_diff(stars) {
+            best = game;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{points, Endless};
+    use std::time::Duration;
+
+    #[test]
+    fn points_reward_difficulty_and_speed() {
+        assert_eq!(points(2, 0, Duration::ZERO), 300);
+        assert_eq!(points(2, 0, Duration::from_secs(600)), 200);
+        assert_eq!(points(2, 2, Duration::from_secs(150)), 200);
+        assert_eq!(points(1, 40, Duration::ZERO), 10);
+    }
+
+    #[test]
+    fn escalating_sessions_raise_the_difficulty() {
+        let mut endless = Endless::new(1, true);
+        for _ in 0..3 {
+            endless.record(1, 0, Duration::from_secs(300));
+        }
+        assert_eq!((endless.solved, endless.score, endless.stars), (3, 300, 2));
+
+        let mut steady = Endless::new(4, false);
+        steady.record(4, 0, Duration::ZERO);
+        assert_eq!(steady.stars, 4);
+        assert_eq!(steady.last_points, Some(500));
+    }
+}
@@ -0,0 +1,155 @@
+// Events reaching the game loop, independent of the terminal backend: each
+// backend in `term` translates its own input into these, and sends a Tick at
+// a fixed rate so timers advance without input.
+
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AppEvent {
+    Key(Key),
+    Mouse(Mouse),
+    // The terminal resizes its buffers on the next draw, so only the fact matters
+    Resize,
+    // Termwiz doesn't report focus changes
+    FocusLost,
+    FocusGained,
+    Tick,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Key {
+    pub code: KeyCode,
+    // Reported by the terminal as auto-repeat of a held key
+    pub repeat: bool,
+    // Shift was held. Most terminals send Shift+1 as '!' instead, without the flag.
+    pub shift: bool,
+}
+
+impl Key {
+    pub fn new(code: KeyCode) -> Self {
+        Self { code, repeat: false, shift: false }
+    }
+}
+
+// The keys the game tells apart; anything else is `Other`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyCode {
+    Char(char),
+    F(u8),
+    Up,
+    Down,
+    Left,
+    Right,
+    Esc,
+    Enter,
+    Backspace,
+    Delete,
+    Other,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mouse {
+    pub column: u16,
+    pub row: u16,
+    pub kind: MouseKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MouseKind {
+    Moved,
+    LeftDown,
+    Other,
+}
+
+// Tames held keys. With `flagged`, the terminal speaks the keyboard
+// enhancement protocol and marks repeats itself, so only those count as held;
+// elsewhere a key is held once the same key comes again within `interval`, as
+// the terminal's repeats do, quicker than anyone taps. Repeats are throttled
+// to one per `interval` for keys that may repeat (cursor movement) and dropped
+// for the rest, so holding a digit doesn't toggle a mark back and forth or
+// pile up entries, while a quick double tap still counts twice.
+pub struct KeyRepeat {
+    interval: Duration,
+    flagged: bool,
+    last: Option<(KeyCode, Instant)>,
+    last_accepted: Option<Instant>,
+}
+
+impl KeyRepeat {
+    pub fn new(interval: Duration, flagged: bool) -> Self {
+        Self { interval, flagged, last: None, last_accepted: None }
+    }
+
+    // Whether to act on `key`, arriving at `now`
+    pub fn accept(&mut self, key: &Key, now: Instant, repeatable: bool) -> bool {
+        let is_repeat = match self.flagged {
+            true => key.repeat,
+            false => key.repeat || self.last.is_some_and(|(code, at)| code == key.code && now.duration_since(at) < self.interval),
+        };
+        self.last = Some((key.code, now));
+
+        let accept = if is_repeat {
+            repeatable && self.last_accepted.is_none_or(|at| now.duration_since(at) >= self.interval)
+        } else {
+            true
+        };
+        if accept {
+            self.last_accepted = Some(now);
+        }
+        accept
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Key, KeyCode, KeyRepeat};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn held_keys_repeat_only_when_allowed() {
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let key = Key::new(KeyCode::Char('3'));
+
+        // The terminal marks repeats: a digit held down counts once, however
+        // long before the first repeat
+        let mut repeat = KeyRepeat::new(Duration::from_millis(80), true);
+        let held = Key { repeat: true, ..key };
+        assert!(repeat.accept(&key, ms(0), false));
+        assert!(!repeat.accept(&held, ms(500), false));
+        assert!(!repeat.accept(&held, ms(530), false));
+        // An arrow held down moves at most once per interval
+        let arrow = Key { code: KeyCode::Down, repeat: true, shift: false };
+        assert!(repeat.accept(&arrow, ms(1000), true));
+        assert!(!repeat.accept(&arrow, ms(1030), true));
+        assert!(repeat.accept(&arrow, ms(1090), true));
+
+        // Without the flag, repeats are the same key again within the
+        // interval; the first one, after the terminal's delay, can't be told
+        // from a press
+        let mut repeat = KeyRepeat::new(Duration::from_millis(80), false);
+        assert!(repeat.accept(&key, ms(0), false));
+        assert!(repeat.accept(&key, ms(500), false));
+        assert!(!repeat.accept(&key, ms(530), false));
+        assert!(!repeat.accept(&key, ms(560), false));
+        // Released and pressed again once the repeats stop
+        assert!(repeat.accept(&key, ms(800), false));
+        let down = Key::new(KeyCode::Down);
+        assert!(repeat.accept(&down, ms(1000), true));
+        assert!(!repeat.accept(&down, ms(1030), true));
+        assert!(!repeat.accept(&down, ms(1060), true));
+        assert!(repeat.accept(&down, ms(1090), true));
+    }
+
+    #[test]
+    fn quick_double_taps_count_twice() {
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let key = Key::new(KeyCode::Char('3'));
+        for flagged in [true, false] {
+            let mut repeat = KeyRepeat::new(Duration::from_millis(80), flagged);
+            assert!(repeat.accept(&key, ms(0), false));
+            assert!(repeat.accept(&key, ms(120), false));
+        }
+    }
+}
@@ -0,0 +1,355 @@
+// Puzzle generation: a filled grid is drawn at random from the seed, then
+// clues are taken out the way the variant says. The same seed and spec always
+// give the same puzzle, so a seed is all it takes to share or replay one.
+// Imported puzzles are read from a share code instead.
+
+use std::{error::Error, fmt};
+
+use rand::prelude::*;
+
+use crate::{
+    constraints::{Constraint, Sandwich, Thermometer},
+    model::{BoardSpec, Cell, Grid, InvariantError},
+    puzzle::{Puzzle, PuzzleMeta, Source, Variant},
+    solver::TechniqueRegistry,
+    symmetry::Transform,
+};
+
+// Clues a classic puzzle keeps
+const CLASSIC_CLUES: usize = 16;
+// Thermometers a thermo puzzle has, each of 3 to THERMO_LENGTH cells
+const THERMOS: usize = 4;
+const THERMO_LENGTH: usize = 5;
+
+// What to generate
+#[derive(Clone, Debug, PartialEq)]
+pub struct PuzzleSpec {
+    pub board: BoardSpec,
+    pub variant: Variant,
+    // A symmetry to put the generated puzzle through. Variant puzzles are
+    // left as they are, as their rules sit on the cells they were made for.
+    pub transform: Option<Transform>,
+}
+
+impl Default for PuzzleSpec {
+    fn default() -> Self {
+        Self { board: Grid::SPEC, variant: Variant::Classic, transform: None }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GenerateError {
+    // Only the game's own 6x6 board with 2x3 boxes is generated for now
+    UnsupportedBoard(BoardSpec),
+    // No built-in technique has this id
+    UnknownTechnique(String),
+    // The seed's puzzle can be solved without the technique; most seeds can
+    NotRequired { seed: u64, technique: String },
+    // An imported puzzle's share code doesn't give a puzzle
+    Import(ImportError),
+    // A quarter turn, which only boards with square boxes take
+    UnsupportedTransform(Transform),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImportError {
+    // Not "RD6:" and 36 digits 0-6
+    ShareCode,
+    // The givens break a rule
+    Invalid(InvariantError),
+    NoSolution,
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GenerateError::UnsupportedBoard(spec) => write!(f, "can't generate {0}x{0} boards with {1}x{2} boxes", spec.size, spec.box_rows, spec.box_cols),
+            GenerateError::UnknownTechnique(id) => write!(f, "unknown technique: {}", id),
+            GenerateError::NotRequired { seed, technique } => write!(f, "seed {} doesn't give a puzzle that needs {}", seed, technique),
+            GenerateError::Import(error) => write!(f, "{}", error),
+            GenerateError::UnsupportedTransform(transform) => write!(f, "can't turn a board with {}x{} boxes {} quarter turns", Grid::SPEC.box_rows, Grid::SPEC.box_cols, transform.turns),
+        }
+    }
+}
+
+impl Error for GenerateError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GenerateError::Import(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImportError::ShareCode => write!(f, "share codes are RD6: and 36 digits 0-6"),
+            ImportError::Invalid(error) => write!(f, "{}", error),
+            ImportError::NoSolution => write!(f, "the puzzle has no solution"),
+        }
+    }
+}
+
+impl Error for ImportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ImportError::Invalid(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+pub struct Generator;
+
+impl Generator {
+    // The puzzle `spec` gives from `seed`, graded by the built-in techniques
+    pub fn generate(seed: u64, spec: &PuzzleSpec) -> Result<Puzzle, GenerateError> {
+        if spec.board != Grid::SPEC {
+            return Err(GenerateError::UnsupportedBoard(spec.board));
+        }
+        if let Variant::Imported(code) = &spec.variant {
+            return Self::import(code).map_err(GenerateError::Import);
+        }
+        let mut techniques = TechniqueRegistry::builtin();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (mut grid, solution) = solved_grid(&mut rng);
+        match &spec.variant {
+            Variant::Classic => remove_random_clues(&mut grid, &mut rng),
+            Variant::Imported(_) => unreachable!("imported puzzles aren't generated"),
+            Variant::Sandwich => {
+                let cells = grid.cells;
+                grid = Grid::with_constraints(vec![Box::new(Sandwich::of(&solution))]);
+                grid.cells = cells;
+                remove_clues_while_unique(&mut grid, &mut rng);
+            }
+            Variant::Thermo => {
+                let cells = grid.cells;
+                let thermos = thermometers(&solution, &mut rng).into_iter().map(|cells| Box::new(Thermometer { cells }) as Box<dyn Constraint>).collect();
+                grid = Grid::with_constraints(thermos);
+                grid.cells = cells;
+                remove_clues_while_unique(&mut grid, &mut rng);
+            }
+            Variant::Requiring(technique) => {
+                if techniques.get(technique).is_none() {
+                    return Err(GenerateError::UnknownTechnique(technique.clone()));
+                }
+                remove_clues_while_solvable(&mut grid, &mut rng, &techniques);
+                if !techniques.requires(&grid, technique) {
+                    return Err(GenerateError::NotRequired { seed, technique: technique.clone() });
+                }
+            }
+        }
+        let transform = spec.transform.filter(|_| matches!(spec.variant, Variant::Classic | Variant::Requiring(_)));
+        let solution = match transform {
+            Some(transform) => {
+                grid = transform.apply(&grid).ok_or(GenerateError::UnsupportedTransform(transform))?;
+                transform.apply_values(&solution)
+            }
+            None => solution,
+        };
+        let meta = PuzzleMeta { seed, difficulty: techniques.grade(&grid), variant: spec.variant.clone(), transform, ..PuzzleMeta::default() };
+        Ok(Puzzle { grid, solution: Some(solution), meta })
+    }
+
+    // The givens a share code lists, unless they break a rule. Whether they
+    // can be completed isn't checked.
+    pub fn givens(code: &str) -> Result<Grid, ImportError> {
+        let grid = Grid::from_share_code(code).ok_or(ImportError::ShareCode)?;
+        grid.invariants().map_err(ImportError::Invalid)?;
+        Ok(grid)
+    }
+
+    // The puzzle a share code lists the givens of. Like generated ones, it may
+    // have more than one solution; entries are checked against the first found.
+    pub fn import(code: &str) -> Result<Puzzle, ImportError> {
+        let grid = Self::givens(code)?;
+        let solution = *grid.solutions(1).first().ok_or(ImportError::NoSolution)?;
+        let difficulty = TechniqueRegistry::builtin().grade(&grid);
+        let meta = PuzzleMeta { difficulty, variant: Variant::Imported(grid.share_code()), source: Source::Import, ..PuzzleMeta::default() };
+        Ok(Puzzle { grid, solution: Some(solution), meta })
+    }
+}
+
+// A randomly filled grid with every cell a clue, and its values
+fn solved_grid<R: Rng + ?Sized>(rng: &mut R) -> (Grid, [[u8; 6]; 6]) {
+    let mut grid = Grid::new();
+
+    // 1. Generate full board
+    // Note: fill_randomly should always succeed for valid Sudoku rules,
+    // but we verify to prevent potential panics
+    let mut success = grid.fill_randomly(rng);
+    if !success {
+        // This should never happen with valid Sudoku logic,
+        // but if it does, try again with a new grid
+        grid = Grid::new();
+        success = grid.fill_randomly(rng);
+
+        // If it fails twice, panic with a clear message
+        if !success {
+            panic!("Failed to generate a valid Sudoku grid after multiple attempts. This indicates a critical bug in the generation algorithm.");
+        }
+    }
+
+    // 2. Capture Solution
+    let mut solution = [[0; 6]; 6];
+    for (solution_row, grid_row) in solution.iter_mut().zip(grid.cells.iter()) {
+        for (value, cell) in solution_row.iter_mut().zip(grid_row.iter()) {
+            // Safe to unwrap here because fill_randomly succeeded
+            *value = cell.value().expect("Grid should be fully filled after successful generation");
+        }
+    }
+
+    // 3. Mark all filled cells as fixed (initially)
+    for cell in grid.cells.iter_mut().flatten() {
+        cell.set_fixed(true);
+    }
+
+    (grid, solution)
+}
+
+// Classic puzzles: random cells emptied until CLASSIC_CLUES are left
+fn remove_random_clues<R: Rng + ?Sized>(grid: &mut Grid, rng: &mut R) {
+    let mut removed_count = 0;
+    let target_removed = 36 - CLASSIC_CLUES;
+
+    while removed_count < target_removed {
+        let r = rng.random_range(0..6);
+        let c = rng.random_range(0..6);
+
+        if grid.cells[r][c].value().is_some() {
+            grid.cells[r][c] = Cell::default();
+            removed_count += 1;
+        }
+    }
+}
+
+// `--require` puzzles: clues are taken out in random order for as long as the
+// techniques can still solve the grid without them
+fn remove_clues_while_solvable<R: Rng + ?Sized>(grid: &mut Grid, rng: &mut R, techniques: &TechniqueRegistry) {
+    let mut order: Vec<(usize, usize)> = (0..36).map(|i| (i / 6, i % 6)).collect();
+    order.shuffle(rng);
+    for (r, c) in order {
+        let clue = grid.cells[r][c];
+        grid.cells[r][c] = Cell::default();
+        if techniques.grade(grid).is_none() {
+            grid.cells[r][c] = clue;
+        }
+    }
+}
+
+// Sandwich puzzles: the sums do much of the work, so clues are taken out in
+// random order for as long as the puzzle keeps a single solution
+fn remove_clues_while_unique<R: Rng + ?Sized>(grid: &mut Grid, rng: &mut R) {
+    let mut order: Vec<(usize, usize)> = (0..36).map(|i| (i / 6, i % 6)).collect();
+    order.shuffle(rng);
+    for (r, c) in order {
+        let clue = grid.cells[r][c];
+        grid.cells[r][c] = Cell::default();
+        if grid.solutions(2).len() > 1 {
+            grid.cells[r][c] = clue;
+        }
+    }
+}
+
+// Paths of rising values through the solution, from random bulbs to
+// neighbouring cells in random order, none crossing another
+fn thermometers<R: Rng + ?Sized>(solution: &[[u8; 6]; 6], rng: &mut R) -> Vec<Vec<(usize, usize)>> {
+    let mut bulbs: Vec<(usize, usize)> = (0..36).map(|i| (i / 6, i % 6)).collect();
+    bulbs.shuffle(rng);
+    let mut used = [[false; 6]; 6];
+    let mut thermos = Vec::new();
+    for bulb in bulbs {
+        if thermos.len() == THERMOS {
+            break;
+        }
+        let mut path = vec![bulb];
+        while path.len() < THERMO_LENGTH {
+            let (r, c) = path[path.len() - 1];
+            let next: Vec<(usize, usize)> = [(r.wrapping_sub(1), c), (r + 1, c), (r, c.wrapping_sub(1)), (r, c + 1)]
+                .into_iter()
+                .filter(|&(nr, nc)| nr < 6 && nc < 6 && !used[nr][nc] && solution[nr][nc] > solution[r][c])
+                .collect();
+            let Some(&step) = next.choose(rng) else {
+                break;
+            };
+            path.push(step);
+        }
+        if path.len() >= 3 && path.iter().all(|&(r, c)| !used[r][c]) {
+            path.iter().for_each(|&(r, c)| used[r][c] = true);
+            thermos.push(path);
+        }
+    }
+    thermos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GenerateError, Generator, ImportError, PuzzleSpec};
+    use crate::{model::BoardSpec, puzzle::Variant};
+
+    #[test]
+    fn generation_is_seeded_and_reports_why_it_fails() {
+        let puzzle = Generator::generate(42, &PuzzleSpec::default()).unwrap();
+        assert_eq!(puzzle.grid.clue_count(), 16);
+        assert_eq!(puzzle.grid.invariants(), Ok(()));
+        assert_eq!(Generator::generate(42, &PuzzleSpec::default()).unwrap().grid.to_string(), puzzle.grid.to_string());
+
+        let board = BoardSpec::new(9, 3, 3);
+        assert_eq!(Generator::generate(42, &PuzzleSpec { board, ..PuzzleSpec::default() }).err(), Some(GenerateError::UnsupportedBoard(board)));
+        let quarter = crate::symmetry::Transform { turns: 1, ..crate::symmetry::Transform::identity() };
+        let turned = PuzzleSpec { transform: Some(quarter), ..PuzzleSpec::default() };
+        assert_eq!(Generator::generate(42, &turned).err(), Some(GenerateError::UnsupportedTransform(quarter)));
+        let requiring = |technique: &str| PuzzleSpec { variant: Variant::Requiring(technique.to_string()), ..PuzzleSpec::default() };
+        assert_eq!(Generator::generate(42, &requiring("guessing")).err(), Some(GenerateError::UnknownTechnique("guessing".to_string())));
+        assert_eq!(
+            Generator::generate(512, &requiring("locked_candidates")).err(),
+            Some(GenerateError::NotRequired { seed: 512, technique: "locked_candidates".to_string() })
+        );
+    }
+
+    #[test]
+    fn sandwich_puzzles_have_one_solution() {
+        let sandwich = PuzzleSpec { variant: Variant::Sandwich, ..PuzzleSpec::default() };
+        let puzzle = Generator::generate(42, &sandwich).unwrap();
+        assert!(puzzle.grid.clue_count() < 16);
+        assert_eq!(puzzle.grid.solutions(2), [puzzle.solution.unwrap()]);
+        assert_eq!(puzzle.grid.constraints.last().map(|rule| rule.name()), Some("Sandwich"));
+        assert_eq!(Generator::generate(42, &sandwich).unwrap().grid.to_string(), puzzle.grid.to_string());
+    }
+
+    #[test]
+    fn thermometers_rise_through_the_solution() {
+        let thermo = PuzzleSpec { variant: Variant::Thermo, ..PuzzleSpec::default() };
+        let puzzle = Generator::generate(42, &thermo).unwrap();
+        let solution = puzzle.solution.unwrap();
+        assert_eq!(puzzle.grid.solutions(2), [solution]);
+        let paths: Vec<_> = puzzle.grid.constraints.iter().filter_map(|rule| rule.path()).collect();
+        assert!(!paths.is_empty());
+        for path in paths {
+            assert!(path.len() >= 3);
+            assert!(path.windows(2).all(|pair| solution[pair[0].0][pair[0].1] < solution[pair[1].0][pair[1].1]));
+            assert!(path.windows(2).all(|pair| pair[0].0.abs_diff(pair[1].0) + pair[0].1.abs_diff(pair[1].1) == 1));
+        }
+    }
+
+    #[test]
+    fn imports_need_a_solution() {
+        let puzzle = Generator::generate(7, &PuzzleSpec::default()).unwrap();
+        let code = puzzle.grid.share_code();
+        let imported = Generator::import(&code).unwrap();
+        assert_eq!((imported.grid.share_code(), imported.meta.key()), (code.clone(), code.clone()));
+        let mut solved = imported.grid.clone();
+        for (r, c) in (0..36).map(|i| (i / 6, i % 6)) {
+            solved.cells[r][c].set_value(imported.solution.map(|solution| solution[r][c]));
+        }
+        assert!(solved.is_solved());
+        assert!(imported.grid.cells.iter().flatten().all(|cell| cell.value().is_none() || cell.is_fixed()));
+
+        assert_eq!(Generator::import("RD6:123").err(), Some(ImportError::ShareCode));
+        let stuck = format!("RD6:123450{}000006", "0".repeat(24));
+        assert_eq!(Generator::import(&stuck).err(), Some(ImportError::NoSolution));
+        let clash = format!("RD6:11{}", "0".repeat(34));
+        assert!(matches!(Generator::import(&clash), Err(ImportError::Invalid(_))));
+    }
+}
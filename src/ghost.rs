@@ -0,0 +1,126 @@
+// Personal-best ghost: for each puzzle solved before, the clock times at which
+// the best solve had 1, 2, 3... correct entries. Replaying the puzzle (the daily
+// one included) shows that solve's progress advancing next to the current
+// one. The fastest solve per puzzle is kept in the data dir, by its key.
+
+use std::{io, path::Path, time::Duration};
+
+use crate::storage;
+
+const GHOSTS_FILE: &str = "ghosts.csv";
+
+#[derive(Debug, Default)]
+pub struct Ghost {
+    // The puzzle's key, see PuzzleMeta::key
+    pub key: String,
+    // The best solve's progress, None if the puzzle hasn't been solved before
+    pub best: Option<Vec<Duration>>,
+    // This solve's progress so far
+    pub current: Vec<Duration>,
+    saved: bool,
+}
+
+impl Ghost {
+    pub fn load(key: &str) -> Self {
+        let best = storage::read(GHOSTS_FILE).and_then(|text| text.lines().filter_map(parse).find(|(k, _)| k == key).map(|(_, times)| times));
+        Self { key: key.to_string(), best, ..Self::default() }
+    }
+
+    // Note how many entries are correct now. Only new highs count, so
+    // correcting a mistake doesn't count twice.
+    pub fn record(&mut self, correct: usize, at: Duration) {
+        while self.current.len() < correct {
+            self.current.push(at);
+        }
+    }
+
+    // Correct entries the best solve had made by `elapsed`
+    pub fn progress(&self, elapsed: Duration) -> Option<usize> {
+        self.best.as_ref().map(|best| best.iter().take_while(|&&at| at <= elapsed).count())
+    }
+
+    // Entries to go in the whole puzzle, known once there's a best solve
+    pub fn total(&self) -> Option<usize> {
+        self.best.as_ref().map(Vec::len)
+    }
+
+    // Keep this solve if it beat the best one. Returns whether it did. Solves
+    // the player didn't make all `entries` of themselves, e.g. finished in
+    // watch mode, aren't kept.
+    pub fn finish(&mut self, entries: usize) -> io::Result<bool> {
+        let (Some(&time), false) = (self.current.last(), self.saved || self.current.len() != entries) else {
+            return Ok(false);
+        };
+        self.saved = true;
+        if self.best.as_ref().and_then(|best| best.last()).is_some_and(|&best| best <= time) {
+            return Ok(false);
+        }
+        // Against the best as saved now, in case another install beat it meanwhile
+        storage::update(GHOSTS_FILE, |text| merge(text, &encode(&self.key, &self.current)))?;
+        Ok(true)
+    }
+}
+
+// Keys of the puzzles solved before, oldest first
+pub fn solved() -> Vec<String> {
+    storage::read(GHOSTS_FILE).map_or(Vec::new(), |text| text.lines().filter_map(parse).map(|(key, _)| key).collect())
+}
+
+// Merge the best solves kept by another install, in its data dir `from`,
+// into ours. The faster solve of each puzzle is kept.
+pub fn import(from: &Path) -> io::Result<bool> {
+    storage::merge_from(from, GHOSTS_FILE, merge)
+}
+
+fn merge(ours: &str, theirs: &str) -> String {
+    let mut best: Vec<(String, Vec<Duration>)> = ours.lines().filter_map(parse).collect();
+    for (key, times) in theirs.lines().filter_map(parse) {
+        match best.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, kept)) if kept.last() <= times.last() => {}
+            Some((_, kept)) => *kept = times,
+            None => best.push((key, times)),
+        }
+    }
+    best.iter().map(|(key, times)| encode(key, times) + "\n").collect()
+}
+
+// e.g. `42,3100 5200 9800`: the puzzle key, then milliseconds per correct entry
+fn encode(key: &str, times: &[Duration]) -> String {
+    let times: Vec<String> = times.iter().map(|at| at.as_millis().to_string()).collect();
+    format!("{},{}", key, times.join(" "))
+}
+
+fn parse(line: &str) -> Option<(String, Vec<Duration>)> {
+    let (key, times) = line.split_once(',')?;
+    let times = times.split_whitespace().map(|ms| ms.parse().ok().map(Duration::from_millis)).collect::<Option<Vec<_>>>()?;
+    Some((key.trim().to_string(), times))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode, merge, parse, Ghost};
+    use std::time::Duration;
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    #[test]
+    fn ghost_replays_the_best_solve() {
+        let mut ghost = Ghost { best: Some(vec![secs(2), secs(5), secs(9)]), ..Ghost::default() };
+        assert_eq!((ghost.progress(secs(1)), ghost.progress(secs(5)), ghost.progress(secs(60))), (Some(0), Some(2), Some(3)));
+        assert_eq!(ghost.total(), Some(3));
+
+        ghost.record(1, secs(3));
+        ghost.record(0, secs(4)); // a correct entry was cleared
+        ghost.record(3, secs(8));
+        assert_eq!(ghost.current, vec![secs(3), secs(8), secs(8)]);
+
+        assert_eq!(parse(&encode("42/naked_pair", &ghost.current)), Some(("42/naked_pair".to_string(), ghost.current.clone())));
+        assert_eq!(parse("42,12 x"), None);
+        assert_eq!(Ghost::default().progress(secs(1)), None);
+
+        // Merging another install's solves keeps the faster of each
+        assert_eq!(merge("1,5 9\n2,4 8\n", "2,3 6\n1,4 10\n3,7\n"), "1,5 9\n2,3 6\n3,7\n");
+    }
+}
@@ -0,0 +1,376 @@
+// Raster board rendering for terminals that speak the Kitty graphics protocol
+// or sixel (which also covers iTerm2). The character-cell board is always drawn
+// first, so an unsupported terminal simply keeps showing it.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    io::{self, Write},
+};
+
+use ratatui::{layout::Rect, style::{Color, Modifier}};
+
+use crate::{model::Game, ui};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Protocol {
+    Kitty,
+    Sixel,
+}
+
+// Resolve the `graphics` config setting ("off", "auto", "kitty" or "sixel").
+// `auto` guesses from the environment, since querying the terminal would
+// race with the input loop.
+pub fn detect(setting: &str, env: impl Fn(&str) -> Option<String>) -> Option<Protocol> {
+    match setting {
+        "kitty" => return Some(Protocol::Kitty),
+        "sixel" => return Some(Protocol::Sixel),
+        "auto" => {}
+        _ => return None,
+    }
+
+    let term = env("TERM").unwrap_or_default();
+    let program = env("TERM_PROGRAM").unwrap_or_default();
+    // Multiplexers don't pass graphics through reliably
+    if term.starts_with("screen") || term.starts_with("tmux") {
+        return None;
+    }
+    if env("KITTY_WINDOW_ID").is_some() || term.contains("kitty") || program == "WezTerm" || program == "ghostty" {
+        return Some(Protocol::Kitty);
+    }
+    if program == "iTerm.app" || term == "foot" || term.starts_with("mlterm") || term.contains("sixel") {
+        return Some(Protocol::Sixel);
+    }
+    None
+}
+
+// An RGB image
+pub struct Raster {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl Raster {
+    fn new(width: usize, height: usize, color: [u8; 3]) -> Self {
+        Self { width, height, pixels: vec![color; width * height] }
+    }
+
+    fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, color: [u8; 3]) {
+        for py in y..(y + h).min(self.height) {
+            for px in x..(x + w).min(self.width) {
+                self.pixels[py * self.width + px] = color;
+            }
+        }
+    }
+
+    // Draw one digit from the 5x7 font with its top-left at (x, y)
+    fn draw_digit(&mut self, digit: char, x: usize, y: usize, scale: usize, color: [u8; 3]) {
+        let Some(rows) = digit.to_digit(10).map(|d| FONT[d as usize]) else {
+            return;
+        };
+        for (dy, row) in rows.iter().enumerate() {
+            for dx in 0..5 {
+                if row & (0b10000 >> dx) != 0 {
+                    self.fill_rect(x + dx * scale, y + dy * scale, scale, scale, color);
+                }
+            }
+        }
+    }
+
+    fn draw_digit_centered(&mut self, digit: char, x: usize, y: usize, w: usize, h: usize, color: [u8; 3]) {
+        let scale = (h * 6 / 10 / 7).min(w * 6 / 10 / 5).max(1);
+        self.draw_digit(digit, x + w.saturating_sub(5 * scale) / 2, y + h.saturating_sub(7 * scale) / 2, scale, color);
+    }
+}
+
+// 5x7 digit glyphs, one row per byte, most significant of the low 5 bits leftmost
+const FONT: [[u8; 7]; 10] = [
+    [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+    [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+    [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+    [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110],
+    [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+    [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+    [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+    [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+    [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+];
+
+// Approximate RGB for the colors the board uses
+fn rgb(color: Option<Color>, fallback: [u8; 3]) -> [u8; 3] {
+    match color {
+        Some(Color::Rgb(r, g, b)) => [r, g, b],
+        Some(Color::Black) => [0, 0, 0],
+        Some(Color::Red) => [205, 49, 49],
+        Some(Color::Green) => [13, 188, 121],
+        Some(Color::Yellow) => [229, 229, 16],
+        Some(Color::Blue) => [36, 114, 200],
+        Some(Color::Cyan) => [17, 168, 205],
+        Some(Color::Gray) => [170, 170, 170],
+        Some(Color::DarkGray) => [102, 102, 102],
+        Some(Color::LightRed) => [241, 76, 76],
+        Some(Color::LightGreen) => [35, 209, 139],
+        Some(Color::White) => [229, 229, 229],
+        _ => fallback,
+    }
+}
+
+// Paint the board into a `width` x `height` image, using the same cell
+// colors and contents as the character-cell renderer
+pub fn render_board(game: &Game, width: usize, height: usize) -> Raster {
+    let line_color = rgb(Some(Color::Blue), [0, 0, 255]);
+    let mut raster = Raster::new(width, height, line_color);
+
+    let thick = (width.min(height) / 80).max(2);
+    let thin = (thick / 2).max(1);
+    // Across: two borders, one thick box line and four thin lines.
+    // Down: two borders, two thick box lines and three thin lines.
+    let cell_w = width.saturating_sub(2 * thick + thick + 4 * thin) / 6;
+    let cell_h = height.saturating_sub(2 * thick + 2 * thick + 3 * thin) / 6;
+
+    let mut y = thick;
+    for r in 0..6 {
+        let mut x = thick;
+        for c in 0..6 {
+            let (text, style) = ui::cell_appearance(game, r, c);
+            let bg = rgb(style.bg, [0, 0, 0]);
+            let fg = rgb(style.fg, [255, 255, 255]);
+            raster.fill_rect(x, y, cell_w, cell_h, bg);
+
+            if text.chars().count() == 6 {
+                // Pencil marks in a 3x2 mini grid
+                for (i, ch) in text.chars().enumerate() {
+                    let (mw, mh) = (cell_w / 3, cell_h / 2);
+                    raster.draw_digit_centered(ch, x + (i % 3) * mw, y + (i / 3) * mh, mw, mh, fg);
+                }
+            } else if let Some(ch) = text.trim().chars().next() {
+                raster.draw_digit_centered(ch, x, y, cell_w, cell_h, fg);
+            }
+            // Single marks and locked entries are underlined
+            if style.add_modifier.contains(Modifier::UNDERLINED) {
+                raster.fill_rect(x + cell_w / 4, y + cell_h * 5 / 6, cell_w / 2, thin, fg);
+            }
+
+            x += cell_w + if c == 2 { thick } else { thin };
+        }
+        y += cell_h + if r % 2 == 1 { thick } else { thin };
+    }
+    raster
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(n >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+// Kitty graphics protocol: transmit raw RGB as image id 1 and place it over
+// `cols` x `rows` cells at the cursor, replacing any previous board image
+pub fn encode_kitty(raster: &Raster, cols: u16, rows: u16) -> String {
+    let bytes: Vec<u8> = raster.pixels.iter().flatten().copied().collect();
+    let payload = base64(&bytes);
+    let chunks: Vec<&str> = payload.as_bytes().chunks(4096).map(|c| std::str::from_utf8(c).unwrap_or("")).collect();
+
+    let mut out = String::from("\x1b_Ga=d,d=I,i=1,q=2\x1b\\");
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        if i == 0 {
+            out.push_str(&format!(
+                "\x1b_Ga=T,f=24,s={},v={},c={},r={},i=1,C=1,q=2,m={};{}\x1b\\",
+                raster.width, raster.height, cols, rows, more, chunk
+            ));
+        } else {
+            out.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+    out
+}
+
+// Sixel: a palette of the image's colors, then bands of six pixel rows,
+// one pass per color, run-length encoded
+pub fn encode_sixel(raster: &Raster) -> String {
+    let mut palette: Vec<[u8; 3]> = Vec::new();
+    let indices: Vec<usize> = raster
+        .pixels
+        .iter()
+        .map(|color| match palette.iter().position(|p| p == color) {
+            Some(i) => i,
+            None => {
+                palette.push(*color);
+                palette.len() - 1
+            }
+        })
+        .collect();
+
+    let mut out = format!("\x1bPq\"1;1;{};{}", raster.width, raster.height);
+    for (i, [r, g, b]) in palette.iter().enumerate() {
+        let pct = |v: u8| v as u32 * 100 / 255;
+        out.push_str(&format!("#{};2;{};{};{}", i, pct(*r), pct(*g), pct(*b)));
+    }
+
+    for band in (0..raster.height).step_by(6) {
+        for color in 0..palette.len() {
+            let sixels: Vec<u8> = (0..raster.width)
+                .map(|x| {
+                    let mut bits = 0;
+                    for dy in 0..6 {
+                        let y = band + dy;
+                        if y < raster.height && indices[y * raster.width + x] == color {
+                            bits |= 1 << dy;
+                        }
+                    }
+                    bits
+                })
+                .collect();
+            if sixels.iter().all(|&bits| bits == 0) {
+                continue;
+            }
+            out.push_str(&format!("#{}", color));
+            let mut x = 0;
+            while x < sixels.len() {
+                let run = sixels[x..].iter().take_while(|&&bits| bits == sixels[x]).count();
+                let ch = (63 + sixels[x]) as char;
+                if run > 3 {
+                    out.push_str(&format!("!{}{}", run, ch));
+                } else {
+                    out.extend(std::iter::repeat_n(ch, run));
+                }
+                x += run;
+            }
+            out.push('$');
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+    out
+}
+
+// Draws the raster board over the character-cell board after each frame,
+// re-sending the image only when the board actually changes
+pub struct GraphicsRenderer {
+    protocol: Protocol,
+    // Pixel size of one terminal cell
+    cell_px: (usize, usize),
+    last: Option<u64>,
+}
+
+impl GraphicsRenderer {
+    // `cell_px` is the terminal's cell size in pixels, if it reports one
+    pub fn new(protocol: Protocol, cell_px: Option<(u16, u16)>) -> Self {
+        let cell_px = cell_px.map_or((10, 20), |(w, h)| (w as usize, h as usize));
+        Self { protocol, cell_px, last: None }
+    }
+
+    // Present the board image at `board` (None when the board is hidden or
+    // covered by a popup). Returns true if the screen must be fully redrawn
+    // to get rid of a stale image.
+    pub fn present<W: Write>(&mut self, out: &mut W, game: &Game, board: Option<Rect>) -> io::Result<bool> {
+        let Some(rect) = board else {
+            let was_shown = self.last.take().is_some();
+            if was_shown && self.protocol == Protocol::Kitty {
+                write!(out, "\x1b_Ga=d,d=I,i=1,q=2\x1b\\")?;
+                out.flush()?;
+            }
+            return Ok(was_shown && self.protocol == Protocol::Sixel);
+        };
+
+        let mut hasher = DefaultHasher::new();
+        (rect.x, rect.y, rect.width, rect.height).hash(&mut hasher);
+        for r in 0..6 {
+            for c in 0..6 {
+                ui::cell_appearance(game, r, c).hash(&mut hasher);
+            }
+        }
+        let hash = hasher.finish();
+        if self.last == Some(hash) {
+            return Ok(false);
+        }
+        self.last = Some(hash);
+
+        let raster = render_board(game, rect.width as usize * self.cell_px.0, rect.height as usize * self.cell_px.1);
+        let image = match self.protocol {
+            Protocol::Kitty => encode_kitty(&raster, rect.width, rect.height),
+            Protocol::Sixel => encode_sixel(&raster),
+        };
+        // Move the cursor (1-based) to the board's top-left corner
+        write!(out, "\x1b[{};{}H", rect.y + 1, rect.x + 1)?;
+        out.write_all(image.as_bytes())?;
+        out.flush()?;
+        Ok(false)
+    }
+
+    // After a resize: the font size may have changed, and whatever image was
+    // on screen is gone or in the wrong place
+    pub fn resized(&mut self, cell_px: Option<(u16, u16)>) {
+        if let Some((w, h)) = cell_px {
+            self.cell_px = (w as usize, h as usize);
+        }
+        self.last = None;
+    }
+
+    // Remove the image before leaving the alternate screen
+    pub fn clear<W: Write>(&mut self, out: &mut W) -> io::Result<()> {
+        if self.protocol == Protocol::Kitty {
+            write!(out, "\x1b_Ga=d,d=I,i=1,q=2\x1b\\")?;
+            out.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{base64, detect, encode_sixel, render_board, Protocol, Raster};
+    use crate::model::Game;
+
+    #[test]
+    fn base64_matches_rfc_vectors() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn detect_uses_setting_then_environment() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |key: &str| vars.iter().find(|(k, _)| *k == key).map(|(_, v)| v.to_string())
+        };
+        assert_eq!(detect("off", env(&[("KITTY_WINDOW_ID", "1")])), None);
+        assert_eq!(detect("sixel", env(&[])), Some(Protocol::Sixel));
+        assert_eq!(detect("auto", env(&[("TERM", "xterm-kitty")])), Some(Protocol::Kitty));
+        assert_eq!(detect("auto", env(&[("TERM_PROGRAM", "iTerm.app")])), Some(Protocol::Sixel));
+        assert_eq!(detect("auto", env(&[("TERM", "tmux-256color"), ("KITTY_WINDOW_ID", "1")])), None);
+        assert_eq!(detect("auto", env(&[("TERM", "xterm-256color")])), None);
+    }
+
+    #[test]
+    fn sixel_encodes_runs_per_color() {
+        let mut raster = Raster::new(8, 6, [0, 0, 0]);
+        raster.fill_rect(0, 0, 2, 6, [255, 255, 255]);
+        let sixel = encode_sixel(&raster);
+        assert!(sixel.starts_with("\x1bPq\"1;1;8;6#0;2;100;100;100#1;2;0;0;0"));
+        assert!(sixel.contains("#0~~!6?$"));
+        assert!(sixel.contains("#1??!6~$"));
+        assert!(sixel.ends_with("-\x1b\\"));
+    }
+
+    #[test]
+    fn render_board_fills_requested_size() {
+        let raster = render_board(&Game::with_seed(5), 300, 280);
+        assert_eq!(raster.pixels.len(), 300 * 280);
+    }
+}
@@ -0,0 +1,73 @@
+// Hot-seat mode: two players share one board and terminal, taking turns. Each
+// value entry ends the turn, counting as placed when correct and as a mistake
+// otherwise. Clearing a value ends it too, counting nothing, so an entry
+// can't be taken back for free. Pencil marks are free. The score is placed minus mistakes.
+
+pub const PLAYERS: usize = 2;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Player {
+    pub placed: u32,
+    pub mistakes: u32,
+}
+
+impl Player {
+    pub fn score(&self) -> i64 {
+        self.placed as i64 - self.mistakes as i64
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct HotSeat {
+    pub players: [Player; PLAYERS],
+    // Index of the player whose turn it is
+    pub turn: usize,
+}
+
+impl HotSeat {
+    // Count an entry for the current player and pass the turn
+    pub fn record(&mut self, correct: bool) {
+        let player = &mut self.players[self.turn];
+        if correct {
+            player.placed += 1;
+        } else {
+            player.mistakes = player.mistakes.saturating_add(1);
+        }
+        self.pass();
+    }
+
+    // Pass the turn to the next player without counting anything
+    pub fn pass(&mut self) {
+        self.turn = (self.turn + 1) % PLAYERS;
+    }
+
+    // The player with the highest score, None on a draw
+    pub fn winner(&self) -> Option<usize> {
+        let best = self.players.iter().map(Player::score).max()?;
+        let mut leaders = (0..PLAYERS).filter(|&i| self.players[i].score() == best);
+        let first = leaders.next()?;
+        leaders.next().is_none().then_some(first)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HotSeat;
+
+    #[test]
+    fn turns_alternate_and_scores_decide_winner() {
+        let mut seat = HotSeat::default();
+        seat.record(true);
+        assert_eq!(seat.turn, 1);
+        seat.record(false);
+        assert_eq!(seat.turn, 0);
+        assert_eq!((seat.players[0].placed, seat.players[1].mistakes), (1, 1));
+        assert_eq!(seat.winner(), Some(0));
+
+        seat.record(false);
+        seat.record(true);
+        assert_eq!(seat.winner(), None); // both at 1 placed, 1 mistake
+        seat.record(true);
+        assert_eq!(seat.winner(), Some(0));
+    }
+}
@@ -0,0 +1,81 @@
+// Minimal blocking HTTP client over plain TCP, enough for the daily
+// puzzle endpoint. Only `http://` URLs are supported (no TLS dependency), and
+// requests are sent as HTTP/1.0 so servers reply without chunked encoding.
+
+use std::{
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(3);
+
+pub fn get(url: &str) -> io::Result<String> {
+    request("GET", url, None)
+}
+
+pub fn post(url: &str, body: &str) -> io::Result<String> {
+    request("POST", url, Some(body))
+}
+
+// Splits `http://host[:port]/path` into (host, port, path)
+fn parse_url(url: &str) -> io::Result<(String, u16, String)> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "only http:// URLs are supported"))?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => {
+            let port = port.parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "bad port"))?;
+            (host, port)
+        }
+        None => (authority, 80),
+    };
+    Ok((host.to_string(), port, path.to_string()))
+}
+
+fn request(method: &str, url: &str, body: Option<&str>) -> io::Result<String> {
+    let (host, port, path) = parse_url(url)?;
+    let addr = (host.as_str(), port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "host did not resolve"))?;
+    let mut stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let body = body.unwrap_or("");
+    write!(
+        stream,
+        "{} {} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\nContent-Type: application/x-www-form-urlencoded\r\nContent-Length: {}\r\n\r\n{}",
+        method,
+        path,
+        host,
+        body.len(),
+        body
+    )?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let (head, content) = response.split_once("\r\n\r\n").unwrap_or((&response, ""));
+    let status = head.split_whitespace().nth(1).unwrap_or("");
+    if !status.starts_with('2') {
+        return Err(io::Error::other(format!("server replied {}", status)));
+    }
+    Ok(content.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_url;
+
+    #[test]
+    fn parse_url_splits_parts() {
+        assert_eq!(parse_url("http://example.com").unwrap(), ("example.com".to_string(), 80, "/".to_string()));
+        assert_eq!(parse_url("http://host:8080/daily/x").unwrap(), ("host".to_string(), 8080, "/daily/x".to_string()));
+        assert!(parse_url("https://example.com").is_err());
+    }
+}
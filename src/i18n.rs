@@ -0,0 +1,581 @@
+// The text the game shows, per language. Messages are looked up by key in
+// the catalog of the language picked at startup, from config `language` or
+// else `LC_ALL`, `LC_MESSAGES` or `LANG`; a missing one falls back to
+// English. `{}` in a message is filled in order, so a translation can't
+// reorder its arguments. Puzzle data such as technique names and the
+// changelog stay in English, as does the F12 debug overlay.
+
+use std::{env, fmt::Display, sync::OnceLock};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::De];
+
+    // A language tag such as "de", "de-AT" or "de_DE.UTF-8"
+    pub fn parse(tag: &str) -> Option<Self> {
+        let language = tag.split(['_', '-', '.', '@']).next()?.to_ascii_lowercase();
+        match language.as_str() {
+            "en" | "c" | "posix" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            _ => None,
+        }
+    }
+
+    // The first of the locale variables that's set, as the C library reads them
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok().filter(|value| !value.is_empty()))
+            .next()
+            .and_then(|value| Self::parse(&value))
+            .unwrap_or_default()
+    }
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => EN,
+            Locale::De => DE,
+        }
+    }
+
+    pub fn lookup(self, key: &str) -> Option<&'static str> {
+        self.catalog().iter().find(|&&(k, _)| k == key).map(|&(_, message)| message)
+    }
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+// Pick the language for the rest of the run; only the first call counts
+pub fn set_locale(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+pub fn locale() -> Locale {
+    LOCALE.get().copied().unwrap_or_default()
+}
+
+// The message for `key`, empty if there's none in English either
+pub fn t(key: &str) -> &'static str {
+    locale().lookup(key).or_else(|| Locale::En.lookup(key)).unwrap_or("")
+}
+
+// The message for `key` with each `{}` filled from `args` in turn
+pub fn tf(key: &str, args: &[&dyn Display]) -> String {
+    fill(t(key), args)
+}
+
+fn fill(message: &str, args: &[&dyn Display]) -> String {
+    let mut parts = message.split("{}");
+    let mut text = parts.next().unwrap_or("").to_string();
+    for (i, part) in parts.enumerate() {
+        if let Some(arg) = args.get(i) {
+            text.push_str(&arg.to_string());
+        }
+        text.push_str(part);
+    }
+    text
+}
+
+const EN: &[(&str, &str)] = &[
+    // Status bar
+    ("status.watching", "Watching {}"),
+    ("status.solved_in", "SOLVED in {}"),
+    ("status.time", "Time: {}"),
+    ("status.mistakes", "Mistakes: {}"),
+    ("status.mistakes_of", "Mistakes: {}/{}"),
+    ("status.what_if", "WHAT-IF"),
+    ("status.player_to_play", "Player {} to play"),
+    ("status.coop", "Co-op P{} with {} others"),
+    ("status.solved_points", "Solved! +{}"),
+    ("status.streak", "Streak {}"),
+    ("status.on_track", "On track: no wrong entries"),
+    ("status.off_track_one", "Off track: 1 entry is wrong"),
+    ("status.off_track", "Off track: {} entries are wrong"),
+    ("status.blocked", "Blocked: {} is already in this {} at r{}c{}"),
+    ("status.dead_end", "Dead end: an entry must be wrong ({}: find it)"),
+    ("status.go_to", "Go to: {}"),
+    ("status.typical", "Typical: {}, pace {}"),
+    ("status.record_macro", "Record a macro"),
+    ("status.play_macro", "Play a macro"),
+    ("status.recording", "Recording macro {}"),
+    ("status.wrong_entry", "Wrong entry, {}: Why?"),
+    ("status.hint_wrong", "Hint: r{}c{} is wrong"),
+    ("status.hint", "Hint: {} at r{}c{}"),
+    ("status.mode", "Mode: {} ({})"),
+    ("status.solved", "SOLVED!"),
+    ("status.won", "YOU WON!"),
+    ("status.heatmap", "HEATMAP: {} wrong entries in {} cells"),
+    ("status.viewing", "VIEWING {}/{}"),
+    ("status.bot_won", "The bot finished {} sooner"),
+    ("status.bot_beaten", "You beat the bot"),
+    ("status.preview", "Start the clock when you're ready"),
+    ("status.generating", "Looking for a puzzle"),
+    ("status.out_of_mistakes", "CHALLENGE OVER: {} mistakes"),
+    ("status.time_up", "TIME'S UP! Streak: {} (best {})"),
+    ("status.paused", "PAUSED at {}"),
+    ("status.thanks", "Thanks for playing!"),
+    ("status.scan", "Scan the code to open this puzzle elsewhere"),
+    ("status.history", "HISTORY: entry {} (branch {}/{})"),
+    ("status.watch_wrong", "WATCH: {} at r{}c{}"),
+    ("status.watch_step", "WATCH: {} r{}c{} = {}"),
+    ("status.watch_thinking", "WATCH: Solver is thinking..."),
+    ("mode.normal", "NORMAL"),
+    ("mode.pencil", "PENCIL"),
+    ("unit.row", "row"),
+    ("unit.column", "column"),
+    ("unit.box", "box"),
+    ("unit.diagonal", "diagonal"),
+    ("status.time_left", "{} left"),
+    // Compact status bar, before the keys
+    ("compact.watch", "watch {} ✗{}"),
+    ("compact.on_track", "on track!"),
+    ("compact.off_track", "off track: {} wrong"),
+    ("compact.blocked", "{} already at r{}c{}"),
+    ("compact.hint_wrong", "r{}c{} is wrong"),
+    ("compact.what_if", "what-if {} ✗{}"),
+    ("compact.what_if_keys", "{}: keep {}: discard"),
+    ("compact.won", "WON {} ✗{}"),
+    ("compact.viewing", "VIEW {}/{}"),
+    ("compact.preview", "PREVIEW {}c {}"),
+    ("compact.searching", "SEARCHING {}"),
+    ("compact.time_up", "TIME UP streak {}"),
+    ("compact.out_of_mistakes", "OUT OF MISTAKES {}"),
+    ("compact.paused", "PAUSED {}"),
+    ("compact.any_key", "any key"),
+    ("compact.any_key_quit", "any key: quit"),
+    ("compact.history", "hist {} {}/{}"),
+    ("compact.watch_solver", "WATCH"),
+    // What a spectator sees the player at
+    ("screen.playing", "Playing"),
+    ("screen.time_up", "Time's up"),
+    ("screen.out_of_mistakes", "Out of mistakes"),
+    ("screen.viewing", "Viewing"),
+    ("screen.preview", "On the preview"),
+    ("screen.generating", "Looking for a puzzle"),
+    ("screen.demo", "Watching the solver"),
+    ("screen.paused", "Paused"),
+    ("screen.menus", "In the menus"),
+    // Post-win review
+    ("review.correction", "{} correction"),
+    ("review.corrections", "{} corrections"),
+    ("review.logical_error", "{} logical error"),
+    ("review.logical_errors", "{} logical errors"),
+    ("review.given", "r{}c{}: given"),
+    ("review.step", "r{}c{}: step {} of {}, {}"),
+    ("review.guessing", "r{}c{}: needs guessing"),
+    // Undo history
+    ("history.start", "start"),
+    ("history.cleared", "r{}c{} cleared"),
+    ("history.locked", "r{}c{} locked"),
+    ("history.unlocked", "r{}c{} unlocked"),
+    ("history.marks", "r{}c{} marks"),
+    // Variant rules, by constraint name
+    ("rule.diagonal", "Diagonal"),
+    ("rule.diagonal_text", "No digit twice on either long diagonal"),
+    ("rule.cage", "Cage"),
+    ("rule.cage_text", "Digits in a cage differ and add up to its sum"),
+    ("rule.consecutive", "Consecutive"),
+    ("rule.consecutive_text", "Marked neighbours differ by one"),
+    ("rule.sandwich", "Sandwich"),
+    ("rule.sandwich_text", "A sum outside the grid adds up the digits between the 1 and the 6 of its row or column"),
+    ("rule.thermometer", "Thermometer"),
+    ("rule.thermometer_text", "Digits rise along each thermometer from its bulb, the brighter end"),
+    // Key help, after the key
+    ("key.yes", "Yes"),
+    ("key.no", "No"),
+    ("key.ok", "OK"),
+    ("key.cancel", "Cancel"),
+    ("key.choose", "Choose"),
+    ("key.quit", "Quit"),
+    ("key.back", "Back"),
+    ("key.hide", "Hide"),
+    ("key.new_game", "New game"),
+    ("key.highlight", "Highlight"),
+    ("key.heatmap", "Heatmap"),
+    ("key.stats", "Stats"),
+    ("key.next", "Next"),
+    ("key.start", "Start"),
+    ("key.reroll", "Reroll"),
+    ("key.previous", "Previous"),
+    ("key.resume", "Resume"),
+    ("key.scroll", "Scroll"),
+    ("key.tab", "Tab"),
+    ("key.step", "Step"),
+    ("key.branch", "Branch"),
+    ("key.stop", "Stop"),
+    ("key.keep", "Keep"),
+    ("key.discard", "Discard"),
+    ("key.why", "Why?"),
+    ("key.enter", "Enter"),
+    ("key.mark", "Mark"),
+    ("key.lock", "Lock"),
+    ("key.row", "Row (or A-F)"),
+    ("key.register", "Register"),
+    ("key.repeat", "Repeat"),
+    ("key.column", "Column"),
+    ("key.unlock", "Unlock"),
+    ("key.clear", "Clear"),
+    ("key.undo", "Undo"),
+    ("key.hint", "Hint"),
+    ("key.counts", "Counts"),
+    ("key.hide_counts", "Hide counts"),
+    ("key.last_cells", "Last cells"),
+    ("key.hide_last_cells", "Hide last cells"),
+    ("key.coordinates", "Coordinates"),
+    ("key.hide_coordinates", "Hide coordinates"),
+    ("key.what_if", "What-if"),
+    ("key.watch", "Watch"),
+    ("key.share", "Share"),
+    ("key.about", "About"),
+    // Title
+    ("title.daily", "Daily {}"),
+    ("title.weekly", "Weekly {}"),
+    ("title.endless", "Endless #{} | Score {}"),
+    ("title.clues", "{} clues"),
+    ("title.seed", "Seed {}"),
+    ("title.by", "by {}"),
+    ("title.solving", "Solving..."),
+    ("title.several_solutions", "Several solutions"),
+    ("title.paused", "PAUSED"),
+    ("preview.title", "NEW PUZZLE"),
+    ("preview.clues", "Clues: {}"),
+    ("preview.symmetry", "Symmetry: {}"),
+    ("preview.needs", "Needs: {}"),
+    ("preview.or", "or"),
+    ("generating.profile", "Looking for a puzzle for profile {}"),
+    ("generating.attempts", "attempt {} of {}, {}s"),
+    ("preview.profiles", "Profiles: {}"),
+    ("preview.beyond", "more than the techniques can do"),
+    ("symmetry.quarter_turn", "quarter turn"),
+    ("symmetry.half_turn", "half turn"),
+    ("symmetry.mirror", "mirror"),
+    ("symmetry.flip", "flip"),
+    ("symmetry.none", "none"),
+    // Popups
+    ("popup.about", "About RustDoku6"),
+    ("popup.stats", "Stats"),
+    ("popup.session", "Session"),
+    ("popup.history", "History"),
+    ("popup.share", "Share Puzzle"),
+    ("popup.explain", "Why is this wrong?"),
+    ("popup.results", "Results"),
+    ("popup.splits", "Splits"),
+    ("splits.digit", "Digit {}"),
+    ("about.author", "Author: {}"),
+    ("about.license", "License: {}"),
+    ("about.source", "Source: {}"),
+    ("about.built_with", "Built with: {}"),
+    ("summary.attempted", "Puzzles attempted: {}"),
+    ("summary.solved", "Solved: {}"),
+    ("summary.total_time", "Total time: {}"),
+    ("summary.best", "Best solve: {}"),
+    ("stats.none", "No games recorded yet"),
+    ("stats.stars", "Stars"),
+    ("stats.played", "Played"),
+    ("stats.solved", "Solved"),
+    ("stats.best", "Best"),
+    ("stats.average", "Average"),
+    ("stats.corrections", "Corrections"),
+    ("stats.errors", "Errors"),
+    ("stats.rating", "Rating {} over {} rated games"),
+    ("stats.weekly", "Weekly challenge"),
+    ("stats.week", "Week"),
+    ("stats.result", "Result"),
+    ("stats.time", "Time"),
+    ("stats.mistakes", "Mistakes"),
+    ("hotseat.wins", "Player {} wins!"),
+    ("hotseat.draw", "It's a draw!"),
+    ("hotseat.player", "Player {}: {} placed, {} mistakes, score {}"),
+    ("hotseat.together", "Solved together in {}"),
+    // Variant legend
+    ("legend.rules", "Rules"),
+    ("legend.cage", "Cage {}: {} so far"),
+    ("legend.cell_left", "{} cell left: {}"),
+    ("legend.cells_left", "{} cells left: {}"),
+    ("legend.nothing_fits", "nothing fits"),
+    // Why an entry is wrong
+    ("explain.clash", "{} = {} clashes with the {} at r{}c{} in the same {}."),
+    ("explain.no_value", "r{}c{} would have no value left"),
+    ("explain.no_place", "{} would have nowhere to go in {} {}"),
+    ("explain.if_then", "If {} were {}, {}."),
+    ("explain.if", "If {} were {}:"),
+    ("explain.forced", "  r{}c{} must be {} ({})"),
+    ("explain.more", "  ...and {} more forced placements"),
+    ("explain.so", "so {}."),
+    ("explain.unknown", "{} can't be {}, but the enabled techniques"),
+    ("explain.unknown_why", "can't show why from here; it takes trial and error."),
+    // Race bars
+    ("race.you", "You"),
+    ("race.best", "Best"),
+    ("race.bot", "Bot"),
+    // Dialogs
+    ("dialog.quit", "Quit?"),
+    ("dialog.quit_text", "This puzzle's entries will be lost."),
+    ("dialog.no_solution", "No solution"),
+    ("dialog.no_solution_text", "These givens can't be completed, so this puzzle can't be won."),
+    ("dialog.no_puzzle", "No puzzle found"),
+    ("dialog.no_puzzle_text", "No puzzle fitting profile {} turned up in {} attempts."),
+    ("dialog.import", "Import puzzle"),
+    ("dialog.import_text", "Type or paste a share code (RD6: and 36 digits):"),
+    ("dialog.import_failed", "Can't import"),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("status.watching", "Zuschauen bei {}"),
+    ("status.solved_in", "GELÖST in {}"),
+    ("status.time", "Zeit: {}"),
+    ("status.mistakes", "Fehler: {}"),
+    ("status.mistakes_of", "Fehler: {}/{}"),
+    ("status.what_if", "WAS-WÄRE-WENN"),
+    ("status.player_to_play", "Spieler {} ist am Zug"),
+    ("status.coop", "Koop S{} mit {} weiteren"),
+    ("status.solved_points", "Gelöst! +{}"),
+    ("status.streak", "Serie {}"),
+    ("status.on_track", "Auf Kurs: keine falschen Einträge"),
+    ("status.off_track_one", "Vom Kurs ab: 1 Eintrag ist falsch"),
+    ("status.off_track", "Vom Kurs ab: {} Einträge sind falsch"),
+    ("status.blocked", "Blockiert: {} steht schon in dieser Einheit ({}) bei r{}c{}"),
+    ("status.dead_end", "Sackgasse: ein Eintrag muss falsch sein ({}: finden)"),
+    ("status.go_to", "Gehe zu: {}"),
+    ("status.typical", "Üblich: {}, Tempo {}"),
+    ("status.record_macro", "Makro aufnehmen"),
+    ("status.play_macro", "Makro abspielen"),
+    ("status.recording", "Makro {} wird aufgenommen"),
+    ("status.wrong_entry", "Falscher Eintrag, {}: Warum?"),
+    ("status.hint_wrong", "Tipp: r{}c{} ist falsch"),
+    ("status.hint", "Tipp: {} bei r{}c{}"),
+    ("status.mode", "Modus: {} ({})"),
+    ("status.solved", "GELÖST!"),
+    ("status.won", "GEWONNEN!"),
+    ("status.heatmap", "HEATMAP: {} falsche Einträge in {} Feldern"),
+    ("status.viewing", "ANSICHT {}/{}"),
+    ("status.bot_won", "Der Bot war {} schneller"),
+    ("status.bot_beaten", "Du hast den Bot geschlagen"),
+    ("status.preview", "Die Uhr läuft erst ab dem Start"),
+    ("status.generating", "Suche ein Rätsel"),
+    ("status.out_of_mistakes", "HERAUSFORDERUNG VORBEI: {} Fehler"),
+    ("status.time_up", "ZEIT ABGELAUFEN! Serie: {} (beste {})"),
+    ("status.paused", "PAUSIERT bei {}"),
+    ("status.thanks", "Danke fürs Spielen!"),
+    ("status.scan", "Code scannen, um dieses Rätsel anderswo zu öffnen"),
+    ("status.history", "VERLAUF: Eintrag {} (Zweig {}/{})"),
+    ("status.watch_wrong", "ZUSEHEN: {} bei r{}c{}"),
+    ("status.watch_step", "ZUSEHEN: {} r{}c{} = {}"),
+    ("status.watch_thinking", "ZUSEHEN: Der Löser denkt nach..."),
+    ("mode.normal", "NORMAL"),
+    ("mode.pencil", "NOTIZEN"),
+    ("unit.row", "Zeile"),
+    ("unit.column", "Spalte"),
+    ("unit.box", "Block"),
+    ("unit.diagonal", "Diagonale"),
+    ("status.time_left", "noch {}"),
+    ("compact.watch", "Zuschauen {} ✗{}"),
+    ("compact.on_track", "auf Kurs!"),
+    ("compact.off_track", "vom Kurs: {} falsch"),
+    ("compact.blocked", "{} schon in r{}c{}"),
+    ("compact.hint_wrong", "r{}c{} ist falsch"),
+    ("compact.what_if", "Was-wäre-wenn {} ✗{}"),
+    ("compact.what_if_keys", "{}: behalten {}: verwerfen"),
+    ("compact.won", "GELÖST {} ✗{}"),
+    ("compact.viewing", "ANSICHT {}/{}"),
+    ("compact.preview", "VORSCHAU {}v {}"),
+    ("compact.searching", "SUCHE {}"),
+    ("compact.time_up", "ZEIT UM Serie {}"),
+    ("compact.out_of_mistakes", "KEINE FEHLER MEHR {}"),
+    ("compact.paused", "PAUSIERT {}"),
+    ("compact.any_key", "beliebige Taste"),
+    ("compact.any_key_quit", "beliebige Taste: beenden"),
+    ("compact.history", "Verl. {} {}/{}"),
+    ("compact.watch_solver", "ZUSEHEN"),
+    ("screen.playing", "Spielt"),
+    ("screen.time_up", "Zeit abgelaufen"),
+    ("screen.out_of_mistakes", "Keine Fehler mehr frei"),
+    ("screen.viewing", "Sieht sich Rätsel an"),
+    ("screen.preview", "In der Vorschau"),
+    ("screen.generating", "Sucht ein Rätsel"),
+    ("screen.demo", "Sieht dem Löser zu"),
+    ("screen.paused", "Pausiert"),
+    ("screen.menus", "In den Menüs"),
+    ("review.correction", "{} Korrektur"),
+    ("review.corrections", "{} Korrekturen"),
+    ("review.logical_error", "{} Denkfehler"),
+    ("review.logical_errors", "{} Denkfehler"),
+    ("review.given", "r{}c{}: vorgegeben"),
+    ("review.step", "r{}c{}: Schritt {} von {}, {}"),
+    ("review.guessing", "r{}c{}: nur durch Raten"),
+    ("history.start", "Anfang"),
+    ("history.cleared", "r{}c{} gelöscht"),
+    ("history.locked", "r{}c{} gesperrt"),
+    ("history.unlocked", "r{}c{} entsperrt"),
+    ("history.marks", "r{}c{} Notizen"),
+    ("rule.diagonal", "Diagonale"),
+    ("rule.diagonal_text", "Keine Ziffer doppelt auf einer der beiden langen Diagonalen"),
+    ("rule.cage", "Käfig"),
+    ("rule.cage_text", "Die Ziffern eines Käfigs sind verschieden und ergeben seine Summe"),
+    ("rule.consecutive", "Nachbarn"),
+    ("rule.consecutive_text", "Markierte Nachbarn unterscheiden sich um eins"),
+    ("rule.sandwich", "Sandwich"),
+    ("rule.sandwich_text", "Eine Summe neben dem Gitter zählt die Ziffern zwischen der 1 und der 6 ihrer Zeile oder Spalte zusammen"),
+    ("rule.thermometer", "Thermometer"),
+    ("rule.thermometer_text", "Die Ziffern steigen entlang jedes Thermometers von der Kugel, dem helleren Ende, an"),
+    ("key.yes", "Ja"),
+    ("key.no", "Nein"),
+    ("key.ok", "OK"),
+    ("key.cancel", "Abbrechen"),
+    ("key.choose", "Auswählen"),
+    ("key.quit", "Beenden"),
+    ("key.back", "Zurück"),
+    ("key.hide", "Ausblenden"),
+    ("key.new_game", "Neues Spiel"),
+    ("key.highlight", "Hervorheben"),
+    ("key.heatmap", "Heatmap"),
+    ("key.stats", "Statistik"),
+    ("key.next", "Nächstes"),
+    ("key.start", "Starten"),
+    ("key.reroll", "Neu würfeln"),
+    ("key.previous", "Vorheriges"),
+    ("key.resume", "Fortsetzen"),
+    ("key.scroll", "Blättern"),
+    ("key.tab", "Reiter"),
+    ("key.step", "Schritt"),
+    ("key.branch", "Zweig"),
+    ("key.stop", "Stopp"),
+    ("key.keep", "Behalten"),
+    ("key.discard", "Verwerfen"),
+    ("key.why", "Warum?"),
+    ("key.enter", "Eintragen"),
+    ("key.mark", "Notieren"),
+    ("key.lock", "Sperren"),
+    ("key.row", "Zeile (oder A-F)"),
+    ("key.register", "Register"),
+    ("key.repeat", "Wiederholen"),
+    ("key.column", "Spalte"),
+    ("key.unlock", "Entsperren"),
+    ("key.clear", "Löschen"),
+    ("key.undo", "Rückgängig"),
+    ("key.hint", "Tipp"),
+    ("key.counts", "Anzahlen"),
+    ("key.hide_counts", "Anzahlen aus"),
+    ("key.last_cells", "Letzte Felder"),
+    ("key.hide_last_cells", "Letzte Felder aus"),
+    ("key.coordinates", "Koordinaten"),
+    ("key.hide_coordinates", "Koordinaten aus"),
+    ("key.what_if", "Was-wäre-wenn"),
+    ("key.watch", "Zusehen"),
+    ("key.share", "Teilen"),
+    ("key.about", "Info"),
+    ("title.daily", "Tagesrätsel {}"),
+    ("title.weekly", "Wochenrätsel {}"),
+    ("title.endless", "Endlos #{} | Punkte {}"),
+    ("title.clues", "{} Vorgaben"),
+    ("title.seed", "Seed {}"),
+    ("title.by", "von {}"),
+    ("title.solving", "Wird gelöst..."),
+    ("title.several_solutions", "Mehrere Lösungen"),
+    ("title.paused", "PAUSIERT"),
+    ("preview.title", "NEUES RÄTSEL"),
+    ("preview.clues", "Vorgaben: {}"),
+    ("preview.symmetry", "Symmetrie: {}"),
+    ("preview.needs", "Braucht: {}"),
+    ("preview.or", "oder"),
+    ("generating.profile", "Suche ein Rätsel für Profil {}"),
+    ("generating.attempts", "Versuch {} von {}, {} s"),
+    ("preview.profiles", "Profile: {}"),
+    ("preview.beyond", "mehr als die Techniken können"),
+    ("symmetry.quarter_turn", "Vierteldrehung"),
+    ("symmetry.half_turn", "halbe Drehung"),
+    ("symmetry.mirror", "gespiegelt"),
+    ("symmetry.flip", "gekippt"),
+    ("symmetry.none", "keine"),
+    ("popup.about", "Über RustDoku6"),
+    ("popup.stats", "Statistik"),
+    ("popup.session", "Sitzung"),
+    ("popup.history", "Verlauf"),
+    ("popup.share", "Rätsel teilen"),
+    ("popup.explain", "Warum ist das falsch?"),
+    ("popup.results", "Ergebnisse"),
+    ("popup.splits", "Zwischenzeiten"),
+    ("splits.digit", "Ziffer {}"),
+    ("about.author", "Autor: {}"),
+    ("about.license", "Lizenz: {}"),
+    ("about.source", "Quelle: {}"),
+    ("about.built_with", "Erstellt mit: {}"),
+    ("summary.attempted", "Versuchte Rätsel: {}"),
+    ("summary.solved", "Gelöst: {}"),
+    ("summary.total_time", "Gesamtzeit: {}"),
+    ("summary.best", "Beste Lösung: {}"),
+    ("stats.none", "Noch keine Spiele aufgezeichnet"),
+    ("stats.stars", "Sterne"),
+    ("stats.played", "Spiele"),
+    ("stats.solved", "Gelöst"),
+    ("stats.best", "Beste"),
+    ("stats.average", "Schnitt"),
+    ("stats.corrections", "Korrekturen"),
+    ("stats.errors", "Fehler"),
+    ("stats.rating", "Wertung {} aus {} gewerteten Spielen"),
+    ("stats.weekly", "Wochen-Herausforderung"),
+    ("stats.week", "Woche"),
+    ("stats.result", "Ergebnis"),
+    ("stats.time", "Zeit"),
+    ("stats.mistakes", "Fehler"),
+    ("hotseat.wins", "Spieler {} gewinnt!"),
+    ("hotseat.draw", "Unentschieden!"),
+    ("hotseat.player", "Spieler {}: {} gesetzt, {} Fehler, {} Punkte"),
+    ("hotseat.together", "Gemeinsam gelöst in {}"),
+    ("legend.rules", "Regeln"),
+    ("legend.cage", "Käfig {}: bisher {}"),
+    ("legend.cell_left", "{} Zelle frei: {}"),
+    ("legend.cells_left", "{} Zellen frei: {}"),
+    ("legend.nothing_fits", "nichts passt"),
+    ("explain.clash", "{} = {} stößt auf die {} in r{}c{}, in derselben Einheit ({})."),
+    ("explain.no_value", "r{}c{} bliebe kein Wert"),
+    ("explain.no_place", "{} hätte keinen Platz in {} {}"),
+    ("explain.if_then", "Wäre {} = {}, {}."),
+    ("explain.if", "Wäre {} = {}:"),
+    ("explain.forced", "  r{}c{} muss {} sein ({})"),
+    ("explain.more", "  ...und {} weitere erzwungene Einträge"),
+    ("explain.so", "also {}."),
+    ("explain.unknown", "{} kann nicht {} sein, doch die aktiven Techniken"),
+    ("explain.unknown_why", "zeigen nicht, warum; das geht nur durch Probieren."),
+    ("race.you", "Du"),
+    ("race.best", "Bestzeit"),
+    ("race.bot", "Gegner"),
+    ("dialog.quit", "Beenden?"),
+    ("dialog.quit_text", "Die Einträge dieses Rätsels gehen verloren."),
+    ("dialog.no_solution", "Keine Lösung"),
+    ("dialog.no_solution_text", "Diese Vorgaben lassen sich nicht vervollständigen, das Rätsel ist nicht zu gewinnen."),
+    ("dialog.no_puzzle", "Kein Rätsel gefunden"),
+    ("dialog.no_puzzle_text", "Für Profil {} fand sich in {} Versuchen kein Rätsel."),
+    ("dialog.import", "Rätsel importieren"),
+    ("dialog.import_text", "Teilcode eingeben oder einfügen (RD6: und 36 Ziffern):"),
+    ("dialog.import_failed", "Import nicht möglich"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::{fill, Locale, EN};
+
+    #[test]
+    fn every_message_is_translated_with_its_arguments() {
+        for locale in Locale::ALL {
+            for &(key, english) in EN {
+                let message = locale.lookup(key).unwrap_or_else(|| panic!("{:?} has no {}", locale, key));
+                assert_eq!(message.matches("{}").count(), english.matches("{}").count(), "{:?} {}", locale, key);
+            }
+            assert_eq!(locale.catalog().len(), EN.len(), "{:?} has keys English doesn't", locale);
+        }
+        assert_eq!(fill("Hint: {} at r{}c{}", &[&"Naked Single", &2, &3]), "Hint: Naked Single at r2c3");
+        assert_eq!(Locale::De.lookup("key.quit"), Some("Beenden"));
+
+        assert_eq!(Locale::parse("de_DE.UTF-8"), Some(Locale::De));
+        assert_eq!(Locale::parse("de-AT"), Some(Locale::De));
+        assert_eq!(Locale::parse("C"), Some(Locale::En));
+        assert_eq!(Locale::parse("fr_FR"), None);
+    }
+}
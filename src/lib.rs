@@ -0,0 +1,48 @@
+// The game model and solver, usable without the terminal UI: boards and their
+// rules, puzzle generation and grading, and the game state the UI drives.
+// The `rustdoku6` binary adds the terminal front end, networking and config.
+//
+// The surface meant for other programs, fuzzers and property tests:
+// - `model::Grid` and `model::Board`, with `parse`, `Display`, `invariants` and
+//   `validate_consistency`
+// - `generator::Generator` for puzzles from a seed
+// - `solver::TechniqueRegistry` for hints, grading and refutations, and
+//   `solver::SolveStats` for what a solve took
+// - `linked::Twin` and `linked::Samurai` for boards that share boxes, which
+//   the terminal game doesn't play yet
+// Their errors are plain enums, one variant per way a call can fail.
+
+pub mod about;
+pub mod adaptive;
+pub mod batch;
+pub mod bench;
+pub mod bot;
+pub mod command;
+pub mod constraints;
+pub mod dialog;
+pub mod endless;
+pub mod events;
+pub mod generator;
+pub mod ghost;
+pub mod hotseat;
+pub mod i18n;
+pub mod linked;
+pub mod macros;
+pub mod model;
+pub mod pregen;
+pub mod profile;
+pub mod puzzle;
+pub mod regions;
+pub mod replay;
+pub mod screen;
+pub mod search;
+pub mod solver;
+pub mod speedrun;
+pub mod stats;
+pub mod storage;
+pub mod symmetry;
+pub mod techniques;
+pub mod timeattack;
+pub mod undotree;
+pub mod viewer;
+pub mod weekly;
@@ -0,0 +1,217 @@
+// Boards laid out on one plane, overlapping where they share a box, as in
+// samurai sudoku. A place on the plane may be on several boards: a value put
+// there goes on each of them and must be allowed by each, so a shared box
+// carries what's known on one board over to the other. Places are (row, col)
+// on the plane, and each board sits at an offset on it. The layouts are the
+// model only, for other programs to build on: the terminal game plays one
+// 6x6 board.
+
+use rand::prelude::*;
+
+use crate::{
+    model::{Board, Cell},
+    solver,
+};
+
+pub struct Linked<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize> {
+    // Each board with the place of its top-left cell
+    pub boards: Vec<((usize, usize), Board<N, BOX_ROWS, BOX_COLS>)>,
+    pub cursor: (usize, usize),
+}
+
+// Two 6x6 boards sharing a box
+pub type Twin = Linked<6, 2, 3>;
+// Classic samurai: five 9x9 boards
+pub type Samurai = Linked<9, 3, 3>;
+
+impl<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize> Linked<N, BOX_ROWS, BOX_COLS> {
+    // Empty boards at `offsets`. Boards are filled in this order, so each
+    // should share boxes only with boards before it.
+    pub fn new(offsets: &[(usize, usize)]) -> Self {
+        Self { boards: offsets.iter().map(|&offset| (offset, Board::new())).collect(), cursor: (0, 0) }
+    }
+
+    // Two boards, the first's bottom-right box the second's top-left one
+    pub fn twin() -> Self {
+        Self::new(&[(0, 0), (N - BOX_ROWS, N - BOX_COLS)])
+    }
+
+    // Four boards around a middle one, each sharing a corner box with it
+    pub fn samurai() -> Self {
+        let (r, c) = (N - BOX_ROWS, N - BOX_COLS);
+        Self::new(&[(r, c), (0, 0), (0, 2 * c), (2 * r, 0), (2 * r, 2 * c)])
+    }
+
+    // Rows and columns of the plane
+    pub fn size(&self) -> (usize, usize) {
+        let rows = self.boards.iter().map(|&((r, _), _)| r + N).max().unwrap_or(0);
+        let cols = self.boards.iter().map(|&((_, c), _)| c + N).max().unwrap_or(0);
+        (rows, cols)
+    }
+
+    // Each board with a cell at (row, col), as (board, row, col) on it
+    pub fn places(&self, row: usize, col: usize) -> Vec<(usize, usize, usize)> {
+        self.boards
+            .iter()
+            .enumerate()
+            .filter(|(_, &((r, c), _))| (r..r + N).contains(&row) && (c..c + N).contains(&col))
+            .map(|(i, &((r, c), _))| (i, row - r, col - c))
+            .collect()
+    }
+
+    pub fn value(&self, row: usize, col: usize) -> Option<u8> {
+        self.places(row, col).first().and_then(|&(i, r, c)| self.boards[i].1.cells[r][c].value())
+    }
+
+    // Whether every board at (row, col) allows `value` there
+    pub fn is_valid_move(&self, row: usize, col: usize, value: u8) -> bool {
+        let places = self.places(row, col);
+        !places.is_empty() && places.iter().all(|&(i, r, c)| self.boards[i].1.is_valid_move(r, c, value))
+    }
+
+    // The values left for (row, col) on every board it's on, as a bitmask
+    pub fn candidates(&self, row: usize, col: usize) -> u16 {
+        let places = self.places(row, col);
+        match places.is_empty() {
+            true => 0,
+            false => places.iter().fold(u16::MAX, |mask, &(i, r, c)| mask & solver::candidates(&self.boards[i].1, r, c)),
+        }
+    }
+
+    // Put `value` at (row, col) on every board there. False if no board is.
+    pub fn set(&mut self, row: usize, col: usize, value: Option<u8>) -> bool {
+        let places = self.places(row, col);
+        for &(i, r, c) in &places {
+            self.boards[i].1.cells[r][c].set_value(value);
+        }
+        !places.is_empty()
+    }
+
+    // Move the cursor a step, carrying on over the gaps between boards to the
+    // next cell on one, so it flows from board to board. It stays put at the
+    // edge of the plane.
+    pub fn move_cursor(&mut self, dr: i8, dc: i8) {
+        let (rows, cols) = self.size();
+        let (mut r, mut c) = (self.cursor.0 as isize, self.cursor.1 as isize);
+        loop {
+            (r, c) = (r + dr as isize, c + dc as isize);
+            if r < 0 || c < 0 || r >= rows as isize || c >= cols as isize || (dr, dc) == (0, 0) {
+                return;
+            }
+            if !self.places(r as usize, c as usize).is_empty() {
+                self.cursor = (r as usize, c as usize);
+                return;
+            }
+        }
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.boards.iter().all(|(_, board)| board.is_solved())
+    }
+
+    // Fill every board, each from what the boards before it left in the
+    // boxes they share
+    pub fn fill_randomly<R: Rng + ?Sized>(&mut self, rng: &mut R) -> bool {
+        for i in 0..self.boards.len() {
+            let ((top, left), _) = self.boards[i];
+            for (r, c) in (0..N).flat_map(|r| (0..N).map(move |c| (r, c))) {
+                let shared = self.places(top + r, left + c).into_iter().find(|&(j, _, _)| j < i);
+                if let Some((j, sr, sc)) = shared {
+                    self.boards[i].1.cells[r][c] = self.boards[j].1.cells[sr][sc];
+                }
+            }
+            if !self.boards[i].1.fill_randomly(rng) {
+                return false;
+            }
+        }
+        true
+    }
+
+    // Make a puzzle of the empty boards: they're filled, then givens are
+    // taken out in random order for as long as each board they're on keeps a
+    // single solution. The plane's places with their values, the solution,
+    // are returned next to it.
+    pub fn generate<R: Rng + ?Sized>(mut self, rng: &mut R) -> Option<(Self, Vec<Vec<u8>>)> {
+        if !self.fill_randomly(rng) {
+            return None;
+        }
+        let (rows, cols) = self.size();
+        let solution = (0..rows).map(|r| (0..cols).map(|c| self.value(r, c).unwrap_or(0)).collect()).collect();
+        for (_, board) in &mut self.boards {
+            board.cells.iter_mut().flatten().for_each(|cell| cell.set_fixed(true));
+        }
+        let mut order: Vec<(usize, usize)> = (0..rows).flat_map(|r| (0..cols).map(move |c| (r, c))).filter(|&(r, c)| !self.places(r, c).is_empty()).collect();
+        order.shuffle(rng);
+        for (row, col) in order {
+            let places = self.places(row, col);
+            let given = places.iter().map(|&(i, r, c)| self.boards[i].1.cells[r][c]).collect::<Vec<_>>();
+            places.iter().for_each(|&(i, r, c)| self.boards[i].1.cells[r][c] = Cell::default());
+            if places.iter().any(|&(i, _, _)| self.boards[i].1.solutions(2).len() > 1) {
+                places.iter().zip(given).for_each(|(&(i, r, c), cell)| self.boards[i].1.cells[r][c] = cell);
+            }
+        }
+        Some((self, solution))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{Samurai, Twin};
+
+    #[test]
+    fn shared_boxes_link_the_boards() {
+        let mut twin = Twin::twin();
+        assert_eq!(twin.size(), (10, 9));
+        assert_eq!(twin.places(5, 4), [(0, 5, 4), (1, 1, 1)]);
+        assert!(twin.places(0, 8).is_empty());
+
+        // A value in the shared box is on both boards, and rules out its
+        // peers on either
+        assert!(twin.set(4, 3, Some(2)));
+        assert_eq!(twin.boards[1].1.cells[0][0].value(), Some(2));
+        assert!(!twin.is_valid_move(4, 8, 2)); // the second board's row
+        assert!(!twin.is_valid_move(0, 3, 2)); // the first board's column
+        assert_eq!(twin.candidates(9, 3) & 1 << 1, 0);
+        assert!(!twin.is_valid_move(0, 8, 1)); // on neither board
+    }
+
+    #[test]
+    fn the_cursor_flows_from_board_to_board() {
+        // It runs on across the gap to the next board, or stays put
+        let mut twin = Twin::twin();
+        twin.cursor = (5, 0);
+        twin.move_cursor(1, 0);
+        assert_eq!(twin.cursor, (5, 0));
+        twin.cursor = (2, 5);
+        twin.move_cursor(0, 1);
+        assert_eq!(twin.cursor, (2, 5));
+        twin.cursor = (5, 2);
+        twin.move_cursor(0, 1);
+        twin.move_cursor(1, 0);
+        assert_eq!(twin.cursor, (6, 3));
+    }
+
+    #[test]
+    fn generated_twins_have_one_solution_per_board() {
+        let (puzzle, solution) = Twin::twin().generate(&mut StdRng::seed_from_u64(7)).unwrap();
+        assert!(puzzle.boards.iter().all(|(_, board)| board.solutions(2).len() == 1 && board.clue_count() < 36));
+        let mut solved = puzzle;
+        for (r, row) in solution.iter().enumerate() {
+            for (c, &value) in row.iter().enumerate() {
+                solved.set(r, c, Some(value).filter(|&v| v > 0));
+            }
+        }
+        assert!(solved.is_solved());
+    }
+
+    #[test]
+    fn samurai_boards_fill_around_the_middle_one() {
+        let mut samurai = Samurai::samurai();
+        assert_eq!(samurai.size(), (21, 21));
+        assert!(samurai.fill_randomly(&mut StdRng::seed_from_u64(1)));
+        assert!(samurai.is_solved());
+        assert_eq!(samurai.places(7, 7).len(), 2);
+    }
+}
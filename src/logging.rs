@@ -0,0 +1,136 @@
+// Opt-in file logging through the `log` facade, since a fullscreen TUI has
+// nowhere to print. Records go to `rustdoku6.log` in the cache directory; when
+// the file grows past MAX_LOG_BYTES it is moved to `rustdoku6.log.1` (replacing
+// the previous one) and a fresh file is started.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+use crate::{daily, storage};
+
+const LOG_FILE: &str = "rustdoku6.log";
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+struct FileLogger {
+    path: PathBuf,
+    // The open file and how many bytes it holds
+    file: Mutex<(File, u64)>,
+}
+
+// Start logging at `level` ("off", "error", "warn", "info", "debug" or "trace").
+// Returns the log file path, or None when logging is off.
+pub fn init(level: &str) -> io::Result<Option<PathBuf>> {
+    let level = parse_level(level)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("unknown log level: {}", level)))?;
+    if level == LevelFilter::Off {
+        return Ok(None);
+    }
+
+    let dir = storage::cache_dir().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory"))?;
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(LOG_FILE);
+    let file = open_rotated(&path, MAX_LOG_BYTES)?;
+    let size = file.metadata()?.len();
+
+    let logger = FileLogger { path: path.clone(), file: Mutex::new((file, size)) };
+    log::set_boxed_logger(Box::new(logger)).map_err(io::Error::other)?;
+    log::set_max_level(level);
+    Ok(Some(path))
+}
+
+fn parse_level(level: &str) -> Option<LevelFilter> {
+    level.parse().ok()
+}
+
+// Open `path` for appending, first rotating it if it has reached `max_bytes`
+fn open_rotated(path: &Path, max_bytes: u64) -> io::Result<File> {
+    if fs::metadata(path).is_ok_and(|meta| meta.len() >= max_bytes) {
+        fs::rename(path, path.with_extension("log.1"))?;
+    }
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+// UTC timestamp with milliseconds, e.g. 2026-10-14T09:30:00.123Z
+fn timestamp() -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = now.as_secs();
+    let (year, month, day) = daily::civil_from_days((secs / 86_400) as i64);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60,
+        now.subsec_millis()
+    )
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!("{} {:<5} {}: {}\n", timestamp(), record.level(), record.target(), record.args());
+        let Ok(mut guard) = self.file.lock() else {
+            return;
+        };
+        // Logging must never take the game down, so write failures are ignored
+        if guard.1 + line.len() as u64 > MAX_LOG_BYTES {
+            if let Ok(file) = open_rotated(&self.path, 0) {
+                *guard = (file, 0);
+            }
+        }
+        if guard.0.write_all(line.as_bytes()).is_ok() {
+            guard.1 += line.len() as u64;
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut guard) = self.file.lock() {
+            let _ = guard.0.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{open_rotated, parse_level};
+    use log::LevelFilter;
+    use std::{fs, io::Write};
+
+    #[test]
+    fn parse_level_accepts_names() {
+        assert_eq!(parse_level("debug"), Some(LevelFilter::Debug));
+        assert_eq!(parse_level("OFF"), Some(LevelFilter::Off));
+        assert_eq!(parse_level("loud"), None);
+    }
+
+    #[test]
+    fn open_rotated_moves_full_log_aside() {
+        let dir = std::env::temp_dir().join(format!("rustdoku6-log-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.log");
+        fs::write(&path, "0123456789").unwrap();
+
+        writeln!(open_rotated(&path, 100).unwrap(), "more").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "0123456789more\n");
+
+        open_rotated(&path, 10).unwrap();
+        assert_eq!(fs::read_to_string(path.with_extension("log.1")).unwrap(), "0123456789more\n");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,87 @@
+// Input macros, recorded and replayed as commands rather than keys, so they
+// work the same under every keymap. `Q` and a digit start recording into that
+// register, `Q` again stops, and `P` and a digit replay it from wherever the
+// cursor is, so a marking pattern recorded once can be run on other cells.
+
+use crate::command::Command;
+
+pub const REGISTERS: usize = 6;
+
+// A macro key waiting on the digit naming its register
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pending {
+    Record,
+    Play,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Macros {
+    registers: [Vec<Command>; REGISTERS],
+    // The register being recorded into (1-based) and what's gone in so far
+    pub recording: Option<(u8, Vec<Command>)>,
+    pub pending: Option<Pending>,
+}
+
+impl Macros {
+    // Give the macros a command before it's applied. None if it isn't one of
+    // theirs, so it's applied (and recorded, while recording); otherwise the
+    // commands to apply in its place, the register's when one is replayed.
+    // Replayed commands come back through here, so a replay while recording
+    // is recorded as the commands it plays.
+    pub fn command(&mut self, command: Command) -> Option<Vec<Command>> {
+        match (self.pending.take(), command) {
+            (Some(Pending::Record), Command::EnterDigit(register)) => self.recording = Some((register, Vec::new())),
+            (Some(Pending::Play), Command::EnterDigit(register)) => return Some(self.registers[register as usize - 1].clone()),
+            (Some(_), Command::Back) => {}
+            (_, Command::RecordMacro) => match self.recording.take() {
+                Some((register, commands)) => self.registers[register as usize - 1] = commands,
+                None => self.pending = Some(Pending::Record),
+            },
+            (_, Command::PlayMacro) => self.pending = Some(Pending::Play),
+            _ => {
+                if let Some((_, commands)) = &mut self.recording {
+                    commands.push(command);
+                }
+                return None;
+            }
+        }
+        Some(Vec::new())
+    }
+
+    // Commands recorded in `register` (1-based)
+    pub fn register(&self, register: u8) -> &[Command] {
+        &self.registers[register as usize - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Macros, Pending};
+    use crate::command::Command;
+
+    #[test]
+    fn macros_record_commands_and_replay_them() {
+        let mut macros = Macros::default();
+        let mark = [Command::EnterAlternate(3), Command::MoveCursor(0, 1)];
+        assert_eq!(macros.command(Command::RecordMacro), Some(Vec::new()));
+        assert_eq!(macros.pending, Some(Pending::Record));
+        assert_eq!(macros.command(Command::EnterDigit(2)), Some(Vec::new()));
+        for command in mark {
+            assert_eq!(macros.command(command), None);
+        }
+        macros.command(Command::RecordMacro);
+        assert_eq!(macros.register(2), mark);
+        assert!(macros.recording.is_none());
+
+        macros.command(Command::PlayMacro);
+        assert_eq!(macros.command(Command::EnterDigit(2)), Some(mark.to_vec()));
+        assert!(macros.register(1).is_empty());
+
+        // Esc drops a pending register; anything else drops it and goes through
+        macros.command(Command::PlayMacro);
+        assert_eq!(macros.command(Command::Back), Some(Vec::new()));
+        macros.command(Command::RecordMacro);
+        assert_eq!(macros.command(Command::Hint), None);
+        assert!(macros.pending.is_none() && macros.recording.is_none());
+    }
+}
@@ -1,93 +1,992 @@
-mod model;
+mod background;
+mod backup;
+#[cfg(feature = "net")]
+mod broadcast;
+mod cast;
+mod config;
+#[cfg(feature = "net")]
+mod coop;
+mod crash;
+mod daily;
+mod graphics;
+#[cfg(feature = "net")]
+mod http;
+mod logging;
+mod qr;
+mod statusbar;
+mod term;
 mod ui;
 
-use std::{error::Error, io, time::Duration};
+// The model lives in the library; importing its modules here keeps
+// `crate::model` and friends working in the front end's modules
+use rustdoku6::{about, adaptive, batch, bench, bot, command, constraints, dialog, endless, events, generator, ghost, hotseat, i18n, macros, model, pregen, profile, puzzle, replay, screen, solver, speedrun, stats, storage, symmetry, timeattack, viewer, weekly};
 
-use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
-use ratatui::{
-    backend::{Backend, CrosstermBackend},
-    Terminal,
+use std::{
+    env,
+    error::Error,
+    io,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    process,
+    time::{Duration, Instant},
 };
 
-use crate::model::Game;
+use rand::{rngs::StdRng, seq::IndexedRandom, SeedableRng};
+use ratatui::{layout::Rect, Terminal};
+
+use crate::{
+    adaptive::Adaptive,
+    backup::Backup,
+    bot::{Bot, Level},
+    background::Background,
+    command::{Command, Keymap},
+    config::Config,
+    endless::Endless,
+    events::{AppEvent, Key, KeyRepeat, MouseKind},
+    ghost::Ghost,
+    profile::Profile,
+    replay::{Replay, Solve},
+    graphics::GraphicsRenderer,
+    hotseat::HotSeat,
+    i18n::Locale,
+    model::{Game, Grid, Timer, WinCheck},
+    generator::PuzzleSpec,
+    puzzle::{PuzzleMeta, Source, Variant},
+    screen::Screen,
+    solver::TechniqueRegistry,
+    speedrun::Speedrun,
+    stats::Stats,
+    symmetry::Transform,
+    term::TermBackend,
+    timeattack::TimeAttack,
+    viewer::Viewer,
+};
+#[cfg(feature = "net")]
+use crate::{broadcast::Broadcast, coop::Coop};
 
 fn main() -> Result<(), Box<dyn Error>> {
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    let args = Args::parse(env::args().skip(1))?;
+    let config = Config::load();
+    if let Err(err) = logging::init(&config.log_level) {
+        eprintln!("Logging disabled: {}", err);
+    }
+    let locale = match config.language.as_str() {
+        "auto" => Locale::from_env(),
+        language => Locale::parse(language).unwrap_or_else(|| {
+            log::warn!("unknown language {:?}, following the locale", language);
+            Locale::from_env()
+        }),
+    };
+    i18n::set_locale(locale);
+    match Keymap::parse(&config.keymap) {
+        Some(keymap) => command::set_keymap(keymap),
+        None => log::warn!("unknown keymap {:?}, using the default", config.keymap),
+    }
+    if let Some(dir) = &args.import {
+        return import_data(dir);
+    }
+    if let Some(path) = &args.backup {
+        return backup_data(path);
+    }
+    if let Some(path) = &args.restore {
+        return restore_data(path);
+    }
+    if let Some(path) = &args.view {
+        return view(path, &config);
+    }
+    if let Some(dir) = &args.rate {
+        return rate_dir(dir);
+    }
+    if args.bench {
+        bench_all();
+        return Ok(());
+    }
+    if let Some((out, key)) = &args.cast {
+        return write_cast(out, key.as_deref(), &config, args.snapshot_size.unwrap_or(cast::SIZE));
+    }
+    let daily = args.daily.then(daily::today);
+    // Everything random from here on, so `--run-seed` plays the same run of
+    // puzzles again
+    let mut rng = args.run_seed.map_or_else(|| StdRng::from_rng(&mut rand::rng()), StdRng::seed_from_u64);
+    let (seed, source) = match (&daily, args.seed) {
+        (Some(date), _) => (Some(daily_seed(&config, date)), Source::Daily),
+        (None, Some(seed)) => (Some(seed), Source::Seed),
+        (None, None) => (None, Source::Random),
+    };
+
+    // Network links: co-op with other players, spectators. A joined co-op game
+    // plays the host's puzzle.
+    let mut links: Vec<Box<dyn Link>> = Vec::new();
+    #[cfg(feature = "net")]
+    let (mut seed, mut source) = (seed, source);
+    #[cfg(feature = "net")]
+    let mut coop_player = None;
+    #[cfg(feature = "net")]
+    {
+        if let Some(addr) = &args.watch {
+            return watch(addr, &config);
+        }
+        if let Some(addr) = &args.join {
+            let (link, host_seed) = Coop::join(addr)?;
+            (seed, source) = (Some(host_seed), Source::CoOp);
+            coop_player = Some(link.player);
+            links.push(Box::new(link));
+        } else if let Some(port) = args.host {
+            coop_player = Some(0);
+            links.push(Box::new(Coop::host(port)?));
+        }
+        if let Some(port) = args.broadcast {
+            links.push(Box::new(Broadcast::start(port)?));
+        }
+    }
+    #[cfg(not(feature = "net"))]
+    if args.host.is_some() || args.join.is_some() || args.broadcast.is_some() || args.watch.is_some() {
+        return Err("--host, --join, --broadcast and watch need a build with the `net` feature".into());
+    }
+
+    if args.adaptive && seed.is_some() {
+        return Err("--adaptive picks its own puzzle, so it can't be combined with --seed, --daily or --join".into());
+    }
+    let adaptive = args.adaptive.then(Adaptive::load);
+    // Finding a puzzle that needs a given technique can take many tries, so
+    // it's done before the terminal is taken over, with a progress line
+    let required = match &args.require {
+        Some(_) if args.daily || args.adaptive || args.host.is_some() || args.join.is_some() || args.broadcast.is_some() => {
+            return Err("--require can't be combined with --daily, --adaptive, --host, --join or --broadcast".into());
+        }
+        Some(technique) => Some(require_puzzle(seed, technique, &mut rng)?),
+        None => None,
+    };
+    let remixed = match &args.remix {
+        Some(_) if seed.is_some() || args.weekly || args.variant.is_some() || args.require.is_some() || args.adaptive => {
+            return Err("remix picks its own puzzle, so it can't be combined with --seed, --daily, --weekly, --variant, --require, --adaptive or --join".into());
+        }
+        Some(key) => Some(remix_puzzle(key.as_deref(), &mut rng)?),
+        None => None,
+    };
+    let (profiles, profile) = load_profiles(&config, args.profile.as_deref())?;
+    // Cached puzzles come in the order they were made, not the run's
+    let puzzle_cache = config.puzzle_cache && args.run_seed.is_none();
+    let picked = seed.is_some() || args.weekly || args.variant.is_some() || args.require.is_some() || args.adaptive || args.remix.is_some() || args.endless || args.time_attack;
+    let profiled = match profile {
+        Some(_) if picked => {
+            return Err("--profile picks its own puzzle, so it can't be combined with --seed, --daily, --weekly, --variant, --require, --adaptive, remix, --endless, --time-attack or --join".into());
+        }
+        Some(i) => Some(profile_puzzle(&profiles[i], puzzle_cache, &mut rng)?),
+        None => None,
+    };
+
+    // Variants have their own generators, which the other ways of picking a
+    // puzzle don't go through, and the daily puzzle is the same classic one for everyone
+    if args.variant.is_some() && (args.daily || args.require.is_some() || args.adaptive || args.endless || args.time_attack || args.join.is_some()) {
+        return Err("--variant can't be combined with --daily, --require, --adaptive, --endless, --time-attack or --join".into());
+    }
+    let mut spec = PuzzleSpec { variant: args.variant.clone().unwrap_or_default(), ..PuzzleSpec::default() };
+    // The daily puzzle is the same for everyone. A given seed turns the same
+    // way every time, so `--seed` plays the same board again.
+    if config.transform_puzzles && !args.daily {
+        let square = Grid::SPEC.box_rows == Grid::SPEC.box_cols;
+        spec.transform = Some(match seed {
+            Some(seed) => Transform::random(&mut StdRng::seed_from_u64(seed), square),
+            None => Transform::random(&mut rng, square),
+        });
+    }
+    // The challenge is the same for everyone and played alone
+    let others = args.seed.is_some() || args.daily || args.variant.is_some() || args.require.is_some() || args.adaptive || args.endless || args.time_attack;
+    if args.weekly && (others || args.hotseat || args.host.is_some() || args.join.is_some()) {
+        return Err("--weekly can't be combined with --seed, --daily, --variant, --require, --adaptive, --endless, --time-attack, --hotseat, --host or --join".into());
+    }
+    let week = args.weekly.then(daily::this_week);
 
     // Create App
-    let mut game = Game::new();
+    let mut game = match (&week, required.or(remixed).or(profiled), seed, &adaptive) {
+        (Some(week), ..) => weekly::puzzle(week)?,
+        (None, Some(game), _, _) => game,
+        (None, None, Some(seed), _) => Game::generate(seed, &spec)?,
+        (None, None, None, Some(adaptive)) => adaptive.new_game(&mut rng),
+        (None, None, None, None) => Game::random_with(&mut rng, &spec)?,
+    };
+    game.rng = rng;
+    game.adaptive = adaptive;
+    game.meta.source = source;
+    game.meta.date = daily;
+    game.set_techniques(TechniqueRegistry::with_disabled(&config.disabled_techniques));
+    game.block_conflicts = config.block_conflicts;
+    game.auto_clear = config.auto_clear_mistakes;
+    game.win_check = WinCheck::parse(&config.win_check).unwrap_or_else(|| {
+        log::warn!("unknown win_check {:?}, checking the rules", config.win_check);
+        WinCheck::default()
+    });
+    game.confirm_quit = config.confirm_quit;
+    game.subtle_regions = config.subtle_regions;
+    game.show_coordinates = config.coordinates;
+    game.transform_puzzles = config.transform_puzzles;
+    game.profiles = profiles;
+    game.profile = profile;
+    game.puzzle_cache = puzzle_cache;
+    if puzzle_cache {
+        pregen::refill(game.profiles.clone(), StdRng::from_rng(&mut game.rng));
+    }
+    game.stats = Some(Stats::load());
+    if let (Some(week), Some(stats)) = (&week, game.stats.as_mut()) {
+        if !stats.start_weekly(week)? {
+            return Err(format!("you've had your attempt at the challenge for the week of {}", week).into());
+        }
+    }
+    if args.speedrun {
+        game.start_speedrun(Speedrun::load());
+    }
+    if args.hotseat {
+        game.hotseat = Some(HotSeat::default());
+    }
+    if args.endless {
+        game.endless = Some(Endless::new(game.stars(), args.escalate));
+    }
+    if args.time_attack {
+        game.time_attack = Some(TimeAttack::load(game.stars()));
+    }
+    if let Some(level) = args.bot {
+        if args.hotseat || args.host.is_some() || args.join.is_some() {
+            return Err("--bot races a game played alone, so it can't be combined with --hotseat, --host or --join".into());
+        }
+        game.bot = Some(Bot::new(level, &game.givens(), &game.techniques));
+    }
+    #[cfg(feature = "net")]
+    {
+        game.coop_player = coop_player;
+    }
+    if let Some(out) = &args.render_snapshot {
+        return render_snapshot(&mut game, &config, out, args.snapshot_size.unwrap_or(SNAPSHOT_SIZE));
+    }
+    // Race the best earlier solve of the seed, in games played alone and one puzzle at a time
+    if game.hotseat.is_none() && game.coop_player.is_none() && game.endless.is_none() && game.time_attack.is_none() {
+        game.ghost = Some(Ghost::load(&game.meta.key()));
+    }
+    if config.replays {
+        game.replay = Some(Replay::default());
+    }
+    // Only a random puzzle can be rerolled, and only by a player alone with it,
+    // one puzzle at a time (as for the ghost) and with no one watching
+    let rerollable = game.meta.source == Source::Random && week.is_none() && args.require.is_none() && args.remix.is_none() && game.adaptive.is_none();
+    if config.preview && rerollable && game.ghost.is_some() && links.is_empty() {
+        game.preview = true;
+        game.show_preview();
+    }
 
-    // Run Loop
-    let res = run_app(&mut terminal, &mut game);
+    // Setup terminal
+    let (mut terminal, mut session) = term::setup(config.mouse && !args.no_mouse, config.background == "auto")?;
+    game.light_background = background(&config, session.background) == Background::Light;
+    log::info!("starting puzzle {} ({:?}, daily {:?}, speedrun {}, hotseat {})", game.meta.key(), game.meta.source, game.meta.date, args.speedrun, args.hotseat);
+
+    // Run Loop. A panic is caught so the terminal can be restored and the state saved.
+    crash::install_panic_hook();
+    let res = panic::catch_unwind(AssertUnwindSafe(|| run_app(&mut terminal, &mut game, &config, &mut links, std::mem::take(&mut session.typed), session.enhanced_keys)));
+    // Later panics print normally again
+    let _ = panic::take_hook();
 
     // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    term::restore(&mut terminal, session)?;
+    #[cfg(feature = "net")]
+    if !daily::finish_submissions(SUBMIT_WAIT) {
+        eprintln!("The daily result was still being sent and may not have reached the server.");
+    }
+
+    let failure = match res {
+        Ok(Ok(Some(export))) => {
+            println!("Speedrun saved to {}", export.display());
+            None
+        }
+        Ok(Ok(None)) => None,
+        Ok(Err(err)) => {
+            log::error!("game loop failed: {}", err);
+            Some(format!("error: {}", err))
+        }
+        Err(_) => Some(crash::take_panic_message()),
+    };
+    if let Some(reason) = failure {
+        eprintln!("RustDoku6 stopped unexpectedly: {}", reason);
+        match crash::dump(&game, &reason) {
+            Ok(path) => eprintln!("The game state was saved to {}; please attach it to a bug report.", path.display()),
+            Err(err) => eprintln!("Could not save the game state: {}", err),
+        }
+        process::exit(1);
+    }
+    // Quitting from the board counted the game already; other ways out haven't
+    game.record_stats();
+    if let Some(mut stats) = game.stats.take() {
+        if let Err(err) = stats.end_session() {
+            eprintln!("Could not save the session to the stats: {}", err);
+        }
+    }
+    if let Some(mut adaptive) = game.adaptive.take() {
+        match adaptive.record(&game) {
+            Ok(Some(outcome)) => {
+                let (rate, count) = adaptive.success_rate(outcome.stars);
+                let verdict = if outcome.is_success() { "success" } else { "not a success" };
+                println!("Adaptive: {}-star game was {}; {:.0}% of the last {} at this level", outcome.stars, verdict, rate * 100.0, count);
+            }
+            Ok(None) => {}
+            Err(err) => eprintln!("Could not save the adaptive history: {}", err),
+        }
+    }
+    if let Some(session) = &game.endless {
+        println!("Endless session: {} puzzles solved, {} points", session.solved, session.score);
+    }
+    if let Some(attack) = game.time_attack.as_mut() {
+        println!("Time attack: streak of {} (best {})", attack.streak, attack.best_streak);
+        // A run quit before the clock ran out still counts toward the best streak
+        if let Err(err) = attack.finish() {
+            eprintln!("Could not save the time-attack streak: {}", err);
+        }
+    }
+
+    Ok(())
+}
+
+// Command-line options
+#[derive(Debug, Default)]
+struct Args {
+    // `--seed <n>` replays a specific puzzle
+    seed: Option<u64>,
+    // `--run-seed <n>` replays a whole run: the first puzzle, when no other
+    // option picks it, and every one after it
+    run_seed: Option<u64>,
+    // `--speedrun` records per-digit splits
+    speedrun: bool,
+    // `--hotseat` has two players take turns on the board
+    hotseat: bool,
+    // `--endless` loads a new puzzle as soon as one is solved; `--escalate`
+    // also makes them harder as the session goes on
+    endless: bool,
+    escalate: bool,
+    // `--time-attack` plays against a countdown, puzzle after puzzle
+    time_attack: bool,
+    // `--bot [relaxed|steady|quick]` races a solver through the puzzle
+    bot: Option<Level>,
+    // `--adaptive` picks the difficulty from recent results
+    adaptive: bool,
+    // `--require <technique>` plays a puzzle that can't be solved without it
+    require: Option<String>,
+    // `--profile <name>` plays puzzles of a generation profile from the config
+    profile: Option<String>,
+    // `--variant <name>` plays puzzles with extra rules, e.g. sandwich sums
+    variant: Option<Variant>,
+    // `--daily` plays today's shared puzzle
+    daily: bool,
+    // `--weekly` takes the one attempt at this week's hard challenge
+    weekly: bool,
+    // `--no-mouse` leaves the mouse to the terminal
+    no_mouse: bool,
+    // `--host <port>` opens the board to co-op players
+    host: Option<u16>,
+    // `--join <host:port>` plays on another player's board
+    join: Option<String>,
+    // `--broadcast <port>` lets spectators watch the game
+    broadcast: Option<u16>,
+    // `watch <host:port>` spectates a broadcasting game
+    watch: Option<String>,
+    // `import <dir>` merges the records kept in another install's data dir
+    import: Option<PathBuf>,
+    // `backup <file>` writes the config and data files into one file, and
+    // `restore <file>` puts them back
+    backup: Option<PathBuf>,
+    restore: Option<PathBuf>,
+    // `view <file>` looks over the puzzles in a file without playing them
+    view: Option<PathBuf>,
+    // `rate --dir <dir>` prints a CSV grading the puzzles of each file there
+    rate: Option<PathBuf>,
+    // `bench` times the engine on each board size and prints a report
+    bench: bool,
+    // `remix [<key>]` plays a solved puzzle, that one or one picked at random,
+    // turned, mirrored and relabeled
+    remix: Option<Option<String>>,
+    // `cast <file> [<key>]` writes the last saved solve, of that puzzle if
+    // given, as an asciinema recording, `-` printing it
+    cast: Option<(String, Option<String>)>,
+    // `--render-snapshot <file>` writes the first screen as text instead of
+    // playing, `-` printing it; `--snapshot-size <cols>x<rows>` sets its size
+    render_snapshot: Option<String>,
+    snapshot_size: Option<(u16, u16)>,
+}
 
-    if let Err(err) = res {
-        println!("{:?}", err)
+impl Args {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, Box<dyn Error>> {
+        let mut args = args.peekable();
+        let mut parsed = Self::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let value = args.next().ok_or("--seed requires a number")?;
+                    parsed.seed = Some(value.parse().map_err(|_| format!("invalid seed: {}", value))?);
+                }
+                "--run-seed" => {
+                    let value = args.next().ok_or("--run-seed requires a number")?;
+                    parsed.run_seed = Some(value.parse().map_err(|_| format!("invalid run seed: {}", value))?);
+                }
+                "--speedrun" => parsed.speedrun = true,
+                "--hotseat" => parsed.hotseat = true,
+                "--endless" => parsed.endless = true,
+                "--time-attack" => parsed.time_attack = true,
+                "--escalate" => {
+                    parsed.endless = true;
+                    parsed.escalate = true;
+                }
+                "--adaptive" => parsed.adaptive = true,
+                "--bot" => parsed.bot = Some(args.next_if(|arg| Level::parse(arg).is_some()).and_then(|level| Level::parse(&level)).unwrap_or_default()),
+                "--require" => parsed.require = Some(args.next().ok_or("--require needs a technique id")?),
+                "--profile" => parsed.profile = Some(args.next().ok_or("--profile needs the name of a profile in the config")?),
+                "--variant" => {
+                    let value = args.next().ok_or("--variant needs a variant: classic, sandwich or thermo")?;
+                    parsed.variant = Some(match value.as_str() {
+                        "classic" => Variant::Classic,
+                        "sandwich" => Variant::Sandwich,
+                        "thermo" => Variant::Thermo,
+                        _ => return Err(format!("unknown variant: {} (one of classic, sandwich, thermo)", value).into()),
+                    });
+                }
+                "--daily" => parsed.daily = true,
+                "--weekly" => parsed.weekly = true,
+                "--no-mouse" => parsed.no_mouse = true,
+                "--host" => {
+                    let value = args.next().ok_or("--host requires a port")?;
+                    parsed.host = Some(value.parse().map_err(|_| format!("invalid port: {}", value))?);
+                }
+                "--join" => parsed.join = Some(args.next().ok_or("--join requires host:port")?),
+                "--broadcast" => {
+                    let value = args.next().ok_or("--broadcast requires a port")?;
+                    parsed.broadcast = Some(value.parse().map_err(|_| format!("invalid port: {}", value))?);
+                }
+                "--render-snapshot" => parsed.render_snapshot = Some(args.next().ok_or("--render-snapshot needs a file, or - to print")?),
+                "--snapshot-size" => {
+                    let value = args.next().ok_or("--snapshot-size needs <cols>x<rows>")?;
+                    let size = value.split_once('x').and_then(|(cols, rows)| Some((cols.parse().ok()?, rows.parse().ok()?)));
+                    parsed.snapshot_size = Some(size.filter(|&(cols, rows)| cols > 0 && rows > 0).ok_or_else(|| format!("invalid size: {}", value))?);
+                }
+                "watch" => parsed.watch = Some(args.next().ok_or("watch requires host:port")?),
+                "remix" => parsed.remix = Some(args.next_if(|arg| !arg.starts_with("--"))),
+                "cast" => {
+                    let out = args.next().ok_or("cast needs a file to write, or - to print")?;
+                    parsed.cast = Some((out, args.next_if(|arg| !arg.starts_with("--"))));
+                }
+                "view" => parsed.view = Some(args.next().ok_or("view needs a puzzle file")?.into()),
+                "rate" => {
+                    args.next_if(|arg| arg == "--dir");
+                    parsed.rate = Some(args.next().ok_or("rate needs --dir and the directory of puzzle files")?.into());
+                }
+                "bench" => parsed.bench = true,
+                "import" => parsed.import = Some(args.next().ok_or("import needs the data directory to merge from")?.into()),
+                "backup" => parsed.backup = Some(args.next().ok_or("backup needs a file to write")?.into()),
+                "restore" => parsed.restore = Some(args.next().ok_or("restore needs the backup file to read")?.into()),
+                other => return Err(format!("unknown argument: {}", other).into()),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+// Show the puzzles of a file, read-only
+fn view(path: &Path, config: &Config) -> Result<(), Box<dyn Error>> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("could not read {}: {}", path.display(), err))?;
+    let mut viewer = Viewer::parse(&text).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let (mut terminal, mut session) = term::setup(false, config.background == "auto")?;
+    let light = background(config, session.background) == Background::Light;
+    for game in &mut viewer.games {
+        game.subtle_regions = config.subtle_regions;
+        game.show_coordinates = config.coordinates;
+        game.light_background = light;
     }
+    let result = view_loop(&mut terminal, &mut viewer, config, std::mem::take(&mut session.typed));
+    term::restore(&mut terminal, session)?;
+    Ok(result?)
+}
 
+// Grade every puzzle in the files of `dir` as CSV on stdout. Files that
+// don't hold puzzles are skipped with a warning.
+fn rate_dir(dir: &Path) -> Result<(), Box<dyn Error>> {
+    let entries = std::fs::read_dir(dir).map_err(|err| format!("could not read {}: {}", dir.display(), err))?;
+    let mut paths: Vec<PathBuf> = entries.filter_map(Result::ok).map(|entry| entry.path()).filter(|path| path.is_file()).collect();
+    paths.sort();
+    let (mut files, mut puzzles, mut rated) = (Vec::new(), Vec::new(), 0);
+    for path in &paths {
+        let name = path.file_name().map_or(String::new(), |name| name.to_string_lossy().into_owned());
+        let viewer = match std::fs::read_to_string(path).map_err(|err| err.to_string()).and_then(|text| Viewer::parse(&text).map_err(|err| err.to_string())) {
+            Ok(viewer) => viewer,
+            Err(err) => {
+                eprintln!("Skipping {}: {}", name, err);
+                continue;
+            }
+        };
+        for (i, game) in viewer.games.iter().enumerate() {
+            files.push((name.clone(), i + 1));
+            puzzles.push(game.givens());
+        }
+        rated += 1;
+    }
+    let graded = batch::grade_all(&puzzles);
+    let duplicates = batch::duplicates(&graded);
+    println!("{}", batch::CSV_HEADER);
+    for (((name, puzzle), graded), duplicate) in files.iter().zip(&graded).zip(&duplicates) {
+        let duplicate_of = duplicate.map(|first| (files[first].0.as_str(), files[first].1));
+        println!("{}", graded.csv_row(name, *puzzle, duplicate_of));
+    }
+    eprintln!("Rated {} puzzles from {} files, {} of them repeats", puzzles.len(), rated, duplicates.iter().flatten().count());
     Ok(())
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, game: &mut Game) -> io::Result<()> {
+// Time generating, solving, grading and checking puzzles on each board size,
+// printing each size's numbers as they're in
+fn bench_all() {
+    println!("rustdoku6 {} benchmark, {:.1} s a task on one thread", env!("CARGO_PKG_VERSION"), BENCH_BUDGET.as_secs_f64());
+    if cfg!(debug_assertions) {
+        eprintln!("This is a debug build; a release build (cargo build --release) runs many times faster");
+    }
+    bench::run(BENCH_BUDGET, |report| {
+        for line in bench::table(report) {
+            println!("{}", line);
+        }
+    });
+}
+
+fn view_loop(terminal: &mut Terminal<TermBackend>, viewer: &mut Viewer, config: &Config, typed: Vec<Key>) -> io::Result<()> {
+    let mut input = term::Input::new(Duration::from_millis(config.tick_ms), typed);
+    let mut shown = None;
     loop {
-        terminal.draw(|f| ui::draw(f, game)).map_err(|e| io::Error::other(e.to_string()))?;
-
-        if event::poll(Duration::from_millis(250))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('q') | KeyCode::Esc => {
-                            if let crate::model::GameState::About = game.state {
-                                game.state = crate::model::GameState::Playing; 
-                            } else {
-                                return Ok(());
-                            }
-                        }
-                        KeyCode::Char('i') | KeyCode::Char('I') => {
-                            if let crate::model::GameState::About = game.state {
-                                game.state = crate::model::GameState::Playing;
-                            } else {
-                                game.state = crate::model::GameState::About;
-                            }
-                        }
-                        KeyCode::Char('p') => if let crate::model::GameState::Playing = game.state { game.toggle_mode() },
-                        KeyCode::Left => if let crate::model::GameState::Playing = game.state { game.move_cursor(0, -1) },
-                        KeyCode::Right => if let crate::model::GameState::Playing = game.state { game.move_cursor(0, 1) },
-                        KeyCode::Up => if let crate::model::GameState::Playing = game.state { game.move_cursor(-1, 0) },
-                        KeyCode::Down => if let crate::model::GameState::Playing = game.state { game.move_cursor(1, 0) },
-                        KeyCode::Char('1') => if let crate::model::GameState::Playing = game.state { game.handle_input(1) },
-                        KeyCode::Char('2') => if let crate::model::GameState::Playing = game.state { game.handle_input(2) },
-                        KeyCode::Char('3') => if let crate::model::GameState::Playing = game.state { game.handle_input(3) },
-                        KeyCode::Char('4') => if let crate::model::GameState::Playing = game.state { game.handle_input(4) },
-                        KeyCode::Char('5') => if let crate::model::GameState::Playing = game.state { game.handle_input(5) },
-                        KeyCode::Char('6') => if let crate::model::GameState::Playing = game.state { game.handle_input(6) },
-                        KeyCode::Backspace | KeyCode::Delete => if let crate::model::GameState::Playing = game.state { game.clear_cell() },
-                        _ => {
-                            // Any key exits About screen if we are in it
-                            if let crate::model::GameState::About = game.state {
-                                game.state = crate::model::GameState::Playing;
-                            }
+        let game = &mut viewer.games[viewer.index];
+        if shown != Some(viewer.index) {
+            term::set_title(terminal, &ui::title_text(game))?;
+            shown = Some(viewer.index);
+        }
+        if game.take_dirty() {
+            terminal
+                .draw(|f| {
+                    ui::draw(f, game, config, &ui::FrameStats::default());
+                })
+                .map_err(|e| io::Error::other(e.to_string()))?;
+        }
+        match input.next(terminal)? {
+            AppEvent::Key(key) if !viewer.apply(command::from_key(key)) => return Ok(()),
+            AppEvent::Resize => viewer.games[viewer.index].mark_dirty(),
+            _ => {}
+        }
+    }
+}
+
+// Merge the stats and records of another install into ours. Counts add up and
+// the better of each best time and streak is kept.
+fn import_data(dir: &Path) -> Result<(), Box<dyn Error>> {
+    if !dir.is_dir() {
+        return Err(format!("{} is not a directory", dir.display()).into());
+    }
+    let source = storage::import_source(dir)?;
+    type Import = fn(&Path) -> io::Result<bool>;
+    let imports: [(&str, Import); 4] = [("stats", Stats::import), ("best solves", ghost::import), ("speedrun records", speedrun::import), ("time-attack streak", timeattack::import)];
+    for (what, import) in imports {
+        match import(dir) {
+            Ok(true) => println!("Merged {}", what),
+            Ok(false) => println!("No {} to merge", what),
+            Err(err) => return Err(format!("could not merge {}: {}", what, err).into()),
+        }
+    }
+    storage::record_import(&source).map_err(|err| format!("could not note the import: {}", err))?;
+    Ok(())
+}
+
+fn backup_data(path: &Path) -> Result<(), Box<dyn Error>> {
+    let backup = Backup::collect().map_err(|err| format!("could not read the data to back up: {}", err))?;
+    std::fs::write(path, backup.encode()).map_err(|err| format!("could not write {}: {}", path.display(), err))?;
+    println!("Backed up {} files to {}", backup.files.len(), path.display());
+    Ok(())
+}
+
+// Put the files of a backup back, replacing the ones here now
+fn restore_data(path: &Path) -> Result<(), Box<dyn Error>> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("could not read {}: {}", path.display(), err))?;
+    let backup = Backup::parse(&text).map_err(|err| format!("{}: {}", path.display(), err))?;
+    for name in backup.restore().map_err(|err| format!("could not restore the backup: {}", err))? {
+        println!("Restored {}", name);
+    }
+    Ok(())
+}
+
+// Terminal size `--render-snapshot` draws at unless given another
+const SNAPSHOT_SIZE: (u16, u16) = (100, 30);
+
+// The screen the game would open on, as plain text. The clock is stopped at
+// zero and the ghost left out, so the same options and seed always give the
+// same text, fit for golden files and bug reports.
+fn render_snapshot(game: &mut Game, config: &Config, out: &str, (cols, rows): (u16, u16)) -> Result<(), Box<dyn Error>> {
+    game.timer = Timer::stopped_at(Duration::ZERO);
+    // The records differ between installs, and with them the estimated time
+    game.stats = None;
+    let text = ui::snapshot(game, config, cols, rows);
+    if out == "-" {
+        print!("{}", text);
+    } else {
+        std::fs::write(out, text).map_err(|err| format!("could not write {}: {}", out, err))?;
+    }
+    Ok(())
+}
+
+fn write_cast(out: &str, key: Option<&str>, config: &Config, size: (u16, u16)) -> Result<(), Box<dyn Error>> {
+    let solve = Solve::last(key).ok_or_else(|| match key {
+        Some(key) => format!("no saved replay of {} (replays = true in the config saves them)", key),
+        None => "no saved replays (replays = true in the config saves them)".to_string(),
+    })?;
+    let text = cast::cast(&solve, config, size)?;
+    if out == "-" {
+        print!("{}", text);
+    } else {
+        std::fs::write(out, text).map_err(|err| format!("could not write {}: {}", out, err))?;
+        eprintln!("Wrote the solve of {} to {}", solve.puzzle, out);
+    }
+    Ok(())
+}
+
+// The `--require` puzzle: the one `--seed` gives, or the first of many random
+// seeds that qualifies
+fn require_puzzle(seed: Option<u64>, technique: &str, rng: &mut StdRng) -> Result<Game, Box<dyn Error>> {
+    let registry = TechniqueRegistry::builtin();
+    let Some(name) = registry.get(technique).map(|t| t.name()) else {
+        let ids: Vec<&str> = registry.enabled().map(|t| t.id()).collect();
+        return Err(format!("unknown technique: {} (one of {})", technique, ids.join(", ")).into());
+    };
+    if let Some(seed) = seed {
+        return Game::with_seed_requiring(seed, technique).ok_or_else(|| format!("seed {} doesn't give a puzzle that needs {}", seed, name).into());
+    }
+    let mut attempts = 0;
+    let found = Game::requiring(technique, rng, |n| {
+        attempts = n;
+        if n % 25 == 1 {
+            eprint!("\rLooking for a puzzle that needs {}... attempt {}", name, n);
+        }
+        true
+    });
+    let Some(game) = found else {
+        eprintln!();
+        return Err(format!("no puzzle that needs {} turned up in {} attempts", name, attempts).into());
+    };
+    eprintln!("\rFound a puzzle that needs {} after {} attempts; replay it with --seed {} --require {}", name, attempts, game.meta.seed, technique);
+    Ok(game)
+}
+
+// The config's generation profiles, and the index of `wanted` among them.
+// Profiles that don't parse are left out, unless they're the one wanted.
+fn load_profiles(config: &Config, wanted: Option<&str>) -> Result<(Vec<Profile>, Option<usize>), Box<dyn Error>> {
+    let mut profiles = Vec::new();
+    for (name, text) in &config.profiles {
+        match Profile::parse(name, text) {
+            Ok(profile) => profiles.push(profile),
+            Err(err) if wanted == Some(name.as_str()) => return Err(format!("profile {}: {}", name, err).into()),
+            Err(err) => log::warn!("skipping profile {}: {}", name, err),
+        }
+    }
+    let Some(wanted) = wanted else {
+        return Ok((profiles, None));
+    };
+    let index = profiles.iter().position(|profile| profile.name == wanted).ok_or_else(|| format!("no profile {} in the config (profile.{} = \"...\")", wanted, wanted))?;
+    Ok((profiles, Some(index)))
+}
+
+// The first puzzle of a profile: one made ahead of time if `cached`, else
+// looked for with a progress line as for `--require`
+fn profile_puzzle(profile: &Profile, cached: bool, rng: &mut StdRng) -> Result<Game, Box<dyn Error>> {
+    if let Some(game) = cached.then(|| pregen::take(profile)).flatten() {
+        return Ok(game);
+    }
+    let found = profile.generate(rng, |n| {
+        if n % 25 == 1 {
+            eprint!("\rLooking for a puzzle for profile {}... attempt {}", profile.name, n);
+        }
+        true
+    });
+    eprintln!();
+    found.ok_or_else(|| format!("no puzzle fitting profile {} turned up", profile.name).into())
+}
+
+// A solved puzzle, `key` or else one picked at random, through a random symmetry
+fn remix_puzzle(key: Option<&str>, rng: &mut StdRng) -> Result<Game, Box<dyn Error>> {
+    let key = match key {
+        Some(key) => key.to_string(),
+        None => ghost::solved().choose(rng).cloned().ok_or("no solved puzzles to remix yet")?,
+    };
+    let meta = PuzzleMeta::from_key(&key).ok_or_else(|| format!("not a puzzle key: {}", key))?;
+    let transform = Transform::random(rng, Grid::SPEC.box_rows == Grid::SPEC.box_cols);
+    let game = meta.remix(transform).ok_or_else(|| format!("{} can't be remixed: only classic, --require and imported puzzles can", key))?;
+    eprintln!("Remixed {} as {}", key, game.meta.key());
+    Ok(game)
+}
+
+// The configured background, else what the terminal or `COLORFGBG` says,
+// else dark
+fn background(config: &Config, detected: Option<Background>) -> Background {
+    Background::from_setting(&config.background)
+        .or(detected)
+        .or_else(|| std::env::var("COLORFGBG").ok().and_then(|value| Background::from_colorfgbg(&value)))
+        .unwrap_or_default()
+}
+
+// The daily seed comes from the configured server when networking is built in,
+// falling back to a seed derived from the date
+fn daily_seed(config: &Config, date: &str) -> u64 {
+    let Some(server) = &config.daily_server else {
+        return daily::local_seed(date);
+    };
+    #[cfg(feature = "net")]
+    match daily::fetch_seed(server, date) {
+        Ok(seed) => return seed,
+        Err(err) => {
+            log::warn!("daily seed fetch from {} failed: {}", server, err);
+            eprintln!("Could not fetch the daily puzzle from {}: {}", server, err);
+        }
+    }
+    #[cfg(not(feature = "net"))]
+    eprintln!("daily_server is set to {} but this build lacks the `net` feature", server);
+    daily::local_seed(date)
+}
+
+// A network connection following the game, synced once per loop iteration
+// and dropped when it fails
+trait Link {
+    fn sync(&mut self, game: &mut Game) -> io::Result<()>;
+}
+
+// `watch host:port`: follow a broadcasting game read-only until it ends or `q`
+#[cfg(feature = "net")]
+fn watch(addr: &str, config: &Config) -> Result<(), Box<dyn Error>> {
+    let spectator = broadcast::Spectator::connect(addr)?;
+    let (mut terminal, mut session) = term::setup(false, config.background == "auto")?;
+    let light = background(config, session.background) == Background::Light;
+    let result = watch_loop(&mut terminal, &spectator, addr, config, light, std::mem::take(&mut session.typed));
+    term::restore(&mut terminal, session)?;
+    if result? {
+        println!("{} stopped broadcasting.", addr);
+    }
+    Ok(())
+}
+
+// Returns whether the broadcast ended, rather than the spectator quitting
+#[cfg(feature = "net")]
+fn watch_loop(terminal: &mut Terminal<TermBackend>, spectator: &broadcast::Spectator, addr: &str, config: &Config, light: bool, typed: Vec<Key>) -> io::Result<bool> {
+    let mut input = term::Input::new(Duration::from_millis(config.tick_ms), typed);
+    let mut game: Option<Game> = None;
+    // The waiting screen needs drawing, at the start and after a resize
+    let mut draw_waiting = true;
+    loop {
+        match spectator.latest() {
+            Ok(Some(frame)) => frame.show(&mut game),
+            Ok(None) => {}
+            Err(_) => return Ok(true),
+        }
+        if let Some(game) = game.as_mut() {
+            if game.take_dirty() {
+                game.spectating = Some(addr.to_string());
+                game.light_background = light;
+                terminal
+                    .draw(|f| {
+                        ui::draw(f, game, config, &ui::FrameStats::default());
+                    })
+                    .map_err(|e| io::Error::other(e.to_string()))?;
+            }
+        } else if draw_waiting {
+            terminal
+                .draw(|f| ui::draw_waiting(f, &format!("Waiting for {}... | q: Quit", addr), light))
+                .map_err(|e| io::Error::other(e.to_string()))?;
+        }
+        draw_waiting = false;
+
+        match input.next(terminal)? {
+            AppEvent::Key(key) if matches!(command::from_key(key), Command::Quit | Command::Back) => return Ok(false),
+            AppEvent::Resize => {
+                draw_waiting = true;
+                if let Some(game) = game.as_mut() {
+                    game.mark_dirty();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Delay between solver moves in watch mode
+const DEMO_STEP_INTERVAL: Duration = Duration::from_secs(1);
+// How long quitting waits for a daily result still being sent
+#[cfg(feature = "net")]
+const SUBMIT_WAIT: Duration = Duration::from_secs(3);
+// Time `bench` gives each task on each board size
+const BENCH_BUDGET: Duration = Duration::from_millis(500);
+
+// Returns where the speedrun was exported, if one finished
+// `typed` holds keys typed before the game loop started, and `flagged_repeats`
+// is whether the terminal marks held keys as repeats
+fn run_app(terminal: &mut Terminal<TermBackend>, game: &mut Game, config: &Config, links: &mut Vec<Box<dyn Link>>, typed: Vec<Key>, flagged_repeats: bool) -> io::Result<Option<PathBuf>> {
+    let protocol = graphics::detect(&config.graphics, |key| env::var(key).ok());
+    log::info!("graphics setting {:?}, using {:?}", config.graphics, protocol);
+    let cell_px = term::cell_pixels(terminal);
+    let mut graphics = protocol.map(|protocol| GraphicsRenderer::new(protocol, cell_px));
+    let mut input = term::Input::new(Duration::from_millis(config.tick_ms), typed);
+    let result = run_loop(terminal, game, config, &mut input, &mut graphics, links, flagged_repeats);
+    if let Some(renderer) = graphics.as_mut() {
+        renderer.clear(&mut term::raw_output(terminal))?;
+    }
+    result
+}
+
+fn run_loop(
+    terminal: &mut Terminal<TermBackend>,
+    game: &mut Game,
+    config: &Config,
+    input: &mut term::Input,
+    graphics: &mut Option<GraphicsRenderer>,
+    links: &mut Vec<Box<dyn Link>>,
+    flagged_repeats: bool,
+) -> io::Result<Option<PathBuf>> {
+    let mut last_demo_step = Instant::now();
+    let mut speedrun_export = None;
+    #[cfg(feature = "net")]
+    let mut daily_submitted = false;
+    let idle_limit = Duration::from_secs(config.idle_pause_minutes * 60);
+    let mut last_input = Instant::now();
+    let mut key_repeat = KeyRepeat::new(Duration::from_millis(config.key_repeat_ms), flagged_repeats);
+    let mut frame_stats = ui::FrameStats::default();
+    // Where the board was last drawn, for mapping mouse positions to cells
+    let mut drawn_board = None;
+    // The terminal's title, set again whenever the puzzle or what's known
+    // of it changes
+    let mut title = None;
+    loop {
+        links.retain_mut(|link| match link.sync(game) {
+            Ok(()) => true,
+            Err(err) => {
+                log::warn!("network link lost: {}", err);
+                false
+            }
+        });
+
+        game.check_countdown();
+        game.end_flash();
+        game.poll_solve();
+        game.poll_search();
+        if game.screen() == Screen::Won && (game.endless.is_some() || game.time_attack.is_some()) {
+            game.next_puzzle_in_run();
+        }
+        let text = ui::title_text(game);
+        if title.as_ref() != Some(&text) {
+            term::set_title(terminal, &text)?;
+            title = Some(text);
+        }
+
+        // Only redraw after input, a resize, a watch-mode step or a clock tick
+        if game.take_dirty() {
+            game.refresh_debug_solve();
+            let started = Instant::now();
+            let (mut board_area, mut area) = (None, Rect::default());
+            terminal
+                .draw(|f| {
+                    area = f.area();
+                    board_area = ui::draw(f, game, config, &frame_stats);
+                })
+                .map_err(|e| io::Error::other(e.to_string()))?;
+            game.about_rows = ui::about_rows(area.height);
+            if let Some(renderer) = graphics.as_mut() {
+                if renderer.present(&mut term::raw_output(terminal), game, board_area)? {
+                    terminal.clear().map_err(|e| io::Error::other(e.to_string()))?;
+                    game.mark_dirty();
+                }
+            }
+            drawn_board = board_area;
+            frame_stats = ui::FrameStats { render_time: started.elapsed(), frames: frame_stats.frames + 1 };
+        }
+
+        if let Some(run) = game.speedrun.as_mut() {
+            if let Some(path) = run.finish(&game.meta.key())? {
+                log::info!("speedrun exported to {}", path.display());
+                speedrun_export = Some(path);
+            }
+        }
+
+        #[cfg(feature = "net")]
+        if let (Screen::Won, Some(date), Some(server), false) = (game.screen(), &game.meta.date, &config.daily_server, daily_submitted) {
+            daily_submitted = true;
+            let (server, date, player) = (server.clone(), date.clone(), config.player_name.clone());
+            // A failed submission only loses the leaderboard entry
+            daily::submit_in_background(server, date, player, game.timer.elapsed(), game.mistakes);
+        }
+
+        if game.screen() == Screen::Demo && last_demo_step.elapsed() >= DEMO_STEP_INTERVAL {
+            game.demo_step();
+            last_demo_step = Instant::now();
+        }
+
+        // Keep best times honest when the player walks away
+        if config.idle_pause_minutes > 0 && last_input.elapsed() >= idle_limit {
+            game.pause_idle(last_input);
+        }
+
+        let event = input.next(terminal)?;
+        // Ticks are picked up by the clock check, and mouse motion only redraws when the hover moves
+        if !matches!(event, AppEvent::Tick | AppEvent::Mouse(_)) {
+            game.mark_dirty();
+        }
+        match event {
+            // Lay out for the new size now rather than on the next draw, so
+            // nothing from the old layout is left on screen
+            AppEvent::Resize => {
+                terminal.autoresize().map_err(|e| io::Error::other(e.to_string()))?;
+                terminal.clear().map_err(|e| io::Error::other(e.to_string()))?;
+                if let Some(renderer) = graphics.as_mut() {
+                    renderer.resized(term::cell_pixels(terminal));
+                }
+            }
+            // Alt-tabbing away shouldn't count against the clock
+            AppEvent::FocusLost => game.pause(),
+            AppEvent::FocusGained => {
+                // Back at the terminal: the idle pause counts from now
+                last_input = Instant::now();
+                game.resume();
+            }
+            AppEvent::Mouse(mouse) => {
+                let cell = drawn_board.and_then(|board| ui::cell_at(board, mouse.column, mouse.row));
+                match mouse.kind {
+                    MouseKind::Moved => game.set_hover(cell),
+                    MouseKind::LeftDown if matches!(game.screen(), Screen::Playing | Screen::Won) => {
+                        last_input = Instant::now();
+                        if let Some((r, c)) = cell {
+                            game.apply(Command::SelectCell(r, c));
+                            game.mark_dirty();
                         }
                     }
+                    _ => {}
+                }
+            }
+            AppEvent::Key(key) => {
+                last_input = Instant::now();
+                // An open dialog takes every key, held ones included
+                if game.dialog.is_some() {
+                    if !game.dialog_key(key) {
+                        return Ok(speedrun_export);
+                    }
+                    continue;
+                }
+                if game.go_to_row_key(key) {
+                    continue;
+                }
+                let command = command::from_key(key);
+                if !key_repeat.accept(&key, last_input, command.repeats()) {
+                    continue;
+                }
+                // Any key other than quit resumes a paused game
+                if game.screen() == Screen::Paused && command != Command::Quit {
+                    game.resume();
+                    continue;
+                }
+                if command == Command::ToggleDemo {
+                    last_demo_step = Instant::now();
+                }
+                if !game.apply(command) {
+                    return Ok(speedrun_export);
                 }
             }
+            _ => {}
         }
     }
 }
@@ -1,11 +1,103 @@
 // use std::ops::{Index, IndexMut};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-#[derive(Default)]
-pub struct Cell {
-    pub value: Option<u8>,
-    pub is_fixed: bool,
-    pub marks: [bool; 6],
+// A cell packed into 32 bits, as boards are copied whole for every undo
+// step, replay frame and solver guess: a pencil mark per digit in the low 16
+// bits (digit d at bit d - 1), then the value (0 for none) in five bits up to
+// 16, whether it's a given, and whether it's locked. The player is sure of a
+// locked value: entries and erases leave it alone until unlocked.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cell<const N: usize = 6>(u32);
+
+const MARK_BITS: u32 = 0xffff;
+const VALUE_SHIFT: u32 = 16;
+const VALUE_BITS: u32 = 0x1f << VALUE_SHIFT;
+const FIXED_BIT: u32 = 1 << 21;
+const LOCKED_BIT: u32 = 1 << 22;
+
+impl<const N: usize> Cell<N> {
+    // The marks take a bit per digit and the value five, so boards up to 16
+    // wide fit; a wider one fails to build at its first entry or mark
+    const FITS: () = assert!(N <= 16, "a cell packs the marks and values of boards up to 16 wide");
+
+    // A clue holding `value`
+    pub fn given(value: u8) -> Self {
+        let mut cell = Self::default();
+        cell.set_value(Some(value));
+        cell.set_fixed(true);
+        cell
+    }
+
+    pub fn value(self) -> Option<u8> {
+        match (self.0 & VALUE_BITS) >> VALUE_SHIFT {
+            0 => None,
+            value => Some(value as u8),
+        }
+    }
+
+    pub fn set_value(&mut self, value: Option<u8>) {
+        let () = Self::FITS;
+        debug_assert!(value.is_none_or(|value| (1..=N as u8).contains(&value)), "{:?} isn't a value of a {}x{} board", value, N, N);
+        self.0 = (self.0 & !VALUE_BITS) | (u32::from(value.unwrap_or(0)) << VALUE_SHIFT & VALUE_BITS);
+    }
+
+    pub fn is_fixed(self) -> bool {
+        self.0 & FIXED_BIT != 0
+    }
+
+    pub fn set_fixed(&mut self, fixed: bool) {
+        self.set_bit(FIXED_BIT, fixed);
+    }
+
+    pub fn locked(self) -> bool {
+        self.0 & LOCKED_BIT != 0
+    }
+
+    pub fn set_locked(&mut self, locked: bool) {
+        self.set_bit(LOCKED_BIT, locked);
+    }
+
+    // Whether digit `value` is pencilled in
+    pub fn mark(self, value: u8) -> bool {
+        (1..=N as u8).contains(&value) && self.0 & (1 << (value - 1)) != 0
+    }
+
+    // Whether each digit is pencilled in, 1 first
+    pub fn marks(self) -> [bool; N] {
+        std::array::from_fn(|i| self.0 & (1 << i) != 0)
+    }
+
+    pub fn has_marks(self) -> bool {
+        self.0 & MARK_BITS != 0
+    }
+
+    pub fn set_mark(&mut self, value: u8, on: bool) {
+        let () = Self::FITS;
+        if (1..=N as u8).contains(&value) {
+            self.set_bit(1 << (value - 1), on);
+        }
+    }
+
+    pub fn toggle_mark(&mut self, value: u8) {
+        self.set_mark(value, !self.mark(value));
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.0 &= !MARK_BITS;
+    }
+
+    fn set_bit(&mut self, bit: u32, on: bool) {
+        match on {
+            true => self.0 |= bit,
+            false => self.0 &= !bit,
+        }
+    }
+}
+
+impl<const N: usize> fmt::Debug for Cell<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let marks: Vec<u8> = (1..=N as u8).filter(|&value| self.mark(value)).collect();
+        f.debug_struct("Cell").field("value", &self.value()).field("is_fixed", &self.is_fixed()).field("marks", &marks).field("locked", &self.locked()).finish()
+    }
 }
 
 
@@ -15,284 +107,2080 @@ pub enum InputMode {
     Pencil,
 }
 
-pub enum GameState {
-    Playing,
-    Won,
-    About,
+// How a full board is judged won (config `win_check`)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum WinCheck {
+    // No rule is broken, so any valid completion of an imported puzzle with
+    // more than one solution wins
+    #[default]
+    Rules,
+    // Every value is the stored solution's
+    Solution,
+}
+
+impl WinCheck {
+    // "rules" or "solution"
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "rules" => Some(WinCheck::Rules),
+            "solution" => Some(WinCheck::Solution),
+            _ => None,
+        }
+    }
+}
+
+// Solve clock that only counts time while running
+pub struct Timer {
+    accumulated: Duration,
+    running_since: Option<Instant>,
 }
 
-pub struct Grid {
-    pub cells: [[Cell; 6]; 6],
+impl Timer {
+    pub fn start() -> Self {
+        Self { accumulated: Duration::ZERO, running_since: Some(Instant::now()) }
+    }
+
+    // A stopped clock showing `elapsed`
+    pub fn stopped_at(elapsed: Duration) -> Self {
+        Self { accumulated: elapsed, running_since: None }
+    }
+
+    pub fn pause(&mut self) {
+        self.pause_at(Instant::now());
+    }
+
+    // Pause as if it had happened at `at`, discarding any time after it
+    pub fn pause_at(&mut self, at: Instant) {
+        if let Some(since) = self.running_since.take() {
+            self.accumulated += at.saturating_duration_since(since);
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.running_since.is_none() {
+            self.running_since = Some(Instant::now());
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.accumulated + self.running_since.map_or(Duration::ZERO, |since| since.elapsed())
+    }
 }
 
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt,
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
 use rand::prelude::*;
 
-const GRID_SIZE: usize = 6;
-const MIN_CELL_VALUE: u8 = 1;
-const MAX_CELL_VALUE: u8 = 6;
+use crate::{
+    about,
+    adaptive::Adaptive,
+    bot::Bot,
+    command::Command,
+    constraints::{self, BoardView, Constraint},
+    dialog::{Answer, Dialog, Outcome},
+    endless::{self, Endless},
+    events::{Key, KeyCode},
+    ghost::Ghost,
+    replay::Replay,
+    hotseat::HotSeat,
+    macros::Macros,
+    i18n::{t, tf},
+    generator::{GenerateError, Generator, PuzzleSpec},
+    pregen,
+    profile::{self, Profile},
+    puzzle::{Puzzle, PuzzleMeta, Source, Variant},
+    regions::RegionMap,
+    screen::{Screen, ScreenStack},
+    search::Search,
+    solver::{self, Contradiction, Deduction, Refutation, SolveStats, TechniqueRegistry, Unit},
+    timeattack::TimeAttack,
+    speedrun::Speedrun,
+    stats::{Category, Stats},
+    symmetry::Transform,
+    undotree::{Cells, UndoTree},
+    weekly,
+};
 
-fn is_in_bounds_and_valid_value(row: usize, col: usize, value: u8) -> bool {
-    row < GRID_SIZE && col < GRID_SIZE && (MIN_CELL_VALUE..=MAX_CELL_VALUE).contains(&value)
+// The shape of a board: its side length and the rows and columns of each box.
+// The game is played on `Grid::SPEC`, 6x6 with 2x3 boxes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoardSpec {
+    pub size: usize,
+    pub box_rows: usize,
+    pub box_cols: usize,
 }
 
-impl Grid {
+impl BoardSpec {
+    pub const fn new(size: usize, box_rows: usize, box_cols: usize) -> Self {
+        assert!(box_rows * box_cols == size, "boxes must hold one of each value");
+        assert!(size <= 16, "candidate masks hold up to 16 values");
+        Self { size, box_rows, box_cols }
+    }
+
+    // Whether (row, col) is on the board and `value` is one of its digits
+    pub fn contains(&self, row: usize, col: usize, value: u8) -> bool {
+        row < self.size && col < self.size && (1..=self.size).contains(&(value as usize))
+    }
+}
+
+// A board of N x N cells holding the values 1..=N. Its regions are boxes of
+// BOX_ROWS x BOX_COLS unless a variant swaps in its own map.
+#[derive(Clone)]
+pub struct Board<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize> {
+    pub cells: [[Cell<N>; N]; N],
+    // The regions and rules are shared between copies of the board, e.g. in the undo tree
+    pub regions: Arc<RegionMap>,
+    pub constraints: Arc<[Box<dyn Constraint>]>,
+}
+
+pub type Grid = Board<6, 2, 3>;
+
+impl<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize> Board<N, BOX_ROWS, BOX_COLS> {
+    pub const SPEC: BoardSpec = BoardSpec::new(N, BOX_ROWS, BOX_COLS);
+
     pub fn new() -> Self {
+        Self::with_constraints(Vec::new())
+    }
+
+    // A board following a variant's constraints as well as the standard ones
+    pub fn with_constraints(variant: Vec<Box<dyn Constraint>>) -> Self {
+        let mut constraints = constraints::standard();
+        constraints.extend(variant);
+        let regions = constraints.iter().flat_map(|constraint| constraint.units(N)).fold(RegionMap::boxes(Self::SPEC), |regions, (kind, unit)| regions.with_unit(kind, unit));
         Self {
-            cells: [[Cell::default(); 6]; 6],
+            cells: [[Cell::default(); N]; N],
+            regions: Arc::new(regions),
+            constraints: constraints.into(),
         }
     }
     
     // Backtracking solver to fill the grid randomly
-    pub fn fill_randomly(&mut self) -> bool {
-        let mut rng = rand::rng();
-        let mut numbers: [u8; 6] = [1, 2, 3, 4, 5, 6];
+    pub fn fill_randomly<R: Rng + ?Sized>(&mut self, rng: &mut R) -> bool {
+        let regions = Arc::clone(&self.regions);
+        let mut numbers: Vec<u8> = regions.values().collect();
         
-        for r in 0..6 {
-            for c in 0..6 {
-                if self.cells[r][c].value.is_none() {
-                    numbers.shuffle(&mut rng);
-                    for &n in &numbers {
-                        if self.is_valid_move(r, c, n) {
-                            self.cells[r][c].value = Some(n);
-                            if self.fill_randomly() {
-                                return true;
-                            }
-                            self.cells[r][c].value = None;
+        for (r, c) in regions.cells() {
+            if self.cells[r][c].value().is_none() {
+                numbers.shuffle(rng);
+                for &n in &numbers {
+                    if self.is_valid_move(r, c, n) {
+                        self.cells[r][c].set_value(Some(n));
+                        if self.fill_randomly(rng) {
+                            return true;
                         }
+                        self.cells[r][c].set_value(None);
                     }
-                    return false;
                 }
+                return false;
             }
         }
         true
     }
 
-    // Check if placing `value` at (row, col) is valid
+    // Check if placing `value` at (row, col) is valid: every constraint allows it
     pub fn is_valid_move(&self, row: usize, col: usize, value: u8) -> bool {
-        if !is_in_bounds_and_valid_value(row, col, value) {
-            return false;
+        Self::SPEC.contains(row, col, value) && self.constraints.iter().all(|constraint| constraint.allows(self, row, col, value))
+    }
+
+    // A peer of (row, col) already holding `value`, if any
+    pub fn conflicting_peer(&self, row: usize, col: usize, value: u8) -> Option<(usize, usize)> {
+        self.regions.peers(row, col).iter().copied().find(|&(r, c)| self.cells[r][c].value() == Some(value))
+    }
+
+    pub fn clue_count(&self) -> usize {
+        self.cells.iter().flatten().filter(|cell| cell.is_fixed()).count()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.cells.iter().flatten().all(|cell| cell.value().is_some())
+    }
+    
+    // Check if the board is completely full AND valid (Win condition)
+    pub fn is_solved(&self) -> bool {
+        self.regions.cells().all(|(r, c)| self.cells[r][c].value().is_some_and(|v| self.is_valid_move(r, c, v)))
+    }
+
+    // The ways of filling the board's empty cells, as values row by row, up to `limit` of them
+    pub fn solutions(&self, limit: usize) -> Vec<[[u8; N]; N]> {
+        self.solutions_with_stats(limit).0
+    }
+
+    // `solutions`, with the nodes and backtracks the search took
+    pub fn solutions_with_stats(&self, limit: usize) -> (Vec<[[u8; N]; N]>, SolveStats) {
+        let mut board = self.clone();
+        let mut found = Vec::new();
+        let mut stats = SolveStats::default();
+        board.collect_solutions(limit, &mut found, &mut stats);
+        (found, stats)
+    }
+
+    fn collect_solutions(&mut self, limit: usize, found: &mut Vec<[[u8; N]; N]>, stats: &mut SolveStats) {
+        let regions = Arc::clone(&self.regions);
+        let Some((r, c)) = regions.cells().find(|&(r, c)| self.cells[r][c].value().is_none()) else {
+            found.push(self.cells.map(|row| row.map(|cell| cell.value().unwrap_or(0))));
+            return;
+        };
+        for value in regions.values() {
+            if found.len() == limit {
+                return;
+            }
+            if self.is_valid_move(r, c, value) {
+                stats.nodes += 1;
+                let before = found.len();
+                self.cells[r][c].set_value(Some(value));
+                self.collect_solutions(limit, found, stats);
+                self.cells[r][c].set_value(None);
+                stats.backtracks += (found.len() == before) as usize;
+            }
         }
+    }
 
-        // Row check
-        for c in 0..6 {
-            if c != col {
-                if let Some(v) = self.cells[row][c].value {
-                    if v == value {
-                        return false;
-                    }
-                }
+    // What holds of every board the game can reach, whatever the player has
+    // done: entries may be wrong, but the givens never break a rule. The first
+    // cell breaking one, in reading order, is reported.
+    pub fn invariants(&self) -> Result<(), InvariantError> {
+        for (row, col) in self.regions.cells() {
+            let cell = &self.cells[row][col];
+            match cell.value() {
+                Some(value) if !Self::SPEC.contains(row, col, value) => return Err(InvariantError::ValueOutOfRange { row, col, value }),
+                None if cell.is_fixed() => return Err(InvariantError::GivenWithoutValue { row, col }),
+                None if cell.locked() => return Err(InvariantError::LockedWithoutValue { row, col }),
+                _ if cell.is_fixed() && cell.locked() => return Err(InvariantError::LockedGiven { row, col }),
+                _ => {}
+            }
+        }
+        for (row, col) in self.regions.cells().filter(|&(r, c)| self.cells[r][c].is_fixed()) {
+            let value = self.cells[row][col].value();
+            if let Some(&peer) = self.regions.peers(row, col).iter().find(|&&(r, c)| self.cells[r][c].is_fixed() && self.cells[r][c].value() == value) {
+                return Err(InvariantError::GivensConflict { a: (row, col), b: peer });
             }
         }
+        Ok(())
+    }
 
-        // Col check
-        for r in 0..6 {
-            if r != row {
-                if let Some(v) = self.cells[r][col].value {
-                    if v == value {
-                        return false;
-                    }
-                }
+    // The invariants, and what holds from one command to the next: givens
+    // carry no pencil marks, and are where and what they were on `before`, the
+    // board as it was ahead of the command. Debug builds check it after every
+    // command, so a change that corrupts the board fails where it's made.
+    pub fn validate_consistency(&self, before: &Self) -> Result<(), InvariantError> {
+        self.invariants()?;
+        for (row, col) in self.regions.cells() {
+            let (cell, was) = (&self.cells[row][col], &before.cells[row][col]);
+            if cell.is_fixed() && cell.has_marks() {
+                return Err(InvariantError::MarkedGiven { row, col });
+            }
+            if (cell.is_fixed() || was.is_fixed()) && (cell.is_fixed(), cell.value()) != (was.is_fixed(), was.value()) {
+                return Err(InvariantError::GivenChanged { row, col });
             }
         }
+        Ok(())
+    }
+}
 
-        // 2x3 Box check (Standard 6x6 is usually 2 rows x 3 cols regions)
-        // Regions are:
-        // (0,0)-(1,2), (0,3)-(1,5)
-        // (2,0)-(3,2), (2,3)-(3,5)
-        // (4,0)-(5,2), (4,3)-(5,5)
-        
-        let start_row = (row / 2) * 2;
-        let start_col = (col / 3) * 3;
-
-        for r in start_row..start_row + 2 {
-            for c in start_col..start_col + 3 {
-                if r != row || c != col {
-                    if let Some(v) = self.cells[r][c].value {
-                        if v == value {
-                            return false;
+impl<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize> Default for Board<N, BOX_ROWS, BOX_COLS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize> BoardView for Board<N, BOX_ROWS, BOX_COLS> {
+    fn value(&self, row: usize, col: usize) -> Option<u8> {
+        self.cells[row][col].value()
+    }
+
+    fn regions(&self) -> &RegionMap {
+        &self.regions
+    }
+}
+
+impl Grid {
+    // Compact share string for the puzzle's givens: "RD6:" followed by 36 digits,
+    // row by row, with 0 for empty cells
+    pub fn share_code(&self) -> String {
+        let digits: String = self
+            .cells
+            .iter()
+            .flatten()
+            .map(|cell| match cell.value() {
+                Some(v) if cell.is_fixed() => (b'0' + v) as char,
+                _ => '0',
+            })
+            .collect();
+        format!("RD6:{}", digits)
+    }
+
+    // The givens a share code lists, None unless it's "RD6:" and 36 digits 0-6
+    pub fn from_share_code(code: &str) -> Option<Self> {
+        let digits = code.trim().strip_prefix("RD6:")?;
+        if digits.chars().count() != 36 {
+            return None;
+        }
+        let mut grid = Grid::new();
+        for (i, ch) in digits.chars().enumerate() {
+            let cell = &mut grid.cells[i / 6][i % 6];
+            match ch {
+                '0' => {}
+                '1'..='6' => *cell = Cell::given(ch as u8 - b'0'),
+                _ => return None,
+            }
+        }
+        Some(grid)
+    }
+
+    // Read a board written by its Display impl. Boards that parse also pass
+    // `invariants`.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let rows: Vec<&str> = text.lines().collect();
+        if rows.len() != 6 {
+            return Err(ParseError::RowCount(rows.len()));
+        }
+        let mut grid = Grid::new();
+        for (row, line) in rows.into_iter().enumerate() {
+            let mut cells = Vec::with_capacity(6);
+            let mut chars = line.chars().peekable();
+            while let Some(ch) = chars.next() {
+                let col = cells.len();
+                let mut cell = Cell::default();
+                match ch {
+                    '.' => {}
+                    '1'..='6' => cell = Cell::given(ch as u8 - b'0'),
+                    'a'..='f' => cell.set_value(Some(ch as u8 - b'a' + 1)),
+                    'A'..='F' => {
+                        cell.set_value(Some(ch as u8 - b'A' + 1));
+                        cell.set_locked(true);
+                    }
+                    _ => return Err(ParseError::InvalidChar { row, col, ch }),
+                }
+                if chars.next_if_eq(&'[').is_some() {
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some(mark @ '1'..='6') => cell.set_mark(mark as u8 - b'0', true),
+                            Some(ch) => return Err(ParseError::InvalidChar { row, col, ch }),
+                            None => return Err(ParseError::UnclosedMarks { row, col }),
                         }
                     }
                 }
+                cells.push(cell);
             }
+            grid.cells[row] = cells.try_into().map_err(|cells: Vec<Cell>| ParseError::RowLength { row, found: cells.len() })?;
         }
-
-        true
+        grid.invariants().map_err(ParseError::Invalid)?;
+        Ok(grid)
     }
+}
 
-    pub fn is_full(&self) -> bool {
-        for row in 0..6 {
-            for col in 0..6 {
-                if self.cells[row][col].value.is_none() {
-                    return false;
+// Six lines of six cells: `.` for an empty cell, `1`-`6` for a given, `a`-`f`
+// for an entry and `A`-`F` for a locked one, each followed by its pencil
+// marks in brackets if it has any, e.g. `.[25]`. Marks and all, it reads back
+// with `Grid::parse`.
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (r, row) in self.cells.iter().enumerate() {
+            if r > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                let ch = match cell.value() {
+                    None => '.',
+                    Some(v) if cell.is_fixed() => (b'0' + v) as char,
+                    Some(v) if cell.locked() => (b'A' + v - 1) as char,
+                    Some(v) => (b'a' + v - 1) as char,
+                };
+                write!(f, "{}", ch)?;
+                if cell.has_marks() {
+                    let marks: String = (1..=6u8).filter(|&v| cell.mark(v)).map(|v| (b'0' + v) as char).collect();
+                    write!(f, "[{}]", marks)?;
                 }
             }
         }
-        true
+        Ok(())
     }
-    
-    // Check if the board is completely full AND valid (Win condition)
-    pub fn is_solved(&self) -> bool {
-        if !self.is_full() {
-            return false;
+}
+
+// Why a board isn't one the game could reach. Cells are (row, col), counting from 0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InvariantError {
+    ValueOutOfRange { row: usize, col: usize, value: u8 },
+    GivenWithoutValue { row: usize, col: usize },
+    LockedWithoutValue { row: usize, col: usize },
+    // Givens can't be changed, so there's nothing to lock
+    LockedGiven { row: usize, col: usize },
+    // Two givens breaking a rule between them, so no solution exists
+    GivensConflict { a: (usize, usize), b: (usize, usize) },
+    // Only from `validate_consistency`: a given with pencil marks, and one
+    // changed, added or taken away by a command
+    MarkedGiven { row: usize, col: usize },
+    GivenChanged { row: usize, col: usize },
+}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            InvariantError::ValueOutOfRange { row, col, value } => write!(f, "r{}c{} holds {}, which isn't on the board", row + 1, col + 1, value),
+            InvariantError::GivenWithoutValue { row, col } => write!(f, "r{}c{} is a given with no value", row + 1, col + 1),
+            InvariantError::LockedWithoutValue { row, col } => write!(f, "r{}c{} is locked with no value", row + 1, col + 1),
+            InvariantError::LockedGiven { row, col } => write!(f, "r{}c{} is a locked given", row + 1, col + 1),
+            InvariantError::GivensConflict { a, b } => write!(f, "givens at r{}c{} and r{}c{} conflict", a.0 + 1, a.1 + 1, b.0 + 1, b.1 + 1),
+            InvariantError::MarkedGiven { row, col } => write!(f, "r{}c{} is a given with pencil marks", row + 1, col + 1),
+            InvariantError::GivenChanged { row, col } => write!(f, "the given at r{}c{} changed", row + 1, col + 1),
         }
-        
-        for r in 0..6 {
-            for c in 0..6 {
-                // Safe because is_full() returned true, but use expect for clarity
-                let val = self.cells[r][c].value.expect("Cell should have value when grid is full");
-                if !self.is_valid_move(r, c, val) {
-                    return false;
+    }
+}
+
+impl Error for InvariantError {}
+
+// Why text isn't a board. Rows and columns count from 0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParseError {
+    // Lines found, instead of 6
+    RowCount(usize),
+    // Cells found on the row, instead of 6
+    RowLength { row: usize, found: usize },
+    InvalidChar { row: usize, col: usize, ch: char },
+    // A cell's pencil marks were opened with `[` but never closed
+    UnclosedMarks { row: usize, col: usize },
+    // The cells read, but the board breaks an invariant
+    Invalid(InvariantError),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::RowCount(found) => write!(f, "expected 6 rows, found {}", found),
+            ParseError::RowLength { row, found } => write!(f, "row {} has {} cells, expected 6", row + 1, found),
+            ParseError::InvalidChar { row, col, ch } => write!(f, "unexpected {:?} at r{}c{}", ch, row + 1, col + 1),
+            ParseError::UnclosedMarks { row, col } => write!(f, "unclosed pencil marks at r{}c{}", row + 1, col + 1),
+            ParseError::Invalid(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Invalid(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+// What a dialog was opened for, which decides what its answer does
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DialogPurpose {
+    Quit,
+    Import,
+    // Only to be read, e.g. why an import failed
+    Notice,
+}
+
+// How long a cell flashes after a blocked entry, or before a wrong one is
+// cleared, and how long a placement lights up its peers
+pub const FLASH_TIME: Duration = Duration::from_millis(600);
+
+// A value the strict input option kept off the board, because `peer` already
+// holds it in the same `unit`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rejection {
+    pub row: usize,
+    pub col: usize,
+    pub value: u8,
+    pub peer: (usize, usize),
+    pub unit: &'static str,
+}
+
+// Wrong entries taken back off the board, told apart by what replaced them.
+// A correction is a slip put right at once, with the right digit in the same
+// cell and nothing entered in between; an error is a wrong entry the solve
+// went on from, or one not replaced with the right digit.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Erases {
+    pub corrections: u32,
+    pub errors: u32,
+}
+
+impl Erases {
+    pub fn add(&mut self, other: Erases) {
+        self.corrections += other.corrections;
+        self.errors += other.errors;
+    }
+}
+
+// The killer cage a cell is in, as far as it's filled: what it adds up to,
+// what its entries add up to so far, and the sets of digits its empty cells
+// could still take
+#[derive(Clone, Debug, PartialEq)]
+pub struct CageSum {
+    pub sum: u32,
+    pub so_far: u32,
+    pub empty: usize,
+    pub combinations: Vec<Vec<u8>>,
+}
+
+impl CageSum {
+    // Each set of `cells` different digits 1-6, none of them in `used`, that
+    // adds up to `sum`, smallest digits first
+    pub fn combinations(sum: u32, cells: usize, used: &[u8]) -> Vec<Vec<u8>> {
+        fn extend(from: u8, sum: u32, cells: usize, used: &[u8], set: &mut Vec<u8>, sets: &mut Vec<Vec<u8>>) {
+            if cells == 0 {
+                if sum == 0 {
+                    sets.push(set.clone());
+                }
+                return;
+            }
+            for digit in from..=6 {
+                if u32::from(digit) > sum {
+                    break;
+                }
+                if !used.contains(&digit) {
+                    set.push(digit);
+                    extend(digit + 1, sum - u32::from(digit), cells - 1, used, set, sets);
+                    set.pop();
                 }
             }
         }
-        true
+        let mut sets = Vec::new();
+        extend(1, sum, cells, used, &mut Vec::new(), &mut sets);
+        sets
     }
 }
 
+// A wrong entry and the solver's case against it; no refutation means the
+// enabled techniques couldn't find one
+#[derive(Clone, Debug, PartialEq)]
+pub struct Explanation {
+    pub row: usize,
+    pub col: usize,
+    pub value: u8,
+    pub refutation: Option<Refutation>,
+}
+
+// A what-if branch: entries made since it was opened aren't judged, and are
+// kept or thrown away together
+pub struct Hypothesis {
+    // The board's cells when the branch was opened
+    pub base: Cells,
+    // Its undo tree node, returned to on discard
+    pub node: usize,
+}
+
+// The value of each cell, row by row
+pub type Solution = [[u8; 6]; 6];
+
 pub struct Game {
     pub grid: Grid,
-    pub solution: [[u8; 6]; 6],
+    // None for a puzzle nobody solved, e.g. one typed in; entries are then
+    // only checked against the rules
+    pub solution: Option<Solution>,
+    // Solutions being found in the background, up to two to tell whether
+    // there's only one
+    solving: Option<Receiver<Vec<Solution>>>,
     pub cursor: (usize, usize),
-    pub state: GameState,
+    // Cell under the mouse pointer, highlighted apart from the cursor
+    pub hover: Option<(usize, usize)>,
+    // The screen shown is on top; see `screen`
+    pub screens: ScreenStack,
     pub mode: InputMode,
     pub mistakes: u32,
+    // Wrong entries taken back so far
+    pub erases: Erases,
+    // Values entered so far, and how many had been when each wrong one still
+    // on the board went in
+    entered: u32,
+    wrong_since: [[Option<u32>; 6]; 6],
+    // A wrong entry just taken off, waiting on what goes in next, and whether
+    // anything was entered after it
+    erased: Option<((usize, usize), bool)>,
+    // Last step taken by the watch (demo) mode, shown in the status bar
+    pub demo_last: Option<Deduction>,
+    // Pending hint, shown in the status bar until the next entry
+    pub hint: Option<Deduction>,
+    // Entries watch mode made, and the player's entries that played a hint
+    // as given; a game won this way isn't rated
+    pub watched: u32,
+    pub hinted: u32,
+    // Refuse values that clash with a peer on the board (config `block_conflicts`)
+    pub block_conflicts: bool,
+    // Clear wrong entries once they've flashed (config `auto_clear_mistakes`)
+    pub auto_clear: bool,
+    pub win_check: WinCheck,
+    // A wrong entry to clear when the flash ends
+    pending_clear: Option<(usize, usize)>,
+    // A go-to chord after `g`: waiting on the row (None) or, with it, on the column
+    pub go_to: Option<Option<usize>>,
+    // Recorded input macros, kept from puzzle to puzzle
+    pub macros: Macros,
+    // The last digit entered or marked, with the mode it went in with, or
+    // None for a clear; what `Command::Repeat` does again
+    pub last_entry: Option<(InputMode, Option<u8>)>,
+    // The last entry refused for clashing, explained in the status bar until the next command
+    pub rejected: Option<Rejection>,
+    // Wrong entries counted by the last on-track check, shown until the next command
+    pub progress_check: Option<usize>,
+    // Cells flash until then, e.g. after a refused entry
+    pub flash_until: Option<Instant>,
+    // The digit just placed, whose row, column and box light up during the flash
+    pub placed: Option<(usize, usize)>,
+    // Units the digit just placed completed, as indices into
+    // `RegionMap::units`, which glow during the flash
+    pub completed: Vec<usize>,
+    // Why the entry under the cursor is wrong, while the explain popup is open
+    pub explanation: Option<Explanation>,
+    // The open what-if branch, if any
+    pub hypothesis: Option<Hypothesis>,
+    // Techniques available to hints, watch mode and grading
+    pub techniques: TechniqueRegistry,
+    // Seed, difficulty and origin of the puzzle
+    pub meta: PuzzleMeta,
+    pub timer: Timer,
+    // Per-digit splits, when playing in speedrun mode
+    pub speedrun: Option<Speedrun>,
+    // Session score and puzzle count, in endless mode
+    pub endless: Option<Endless>,
+    // Countdown and streak, in time-attack mode
+    pub time_attack: Option<TimeAttack>,
+    // The week (its Monday's date) of the weekly challenge being played,
+    // which takes no assists and ends at its mistake limit
+    pub weekly: Option<String>,
+    // The best earlier solve of this seed and this one's progress, in games played alone
+    pub ghost: Option<Ghost>,
+    // A practice opponent racing through the puzzle (`--bot`)
+    pub bot: Option<Bot>,
+    // The timed entries of the solve, saved when it's finished (config `replays`)
+    pub replay: Option<Replay>,
+    // Recent results, when `--adaptive` picks each puzzle's difficulty
+    pub adaptive: Option<Adaptive>,
+    // Records of the games played, counted as each one ends
+    pub stats: Option<Stats>,
+    // Tab of the stats screen shown
+    pub stats_tab: usize,
+    // How the enabled techniques solve the puzzle from the givens, worked out
+    // once it's won for the review
+    pub review: Vec<Deduction>,
+    // Digit picked out on the finished board
+    pub highlight: Option<u8>,
+    // Wrong entries made in each cell over the whole solve, while the review shows them
+    pub heatmap: Option<[[u32; 6]; 6]>,
+    // Show how many candidates each empty cell has while playing
+    pub show_counts: bool,
+    // Light up units with a single empty cell while playing; apart from the
+    // counts, as some players want one assist and not the other
+    pub show_last_cells: bool,
+    // Row letters and column numbers around the board (config `coordinates`)
+    pub show_coordinates: bool,
+    // Turns and per-player counts, when two players share the board
+    pub hotseat: Option<HotSeat>,
+    // Our player number when solving over the network with others
+    pub coop_player: Option<u8>,
+    // Where the other co-op players' cursors are, by player number
+    pub peer_cursors: Vec<(u8, (usize, usize))>,
+    // Address of the game being watched, for a spectator's read-only copy
+    pub spectating: Option<String>,
+    // Which puzzle of how many in the file, when opened with `view`
+    pub viewing: Option<(usize, usize)>,
+    // How long generating and grading the puzzle took
+    pub generation_time: Duration,
+    // Where the seeds and symmetries of the puzzles that follow come from,
+    // carried from one puzzle to the next; a seeded one plays the same run of
+    // puzzles again
+    pub rng: StdRng,
+    // Show the F12 debug overlay
+    pub show_debug: bool,
+    // What solving the board from here takes, by search and by logic, for the
+    // debug overlay, with the board it was worked out for
+    pub debug_solve: Option<(Cells, SolveStats, SolveStats)>,
+    // Line of the About screen shown at its top
+    pub about_scroll: usize,
+    // Lines the About screen has room for, as the front end last drew it
+    pub about_rows: usize,
+    // The open dialog, taking every key until it's answered
+    pub dialog: Option<(DialogPurpose, Dialog)>,
+    // Ask before quitting a puzzle with entries on it
+    pub confirm_quit: bool,
+    // Open each new puzzle on the preview, to start or reroll (config `preview`)
+    pub preview: bool,
+    // The hardest techniques the previewed puzzle needs, graded as it opens;
+    // None if they can't solve it
+    pub preview_needs: Option<Vec<&'static str>>,
+    // The generation profiles of the config, and the one new puzzles follow
+    pub profiles: Vec<Profile>,
+    pub profile: Option<usize>,
+    // Take profile puzzles from those made ahead of time (config `puzzle_cache`)
+    pub puzzle_cache: bool,
+    // A profile's next puzzle being looked for, behind the progress screen
+    pub search: Option<Search>,
+    // Put each new puzzle through a random symmetry (config `transform_puzzles`)
+    pub transform_puzzles: bool,
+    // Draw regions faintly (config `subtle_regions`)
+    pub subtle_regions: bool,
+    // Text on the terminal's own background is darkened for a light one
+    pub light_background: bool,
+    // The most recent commands with the clock time they were applied, for crash reports
+    pub actions: VecDeque<(Duration, Command)>,
+    // Every board the solve went through, including undone branches
+    pub undo_tree: UndoTree,
+    // The node shown while browsing the history
+    pub history_node: usize,
+    // Board and cursor to return to when the browser is closed without resuming
+    browsing_from: Option<(Cells, (usize, usize))>,
+    // Already counted in the stats
+    counted: bool,
+    // Something visible changed since the last redraw
+    dirty: bool,
+    // Whole seconds on the clock at the last redraw
+    drawn_secs: u64,
+}
+
+// Highest star rating; awarded to puzzles the enabled techniques cannot finish
+pub const MAX_STARS: u8 = 4;
+
+// Seeds `Game::requiring` tries before giving up. Puzzles needing the hardest
+// built-in technique turn up about once in a few thousand.
+const MAX_REQUIRE_ATTEMPTS: usize = 20_000;
+
+// How many recent commands `Game::actions` keeps
+const MAX_ACTIONS: usize = 200;
+
+// A random puzzle
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Game {
     pub fn new() -> Self {
-        let mut grid = Grid::new();
-        
-        // 1. Generate full board
-        // Note: fill_randomly should always succeed for valid Sudoku rules,
-        // but we verify to prevent potential panics
-        let mut success = grid.fill_randomly();
-        if !success {
-            // This should never happen with valid Sudoku logic,
-            // but if it does, try again with a new grid
-            grid = Grid::new();
-            success = grid.fill_randomly();
-            
-            // If it fails twice, panic with a clear message
-            if !success {
-                panic!("Failed to generate a valid Sudoku grid after multiple attempts. This indicates a critical bug in the generation algorithm.");
-            }
-        }
-        
-        // 2. Capture Solution
-        let mut solution = [[0; 6]; 6];
-        for r in 0..6 {
-            for c in 0..6 {
-                // Safe to unwrap here because fill_randomly succeeded
-                solution[r][c] = grid.cells[r][c].value.expect("Grid should be fully filled after successful generation");
-            }
-        }
-        
-        // 3. Mark all filled cells as fixed (initially)
-        for r in 0..6 {
-            for c in 0..6 {
-                if grid.cells[r][c].value.is_some() {
-                    grid.cells[r][c].is_fixed = true;
-                }
-            }
-        }
-        
-        // 4. Remove random cells to create puzzle
-        let mut rng = rand::rng();
-        let mut removed_count = 0;
-        let target_removed = 20; // 16 clues left
-        
-        while removed_count < target_removed {
-            let r = rng.random_range(0..6);
-            let c = rng.random_range(0..6);
-            
-            if grid.cells[r][c].value.is_some() {
-                grid.cells[r][c].value = None;
-                grid.cells[r][c].is_fixed = false;
-                removed_count += 1;
-            }
-        }
+        Self::random(&PuzzleSpec::default()).expect("classic puzzles generate from any seed")
+    }
+
+    // A puzzle of `spec` from a random seed
+    pub fn random(spec: &PuzzleSpec) -> Result<Self, GenerateError> {
+        Self::random_with(&mut rand::rng(), spec)
+    }
+
+    // A puzzle of `spec` from a seed `rng` picks
+    pub fn random_with<R: Rng + ?Sized>(rng: &mut R, spec: &PuzzleSpec) -> Result<Self, GenerateError> {
+        // Keep seeds short enough to read out or type back in
+        Self::generate(rng.random::<u32>() as u64, spec)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self::generate(seed, &PuzzleSpec::default()).expect("classic puzzles generate from any seed")
+    }
+
+    // The puzzle `--require <technique>` plays from `seed`: clues are taken out
+    // of the filled grid for as long as the built-in techniques can still solve
+    // it, and it qualifies only if they can't do without `technique`. Most
+    // seeds don't, so callers try seed after seed.
+    pub fn with_seed_requiring(seed: u64, technique: &str) -> Option<Self> {
+        let spec = PuzzleSpec { variant: Variant::Requiring(technique.to_string()), ..PuzzleSpec::default() };
+        Self::generate(seed, &spec).ok()
+    }
+
+    // Play the puzzle `spec` gives from `seed`
+    pub fn generate(seed: u64, spec: &PuzzleSpec) -> Result<Self, GenerateError> {
+        let started = Instant::now();
+        let puzzle = Generator::generate(seed, spec)?;
+        Ok(Self::from_puzzle(puzzle, started.elapsed()))
+    }
+
+    // Try seeds from `rng` until one gives a puzzle that needs `technique`,
+    // calling `attempt` before each try; it returns false to stop early. None
+    // after MAX_REQUIRE_ATTEMPTS or once stopped.
+    pub fn requiring<R: Rng + ?Sized>(technique: &str, rng: &mut R, mut attempt: impl FnMut(usize) -> bool) -> Option<Self> {
+        (1..=MAX_REQUIRE_ATTEMPTS).take_while(|&n| attempt(n)).find_map(|_| Self::with_seed_requiring(rng.random::<u32>() as u64, technique))
+    }
+
+    // Play `grid` as it is, without solving it first, so entries are checked
+    // against the rules alone. Its givens are still graded.
+    pub fn unsolved(grid: Grid) -> Self {
+        let started = Instant::now();
+        let meta = PuzzleMeta { variant: Variant::Imported(grid.share_code()), source: Source::Import, ..PuzzleMeta::default() };
+        let mut game = Self::from_puzzle(Puzzle { grid, solution: None, meta }, Duration::ZERO);
+        game.meta.difficulty = game.techniques.grade(&game.givens());
+        game.generation_time = started.elapsed();
+        game
+    }
+
+    fn from_puzzle(puzzle: Puzzle, generation_time: Duration) -> Self {
+        let Puzzle { grid, solution, meta } = puzzle;
+        let techniques = TechniqueRegistry::builtin();
+        let undo_tree = UndoTree::new(&grid);
+        log::debug!("generated puzzle {} in {:?}, grade {:?}", meta.key(), generation_time, meta.difficulty);
 
         Self {
             grid,
             solution,
+            solving: None,
             cursor: (0, 0),
-            state: GameState::Playing,
+            hover: None,
+            screens: ScreenStack::new(Screen::Playing),
             mode: InputMode::Normal,
             mistakes: 0,
+            erases: Erases::default(),
+            entered: 0,
+            wrong_since: [[None; 6]; 6],
+            erased: None,
+            demo_last: None,
+            hint: None,
+            watched: 0,
+            hinted: 0,
+            block_conflicts: false,
+            auto_clear: false,
+            win_check: WinCheck::default(),
+            pending_clear: None,
+            go_to: None,
+            macros: Macros::default(),
+            last_entry: None,
+            rejected: None,
+            progress_check: None,
+            flash_until: None,
+            placed: None,
+            completed: Vec::new(),
+            explanation: None,
+            hypothesis: None,
+            techniques,
+            meta,
+            timer: Timer::start(),
+            speedrun: None,
+            endless: None,
+            time_attack: None,
+            weekly: None,
+            ghost: None,
+            bot: None,
+            replay: None,
+            adaptive: None,
+            stats: None,
+            stats_tab: 0,
+            review: Vec::new(),
+            highlight: None,
+            heatmap: None,
+            show_counts: false,
+            show_coordinates: false,
+            show_last_cells: false,
+            hotseat: None,
+            coop_player: None,
+            peer_cursors: Vec::new(),
+            spectating: None,
+            viewing: None,
+            generation_time,
+            rng: StdRng::from_rng(&mut rand::rng()),
+            show_debug: false,
+            debug_solve: None,
+            about_scroll: 0,
+            about_rows: 0,
+            dialog: None,
+            confirm_quit: false,
+            preview: false,
+            preview_needs: None,
+            profiles: Vec::new(),
+            profile: None,
+            puzzle_cache: false,
+            search: None,
+            transform_puzzles: false,
+            subtle_regions: false,
+            light_background: false,
+            actions: VecDeque::with_capacity(MAX_ACTIONS),
+            undo_tree,
+            history_node: 0,
+            browsing_from: None,
+            counted: false,
+            dirty: true,
+            drawn_secs: 0,
         }
     }
-    
-    // Check if the value matches the solution
-    pub fn is_correct_move(&self, row: usize, col: usize, value: u8) -> bool {
-        if !is_in_bounds_and_valid_value(row, col, value) {
-            return false;
-        }
-        self.solution[row][col] == value
-    }
 
-    pub fn move_cursor(&mut self, dr: i8, dc: i8) {
-        let new_r = (self.cursor.0 as i8 + dr).clamp(0, 5) as usize;
-        let new_c = (self.cursor.1 as i8 + dc).clamp(0, 5) as usize;
-        self.cursor = (new_r, new_c);
+    // Race the clock, recording a split each time a digit is completed
+    pub fn start_speedrun(&mut self, run: Speedrun) {
+        self.speedrun = Some(run);
+        self.record_splits();
     }
 
-    pub fn handle_input(&mut self, num: u8) {
-        // Validate input is in valid range
-        if !(1..=6).contains(&num) {
+    // Endless and time-attack runs: score the solved puzzle and swap in the
+    // next one, keeping the run and the player's settings
+    pub fn next_puzzle_in_run(&mut self) {
+        let (mut session, mut attack) = (self.endless.take(), self.time_attack.take());
+        if session.is_none() && attack.is_none() {
             return;
         }
-        
-        let (r, c) = self.cursor;
-        if self.grid.cells[r][c].is_fixed {
-            return;
+        self.record_stats();
+        let mut stars = self.stars();
+        if let Some(session) = session.as_mut() {
+            session.record(stars, self.mistakes, self.timer.elapsed());
+            log::info!("endless puzzle {} solved, score {}", session.solved, session.score);
+            stars = session.stars;
         }
+        let mut next = endless::next_puzzle(stars, &mut self.rng);
+        self.keep_settings(&mut next);
+        if let Some(attack) = attack.as_mut() {
+            attack.solved(next.stars());
+            log::info!("time attack streak {}", attack.streak);
+        }
+        next.endless = session;
+        next.time_attack = attack;
+        *self = next;
+    }
 
-        match self.mode {
-            InputMode::Normal => {
-                // Track mistakes before setting the value
-                // Use saturating_add to prevent overflow. In normal gameplay, reaching u32::MAX
-                // (4+ billion mistakes) is impossible, but this prevents undefined behavior
-                // if the counter is somehow incremented excessively.
-                if !self.is_correct_move(r, c, num) {
-                    self.mistakes = self.mistakes.saturating_add(1);
-                }
-                
-                self.grid.cells[r][c].value = Some(num);
-                // Clear marks on set
-                self.grid.cells[r][c].marks = [false; 6];
-                
-                if self.grid.is_solved() {
-                    self.state = GameState::Won;
-                }
+    // A fresh puzzle from the review of a finished one. With adaptive
+    // difficulty, the finished game is recorded first so it counts toward the
+    // pick. A profile without a puzzle made ahead of time has one looked for
+    // in the background, behind the progress screen. Off when others share
+    // the board.
+    pub fn new_game(&mut self) {
+        if self.hotseat.is_some() || self.coop_player.is_some() {
+            return;
+        }
+        self.record_stats();
+        let mut adaptive = self.adaptive.take();
+        if let Some(adaptive) = adaptive.as_mut() {
+            if let Err(err) = adaptive.record(self) {
+                log::warn!("could not save the adaptive history: {}", err);
+            }
+        }
+        // A variant puzzle is followed by another of the variant
+        let mut spec = match self.meta.variant {
+            Variant::Sandwich | Variant::Thermo => PuzzleSpec { variant: self.meta.variant.clone(), ..PuzzleSpec::default() },
+            _ => PuzzleSpec::default(),
+        };
+        if self.transform_puzzles {
+            spec.transform = Some(Transform::random(&mut self.rng, Grid::SPEC.box_rows == Grid::SPEC.box_cols));
+        }
+        let profile = self.profile.and_then(|i| self.profiles.get(i));
+        let next = match (&adaptive, profile) {
+            (Some(adaptive), _) => adaptive.new_game(&mut self.rng),
+            (None, Some(profile)) => {
+                let cached = self.puzzle_cache.then(|| pregen::take(profile)).flatten();
+                if self.puzzle_cache {
+                    pregen::refill(self.profiles.clone(), StdRng::from_rng(&mut self.rng));
+                }
+                match cached {
+                    Some(game) => game,
+                    None => return self.search_profile(profile.clone()),
+                }
+            }
+            (None, None) => Game::random_with(&mut self.rng, &spec).unwrap_or_else(|_| Game::new()),
+        };
+        self.play_next(next, adaptive);
+    }
+
+    // Look for a puzzle of `profile` on another thread; `poll_search` takes
+    // it up once found
+    fn search_profile(&mut self, profile: Profile) {
+        log::info!("looking for a puzzle of profile {}", profile.name);
+        let name = profile.name.clone();
+        let mut rng = StdRng::from_rng(&mut self.rng);
+        self.search = Some(Search::start(&name, profile::MAX_ATTEMPTS, move |attempt| profile.generate(&mut rng, attempt)));
+        self.open_screen(Screen::Generating);
+    }
+
+    // Play the puzzle the background search found once it's in. Returns
+    // whether the search ended.
+    pub fn poll_search(&mut self) -> bool {
+        let Some(found) = self.search.as_ref().and_then(Search::poll) else {
+            return false;
+        };
+        let search = self.search.take().expect("a search was polled");
+        self.dirty = true;
+        match found {
+            Some(next) => {
+                log::info!("found a puzzle of profile {} after {} attempts", search.label, search.attempts());
+                self.play_next(next, None);
+            }
+            None => {
+                log::warn!("no puzzle fitting profile {} turned up", search.label);
+                self.screens.pop();
+                self.dialog = Some((DialogPurpose::Notice, Dialog::message(t("dialog.no_puzzle"), &tf("dialog.no_puzzle_text", &[&search.label, &search.attempts()]))));
+            }
+        }
+        true
+    }
+
+    // Call the background search off, back to the screen it was started from
+    pub fn cancel_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.cancel();
+            log::info!("stopped looking for a puzzle of profile {} after {} attempts", search.label, search.attempts());
+        }
+        if self.screen() == Screen::Generating {
+            self.screens.pop();
+        }
+    }
+
+    // Carry the settings over to `next` and play it
+    fn play_next(&mut self, mut next: Game, adaptive: Option<Adaptive>) {
+        self.keep_settings(&mut next);
+        next.ghost = self.ghost.as_ref().map(|_| Ghost::load(&next.meta.key()));
+        next.adaptive = adaptive;
+        log::info!("new game, puzzle {}", next.meta.key());
+        *self = next;
+        if self.preview {
+            self.show_preview();
+        }
+    }
+
+    // Hold the puzzle on the preview, its clock stopped at zero until it's started
+    pub fn show_preview(&mut self) {
+        self.screens = ScreenStack::new(Screen::Preview);
+        self.timer = Timer::stopped_at(Duration::ZERO);
+        self.preview_needs = self.techniques.hardest_needed(&self.givens());
+    }
+
+    // Leave the preview for play, starting the clock
+    fn start_previewed(&mut self) {
+        self.screens = ScreenStack::new(Screen::Playing);
+        self.timer.resume();
+    }
+
+    // Carry the player's settings over to the next puzzle
+    fn keep_settings(&mut self, next: &mut Game) {
+        next.set_techniques(std::mem::replace(&mut self.techniques, TechniqueRegistry::builtin()));
+        next.mode = self.mode;
+        next.show_debug = self.show_debug;
+        next.about_rows = self.about_rows;
+        next.show_counts = self.show_counts;
+        next.show_coordinates = self.show_coordinates;
+        next.show_last_cells = self.show_last_cells;
+        next.block_conflicts = self.block_conflicts;
+        next.auto_clear = self.auto_clear;
+        next.win_check = self.win_check;
+        next.confirm_quit = self.confirm_quit;
+        next.preview = self.preview;
+        next.profiles = std::mem::take(&mut self.profiles);
+        next.profile = self.profile;
+        next.puzzle_cache = self.puzzle_cache;
+        next.rng = self.rng.clone();
+        next.transform_puzzles = self.transform_puzzles;
+        next.subtle_regions = self.subtle_regions;
+        next.light_background = self.light_background;
+        next.stats = self.stats.take();
+        next.macros = std::mem::take(&mut self.macros);
+        next.replay = self.replay.as_ref().map(|_| Replay::default());
+        next.bot = self.bot.as_ref().map(|bot| Bot::new(bot.level, &next.givens(), &next.techniques));
+    }
+
+    // Count the game in the stats, as it's left for another or quit. A game
+    // is only counted once.
+    pub fn record_stats(&mut self) {
+        if self.counted {
+            return;
+        }
+        let Some(mut stats) = self.stats.take() else {
+            return;
+        };
+        self.counted = true;
+        if let Err(err) = stats.record(self) {
+            log::warn!("could not save the stats: {}", err);
+        }
+        self.stats = Some(stats);
+    }
+
+    // The board is solved: stop the clock, keep the solve if it's a new best
+    // and work out the review
+    fn finish(&mut self) {
+        self.screens = ScreenStack::new(Screen::Won);
+        self.timer.pause();
+        let entries = 36 - self.grid.clue_count();
+        if let Some(ghost) = self.ghost.as_mut() {
+            match ghost.finish(entries) {
+                Ok(true) => log::info!("new best solve of puzzle {}", ghost.key),
+                Ok(false) => {}
+                Err(err) => log::warn!("could not save the best solve: {}", err),
+            }
+        }
+        self.review = self.techniques.solve_path(&self.givens());
+    }
+
+    // How long the player typically takes over a puzzle like this one
+    pub fn estimate(&self) -> Option<Duration> {
+        self.stats.as_ref()?.estimate(&Category::of(self))
+    }
+
+    // Correct entries ahead of (+) or behind (-) a typical solve by now, one
+    // that fills the empty cells at an even rate over the estimated time
+    pub fn pace(&self) -> Option<i64> {
+        let estimate = self.estimate()?.as_secs_f64().max(1.0);
+        let share = (self.timer.elapsed().as_secs_f64() / estimate).min(1.0);
+        let typical = ((36 - self.grid.clue_count()) as f64 * share).round() as i64;
+        Some(self.correct_entries() as i64 - typical)
+    }
+
+    // Entered values that match the solution
+    pub fn correct_entries(&self) -> usize {
+        (0..36)
+            .map(|i| (i / 6, i % 6))
+            .filter(|&(r, c)| !self.grid.cells[r][c].is_fixed() && self.grid.cells[r][c].value().is_some_and(|v| self.is_correct_move(r, c, v)))
+            .count()
+    }
+
+    // Solve the givens on another thread, so an imported puzzle can be played
+    // at once; until `poll_solve` gets the solution, entries are checked by
+    // the rules
+    pub fn solve_in_background(&mut self) {
+        let (sender, solved) = mpsc::channel();
+        let givens = self.givens();
+        thread::spawn(move || {
+            let _ = sender.send(givens.solutions(2));
+        });
+        self.solving = Some(solved);
+    }
+
+    // Take the background solve's result once it's in, checking entries
+    // against the solution from then on. Returns whether it came in.
+    pub fn poll_solve(&mut self) -> bool {
+        let solutions = match self.solving.as_ref().map(Receiver::try_recv) {
+            Some(Ok(solutions)) => solutions,
+            Some(Err(TryRecvError::Disconnected)) => {
+                self.solving = None;
+                return false;
+            }
+            Some(Err(TryRecvError::Empty)) | None => return false,
+        };
+        self.solving = None;
+        self.solution = solutions.first().copied();
+        self.meta.unique = Some(solutions.len() == 1);
+        log::info!("solved puzzle {}: {} solution(s) found", self.meta.key(), solutions.len());
+        if solutions.is_empty() {
+            self.dialog = Some((DialogPurpose::Notice, Dialog::message(t("dialog.no_solution"), t("dialog.no_solution_text"))));
+        }
+        self.dirty = true;
+        true
+    }
+
+    // Whether the background solve is still running
+    pub fn is_solving(&self) -> bool {
+        self.solving.is_some()
+    }
+
+    // The weekly challenge is lost: the board stays as it was, and the
+    // attempt is over
+    fn out_of_mistakes(&mut self) {
+        self.screens = ScreenStack::new(Screen::OutOfMistakes);
+        self.timer.pause();
+        self.dirty = true;
+        log::info!("weekly challenge {:?} lost after {} mistakes", self.weekly, self.mistakes);
+    }
+
+    // End a time-attack run once its countdown reaches zero
+    pub fn check_countdown(&mut self) {
+        let Some(attack) = self.time_attack.as_mut() else {
+            return;
+        };
+        if self.screens.top() == Screen::Playing && attack.remaining(self.timer.elapsed()).is_zero() {
+            self.screens = ScreenStack::new(Screen::TimeUp);
+            self.timer.pause();
+            self.dirty = true;
+            log::info!("time attack over, streak {}", attack.streak);
+            if let Err(err) = attack.finish() {
+                log::warn!("could not save the time-attack streak: {}", err);
+            }
+        }
+    }
+
+    // Stop a flash once its time is up, redrawing the board without it and
+    // clearing the wrong entry that flashed
+    pub fn end_flash(&mut self) {
+        if self.flash_until.is_some_and(|until| Instant::now() >= until) {
+            self.flash_until = None;
+            self.placed = None;
+            self.completed.clear();
+            let before = self.grid.clone();
+            self.clear_pending();
+            self.track_erases(&before);
+            self.dirty = true;
+        }
+    }
+
+    // Classify the wrong entries the last change to the board took off, by
+    // what goes in after them. What-if entries aren't judged, so they're left
+    // out, as are boards stepped through in the history browser.
+    fn track_erases(&mut self, before: &Grid) {
+        let changed: Vec<(usize, usize)> = (0..36).map(|i| (i / 6, i % 6)).filter(|&(r, c)| before.cells[r][c].value() != self.grid.cells[r][c].value()).collect();
+        for &(r, c) in &changed {
+            if before.cells[r][c].value().is_some_and(|v| !self.fits(before, r, c, v)) {
+                let built_on = self.wrong_since[r][c].is_none_or(|since| self.entered > since);
+                if self.erased.replace(((r, c), built_on)).is_some() {
+                    self.erases.errors += 1;
+                }
+                self.wrong_since[r][c] = None;
+            }
+        }
+        let at = self.timer.elapsed();
+        for &(r, c) in &changed {
+            let value = self.grid.cells[r][c].value();
+            let correct = value.is_some_and(|v| self.is_correct_move(r, c, v));
+            if let Some(replay) = self.replay.as_mut() {
+                replay.record(at, (r, c), value, correct);
+            }
+            if value.is_none() {
+                continue;
+            }
+            self.entered += 1;
+            if !correct {
+                self.wrong_since[r][c] = Some(self.entered);
+            }
+            match self.erased.take() {
+                Some((cell, false)) if cell == (r, c) && correct => self.erases.corrections += 1,
+                Some(_) => self.erases.errors += 1,
+                None => {}
+            }
+        }
+    }
+
+    fn clear_pending(&mut self) {
+        if let Some((r, c)) = self.pending_clear.take() {
+            if self.grid.cells[r][c].value().is_some_and(|v| !self.is_correct_move(r, c, v)) {
+                self.grid.cells[r][c] = Cell::default();
+                // Its own entry in the tree, so the clear can be undone
+                self.undo_tree.record(&self.grid, (r, c));
+            }
+        }
+    }
+
+    // Units whose cells are all filled in correctly, as indices into
+    // `RegionMap::units`, however many units the board's rules add
+    pub fn complete_units(&self) -> Vec<usize> {
+        let correct = |&(r, c): &(usize, usize)| self.grid.cells[r][c].value().is_some_and(|v| self.is_correct_move(r, c, v));
+        self.grid.regions.units().iter().enumerate().filter(|(_, (_, unit))| unit.iter().all(correct)).map(|(i, _)| i).collect()
+    }
+
+    // A digit is complete once all six of its placements are correct
+    pub fn is_digit_complete(&self, digit: u8) -> bool {
+        let placed = (0..36)
+            .filter(|&i| self.grid.cells[i / 6][i % 6].value() == Some(digit) && self.is_correct_move(i / 6, i % 6, digit))
+            .count();
+        placed == 6
+    }
+
+    fn record_splits(&mut self) {
+        let at = self.timer.elapsed();
+        let complete: Vec<u8> = (1..=6).filter(|&d| self.is_digit_complete(d)).collect();
+        if let Some(run) = self.speedrun.as_mut() {
+            for digit in complete {
+                run.record(digit, at);
+            }
+        }
+    }
+
+    // Swap in a different technique set, regrading the puzzle with it
+    pub fn set_techniques(&mut self, techniques: TechniqueRegistry) {
+        self.meta.difficulty = techniques.grade(&self.givens());
+        self.techniques = techniques;
+    }
+
+    // The puzzle as it started, with only the givens
+    pub fn givens(&self) -> Grid {
+        let mut puzzle = self.grid.clone();
+        for cell in puzzle.cells.iter_mut().flatten().filter(|cell| !cell.is_fixed()) {
+            *cell = Cell::default();
+        }
+        puzzle
+    }
+
+    // Difficulty as a 1..=MAX_STARS rating
+    pub fn stars(&self) -> u8 {
+        self.meta.difficulty.map_or(MAX_STARS, |d| d.clamp(1, MAX_STARS - 1))
+    }
+    
+    // How many digits the empty cell (row, col) can take without clashing
+    // with a peer; None if it holds a value
+    pub fn candidate_count(&self, row: usize, col: usize) -> Option<u32> {
+        self.grid.cells[row][col].value().is_none().then(|| solver::candidates(&self.grid, row, col).count_ones())
+    }
+
+    // The killer cage (row, col) is in, if the board has one there
+    pub fn cage_at(&self, row: usize, col: usize) -> Option<CageSum> {
+        let (cells, sum) = self.grid.constraints.iter().filter_map(|rule| rule.cage()).find(|(cells, _)| cells.contains(&(row, col)))?;
+        let used: Vec<u8> = cells.iter().filter_map(|&(r, c)| self.grid.cells[r][c].value()).collect();
+        let so_far = used.iter().map(|&v| u32::from(v)).sum::<u32>();
+        let empty = cells.len() - used.len();
+        let combinations = match sum.checked_sub(so_far) {
+            Some(left) => CageSum::combinations(left, empty, &used),
+            None => Vec::new(),
+        };
+        Some(CageSum { sum, so_far, empty, combinations })
+    }
+
+    // Rows, columns and boxes with exactly one empty cell, which the one
+    // digit they're missing must go in
+    pub fn last_cells(&self) -> Vec<Unit> {
+        let empty = |unit: &Unit| unit.iter().filter(|&&(r, c)| self.grid.cells[r][c].value().is_none()).count();
+        self.grid.regions.units().into_iter().map(|(_, unit)| unit).filter(|unit| empty(unit) == 1).collect()
+    }
+
+    // Whether the board is full and passes the win check
+    pub fn is_won(&self) -> bool {
+        match self.win_check {
+            WinCheck::Rules => self.grid.is_solved(),
+            // Without a solution there are only the rules to go by
+            WinCheck::Solution if self.solution.is_none() => self.grid.is_solved(),
+            WinCheck::Solution => self.grid.regions.cells().all(|(r, c)| self.grid.cells[r][c].value().is_some_and(|v| self.is_correct_move(r, c, v))),
+        }
+    }
+
+    // Check if the value matches the solution, or with none known, that it
+    // breaks no rule
+    pub fn is_correct_move(&self, row: usize, col: usize, value: u8) -> bool {
+        Grid::SPEC.contains(row, col, value) && self.fits(&self.grid, row, col, value)
+    }
+
+    fn fits(&self, grid: &Grid, row: usize, col: usize, value: u8) -> bool {
+        match &self.solution {
+            Some(solution) => solution[row][col] == value,
+            // A given is never the one to blame for a clash
+            None => grid.cells[row][col].is_fixed() || grid.is_valid_move(row, col, value),
+        }
+    }
+
+    pub fn move_cursor(&mut self, dr: i8, dc: i8) {
+        let new_r = (self.cursor.0 as i8 + dr).clamp(0, 5) as usize;
+        let new_c = (self.cursor.1 as i8 + dc).clamp(0, 5) as usize;
+        self.cursor = (new_r, new_c);
+    }
+
+    // Give a pending go-to chord its row by the letter the coordinates label
+    // it with, returning whether the key was taken
+    pub fn go_to_row_key(&mut self, key: Key) -> bool {
+        let (Some(None), KeyCode::Char(ch)) = (self.go_to, key.code) else {
+            return false;
+        };
+        let Some(row) = ('a'..='f').position(|letter| letter == ch.to_ascii_lowercase()) else {
+            return false;
+        };
+        self.go_to = Some(Some(row));
+        self.dirty = true;
+        true
+    }
+
+    // Give a pending go-to chord the command, returning whether it took it.
+    // Digits pick the row, then the column, and Esc drops the chord; any
+    // other command drops it and is applied as usual.
+    fn go_to_key(&mut self, command: Command) -> bool {
+        let Some(row) = self.go_to.take() else {
+            return false;
+        };
+        match (command, row) {
+            (Command::EnterDigit(col), Some(row)) => self.cursor = (row, col as usize - 1),
+            (Command::EnterDigit(row), None) => self.go_to = Some(Some(row as usize - 1)),
+            (Command::Back, _) => {}
+            _ => return false,
+        }
+        true
+    }
+
+    // Track the cell under the mouse pointer, redrawing only when it changes
+    pub fn set_hover(&mut self, hover: Option<(usize, usize)>) {
+        if self.hover != hover {
+            self.hover = hover;
+            self.dirty = true;
+        }
+    }
+
+    pub fn handle_input(&mut self, num: u8) {
+        // Validate input is in valid range
+        if !(1..=6).contains(&num) {
+            return;
+        }
+        
+        let (r, c) = self.cursor;
+        if self.grid.cells[r][c].is_fixed() || self.grid.cells[r][c].locked() || self.screen() == Screen::Won {
+            return;
+        }
+        // Entering the value a cell already holds changes nothing and isn't another mistake
+        if self.mode == InputMode::Normal && self.grid.cells[r][c].value() == Some(num) {
+            return;
+        }
+        if self.mode == InputMode::Normal && self.block_conflicts {
+            if let Some(peer) = self.grid.conflicting_peer(r, c, num) {
+                let unit = solver::shared_unit((r, c), peer);
+                self.rejected = Some(Rejection { row: r, col: c, value: num, peer, unit });
+                self.flash_until = Some(Instant::now() + FLASH_TIME);
+                log::debug!("blocked r{}c{} = {}: clashes with r{}c{}", r + 1, c + 1, num, peer.0 + 1, peer.1 + 1);
+                return;
+            }
+        }
+        if self.mode == InputMode::Normal && self.hint.is_some_and(|hint| (hint.row, hint.col, hint.value) == (r, c, num)) {
+            self.hinted += 1;
+        }
+        self.hint = None;
+
+        match self.mode {
+            InputMode::Normal => {
+                // Track mistakes before setting the value
+                // Use saturating_add to prevent overflow. In normal gameplay, reaching u32::MAX
+                // (4+ billion mistakes) is impossible, but this prevents undefined behavior
+                // if the counter is somehow incremented excessively.
+                let correct = self.is_correct_move(r, c, num);
+                // What-if entries are judged when the branch is kept
+                let judged = self.hypothesis.is_none();
+                if !correct && judged {
+                    self.mistakes = self.mistakes.saturating_add(1);
+                    log::debug!("mistake: r{}c{} = {}", r + 1, c + 1, num);
+                    if self.weekly.is_some() && self.mistakes >= weekly::MAX_MISTAKES {
+                        self.out_of_mistakes();
+                        return;
+                    }
+                    // Clearing them is an assist the weekly challenge goes without
+                    if self.auto_clear && self.weekly.is_none() {
+                        // Only one wrong entry flashes at a time
+                        self.clear_pending();
+                        self.pending_clear = Some((r, c));
+                        self.flash_until = Some(Instant::now() + FLASH_TIME);
+                    }
+                }
+                if let Some(seat) = self.hotseat.as_mut() {
+                    seat.record(correct);
+                }
+                if let (Some(attack), true) = (self.time_attack.as_mut(), correct && judged) {
+                    attack.bonus((r, c));
+                }
+                
+                let complete = self.complete_units();
+                self.grid.cells[r][c].set_value(Some(num));
+                // Clear marks on set
+                self.grid.cells[r][c].clear_marks();
+                let (correct, at) = (self.correct_entries(), self.timer.elapsed());
+                if let Some(ghost) = self.ghost.as_mut() {
+                    ghost.record(correct, at);
+                }
+                // Light up the peers, to scan them for new singles
+                self.placed = Some((r, c));
+                self.completed = self.complete_units().into_iter().filter(|unit| !complete.contains(unit)).collect();
+                self.flash_until = Some(Instant::now() + FLASH_TIME);
+                self.record_splits();
+                
+                if self.is_won() {
+                    // A solved board leaves nothing to discard
+                    self.commit_hypothesis();
+                    self.finish();
+                }
             }
             InputMode::Pencil => {
                 // Toggle mark (num is already validated to be 1..=6)
-                let idx = (num - 1) as usize;
-                self.grid.cells[r][c].marks[idx] = !self.grid.cells[r][c].marks[idx];
+                self.grid.cells[r][c].toggle_mark(num);
+            }
+        }
+        self.undo_tree.record(&self.grid, self.cursor);
+    }
+    
+    // Enter `num` the way the other input mode would, leaving the mode as it is
+    pub fn enter_alternate(&mut self, num: u8) {
+        self.toggle_mode();
+        self.handle_input(num);
+        self.toggle_mode();
+    }
+
+    // The mode Shift+digit enters digits in
+    fn alternate_mode(&self) -> InputMode {
+        match self.mode {
+            InputMode::Normal => InputMode::Pencil,
+            InputMode::Pencil => InputMode::Normal,
+        }
+    }
+
+    // Make the last entry again at the cursor, in the mode it was made in
+    // whatever the mode is now
+    fn repeat_entry(&mut self) {
+        match self.last_entry {
+            Some((mode, Some(num))) if mode == self.mode => self.handle_input(num),
+            Some((_, Some(num))) => self.enter_alternate(num),
+            Some((_, None)) => self.clear_cell(),
+            None => {}
+        }
+    }
+
+    pub fn toggle_mode(&mut self) {
+        self.mode = self.alternate_mode();
+    }
+    
+    pub fn clear_cell(&mut self) {
+        let (r, c) = self.cursor;
+        if self.grid.cells[r][c].is_fixed() || self.grid.cells[r][c].locked() || self.screen() == Screen::Won {
+            return;
+        }
+        self.hint = None;
+        if self.grid.cells[r][c].value().is_some() {
+            if let Some(seat) = self.hotseat.as_mut() {
+                seat.pass();
+            }
+        }
+        self.grid.cells[r][c].set_value(None);
+        self.grid.cells[r][c].clear_marks();
+        self.undo_tree.record(&self.grid, self.cursor);
+    }
+
+    // Lock the entry under the cursor against overwrites and erases, or unlock it
+    pub fn toggle_lock(&mut self) {
+        let cell = &mut self.grid.cells[self.cursor.0][self.cursor.1];
+        if cell.is_fixed() || cell.value().is_none() {
+            return;
+        }
+        cell.set_locked(!cell.locked());
+        self.undo_tree.record(&self.grid, self.cursor);
+    }
+
+    // An entry made by another co-op player. It isn't ours to count as a mistake.
+    pub fn set_shared_value(&mut self, r: usize, c: usize, value: Option<u8>) {
+        let cell = &mut self.grid.cells[r][c];
+        // The finished board stays as it was solved
+        if cell.is_fixed() || cell.value() == value || self.screens.top() == Screen::Won {
+            return;
+        }
+        cell.set_value(value);
+        cell.clear_marks();
+        cell.set_locked(false);
+        self.hint = None;
+        self.dirty = true;
+        // Undo is off with others on the board, but the tree keeps up so the
+        // history browser shows their entries too
+        self.undo_tree.record(&self.grid, (r, c));
+        self.record_splits();
+        if self.is_won() && self.screen() == Screen::Playing {
+            self.finish();
+        }
+    }
+
+    // Take back the last entry or erase. Mistakes already made still count.
+    pub fn undo(&mut self) {
+        // With others on the board, an undo could erase another player's entry
+        if self.hotseat.is_some() || self.coop_player.is_some() {
+            return;
+        }
+        // Undo stops where a what-if branch was opened; discarding goes back further
+        if self.hypothesis.as_ref().is_some_and(|branch| branch.node == self.undo_tree.current) {
+            return;
+        }
+        if let Some((cells, cursor)) = self.undo_tree.undo() {
+            self.grid.cells = *cells;
+            self.cursor = cursor;
+            self.hint = None;
+        }
+    }
+
+    // Open a what-if branch on the current board, or keep the open one. Like
+    // undo, it's off when others share the board.
+    pub fn toggle_hypothesis(&mut self) {
+        if self.hypothesis.is_some() {
+            self.commit_hypothesis();
+        } else if self.hotseat.is_none() && self.coop_player.is_none() {
+            self.hypothesis = Some(Hypothesis { base: self.grid.cells, node: self.undo_tree.current });
+        }
+    }
+
+    // Keep the what-if entries, counting the wrong ones as mistakes now and
+    // writing them to the replay as if they'd just gone in
+    pub fn commit_hypothesis(&mut self) {
+        let wrong = (0..36).filter(|&i| self.is_hypothetical(i / 6, i % 6) && self.wrong_at(i / 6, i % 6).is_some()).count();
+        self.mistakes = self.mistakes.saturating_add(wrong as u32);
+        if let Some(branch) = self.hypothesis.take() {
+            let mut before = self.grid.clone();
+            before.cells = branch.base;
+            self.track_erases(&before);
+        }
+        log::debug!("kept what-if branch with {} wrong entries", wrong);
+    }
+
+    // Throw away everything entered since the what-if branch was opened. Its
+    // entries stay in the undo tree as a branch.
+    pub fn discard_hypothesis(&mut self) {
+        if let Some(branch) = self.hypothesis.take() {
+            self.grid.cells = branch.base;
+            self.undo_tree.jump(branch.node);
+            self.hint = None;
+        }
+    }
+
+    // Whether (r, c) holds a value entered in the open what-if branch
+    pub fn is_hypothetical(&self, r: usize, c: usize) -> bool {
+        let value = self.grid.cells[r][c].value();
+        self.hypothesis.as_ref().is_some_and(|branch| value.is_some() && branch.base[r][c].value() != value)
+    }
+
+    // Open the history browser on the current board. Like undo, it's off when
+    // others share the board, and while a what-if branch is open.
+    pub fn browse_history(&mut self) {
+        if self.hotseat.is_some() || self.coop_player.is_some() || self.hypothesis.is_some() || self.undo_tree.nodes.len() == 1 {
+            return;
+        }
+        self.browsing_from = Some((self.grid.cells, self.cursor));
+        self.history_node = self.undo_tree.current;
+        self.open_screen(Screen::History);
+    }
+
+    // Left/Right step back and forward along the branch, Up/Down switch to the
+    // previous or next branch at the board shown
+    pub fn step_history(&mut self, dr: i8, dc: i8) {
+        let tree = &self.undo_tree;
+        let node = tree.node(self.history_node);
+        self.history_node = match (dr, dc) {
+            (_, ..=-1) => node.parent.unwrap_or(self.history_node),
+            (_, 1..) => node.last_child.or(node.children.last().copied()).unwrap_or(self.history_node),
+            (dr, _) => tree.sibling(self.history_node, dr),
+        };
+        self.grid.cells = tree.cells_at(self.history_node);
+        self.cursor = tree.node(self.history_node).cursor;
+    }
+
+    // Play on from the board shown. Entries made after it stay in the tree as
+    // another branch.
+    pub fn resume_from_history(&mut self) {
+        self.undo_tree.jump(self.history_node);
+        self.browsing_from = None;
+        self.hint = None;
+        self.screens.pop();
+    }
+
+    // Back to the current board, as if the browser was never opened
+    pub fn leave_history(&mut self) {
+        if let Some((cells, cursor)) = self.browsing_from.take() {
+            self.grid.cells = cells;
+            self.cursor = cursor;
+        }
+        self.screens.pop();
+    }
+
+    // Carry out a player command. Returns false when the command quits the game.
+    // In debug builds, a command that leaves the puzzle's board inconsistent
+    // panics; see `Grid::validate_consistency`.
+    pub fn apply(&mut self, command: Command) -> bool {
+        let before = cfg!(debug_assertions).then(|| (self.meta.key(), self.grid.clone()));
+        let going_on = self.apply_command(command);
+        // A new puzzle starts over
+        if let Some((_, before)) = before.filter(|(key, _)| *key == self.meta.key()) {
+            if let Err(err) = self.grid.validate_consistency(&before) {
+                panic!("{:?} left the board of puzzle {} inconsistent: {}\n{}", command, self.meta.key(), err, self.grid);
+            }
+        }
+        going_on
+    }
+
+    fn apply_command(&mut self, command: Command) -> bool {
+        log::trace!("{:?} in {:?} at {:?}", command, self.screen(), self.cursor);
+        if self.actions.len() == MAX_ACTIONS {
+            self.actions.pop_front();
+        }
+        self.actions.push_back((self.timer.elapsed(), command));
+        self.rejected = None;
+        self.progress_check = None;
+        if let Some(commands) = self.macros.command(command) {
+            return commands.into_iter().all(|command| self.apply(command));
+        }
+        if self.go_to_key(command) {
+            return true;
+        }
+        let tracked = self.screen() == Screen::Playing && self.hypothesis.is_none();
+        let was_won = self.screen() == Screen::Won;
+        let before = tracked.then(|| self.grid.clone());
+        let in_popup = matches!(self.screen(), Screen::About | Screen::Stats | Screen::Share | Screen::Explain);
+        match command {
+            // Any key leaves the session summary
+            _ if self.screen() == Screen::Summary => return false,
+            // The progress screen only takes calling the search off
+            Command::Back | Command::Quit | Command::Dismiss if self.screen() == Screen::Generating => self.cancel_search(),
+            _ if self.screen() == Screen::Generating => {}
+            // Esc closes the screen shown; on the game's own screen it quits
+            Command::Back if self.screen().is_overlay() => self.close_screen(),
+            // Quitting a game under way asks first, whichever key quits
+            Command::Back | Command::Quit if self.confirm_quit && self.screen() == Screen::Playing && self.correct_entries() + self.wrong_entries() > 0 => {
+                self.dialog = Some((DialogPurpose::Quit, Dialog::confirm(t("dialog.quit"), t("dialog.quit_text"))));
+            }
+            Command::Back => return self.quit(),
+            Command::Quit | Command::Dismiss if in_popup => self.close_screen(),
+            Command::MoveCursor(dr, _) if self.screen() == Screen::About => self.scroll_about(dr),
+            Command::MoveCursor(_, dc) if self.screen() == Screen::Stats => self.switch_stats_tab(dc),
+            Command::Quit if self.screen() == Screen::History => self.leave_history(),
+            Command::Quit => return self.quit(),
+            Command::Dismiss => {}
+            // The weekly challenge is played without assists
+            Command::ToggleDemo | Command::Hint | Command::Explain | Command::CheckProgress | Command::ToggleCounts | Command::ToggleLastCells if self.weekly.is_some() => {}
+            Command::ToggleDemo => self.toggle_demo(),
+            Command::ToggleDebug => self.show_debug = !self.show_debug,
+            Command::ToggleCoordinates => self.show_coordinates = !self.show_coordinates,
+            Command::ToggleAbout if self.screen() == Screen::About => self.close_screen(),
+            Command::ToggleAbout => {
+                self.open_screen(Screen::About);
+                self.about_scroll = 0;
+            }
+            Command::ToggleStats if self.screen() == Screen::Stats => self.close_screen(),
+            Command::ToggleStats => self.open_screen(Screen::Stats),
+            Command::MoveCursor(dr, dc) if self.screen() == Screen::History => self.step_history(dr, dc),
+            Command::Confirm if self.screen() == Screen::History => self.resume_from_history(),
+            // The finished board can be looked over, but not changed
+            Command::MoveCursor(dr, dc) if self.screen() == Screen::Won => self.move_cursor(dr, dc),
+            Command::SelectCell(r, c) if self.screen() == Screen::Won => self.cursor = (r.min(5), c.min(5)),
+            Command::EnterDigit(num) if self.screen() == Screen::Won => {
+                self.highlight = if self.highlight == Some(num) { None } else { Some(num) };
+            }
+            Command::NewGame if self.screen() == Screen::Won => self.new_game(),
+            // Rerolling goes through a new game, which comes back to the preview
+            Command::NewGame if self.screen() == Screen::Preview => self.new_game(),
+            Command::Confirm if self.screen() == Screen::Preview => self.start_previewed(),
+            // A digit rerolls from the profile of that number
+            Command::EnterDigit(num) if self.screen() == Screen::Preview && (1..=self.profiles.len()).contains(&(num as usize)) => {
+                self.profile = Some(num as usize - 1);
+                self.new_game();
+            }
+            Command::Import if matches!(self.screen(), Screen::Playing | Screen::Won) && self.hotseat.is_none() && self.coop_player.is_none() => {
+                self.dialog = Some((DialogPurpose::Import, Dialog::text_input(t("dialog.import"), t("dialog.import_text"))));
+            }
+            Command::ToggleHeatmap if self.screen() == Screen::Won => self.toggle_heatmap(),
+            Command::GoTo if matches!(self.screen(), Screen::Playing | Screen::Won) => self.go_to = Some(None),
+            // Everything else acts on the board, which only takes input while playing
+            _ if self.screen() != Screen::Playing => {}
+            Command::Share => self.open_screen(Screen::Share),
+            Command::Hint => self.hint(),
+            Command::Explain => self.explain(),
+            Command::CheckProgress => self.progress_check = Some(self.wrong_entries()),
+            Command::BrowseHistory => self.browse_history(),
+            Command::Hypothesis => self.toggle_hypothesis(),
+            Command::DiscardHypothesis => self.discard_hypothesis(),
+            Command::ToggleCounts => self.show_counts = !self.show_counts,
+            Command::ToggleLastCells => self.show_last_cells = !self.show_last_cells,
+            Command::Confirm | Command::NewGame | Command::ToggleHeatmap | Command::Import | Command::NextPuzzle | Command::PreviousPuzzle | Command::GoTo => {}
+            Command::RecordMacro | Command::PlayMacro => {}
+            Command::ToggleMode => self.toggle_mode(),
+            Command::MoveCursor(dr, dc) => self.move_cursor(dr, dc),
+            Command::SelectCell(r, c) => self.cursor = (r.min(5), c.min(5)),
+            Command::EnterDigit(num) => {
+                self.handle_input(num);
+                self.last_entry = Some((self.mode, Some(num)));
+            }
+            Command::EnterAlternate(num) => {
+                self.enter_alternate(num);
+                self.last_entry = Some((self.alternate_mode(), Some(num)));
+            }
+            Command::ClearCell => {
+                self.clear_cell();
+                self.last_entry = Some((self.mode, None));
+            }
+            Command::Repeat => self.repeat_entry(),
+            Command::ToggleLock => self.toggle_lock(),
+            Command::Undo => self.undo(),
+        }
+        if let Some(before) = before.filter(|_| self.hypothesis.is_none() && matches!(self.screen(), Screen::Playing | Screen::Won)) {
+            self.track_erases(&before);
+        }
+        // The entry that solved the board is in the replay by now
+        if self.screen() == Screen::Won && !was_won {
+            self.save_replay();
+        }
+        true
+    }
+
+    fn save_replay(&self) {
+        if let Some(replay) = &self.replay {
+            if let Err(err) = replay.save(&self.meta.key(), self.timer.elapsed(), self.mistakes) {
+                log::warn!("could not save the replay: {}", err);
+            }
+        }
+    }
+
+    // Give the open dialog a key, acting on its answer once it has one.
+    // Returns false when the answer quits the game.
+    pub fn dialog_key(&mut self, key: Key) -> bool {
+        let Some((purpose, dialog)) = self.dialog.as_mut() else {
+            return true;
+        };
+        let purpose = *purpose;
+        let outcome = dialog.handle(key);
+        if outcome != Outcome::Open {
+            self.dialog = None;
+        }
+        match (purpose, outcome) {
+            (DialogPurpose::Quit, Outcome::Answered(Answer::Yes)) => return self.quit(),
+            (DialogPurpose::Import, Outcome::Answered(Answer::Text(code))) => self.import(&code),
+            _ => {}
+        }
+        true
+    }
+
+    // Count the game and show how the session went, if any game was counted
+    // in it. Returns false when there's nothing to show and the game can end.
+    fn quit(&mut self) -> bool {
+        self.record_stats();
+        if self.stats.as_ref().is_none_or(|stats| stats.session.attempted == 0) {
+            return false;
+        }
+        self.timer.pause();
+        self.screens.push(Screen::Summary);
+        true
+    }
+
+    // Play the puzzle a share code gives. It's checked for broken rules here,
+    // but solved in the background.
+    fn import(&mut self, code: &str) {
+        match Generator::givens(code).map(Game::unsolved) {
+            Ok(mut next) => {
+                next.solve_in_background();
+                self.record_stats();
+                self.keep_settings(&mut next);
+                next.ghost = self.ghost.as_ref().map(|_| Ghost::load(&next.meta.key()));
+                log::info!("imported puzzle {}", next.meta.key());
+                *self = next;
+            }
+            Err(err) => self.dialog = Some((DialogPurpose::Notice, Dialog::message(t("dialog.import_failed"), &err.to_string()))),
+        }
+    }
+
+    // Scroll the About screen by `lines`, until its last line is in view
+    pub fn scroll_about(&mut self, lines: i8) {
+        let last = about::lines().len().saturating_sub(self.about_rows.max(1));
+        self.about_scroll = self.about_scroll.saturating_add_signed(lines as isize).min(last);
+    }
+
+    // Show the stats tab `tabs` to the left or right, wrapping around
+    pub fn switch_stats_tab(&mut self, tabs: i8) {
+        let count = self.stats.as_ref().map_or(0, |stats| stats.tabs().len()).max(1);
+        self.stats_tab = (self.stats_tab as isize + tabs as isize).rem_euclid(count as isize) as usize;
+    }
+
+    // Show or hide where the wrong entries went, counting every one the undo
+    // tree holds, including those undone or later corrected
+    pub fn toggle_heatmap(&mut self) {
+        self.heatmap = match self.heatmap {
+            Some(_) => None,
+            None => Some(self.undo_tree.entry_counts(|r, c, v| !self.is_correct_move(r, c, v))),
+        };
+    }
+
+    // The review's step that places (r, c), numbered from 1
+    pub fn review_step(&self, r: usize, c: usize) -> Option<(usize, &Deduction)> {
+        self.review.iter().enumerate().find(|(_, step)| (step.row, step.col) == (r, c)).map(|(i, step)| (i + 1, step))
+    }
+
+    // Point out the next logical step without playing it
+    pub fn hint(&mut self) {
+        self.hint = Some(self.next_assist_step());
+    }
+
+    // Whether the entries so far leave a cell without a legal value, or a value
+    // with nowhere to go, so one of them must be wrong
+    pub fn dead_end(&self) -> Option<Contradiction> {
+        solver::contradiction(&self.grid, &solver::Candidates::from_grid(&self.grid))
+    }
+
+    // Entered values that don't match the solution, leaving out what-if entries
+    pub fn wrong_entries(&self) -> usize {
+        (0..36).filter(|&i| !self.is_hypothetical(i / 6, i % 6) && self.wrong_at(i / 6, i % 6).is_some()).count()
+    }
+
+    // The value under the cursor, if the player entered it outside a what-if branch and it's wrong
+    pub fn wrong_at_cursor(&self) -> Option<u8> {
+        let (r, c) = self.cursor;
+        self.wrong_at(r, c).filter(|_| !self.is_hypothetical(r, c))
+    }
+
+    // The value at (r, c), if the player entered it and it's wrong
+    fn wrong_at(&self, r: usize, c: usize) -> Option<u8> {
+        let cell = &self.grid.cells[r][c];
+        cell.value().filter(|&v| !cell.is_fixed() && !self.is_correct_move(r, c, v))
+    }
+
+    // Open the explain popup for the wrong entry under the cursor. The solver
+    // reasons from the givens and the correct entries only.
+    pub fn explain(&mut self) {
+        let Some(value) = self.wrong_at_cursor() else {
+            return;
+        };
+        let (row, col) = self.cursor;
+        let mut grid = self.grid.clone();
+        for (r, c) in (0..36).map(|i| (i / 6, i % 6)) {
+            if grid.cells[r][c].value().is_some_and(|v| !self.is_correct_move(r, c, v)) {
+                grid.cells[r][c].set_value(None);
+            }
+        }
+        let refutation = self.techniques.refute(&grid, row, col, value);
+        log::debug!("explaining r{}c{} = {}: {:?}", row + 1, col + 1, value, refutation);
+        self.explanation = Some(Explanation { row, col, value, refutation });
+        self.open_screen(Screen::Explain);
+    }
+
+    // Enter or leave watch mode, where the solver plays the puzzle on its own
+    pub fn toggle_demo(&mut self) {
+        match self.screen() {
+            Screen::Playing => self.open_screen(Screen::Demo),
+            Screen::Demo => {
+                self.screens.pop();
             }
+            _ => {}
         }
+        self.demo_last = None;
+    }
+
+    // Stop the clock, e.g. when the terminal loses focus
+    pub fn pause(&mut self) {
+        if self.screen() == Screen::Playing {
+            self.open_screen(Screen::Paused);
+        }
+    }
+
+    // Pause after a period of inactivity; the idle time since `last_input` is not counted
+    pub fn pause_idle(&mut self, last_input: Instant) {
+        if self.screen() == Screen::Playing {
+            self.screens.push(Screen::Paused);
+            self.timer.pause_at(last_input);
+            self.dirty = true;
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.screen() == Screen::Paused {
+            self.screens.pop();
+            self.timer.resume();
+        }
+    }
+
+    // The screen shown
+    pub fn screen(&self) -> Screen {
+        self.screens.top()
+    }
+
+    // Open an overlay over the screen shown, stopping the clock if it's one
+    // that takes the player away from play
+    pub fn open_screen(&mut self, screen: Screen) {
+        if self.screen() == Screen::Playing && screen.stops_clock() {
+            self.timer.pause();
+        }
+        self.screens.push(screen);
+    }
+
+    // Close the screen shown, back to the one it was opened over. Leaving the
+    // history browser puts the current board back, and the clock starts again
+    // on getting back to play from an overlay that stopped it.
+    pub fn close_screen(&mut self) {
+        match self.screen() {
+            Screen::History => self.leave_history(),
+            Screen::Paused => self.resume(),
+            Screen::Demo => self.toggle_demo(),
+            Screen::Generating => self.cancel_search(),
+            _ => {
+                let closed = self.screens.pop();
+                if closed.is_some_and(Screen::stops_clock) && self.screen() == Screen::Playing {
+                    self.timer.resume();
+                }
+            }
+        }
+    }
+
+    // Request a redraw after a change made outside the game's own ticks (input, resize)
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
     }
-    
-    pub fn toggle_mode(&mut self) {
-        self.mode = match self.mode {
-            InputMode::Normal => InputMode::Pencil,
-            InputMode::Pencil => InputMode::Normal,
-        };
+
+    // Whether the UI needs redrawing: something changed, the clock ticked over
+    // a second or a search is showing its progress. Clears the flag, so call
+    // it once per frame.
+    pub fn take_dirty(&mut self) -> bool {
+        let secs = self.timer.elapsed().as_secs();
+        let dirty = self.dirty || secs != self.drawn_secs || self.search.is_some();
+        self.dirty = false;
+        self.drawn_secs = secs;
+        dirty
     }
-    
-    pub fn clear_cell(&mut self) {
-        let (r, c) = self.cursor;
-        if self.grid.cells[r][c].is_fixed {
+
+    // Work the debug overlay's solve out again while it's up, once the board
+    // has changed since the last time; call it before drawing a dirty frame
+    pub fn refresh_debug_solve(&mut self) {
+        if !self.show_debug || self.debug_solve.as_ref().is_some_and(|(cells, ..)| *cells == self.grid.cells) {
             return;
         }
-        self.grid.cells[r][c].value = None;
-        self.grid.cells[r][c].marks = [false; 6];
+        let (_, search) = self.grid.solutions_with_stats(2);
+        let (_, logic) = self.techniques.grade_with_stats(&self.grid);
+        self.debug_solve = Some((self.grid.cells, search, logic));
+    }
+
+    // Perform one watch-mode step: undo a wrong entry if there is one, otherwise
+    // place the next logical deduction (or reveal a cell if logic gets stuck).
+    pub fn demo_step(&mut self) {
+        let step = self.next_assist_step();
+        let cell = &mut self.grid.cells[step.row][step.col];
+        cell.set_value(if step.value == 0 { None } else { Some(step.value) });
+        cell.clear_marks();
+        cell.set_locked(false);
+        self.cursor = (step.row, step.col);
+        self.undo_tree.record(&self.grid, self.cursor);
+        self.demo_last = Some(step);
+        self.watched += 1;
+        self.dirty = true;
+
+        if self.is_won() {
+            self.finish();
+        }
+    }
+
+    // The next step for hints and watch mode, using the enabled techniques
+    fn next_assist_step(&self) -> Deduction {
+        // The solver assumes every entry is right, so wrong ones are cleared first
+        // (a value of 0 clears the cell)
+        for r in 0..6 {
+            for c in 0..6 {
+                if let Some(v) = self.grid.cells[r][c].value() {
+                    if !self.is_correct_move(r, c, v) {
+                        return Deduction { row: r, col: c, value: 0, technique: "Correction" };
+                    }
+                }
+            }
+        }
+
+        if let Some(step) = self.techniques.next_deduction(&self.grid) {
+            return step;
+        }
+
+        // Generated puzzles are not guaranteed to be solvable by singles
+        // alone. Without a solution, the rest of the board is solved as it is;
+        // if it can't be, some entry must go, though the rules don't say which.
+        let solution = self.solution.or_else(|| self.grid.solutions(1).first().copied());
+        let entry = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !self.grid.cells[r][c].is_fixed() && self.grid.cells[r][c].value().is_some());
+        for r in 0..6 {
+            for c in 0..6 {
+                match (self.grid.cells[r][c].value(), solution, entry) {
+                    (None, Some(solution), _) => return Deduction { row: r, col: c, value: solution[r][c], technique: "Reveal" },
+                    (None, None, Some((row, col))) => return Deduction { row, col, value: 0, technique: "Correction" },
+                    // The givens themselves have no solution
+                    (None, None, None) => return Deduction { row: r, col: c, value: 0, technique: "Unsolvable" },
+                    _ => {}
+                }
+            }
+        }
+        unreachable!("demo_step called on a full board")
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Game, Grid};
+    use super::{CageSum, Cell, Contradiction, DialogPurpose, Erases, Game, Grid, InputMode, InvariantError, ParseError, Unit, WinCheck};
+    use crate::{
+        command::Command,
+        constraints::KillerCage,
+        events::{Key, KeyCode},
+        screen::{Screen, ScreenStack},
+    };
+
+    #[test]
+    fn cells_pack_every_field_into_four_bytes() {
+        assert_eq!(std::mem::size_of::<Cell<16>>(), 4);
+        let mut cell = Cell::<16>::given(16);
+        cell.set_locked(true);
+        cell.set_mark(1, true);
+        cell.toggle_mark(16);
+        assert_eq!((cell.value(), cell.is_fixed(), cell.locked()), (Some(16), true, true));
+        assert_eq!((1..=16).filter(|&v| cell.mark(v)).collect::<Vec<_>>(), [1, 16]);
+        cell.clear_marks();
+        cell.set_value(None);
+        assert_eq!((cell.value(), cell.has_marks(), cell.is_fixed(), cell.locked()), (None, false, true, true));
+        // Digits off the board are never marked
+        cell.set_mark(17, true);
+        assert!(!cell.has_marks() && !cell.mark(0));
+    }
 
     #[test]
     fn is_valid_move_rejects_out_of_bounds_or_invalid_values() {
@@ -305,10 +2193,973 @@ mod tests {
         assert!(!grid.is_valid_move(0, 0, 7));
     }
 
+    #[test]
+    fn demo_step_plays_puzzle_to_completion() {
+        let mut game = Game::new();
+        game.toggle_demo();
+        // Plant a wrong entry for the demo to correct
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| game.grid.cells[r][c].value().is_none()).unwrap();
+        game.grid.cells[r][c].set_value(Some(game.solution.unwrap()[r][c] % 6 + 1));
+
+        for _ in 0..36 {
+            if let Screen::Won = game.screen() {
+                break;
+            }
+            game.demo_step();
+        }
+        assert!(matches!(game.screen(), Screen::Won));
+        assert_eq!(game.mistakes, 0);
+    }
+
+    #[test]
+    fn hint_points_at_correct_value_and_clears_on_entry() {
+        let mut game = Game::new();
+        game.hint();
+        let hint = game.hint.expect("a fresh puzzle always has a next step");
+        assert_eq!(game.solution.unwrap()[hint.row][hint.col], hint.value);
+
+        game.cursor = (hint.row, hint.col);
+        game.handle_input(hint.value);
+        assert!(game.hint.is_none());
+    }
+
+    #[test]
+    fn required_technique_puzzles_need_it() {
+        let game = Game::with_seed_requiring(513, "locked_candidates").expect("seed 513 needs locked candidates");
+        assert!(game.meta.difficulty.is_some());
+        assert_eq!(game.grid.cells, Game::with_seed_requiring(513, "locked_candidates").unwrap().grid.cells);
+        let mut techniques = crate::solver::TechniqueRegistry::builtin();
+        techniques.set_enabled("locked_candidates", false);
+        assert!(techniques.grade(&game.grid).is_none());
+        assert!(Game::with_seed_requiring(512, "locked_candidates").is_none());
+    }
+
+    #[test]
+    fn same_seed_gives_same_puzzle() {
+        let a = Game::with_seed(42);
+        let b = Game::with_seed(42);
+        assert_eq!(a.solution, b.solution);
+        assert_eq!(a.grid.cells, b.grid.cells);
+        assert_eq!(a.grid.clue_count(), 16);
+        assert!((1..=super::MAX_STARS).contains(&a.stars()));
+    }
+
+    #[test]
+    fn pause_freezes_timer_until_resume() {
+        let mut game = Game::new();
+        game.pause();
+        assert_eq!(game.screen(), Screen::Paused);
+        let frozen = game.timer.elapsed();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(game.timer.elapsed(), frozen);
+
+        game.resume();
+        assert_eq!(game.screen(), Screen::Playing);
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert!(game.timer.elapsed() > frozen);
+    }
+
+    #[test]
+    fn pause_idle_discards_idle_time() {
+        let mut game = Game::new();
+        let last_input = std::time::Instant::now();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        game.pause_idle(last_input);
+
+        assert_eq!(game.screen(), Screen::Paused);
+        assert!(game.timer.elapsed() < std::time::Duration::from_millis(20));
+    }
+
+    #[test]
+    fn undo_restores_previous_entry() {
+        let mut game = Game::with_seed(7);
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        game.cursor = (r, c);
+        game.apply(Command::EnterDigit(game.solution.unwrap()[r][c]));
+        game.apply(Command::MoveCursor(1, 1));
+        game.apply(Command::Undo);
+
+        assert_eq!(game.grid.cells[r][c].value(), None);
+        assert_eq!(game.cursor, (r, c));
+        game.apply(Command::Undo); // nothing left to undo
+        assert_eq!(game.grid.cells[r][c].value(), None);
+    }
+
+    #[test]
+    fn repeating_a_wrong_entry_counts_one_mistake() {
+        let mut game = Game::with_seed(7);
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        let wrong = (1..=6).find(|&v| v != game.solution.unwrap()[r][c]).unwrap();
+        game.cursor = (r, c);
+        for _ in 0..3 {
+            game.apply(Command::EnterDigit(wrong));
+        }
+        assert_eq!(game.mistakes, 1);
+    }
+
+    #[test]
+    fn apply_respects_game_state() {
+        let mut game = Game::with_seed(7);
+        assert!(game.apply(Command::Share));
+        assert_eq!(game.screen(), Screen::Share);
+        assert!(game.apply(Command::Quit)); // closes the popup rather than quitting
+        assert_eq!(game.screen(), Screen::Playing);
+
+        game.apply(Command::ToggleDemo);
+        game.apply(Command::MoveCursor(1, 0));
+        assert_eq!(game.cursor, (0, 0)); // the board ignores input in watch mode
+        assert!(game.apply(Command::Back));
+        assert_eq!(game.screen(), Screen::Playing);
+
+        // The clock holds while About is open over play, with the board as it was
+        let cursor = game.cursor;
+        game.apply(Command::ToggleAbout);
+        let held = game.timer.elapsed();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(game.timer.elapsed(), held);
+        game.apply(Command::ToggleStats);
+        game.apply(Command::Back);
+        game.apply(Command::Back);
+        assert_eq!((game.screen(), game.cursor), (Screen::Playing, cursor));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert!(game.timer.elapsed() > held);
+
+        // Esc goes back to the screen About was opened over
+        game.screens = ScreenStack::new(Screen::Won);
+        game.apply(Command::ToggleAbout);
+        assert!(game.apply(Command::Back));
+        assert_eq!(game.screen(), Screen::Won);
+        assert!(!game.apply(Command::Back));
+    }
+
+    #[test]
+    fn hotseat_entries_pass_the_turn() {
+        let mut game = Game::with_seed(7);
+        game.hotseat = Some(crate::hotseat::HotSeat::default());
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        game.cursor = (r, c);
+        let right = game.solution.unwrap()[r][c];
+        game.handle_input(right % 6 + 1);
+        game.handle_input(right);
+
+        let seat = game.hotseat.as_ref().unwrap();
+        assert_eq!(seat.turn, 0);
+        assert_eq!((seat.players[0].mistakes, seat.players[1].placed), (1, 1));
+        game.undo(); // disabled while taking turns
+        assert_eq!(game.grid.cells[r][c].value(), Some(right));
+
+        // Clearing a value takes a turn; clearing marks doesn't
+        game.clear_cell();
+        assert_eq!(game.hotseat.as_ref().unwrap().turn, 1);
+        game.clear_cell();
+        assert_eq!(game.hotseat.as_ref().unwrap().turn, 1);
+    }
+
+    #[test]
+    fn shared_values_fill_the_board_without_mistakes() {
+        let mut game = Game::with_seed(7);
+        game.coop_player = Some(1);
+        for (r, c) in (0..36).map(|i| (i / 6, i % 6)) {
+            game.set_shared_value(r, c, Some(game.solution.unwrap()[r][c] % 6 + 1));
+            game.set_shared_value(r, c, Some(game.solution.unwrap()[r][c]));
+        }
+        assert_eq!(game.mistakes, 0);
+        assert_eq!(game.screen(), Screen::Won);
+    }
+
+    #[test]
+    fn endless_mode_moves_on_to_a_new_puzzle() {
+        let mut game = Game::with_seed(7);
+        game.endless = Some(crate::endless::Endless::new(game.stars(), false));
+        game.mode = InputMode::Pencil;
+        game.screens = ScreenStack::new(Screen::Won);
+        game.next_puzzle_in_run();
+
+        let session = game.endless.as_ref().unwrap();
+        assert_eq!(session.solved, 1);
+        assert!(session.score > 0);
+        assert_eq!(game.screen(), Screen::Playing);
+        assert_eq!(game.mode, InputMode::Pencil);
+    }
+
+    #[test]
+    fn time_attack_rewards_correct_placements() {
+        let mut game = Game::with_seed(7);
+        game.time_attack = Some(crate::timeattack::TimeAttack::default());
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        game.cursor = (r, c);
+        game.handle_input(game.solution.unwrap()[r][c] % 6 + 1);
+        game.handle_input(game.solution.unwrap()[r][c]);
+        assert_eq!(game.time_attack.as_ref().unwrap().allowance, crate::timeattack::BONUS);
+
+        // Clearing the cell and placing it again earns nothing more
+        game.clear_cell();
+        game.handle_input(game.solution.unwrap()[r][c]);
+        assert_eq!(game.time_attack.as_ref().unwrap().allowance, crate::timeattack::BONUS);
+    }
+
+    #[test]
+    fn explain_opens_only_on_wrong_entries() {
+        let mut game = Game::with_seed(3);
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        game.cursor = (r, c);
+        game.handle_input(game.solution.unwrap()[r][c]);
+        game.apply(Command::Explain);
+        assert_eq!(game.screen(), Screen::Playing);
+
+        let wrong = game.solution.unwrap()[r][c] % 6 + 1;
+        game.handle_input(wrong);
+        game.apply(Command::Explain);
+        assert_eq!(game.screen(), Screen::Explain);
+        let explanation = game.explanation.as_ref().unwrap();
+        assert_eq!((explanation.row, explanation.col, explanation.value), (r, c, wrong));
+        game.apply(Command::Dismiss);
+        assert_eq!(game.screen(), Screen::Playing);
+    }
+
+    #[test]
+    fn block_conflicts_refuses_clashing_digits() {
+        let mut game = Game::with_seed(5);
+        game.block_conflicts = true;
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        let peer = game.grid.regions.peers(r, c).iter().copied().find(|&(pr, pc)| game.grid.cells[pr][pc].is_fixed()).unwrap();
+        let clash = game.grid.cells[peer.0][peer.1].value().unwrap();
+        game.apply(Command::SelectCell(r, c));
+        game.apply(Command::EnterDigit(clash));
+        assert_eq!(game.grid.cells[r][c].value(), None);
+        assert_eq!(game.mistakes, 0);
+        let rejected = game.rejected.expect("the clash is explained");
+        assert_eq!(rejected.value, clash);
+        assert!(game.flash_until.is_some());
+
+        // The explanation lasts until the next command
+        game.apply(Command::MoveCursor(0, 0));
+        assert_eq!(game.rejected, None);
+        game.apply(Command::EnterDigit(game.solution.unwrap()[r][c]));
+        assert_eq!(game.grid.cells[r][c].value(), Some(game.solution.unwrap()[r][c]));
+    }
+
+    #[test]
+    fn auto_clear_removes_wrong_entries_after_the_flash() {
+        let mut game = Game::with_seed(8);
+        game.auto_clear = true;
+        let mut empty = (0..36).map(|i| (i / 6, i % 6)).filter(|&(r, c)| !game.grid.cells[r][c].is_fixed());
+        let (first, second) = (empty.next().unwrap(), empty.next().unwrap());
+        let wrong = |game: &Game, (r, c): (usize, usize)| game.solution.unwrap()[r][c] % 6 + 1;
+
+        game.cursor = first;
+        game.handle_input(wrong(&game, first));
+        assert!(game.grid.cells[first.0][first.1].value().is_some());
+        // A second mistake clears the first straight away
+        game.cursor = second;
+        game.handle_input(wrong(&game, second));
+        assert_eq!(game.grid.cells[first.0][first.1].value(), None);
+
+        game.flash_until = Some(std::time::Instant::now());
+        game.end_flash();
+        assert_eq!(game.grid.cells[second.0][second.1].value(), None);
+        assert_eq!(game.mistakes, 2);
+        // Each clear is an entry of its own, so undo brings the wrong value back
+        game.undo();
+        assert_eq!(game.grid.cells[second.0][second.1].value(), Some(wrong(&game, second)));
+        game.undo();
+        game.undo();
+        assert_eq!(game.grid.cells[first.0][first.1].value(), Some(wrong(&game, first)));
+        assert_eq!(game.grid.cells[second.0][second.1].value(), None);
+
+        // Not in the weekly challenge, which goes without assists
+        game.weekly = Some("2026-10-12".to_string());
+        game.mistakes = 0;
+        game.cursor = first;
+        game.handle_input(wrong(&game, first));
+        game.flash_until = Some(std::time::Instant::now());
+        game.end_flash();
+        assert!(game.grid.cells[first.0][first.1].value().is_some());
+    }
+
+    #[test]
+    fn alternate_entry_marks_without_switching_modes() {
+        let mut game = Game::with_seed(4);
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        game.cursor = (r, c);
+        game.apply(Command::EnterAlternate(2));
+        assert!(game.grid.cells[r][c].marks()[1]);
+        assert_eq!((game.mode, game.grid.cells[r][c].value()), (InputMode::Normal, None));
+
+        game.toggle_mode();
+        game.apply(Command::EnterAlternate(game.solution.unwrap()[r][c]));
+        assert_eq!((game.mode, game.grid.cells[r][c].value()), (InputMode::Pencil, Some(game.solution.unwrap()[r][c])));
+    }
+
+    #[test]
+    fn go_to_chord_jumps_to_the_cell_typed() {
+        let mut game = Game::with_seed(4);
+        let before = game.grid.cells[1][4];
+        for command in [Command::GoTo, Command::EnterDigit(2), Command::EnterDigit(5)] {
+            game.apply(command);
+        }
+        assert_eq!((game.cursor, game.go_to), ((1, 4), None));
+        assert_eq!(game.grid.cells[1][4].value(), before.value());
+
+        // Esc drops the chord; another command drops it and goes through
+        game.apply(Command::GoTo);
+        game.apply(Command::Back);
+        assert!(game.go_to.is_none() && game.screen() == Screen::Playing);
+        game.apply(Command::GoTo);
+        game.apply(Command::EnterDigit(6));
+        assert_eq!(game.go_to, Some(Some(5)));
+        game.apply(Command::MoveCursor(0, -1));
+        assert_eq!((game.cursor, game.go_to), ((1, 3), None));
+
+        // The row goes by its letter too, as the coordinates show it
+        let letter = |ch| Key::new(KeyCode::Char(ch));
+        assert!(!game.go_to_row_key(letter('c')));
+        game.apply(Command::GoTo);
+        assert!(!game.go_to_row_key(letter('g')));
+        assert!(game.go_to_row_key(letter('C')));
+        game.apply(Command::EnterDigit(2));
+        assert_eq!((game.cursor, game.go_to), ((2, 1), None));
+    }
+
+    #[test]
+    fn repeat_makes_the_last_entry_at_the_cursor() {
+        let mut game = Game::with_seed(4);
+        let blanks: Vec<(usize, usize)> = (0..36).map(|i| (i / 6, i % 6)).filter(|&(r, c)| !game.grid.cells[r][c].is_fixed()).take(3).collect();
+        game.cursor = blanks[0];
+        game.apply(Command::Repeat); // nothing to repeat yet
+        game.apply(Command::EnterAlternate(4));
+        game.cursor = blanks[1];
+        game.apply(Command::Repeat);
+        assert!(game.grid.cells[blanks[1].0][blanks[1].1].marks()[3]);
+
+        // A mark is repeated as a mark after switching to pencil mode too
+        game.apply(Command::ToggleMode);
+        game.cursor = blanks[2];
+        game.apply(Command::Repeat);
+        assert!(game.grid.cells[blanks[2].0][blanks[2].1].marks()[3]);
+
+        game.apply(Command::ClearCell);
+        game.cursor = blanks[0];
+        game.apply(Command::Repeat);
+        assert_eq!(game.grid.cells[blanks[0].0][blanks[0].1].marks(), [false; 6]);
+    }
+
+    #[test]
+    fn preview_holds_the_clock_until_the_puzzle_is_started() {
+        let mut game = Game::with_seed(4);
+        game.preview = true;
+        game.show_preview();
+        let blank = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        game.cursor = blank;
+        game.apply(Command::EnterDigit(1));
+        assert_eq!(game.grid.cells[blank.0][blank.1].value(), None);
+        assert_eq!(game.timer.elapsed(), std::time::Duration::ZERO);
+
+        // A reroll is another puzzle, on the preview again
+        let seed = game.meta.seed;
+        game.apply(Command::NewGame);
+        assert_ne!(game.meta.seed, seed);
+        assert_eq!(game.screen(), Screen::Preview);
+        assert_eq!(game.timer.elapsed(), std::time::Duration::ZERO);
+
+        // A digit with no profile of its number is ignored; the first
+        // profile's is the first profile from now on
+        game.profiles = vec![crate::profile::Profile::parse("symmetric", "symmetry=mirror").unwrap()];
+        let key = game.meta.key();
+        game.apply(Command::EnterDigit(2));
+        assert_eq!((game.profile, game.meta.key()), (None, key.clone()));
+        // The profile's puzzle is looked for in the background, and calling
+        // the search off leaves the preview as it was
+        game.apply(Command::EnterDigit(1));
+        assert_eq!((game.profile, game.screen()), (Some(0), Screen::Generating));
+        game.apply(Command::EnterDigit(3));
+        assert!(game.search.is_some());
+        game.apply(Command::Back);
+        assert_eq!((game.search.is_none(), game.screen(), game.meta.key()), (true, Screen::Preview, key));
+        game.apply(Command::EnterDigit(1));
+        let started = std::time::Instant::now();
+        while !game.poll_search() {
+            assert!(started.elapsed() < std::time::Duration::from_secs(30));
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert_eq!((game.profile, game.screen()), (Some(0), Screen::Preview));
+        assert!(game.profiles[0].fits(&game));
+
+        game.apply(Command::Confirm);
+        assert_eq!(game.screen(), Screen::Playing);
+        assert!(game.preview);
+        assert_eq!(game.profiles.len(), 1);
+    }
+
+    #[test]
+    fn a_seeded_rng_plays_the_same_run_of_puzzles() {
+        use rand::{rngs::StdRng, SeedableRng};
+        let run = |seed| {
+            let mut game = Game::with_seed(4);
+            game.rng = StdRng::seed_from_u64(seed);
+            game.transform_puzzles = true;
+            (0..3)
+                .map(|_| {
+                    game.new_game();
+                    game.meta.key()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+
+    #[test]
+    fn replay_times_each_entry_and_clear() {
+        let mut game = Game::with_seed(4);
+        game.replay = Some(crate::replay::Replay::default());
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        let right = game.solution.unwrap()[r][c];
+        game.cursor = (r, c);
+        game.apply(Command::EnterDigit(right % 6 + 1));
+        game.apply(Command::ClearCell);
+        game.apply(Command::EnterAlternate(right)); // marks aren't entries
+        game.apply(Command::EnterDigit(right));
+        let moves = &game.replay.as_ref().unwrap().moves;
+        let entries: Vec<_> = moves.iter().map(|step| (step.row, step.col, step.value, step.correct)).collect();
+        assert_eq!(entries, [(r, c, Some(right % 6 + 1), false), (r, c, None, false), (r, c, Some(right), true)]);
+        assert!(moves.windows(2).all(|pair| pair[0].at <= pair[1].at));
+    }
+
+    #[test]
+    fn debug_solve_is_worked_out_again_only_for_a_changed_board() {
+        let mut game = Game::with_seed(4);
+        game.refresh_debug_solve();
+        assert!(game.debug_solve.is_none());
+        game.apply(Command::ToggleDebug);
+        game.refresh_debug_solve();
+        let (cells, search, _) = game.debug_solve.clone().unwrap();
+        assert_eq!(cells, game.grid.cells);
+
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        game.cursor = (r, c);
+        game.apply(Command::EnterDigit(game.solution.unwrap()[r][c]));
+        game.refresh_debug_solve();
+        let (cells, after, _) = game.debug_solve.clone().unwrap();
+        assert_eq!(cells, game.grid.cells);
+        assert!(after.nodes < search.nodes);
+    }
+
+    #[test]
+    fn kept_what_if_entries_go_in_the_replay() {
+        let mut game = Game::with_seed(4);
+        game.replay = Some(crate::replay::Replay::default());
+        let blanks: Vec<_> = (0..36).map(|i| (i / 6, i % 6)).filter(|&(r, c)| !game.grid.cells[r][c].is_fixed()).take(3).collect();
+        let solution = game.solution.unwrap();
+        game.cursor = blanks[0];
+        game.apply(Command::EnterDigit(solution[blanks[0].0][blanks[0].1]));
+        game.apply(Command::Hypothesis);
+        for &(r, c) in &blanks[1..] {
+            game.cursor = (r, c);
+            game.apply(Command::EnterDigit(solution[r][c]));
+        }
+        game.apply(Command::Hypothesis);
+        assert!(game.hypothesis.is_none());
+
+        // Playing the moves over the givens gives back the kept board
+        let mut board = game.grid.cells;
+        for row in board.iter_mut() {
+            for cell in row.iter_mut().filter(|cell| !cell.is_fixed()) {
+                cell.set_value(None);
+            }
+        }
+        for step in &game.replay.as_ref().unwrap().moves {
+            board[step.row][step.col].set_value(step.value);
+        }
+        let values = |cells: &crate::undotree::Cells| cells.map(|row| row.map(|cell| cell.value()));
+        assert_eq!(values(&board), values(&game.grid.cells));
+        assert_eq!(game.replay.as_ref().unwrap().moves.len(), 3);
+    }
+
+    #[test]
+    fn dead_end_spots_cells_left_without_values() {
+        let mut game = Game::with_seed(6);
+        assert_eq!(game.dead_end(), None);
+
+        // r1c6 can only be 6, which is already in its column
+        game.grid = Grid::new();
+        for (c, v) in [1, 2, 3, 4, 5].into_iter().enumerate() {
+            game.grid.cells[0][c].set_value(Some(v));
+        }
+        game.grid.cells[3][5].set_value(Some(6));
+        assert_eq!(game.dead_end(), Some(Contradiction::NoCandidates { row: 0, col: 5 }));
+    }
+
+    #[test]
+    fn progress_check_counts_wrong_entries() {
+        let mut game = Game::with_seed(9);
+        game.apply(Command::CheckProgress);
+        assert_eq!(game.progress_check, Some(0));
+
+        let empty: Vec<_> = (0..36).map(|i| (i / 6, i % 6)).filter(|&(r, c)| !game.grid.cells[r][c].is_fixed()).take(3).collect();
+        for (i, &(r, c)) in empty.iter().enumerate() {
+            game.cursor = (r, c);
+            game.handle_input(if i == 0 { game.solution.unwrap()[r][c] } else { game.solution.unwrap()[r][c] % 6 + 1 });
+        }
+        game.apply(Command::CheckProgress);
+        assert_eq!(game.progress_check, Some(2));
+        game.apply(Command::MoveCursor(1, 0));
+        assert_eq!(game.progress_check, None);
+    }
+
+    #[test]
+    fn history_browser_resumes_from_an_earlier_board() {
+        let mut game = Game::with_seed(10);
+        let empty: Vec<_> = (0..36).map(|i| (i / 6, i % 6)).filter(|&(r, c)| !game.grid.cells[r][c].is_fixed()).take(3).collect();
+        for &(r, c) in &empty {
+            game.apply(Command::SelectCell(r, c));
+            game.apply(Command::EnterDigit(game.solution.unwrap()[r][c]));
+        }
+        let current = game.grid.clone();
+
+        // Esc goes back to the current board
+        game.apply(Command::BrowseHistory);
+        assert_eq!(game.screen(), Screen::History);
+        assert_eq!(game.undo_tree.depth(game.history_node), 3);
+        game.apply(Command::MoveCursor(0, -1));
+        assert_eq!(game.grid.cells[empty[2].0][empty[2].1].value(), None);
+        game.apply(Command::Back);
+        assert_eq!(game.screen(), Screen::Playing);
+        assert!(game.grid.cells == current.cells);
+
+        // Enter resumes from the board shown; the later entry stays as a branch
+        game.apply(Command::BrowseHistory);
+        game.apply(Command::MoveCursor(0, -1));
+        game.apply(Command::MoveCursor(0, -1));
+        game.apply(Command::MoveCursor(0, 1));
+        game.apply(Command::Confirm);
+        assert_eq!(game.screen(), Screen::Playing);
+        assert!(game.grid.cells[empty[1].0][empty[1].1].value().is_some());
+        assert_eq!(game.grid.cells[empty[2].0][empty[2].1].value(), None);
+
+        let (r, c) = empty[2];
+        game.apply(Command::SelectCell(r, c));
+        game.apply(Command::EnterDigit(game.solution.unwrap()[r][c] % 6 + 1));
+        let parent = game.undo_tree.node(game.undo_tree.current).parent.unwrap();
+        assert_eq!(game.undo_tree.node(parent).children.len(), 2);
+
+        // Up/Down switch between the two branches
+        game.apply(Command::BrowseHistory);
+        game.apply(Command::MoveCursor(-1, 0));
+        assert_eq!(game.grid.cells[r][c].value(), Some(game.solution.unwrap()[r][c]));
+        game.apply(Command::MoveCursor(1, 0));
+        assert_eq!(game.grid.cells[r][c].value(), Some(game.solution.unwrap()[r][c] % 6 + 1));
+    }
+
+    #[test]
+    fn finished_board_is_reviewed_but_not_changed() {
+        let mut game = Game::with_seed(14);
+        game.block_conflicts = true;
+        let empty: Vec<_> = (0..36).map(|i| (i / 6, i % 6)).filter(|&(r, c)| !game.grid.cells[r][c].is_fixed()).collect();
+        for &(r, c) in &empty {
+            game.apply(Command::SelectCell(r, c));
+            game.apply(Command::EnterDigit(game.solution.unwrap()[r][c]));
+        }
+        assert_eq!(game.screen(), Screen::Won);
+        if game.meta.difficulty.is_some() {
+            assert_eq!(game.review.len(), empty.len());
+        }
+
+        let (r, c) = empty[0];
+        let value = game.grid.cells[r][c].value();
+        game.apply(Command::SelectCell(r, c));
+        game.apply(Command::ClearCell);
+        game.apply(Command::EnterDigit(game.solution.unwrap()[r][c] % 6 + 1));
+        game.apply(Command::Undo);
+        assert_eq!((game.grid.cells[r][c].value(), game.highlight), (value, Some(game.solution.unwrap()[r][c] % 6 + 1)));
+        game.apply(Command::MoveCursor(0, 1));
+        assert_eq!(game.cursor, (r, (c + 1).min(5)));
+
+        game.apply(Command::ToggleHeatmap);
+        assert!(game.heatmap.is_some_and(|counts| counts.iter().flatten().all(|&n| n == 0)));
+        game.apply(Command::ToggleHeatmap);
+        assert!(game.heatmap.is_none());
+
+        game.show_counts = true;
+        game.apply(Command::NewGame);
+        assert_eq!(game.screen(), Screen::Playing);
+        assert!(game.block_conflicts && game.show_counts);
+        assert!(game.review.is_empty());
+    }
+
+    #[test]
+    fn units_with_one_empty_cell_are_found() {
+        let mut game = Game::with_seed(13);
+        for c in 1..6 {
+            game.grid.cells[0][c].set_value(Some(game.solution.unwrap()[0][c]));
+        }
+        game.grid.cells[0][0] = Cell::default();
+        let row: Unit = (0..6).map(|c| (0, c)).collect();
+        assert!(game.last_cells().contains(&row));
+        assert!(game.last_cells().iter().all(|unit| unit.iter().filter(|&&(r, c)| game.grid.cells[r][c].value().is_none()).count() == 1));
+    }
+
+    #[test]
+    fn cages_list_the_digits_their_empty_cells_can_take() {
+        assert_eq!(CageSum::combinations(10, 3, &[]), [vec![1, 3, 6], vec![1, 4, 5], vec![2, 3, 5]]);
+        assert_eq!(CageSum::combinations(10, 3, &[5]), [vec![1, 3, 6]]);
+        assert!(CageSum::combinations(3, 3, &[]).is_empty());
+
+        let mut game = Game::unsolved(Grid::with_constraints(vec![Box::new(KillerCage { cells: vec![(0, 0), (0, 1), (1, 0)], sum: 12 })]));
+        assert_eq!(game.cage_at(2, 2), None);
+        game.grid.cells[0][1].set_value(Some(4));
+        let cage = game.cage_at(0, 0).unwrap();
+        assert_eq!((cage.sum, cage.so_far, cage.empty), (12, 4, 2));
+        assert_eq!(cage.combinations, [vec![2, 6], vec![3, 5]]);
+        // Entries over the sum leave nothing
+        game.grid.cells[0][0].set_value(Some(6));
+        game.grid.cells[1][0].set_value(Some(5));
+        assert_eq!(game.cage_at(1, 0).unwrap().combinations, Vec::<Vec<u8>>::new());
+    }
+
+    #[test]
+    fn placements_light_up_their_peers_briefly() {
+        let mut game = Game::with_seed(13);
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        game.apply(Command::SelectCell(r, c));
+        game.apply(Command::ToggleMode);
+        game.apply(Command::EnterDigit(1));
+        assert_eq!(game.placed, None);
+
+        game.apply(Command::ToggleMode);
+        game.apply(Command::EnterDigit(game.solution.unwrap()[r][c]));
+        assert_eq!(game.placed, Some((r, c)));
+        game.end_flash();
+        assert_eq!(game.placed, Some((r, c)));
+
+        game.flash_until = Some(std::time::Instant::now());
+        game.end_flash();
+        assert_eq!((game.placed, game.flash_until), (None, None));
+    }
+
+    #[test]
+    fn full_boards_win_by_the_rules_or_the_solution() {
+        let mut game = Game::with_seed(13);
+        for (r, c) in (0..36).map(|i| (i / 6, i % 6)) {
+            game.grid.cells[r][c].set_value(Some(game.solution.unwrap()[r][c]));
+        }
+        // As if the puzzle had another solution, and the game held that one
+        let solution = game.solution.as_mut().unwrap();
+        solution[0][0] = solution[0][0] % 6 + 1;
+        assert!(game.is_won());
+        game.win_check = WinCheck::Solution;
+        assert!(!game.is_won());
+        assert_eq!(WinCheck::parse("solution"), Some(WinCheck::Solution));
+        assert_eq!(WinCheck::parse("strict"), None);
+    }
+
+    #[test]
+    fn puzzles_without_a_solution_are_checked_by_the_rules() {
+        let solved = Game::with_seed(13);
+        let mut game = Game::unsolved(solved.grid.clone());
+        game.win_check = WinCheck::Solution;
+        assert_eq!((game.solution, game.meta.difficulty), (None, solved.meta.difficulty));
+
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        let (&(pr, pc), clash) = game.grid.regions.peers(r, c).iter().find_map(|peer| Some((peer, game.grid.cells[peer.0][peer.1].value()?))).unwrap();
+        game.apply(Command::SelectCell(r, c));
+        game.apply(Command::EnterDigit(clash));
+        assert_eq!(game.wrong_entries(), 1, "clashes with r{}c{}", pr + 1, pc + 1);
+        game.apply(Command::Hint);
+        assert_eq!(game.hint.map(|step| (step.row, step.col, step.value)), Some((r, c, 0)));
+
+        // Watch mode finishes it, and any completion within the rules wins
+        game.apply(Command::ClearCell);
+        while game.screen() == Screen::Playing {
+            game.demo_step();
+        }
+        assert_eq!(game.screen(), Screen::Won);
+        assert!(game.grid.is_solved());
+    }
+
+    #[test]
+    fn erases_are_told_apart_by_what_replaced_them() {
+        let mut game = Game::with_seed(13);
+        let empty: Vec<_> = (0..36).map(|i| (i / 6, i % 6)).filter(|&(r, c)| !game.grid.cells[r][c].is_fixed()).collect();
+        let enter = |game: &mut Game, (r, c): (usize, usize), right: bool| {
+            game.apply(Command::SelectCell(r, c));
+            game.apply(Command::EnterDigit(if right { game.solution.unwrap()[r][c] } else { game.solution.unwrap()[r][c] % 6 + 1 }));
+        };
+
+        // A slip typed over with the right digit, and one undone and retyped
+        enter(&mut game, empty[0], false);
+        enter(&mut game, empty[0], true);
+        enter(&mut game, empty[1], false);
+        game.apply(Command::Undo);
+        enter(&mut game, empty[1], true);
+        assert_eq!(game.erases, Erases { corrections: 2, errors: 0 });
+
+        // A wrong entry the solve went on from, cleared later
+        enter(&mut game, empty[2], false);
+        enter(&mut game, empty[3], true);
+        game.apply(Command::SelectCell(empty[2].0, empty[2].1));
+        game.apply(Command::ClearCell);
+        enter(&mut game, empty[2], true);
+        // Cleared and left for another cell
+        enter(&mut game, empty[4], false);
+        game.apply(Command::ClearCell);
+        enter(&mut game, empty[5], true);
+        assert_eq!(game.erases, Erases { corrections: 2, errors: 2 });
+
+        // What-if entries aren't judged
+        game.apply(Command::Hypothesis);
+        enter(&mut game, empty[6], false);
+        game.apply(Command::DiscardHypothesis);
+        enter(&mut game, empty[6], true);
+        assert_eq!(game.erases, Erases { corrections: 2, errors: 2 });
+    }
+
+    #[test]
+    fn completing_a_unit_makes_it_glow() {
+        let mut game = Game::with_seed(13);
+        let empty: Vec<_> = (0..6).map(|c| (0, c)).filter(|&(r, c)| !game.grid.cells[r][c].is_fixed()).collect();
+        for &(r, c) in &empty {
+            assert!(!game.complete_units().contains(&0));
+            game.apply(Command::SelectCell(r, c));
+            game.apply(Command::EnterDigit(game.solution.unwrap()[r][c]));
+        }
+        // Row 0 comes first; the column and box may have been completed too
+        assert_eq!(game.completed.first(), Some(&0));
+        assert!(game.completed.iter().all(|unit| game.complete_units().contains(unit)));
+        game.flash_until = Some(std::time::Instant::now());
+        game.end_flash();
+        assert!(game.completed.is_empty());
+    }
+
+    #[test]
+    fn locked_entries_ignore_overwrites_until_unlocked() {
+        let mut game = Game::with_seed(12);
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        game.apply(Command::SelectCell(r, c));
+        game.apply(Command::ToggleLock); // nothing to lock yet
+        assert!(!game.grid.cells[r][c].locked());
+
+        game.apply(Command::EnterDigit(game.solution.unwrap()[r][c]));
+        game.apply(Command::ToggleLock);
+        game.apply(Command::EnterDigit(game.solution.unwrap()[r][c] % 6 + 1));
+        game.apply(Command::ClearCell);
+        assert_eq!(game.grid.cells[r][c].value(), Some(game.solution.unwrap()[r][c]));
+        assert_eq!(game.mistakes, 0);
+
+        game.apply(Command::ToggleLock);
+        game.apply(Command::ClearCell);
+        assert_eq!(game.grid.cells[r][c].value(), None);
+    }
+
+    #[test]
+    fn what_if_entries_are_judged_only_when_kept() {
+        let mut game = Game::with_seed(11);
+        let empty: Vec<_> = (0..36).map(|i| (i / 6, i % 6)).filter(|&(r, c)| !game.grid.cells[r][c].is_fixed()).take(2).collect();
+        let wrong = |game: &Game, (r, c): (usize, usize)| game.solution.unwrap()[r][c] % 6 + 1;
+
+        // Discarding throws the entries away without counting them
+        game.apply(Command::Hypothesis);
+        game.apply(Command::SelectCell(empty[0].0, empty[0].1));
+        game.apply(Command::EnterDigit(wrong(&game, empty[0])));
+        assert!(game.is_hypothetical(empty[0].0, empty[0].1));
+        assert_eq!((game.mistakes, game.wrong_entries(), game.wrong_at_cursor()), (0, 0, None));
+        game.apply(Command::Undo);
+        game.apply(Command::Undo); // stops at the start of the branch
+        game.apply(Command::EnterDigit(wrong(&game, empty[0])));
+        game.apply(Command::DiscardHypothesis);
+        assert!(game.hypothesis.is_none());
+        assert_eq!((game.grid.cells[empty[0].0][empty[0].1].value(), game.mistakes), (None, 0));
+
+        // Keeping them counts the wrong ones as mistakes
+        game.apply(Command::Hypothesis);
+        for &(r, c) in &empty {
+            game.apply(Command::SelectCell(r, c));
+            game.apply(Command::EnterDigit(if r == empty[0].0 && c == empty[0].1 { game.solution.unwrap()[r][c] } else { wrong(&game, (r, c)) }));
+        }
+        game.apply(Command::Hypothesis);
+        assert!(game.hypothesis.is_none());
+        assert_eq!((game.mistakes, game.wrong_entries()), (1, 1));
+
+        // Every wrong entry shows on the heatmap, kept or not
+        game.screens = ScreenStack::new(Screen::Won);
+        game.apply(Command::ToggleHeatmap);
+        let counts = game.heatmap.unwrap();
+        assert_eq!((counts[empty[0].0][empty[0].1], counts[empty[1].0][empty[1].1]), (2, 1));
+    }
+
+    #[test]
+    fn take_dirty_reports_changes_once() {
+        let mut game = Game::with_seed(7);
+        assert!(game.take_dirty());
+        assert!(!game.take_dirty());
+
+        game.demo_step();
+        assert!(game.take_dirty());
+        game.mark_dirty();
+        assert!(game.take_dirty());
+        assert!(!game.take_dirty());
+
+        // Moving the pointer within a cell doesn't redraw
+        game.set_hover(Some((2, 3)));
+        assert!(game.take_dirty());
+        game.set_hover(Some((2, 3)));
+        assert!(!game.take_dirty());
+    }
+
+    #[test]
+    fn speedrun_records_split_when_digit_completed() {
+        let mut game = Game::with_seed(7);
+        game.start_speedrun(crate::speedrun::Speedrun::default());
+        let already = game.speedrun.as_ref().unwrap().splits.len();
+
+        let digit = (1..=6).find(|&d| !game.is_digit_complete(d)).unwrap();
+        for i in 0..36 {
+            let (r, c) = (i / 6, i % 6);
+            if game.solution.unwrap()[r][c] == digit && game.grid.cells[r][c].value().is_none() {
+                game.cursor = (r, c);
+                game.handle_input(digit);
+            }
+        }
+
+        let splits = &game.speedrun.as_ref().unwrap().splits;
+        assert_eq!(splits.len(), already + 1);
+        assert_eq!(splits.last().unwrap().digit, digit);
+    }
+
+    #[test]
+    fn share_code_lists_givens_only() {
+        let mut game = Game::with_seed(11);
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        game.cursor = (r, c);
+        game.handle_input(game.solution.unwrap()[r][c]);
+
+        let code = game.grid.share_code();
+        assert_eq!(code.len(), 40);
+        assert!(code.starts_with("RD6:"));
+        assert_eq!(code[4..].chars().filter(|&ch| ch != '0').count(), game.grid.clue_count());
+    }
+
+    #[test]
+    fn backing_out_of_a_game_under_way_asks_first() {
+        let mut game = Game { confirm_quit: true, ..Game::with_seed(11) };
+        assert!(!Game { confirm_quit: true, ..Game::with_seed(11) }.apply(Command::Back)); // nothing entered yet
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        game.apply(Command::SelectCell(r, c));
+        game.apply(Command::EnterDigit(game.solution.unwrap()[r][c]));
+        assert!(game.apply(Command::Back));
+        assert!(matches!(game.dialog, Some((DialogPurpose::Quit, _))));
+        assert!(!game.dialog_key(Key::new(KeyCode::Enter)));
+    }
+
+    #[test]
+    fn quitting_asks_first_and_imports_go_through_a_dialog() {
+        let key = |ch| Key::new(KeyCode::Char(ch));
+        let mut game = Game::with_seed(11);
+        game.confirm_quit = true;
+        assert!(!Game { confirm_quit: true, ..Game::with_seed(11) }.apply(Command::Quit)); // nothing entered yet
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        game.apply(Command::SelectCell(r, c));
+        game.apply(Command::EnterDigit(game.solution.unwrap()[r][c]));
+        assert!(game.apply(Command::Quit));
+        assert!(game.dialog.is_some());
+        assert!(game.dialog_key(key('n')));
+        assert!(game.dialog.is_none());
+        game.apply(Command::Quit);
+        assert!(!game.dialog_key(Key::new(KeyCode::Enter)));
+
+        let code = Game::with_seed(3).grid.share_code();
+        game.apply(Command::Import);
+        for ch in "RD6:1".chars() {
+            game.dialog_key(key(ch));
+        }
+        game.dialog_key(Key::new(KeyCode::Enter));
+        assert!(matches!(&game.dialog, Some((DialogPurpose::Notice, _))));
+        game.dialog_key(key('x'));
+        game.apply(Command::Import);
+        for ch in code.chars() {
+            game.dialog_key(key(ch));
+        }
+        game.dialog_key(Key::new(KeyCode::Enter));
+        assert_eq!((game.grid.share_code(), game.meta.key()), (code.clone(), code.clone()));
+        assert!(game.dialog.is_none() && game.confirm_quit);
+
+        // It's solved in the background, and checked by the rules until then
+        let solve = |game: &mut Game| {
+            let started = std::time::Instant::now();
+            while !game.poll_solve() {
+                assert!(game.is_solving() && started.elapsed() < std::time::Duration::from_secs(10));
+                std::thread::sleep(std::time::Duration::from_millis(1));
+            }
+        };
+        solve(&mut game);
+        let solutions = Grid::from_share_code(&code).unwrap().solutions(2);
+        assert_eq!((game.solution, game.meta.unique), (solutions.first().copied(), Some(solutions.len() == 1)));
+        assert!(!game.is_solving() && !game.poll_solve());
+
+        game.import(&format!("RD6:123450{}000006", "0".repeat(24)));
+        solve(&mut game);
+        assert_eq!(game.solution, None);
+        assert!(matches!(&game.dialog, Some((DialogPurpose::Notice, _))));
+    }
+
+    #[test]
+    fn boards_round_trip_through_text() {
+        let mut game = Game::with_seed(11);
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        game.cursor = (r, c);
+        game.handle_input(game.solution.unwrap()[r][c]);
+        game.toggle_lock();
+        game.cursor = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| game.grid.cells[r][c].value().is_none()).unwrap();
+        game.toggle_mode();
+        game.handle_input(2);
+        game.handle_input(5);
+
+        let text = game.grid.to_string();
+        assert!(text.contains("[25]"));
+        assert_eq!(Grid::parse(&text).unwrap().cells, game.grid.cells);
+
+        assert_eq!(Grid::parse("123456").err(), Some(ParseError::RowCount(1)));
+        let rows = |first: &str| format!("{}\n......\n......\n......\n......\n......", first);
+        assert_eq!(Grid::parse(&rows(".....")).err(), Some(ParseError::RowLength { row: 0, found: 5 }));
+        assert_eq!(Grid::parse(&rows("..7...")).err(), Some(ParseError::InvalidChar { row: 0, col: 2, ch: '7' }));
+        assert_eq!(Grid::parse(&rows(".[12")).err(), Some(ParseError::UnclosedMarks { row: 0, col: 0 }));
+        let conflict = ParseError::Invalid(InvariantError::GivensConflict { a: (0, 0), b: (0, 5) });
+        assert_eq!(Grid::parse(&rows("1....1")).err(), Some(conflict));
+        assert!(Grid::parse(&rows("a....a")).is_ok()); // entries may be wrong
+
+        let mut grid = Grid::new();
+        grid.cells[1][2].set_locked(true);
+        assert_eq!(grid.invariants(), Err(InvariantError::LockedWithoutValue { row: 1, col: 2 }));
+    }
+
+    #[test]
+    fn commands_keep_the_givens_as_they_were() {
+        let mut game = Game::with_seed(4);
+        let before = game.grid.clone();
+        let blank = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        game.cursor = blank;
+        game.apply(Command::EnterDigit(1));
+        game.apply(Command::ToggleMode);
+        game.apply(Command::EnterDigit(2));
+        assert_eq!(game.grid.validate_consistency(&before), Ok(()));
+
+        let given = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| game.grid.cells[r][c].is_fixed()).unwrap();
+        let mut marked = game.grid.clone();
+        marked.cells[given.0][given.1].set_mark(1, true);
+        assert_eq!(marked.validate_consistency(&before), Err(InvariantError::MarkedGiven { row: given.0, col: given.1 }));
+        let mut taken = game.grid.clone();
+        taken.cells[given.0][given.1] = Cell::default();
+        assert_eq!(taken.validate_consistency(&before), Err(InvariantError::GivenChanged { row: given.0, col: given.1 }));
+        let mut added = game.grid.clone();
+        let value = game.solution.unwrap()[blank.0][blank.1];
+        added.cells[blank.0][blank.1] = Cell::given(value);
+        assert_eq!(added.validate_consistency(&before), Err(InvariantError::GivenChanged { row: blank.0, col: blank.1 }));
+    }
+
     #[test]
     fn is_correct_move_rejects_out_of_bounds_or_invalid_values() {
         let game = Game::new();
-        let valid_value = game.solution[0][0];
+        let valid_value = game.solution.unwrap()[0][0];
 
         assert!(game.is_correct_move(0, 0, valid_value));
         assert!(!game.is_correct_move(6, 0, 1));
@@ -0,0 +1,149 @@
+// Puzzles of the generation profiles made ahead of time, so a profile whose
+// puzzles take thousands of seeds to find still starts and rerolls at once. A
+// few per profile are kept in `puzzles.txt` in the cache dir, a line each:
+// the profile's name, a tab and the puzzle's key. Taking one leaves a gap a
+// thread in the background fills again while the game goes on. With config
+// `puzzle_cache` off, each puzzle is looked for when it's wanted.
+
+use std::{
+    fs, io,
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+};
+
+use rand::rngs::StdRng;
+
+use crate::{model::Game, profile::Profile, puzzle::PuzzleMeta, storage};
+
+const CACHE_FILE: &str = "puzzles.txt";
+// Puzzles kept ready for each profile
+pub const PER_PROFILE: usize = 3;
+
+// Whether a refill is under way; one at a time is enough
+static REFILLING: AtomicBool = AtomicBool::new(false);
+
+// A ready puzzle of `profile`, taken out of the cache. Puzzles that no longer
+// fit it, as the profile was changed since they were made, are dropped.
+pub fn take(profile: &Profile) -> Option<Game> {
+    match storage::cached_path(CACHE_FILE) {
+        Ok(path) => take_from(&path, profile),
+        Err(err) => {
+            log::warn!("could not read the puzzle cache: {}", err);
+            None
+        }
+    }
+}
+
+fn take_from(path: &Path, profile: &Profile) -> Option<Game> {
+    loop {
+        let mut key = None;
+        let taken = storage::update_file(path, |text| {
+            let (first, rest) = take_key(text, &profile.name);
+            key = first;
+            rest
+        });
+        if let Err(err) = taken {
+            log::warn!("could not read the puzzle cache: {}", err);
+            return None;
+        }
+        let game = PuzzleMeta::from_key(&key?).and_then(|meta| meta.regenerate());
+        if let Some(game) = game.filter(|game| profile.fits(game)) {
+            log::info!("took puzzle {} of profile {} from the cache", game.meta.key(), profile.name);
+            return Some(game);
+        }
+    }
+}
+
+// Top the cache up to PER_PROFILE puzzles of each profile, on another thread.
+// Does nothing while a refill is already running, as it goes on until every
+// profile is topped up, taken puzzles included.
+pub fn refill(profiles: Vec<Profile>, mut rng: StdRng) {
+    if profiles.is_empty() || REFILLING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    thread::spawn(move || {
+        if let Err(err) = storage::cached_path(CACHE_FILE).and_then(|path| fill(&path, &profiles, &mut rng)) {
+            log::warn!("could not refill the puzzle cache: {}", err);
+        }
+        REFILLING.store(false, Ordering::SeqCst);
+    });
+}
+
+fn fill(path: &Path, profiles: &[Profile], rng: &mut StdRng) -> io::Result<()> {
+    // Profiles no puzzle turns up for are given up on until the next refill
+    let mut hopeless = Vec::new();
+    loop {
+        let text = fs::read_to_string(path).unwrap_or_default();
+        let Some(profile) = profiles.iter().find(|profile| !hopeless.contains(&profile.name) && count(&text, &profile.name) < PER_PROFILE) else {
+            return Ok(());
+        };
+        match profile.generate(rng, |_| true) {
+            Some(game) => {
+                storage::update_file(path, |text| format!("{}{}\t{}\n", text, profile.name, game.meta.key()))?;
+            }
+            None => hopeless.push(profile.name.clone()),
+        }
+    }
+}
+
+// The first key kept for profile `name`, and the cache without it
+fn take_key(text: &str, name: &str) -> (Option<String>, String) {
+    let mut key = None;
+    let mut rest = String::new();
+    for line in text.lines() {
+        match line.split_once('\t') {
+            Some((profile, found)) if key.is_none() && profile == name => key = Some(found.to_string()),
+            _ => rest += &format!("{}\n", line),
+        }
+    }
+    (key, rest)
+}
+
+fn count(text: &str, name: &str) -> usize {
+    text.lines().filter(|line| line.split_once('\t').is_some_and(|(profile, _)| profile == name)).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count, fill, take_from, take_key, PER_PROFILE};
+    use crate::profile::Profile;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::fs;
+
+    #[test]
+    fn puzzles_are_taken_first_in_first_out() {
+        let text = "pairs\t513/naked_pair\neasy\t7\npairs\t9/naked_pair\n";
+        let (key, rest) = take_key(text, "pairs");
+        assert_eq!(key.as_deref(), Some("513/naked_pair"));
+        assert_eq!(rest, "easy\t7\npairs\t9/naked_pair\n");
+        assert_eq!((count(&rest, "pairs"), count(&rest, "easy"), count(&rest, "hard")), (1, 1, 0));
+        assert_eq!(take_key(&rest, "hard"), (None, rest.clone()));
+    }
+
+    #[test]
+    fn the_cache_is_filled_taken_from_and_passed_over_when_broken() {
+        let dir = std::env::temp_dir().join(format!("rustdoku6-pregen-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("puzzles.txt");
+        let (any, single) = (Profile::parse("any", "").unwrap(), Profile::parse("single", "stars=1").unwrap());
+
+        // Nothing made yet, so the puzzle is looked for when it's wanted
+        assert!(take_from(&path, &any).is_none());
+
+        fill(&path, &[any.clone(), single.clone()], &mut StdRng::seed_from_u64(5)).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert_eq!((count(&text, "any"), count(&text, "single")), (PER_PROFILE, PER_PROFILE));
+        let (first, _) = take_key(&text, "single");
+        let game = take_from(&path, &single).unwrap();
+        assert_eq!(Some(game.meta.key()), first);
+        assert!(single.fits(&game));
+        assert_eq!(count(&fs::read_to_string(&path).unwrap(), "single"), PER_PROFILE - 1);
+
+        // Lines that don't give a puzzle of the profile are dropped on the way
+        fs::write(&path, "garbage\nany\tnot a key\nany\t513/no_such_technique\n").unwrap();
+        assert!(take_from(&path, &any).is_none());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "garbage\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,182 @@
+// Generation profiles: named sets of what a new puzzle should be like, kept
+// in the config, e.g.
+//
+//   profile.pairs = "symmetry=half_turn clues=12-14 require=naked_pair"
+//
+// `--profile <name>` starts with one, and on the new-puzzle preview the
+// digit keys switch to the profile of that number. Setting out a symmetry or
+// a clue count needs the clues picked for it: they're taken out of the
+// solution an orbit of the symmetry at a time, and the puzzle is played as an
+// imported one, named by its share code. Otherwise the profile keeps to
+// generated puzzles, trying seed after seed until one fits.
+
+use std::ops::RangeInclusive;
+
+use rand::prelude::*;
+
+use crate::{
+    generator::PuzzleSpec,
+    model::{Cell, Game, Grid},
+    puzzle::Variant,
+    solver::TechniqueRegistry,
+    symmetry::{clue_symmetries, Symmetry},
+};
+
+// Seeds tried before giving up on a profile, as many as for `--require`
+pub const MAX_ATTEMPTS: usize = 20_000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    // Classic, sandwich or thermo
+    pub variant: Variant,
+    pub stars: Option<RangeInclusive<u8>>,
+    pub clues: Option<RangeInclusive<usize>>,
+    // A symmetry the givens must be laid out with
+    pub symmetry: Option<Symmetry>,
+    // The id of a technique the puzzle must need
+    pub require: Option<String>,
+}
+
+impl Profile {
+    // `text` is `key=value` settings apart by spaces or commas: `size` (only
+    // 6 boards are generated), `variant`, `stars` and `clues` (a number or a
+    // range such as 2-3), `symmetry` and `require`
+    pub fn parse(name: &str, text: &str) -> Result<Self, String> {
+        let mut profile = Self { name: name.to_string(), variant: Variant::Classic, stars: None, clues: None, symmetry: None, require: None };
+        for setting in text.split([' ', ',']).filter(|setting| !setting.is_empty()) {
+            let (key, value) = setting.split_once('=').ok_or_else(|| format!("expected key=value, found {}", setting))?;
+            match key {
+                "size" if matches!(value, "6" | "6x6") => {}
+                "size" => return Err(format!("only 6x6 boards are generated, not {}", value)),
+                "variant" => {
+                    profile.variant = match value {
+                        "classic" => Variant::Classic,
+                        "sandwich" => Variant::Sandwich,
+                        "thermo" => Variant::Thermo,
+                        _ => return Err(format!("unknown variant: {} (one of classic, sandwich, thermo)", value)),
+                    }
+                }
+                "stars" => profile.stars = Some(range(value).ok_or_else(|| format!("stars should be a number or range, not {}", value))?),
+                "clues" => profile.clues = Some(range(value).ok_or_else(|| format!("clues should be a number or range, not {}", value))?),
+                "symmetry" => profile.symmetry = Some(Symmetry::parse(value).ok_or_else(|| format!("unknown symmetry: {} (one of quarter_turn, half_turn, mirror, flip)", value))?),
+                "require" if TechniqueRegistry::builtin().get(value).is_some() => profile.require = Some(value.to_string()),
+                "require" => return Err(format!("unknown technique: {}", value)),
+                _ => return Err(format!("unknown setting: {}", key)),
+            }
+        }
+        let picks_clues = profile.require.is_some() || profile.symmetry.is_some() || profile.clues.is_some();
+        if profile.variant != Variant::Classic && picks_clues {
+            return Err("sandwich and thermo puzzles pick their own clues, so they don't take clues, symmetry or require".to_string());
+        }
+        Ok(profile)
+    }
+
+    // A puzzle that fits from the seeds `rng` picks, calling `attempt` with
+    // each seed's number before it's tried; it returns false to stop early.
+    // None after MAX_ATTEMPTS or once stopped.
+    pub fn generate<R: Rng + ?Sized>(&self, rng: &mut R, mut attempt: impl FnMut(usize) -> bool) -> Option<Game> {
+        (1..=MAX_ATTEMPTS).take_while(|&n| attempt(n)).find_map(|_| self.generate_from(rng.random::<u32>() as u64))
+    }
+
+    // The puzzle `seed` gives, if it fits
+    pub fn generate_from(&self, seed: u64) -> Option<Game> {
+        let game = match (self.symmetry, &self.clues) {
+            (None, None) => {
+                let variant = self.require.clone().map_or(self.variant.clone(), Variant::Requiring);
+                Game::generate(seed, &PuzzleSpec { variant, ..PuzzleSpec::default() }).ok()?
+            }
+            _ => {
+                let grid = self.carve(&Game::with_seed(seed).solution?, seed);
+                Game::generate(seed, &PuzzleSpec { variant: Variant::Imported(grid.share_code()), ..PuzzleSpec::default() }).ok()?
+            }
+        };
+        self.fits(&game).then_some(game)
+    }
+
+    // Givens taken out of `solution` an orbit of the symmetry at a time, in
+    // random order, for as long as the puzzle keeps one solution (or the
+    // techniques can still solve it, when one is required) and no fewer than
+    // the fewest clues allowed are left
+    fn carve(&self, solution: &[[u8; 6]; 6], seed: u64) -> Grid {
+        let mut grid = Grid::new();
+        for (r, row) in solution.iter().enumerate() {
+            for (c, &value) in row.iter().enumerate() {
+                grid.cells[r][c] = Cell::given(value);
+            }
+        }
+        let fewest = self.clues.as_ref().map_or(0, |clues| *clues.start());
+        let techniques = TechniqueRegistry::builtin();
+        let mut orbits: Vec<Vec<(usize, usize)>> = Vec::new();
+        for cell in (0..36).map(|i| (i / 6, i % 6)) {
+            let orbit = self.symmetry.map_or(vec![cell], |symmetry| symmetry.orbit(cell, 6));
+            if !orbits.iter().any(|taken| taken.contains(&cell)) {
+                orbits.push(orbit);
+            }
+        }
+        orbits.shuffle(&mut StdRng::seed_from_u64(seed));
+        for orbit in orbits {
+            if grid.clue_count() < fewest + orbit.len() {
+                continue;
+            }
+            let before = grid.clone();
+            for &(r, c) in &orbit {
+                grid.cells[r][c] = Cell::default();
+            }
+            let keeps = match self.require {
+                Some(_) => techniques.grade(&grid).is_some(),
+                None => grid.solutions(2).len() == 1,
+            };
+            if !keeps {
+                grid = before;
+            }
+        }
+        grid
+    }
+
+    // Whether a puzzle is one the profile asks for
+    pub fn fits(&self, game: &Game) -> bool {
+        let givens = game.givens();
+        let stars = self.stars.as_ref().is_none_or(|stars| stars.contains(&game.stars()));
+        let clues = self.clues.as_ref().is_none_or(|clues| clues.contains(&givens.clue_count()));
+        let symmetry = self.symmetry.is_none_or(|symmetry| clue_symmetries(&givens).contains(&symmetry));
+        let require = self.require.as_ref().is_none_or(|id| TechniqueRegistry::builtin().requires(&givens, id));
+        stars && clues && symmetry && require
+    }
+}
+
+// "3" or "2-4"
+fn range<T: std::str::FromStr + Copy + PartialOrd>(text: &str) -> Option<RangeInclusive<T>> {
+    let (low, high) = text.split_once('-').unwrap_or((text, text));
+    let (low, high) = (low.parse().ok()?, high.parse().ok()?);
+    (low <= high).then_some(low..=high)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Profile;
+    use crate::symmetry::{clue_symmetries, Symmetry};
+
+    #[test]
+    fn profiles_make_puzzles_to_order() {
+        let profile = Profile::parse("sym", "size=6, symmetry=half_turn clues=12-16").unwrap();
+        assert_eq!((profile.symmetry, profile.clues.clone()), (Some(Symmetry::HalfTurn), Some(12..=16)));
+        let game = (1..).find_map(|seed| profile.generate_from(seed)).unwrap();
+        let givens = game.givens();
+        assert!(clue_symmetries(&givens).contains(&Symmetry::HalfTurn));
+        assert!((12..=16).contains(&givens.clue_count()));
+        assert_eq!(givens.solutions(2).len(), 1);
+        assert!(game.meta.key().starts_with("RD6:"));
+
+        // Without clues to pick, puzzles are generated ones
+        let stars = Profile::parse("easy", "stars=1").unwrap();
+        let easy = (1..).find_map(|seed| stars.generate_from(seed)).unwrap();
+        assert_eq!((easy.stars(), easy.meta.key()), (1, easy.meta.seed.to_string()));
+
+        assert!(Profile::parse("big", "size=9").is_err());
+        assert!(Profile::parse("x", "require=nothing").is_err());
+        assert!(Profile::parse("x", "variant=thermo symmetry=mirror").is_err());
+        assert!(Profile::parse("x", "stars=3-1").is_err());
+        assert!(Profile::parse("x", "clues").is_err());
+    }
+}
@@ -0,0 +1,200 @@
+// What's known about a puzzle apart from its cells: how to generate it again,
+// how hard it is and where it came from. It travels with the game into the
+// title bar, crash reports and the records kept per puzzle.
+
+use std::fmt;
+
+use crate::{
+    generator::{Generator, PuzzleSpec},
+    model::{Game, Grid, Solution},
+    symmetry::Transform,
+};
+
+// A generated puzzle: its givens, the values that solve it if known, and
+// what's known about it
+#[derive(Clone)]
+pub struct Puzzle {
+    pub grid: Grid,
+    pub solution: Option<Solution>,
+    pub meta: PuzzleMeta,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PuzzleMeta {
+    // Seed the puzzle was generated from; with the variant and transform, the
+    // same seed always gives the same puzzle
+    pub seed: u64,
+    // Hardest technique difficulty needed to solve logically, None if guessing is required
+    pub difficulty: Option<u8>,
+    pub variant: Variant,
+    // The symmetry the generated puzzle was put through, if any
+    pub transform: Option<Transform>,
+    pub source: Source,
+    // Who set the puzzle; None for generated ones
+    pub author: Option<String>,
+    // Date (YYYY-MM-DD) of the daily puzzle
+    pub date: Option<String>,
+    // Whether the givens have just one solution, once an imported puzzle's
+    // have been counted; generated puzzles aren't checked
+    pub unique: Option<bool>,
+}
+
+// How the clues were picked from the filled grid
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Variant {
+    // 16 clues at random
+    #[default]
+    Classic,
+    // As few clues as logic allows, needing the technique with this id (`--require`)
+    Requiring(String),
+    // The givens of this share code, typed in by the player
+    Imported(String),
+    // Sandwich sums for every row and column, and givens taken out for as
+    // long as the puzzle keeps one solution (`--variant sandwich`)
+    Sandwich,
+    // Thermometers traced through the solution, givens taken out the same way
+    // (`--variant thermo`)
+    Thermo,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Source {
+    // A random seed
+    #[default]
+    Random,
+    // A seed given with `--seed`
+    Seed,
+    Daily,
+    // The host's puzzle, when joining a co-op game
+    CoOp,
+    // A share code typed in
+    Import,
+}
+
+impl PuzzleMeta {
+    // Names the puzzle in records kept per puzzle, e.g. `513`,
+    // `513/locked_candidates` or `513/thermo`. Classic puzzles are named by
+    // their seed alone, imported ones by their share code. A transform
+    // follows a `~`, as in `513~3512462m`.
+    pub fn key(&self) -> String {
+        let key = self.variant_key();
+        match self.transform {
+            Some(transform) => format!("{}~{}", key, transform.code()),
+            None => key,
+        }
+    }
+
+    fn variant_key(&self) -> String {
+        match &self.variant {
+            Variant::Classic => self.seed.to_string(),
+            Variant::Requiring(technique) => format!("{}/{}", self.seed, technique),
+            Variant::Imported(code) => code.clone(),
+            Variant::Sandwich => format!("{}/sandwich", self.seed),
+            Variant::Thermo => format!("{}/thermo", self.seed),
+        }
+    }
+
+    // The seed and variant named by a key
+    pub fn from_key(key: &str) -> Option<Self> {
+        if let Some((key, code)) = key.split_once('~') {
+            let transform = Some(Transform::parse(code)?);
+            return Some(Self { transform, ..Self::from_key(key)? });
+        }
+        if key.starts_with("RD6:") {
+            return Some(Self { variant: Variant::Imported(key.to_string()), source: Source::Import, ..Self::default() });
+        }
+        let (seed, variant) = match key.split_once('/') {
+            Some((seed, "sandwich")) => (seed, Variant::Sandwich),
+            Some((seed, "thermo")) => (seed, Variant::Thermo),
+            Some((seed, technique)) if !technique.is_empty() => (seed, Variant::Requiring(technique.to_string())),
+            Some(_) => return None,
+            None => (key, Variant::Classic),
+        };
+        Some(Self { seed: seed.parse().ok()?, variant, ..Self::default() })
+    }
+
+    // Generate the puzzle again. None if the seed doesn't give one of its variant.
+    pub fn regenerate(&self) -> Option<Game> {
+        let spec = PuzzleSpec { variant: self.variant.clone(), transform: self.transform, ..PuzzleSpec::default() };
+        Game::generate(self.seed, &spec).ok()
+    }
+
+    // The puzzle again through `transform`, to practice its logic on a board
+    // that looks new. Generated puzzles are generated again with it in place
+    // of any earlier one, imported ones have their givens moved. None for a
+    // variant, whose rules sit on the cells, or a puzzle that doesn't
+    // regenerate.
+    pub fn remix(&self, transform: Transform) -> Option<Game> {
+        match &self.variant {
+            Variant::Classic | Variant::Requiring(_) => Self { transform: Some(transform), ..self.clone() }.regenerate(),
+            Variant::Imported(code) => {
+                let grid = transform.apply(&Generator::givens(code).ok()?)?;
+                Self { variant: Variant::Imported(grid.share_code()), ..self.clone() }.regenerate()
+            }
+            Variant::Sandwich | Variant::Thermo => None,
+        }
+    }
+}
+
+impl Variant {
+    // The variant apart from its technique or code, e.g. "requiring"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Variant::Classic => "classic",
+            Variant::Requiring(_) => "requiring",
+            Variant::Imported(_) => "imported",
+            Variant::Sandwich => "sandwich",
+            Variant::Thermo => "thermo",
+        }
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Variant::Classic => write!(f, "classic"),
+            Variant::Requiring(technique) => write!(f, "requires {}", technique),
+            Variant::Imported(_) => write!(f, "imported"),
+            Variant::Sandwich => write!(f, "sandwich"),
+            Variant::Thermo => write!(f, "thermo"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PuzzleMeta, Variant};
+
+    #[test]
+    fn keys_and_regeneration_follow_the_variant() {
+        let classic = PuzzleMeta { seed: 513, ..PuzzleMeta::default() };
+        assert_eq!(classic.key(), "513");
+        assert_eq!(classic.regenerate().unwrap().grid.cells, crate::model::Game::with_seed(513).grid.cells);
+
+        let required = PuzzleMeta { variant: Variant::Requiring("locked_candidates".to_string()), ..classic };
+        assert_eq!(required.key(), "513/locked_candidates");
+        assert_eq!(PuzzleMeta::from_key(&required.key()).as_ref(), Some(&required));
+        assert_eq!(PuzzleMeta::from_key("513/"), None);
+        let sandwich = PuzzleMeta { seed: 513, variant: Variant::Sandwich, ..PuzzleMeta::default() };
+        assert_eq!(PuzzleMeta::from_key(&sandwich.key()), Some(sandwich));
+        let code = crate::model::Game::with_seed(5).grid.share_code();
+        assert_eq!(PuzzleMeta::from_key(&code).unwrap().regenerate().unwrap().grid.share_code(), code);
+        let turned = PuzzleMeta { transform: crate::symmetry::Transform::parse("6543212"), ..PuzzleMeta::default() };
+        assert_eq!(turned.key(), "0~6543212");
+        assert_eq!(PuzzleMeta::from_key("0~6543212").as_ref(), Some(&turned));
+        assert_eq!(PuzzleMeta::from_key("0~66"), None);
+        assert_eq!(turned.regenerate().unwrap().meta.transform, turned.transform);
+        let game = required.regenerate().unwrap();
+        assert_eq!(game.meta.variant, required.variant);
+        assert!(game.grid.clue_count() < 16);
+
+        // A remix has the same clues, moved
+        let half_turn = crate::symmetry::Transform { turns: 2, ..crate::symmetry::Transform::identity() };
+        let remixed = required.remix(half_turn).unwrap();
+        assert_eq!(remixed.meta.key(), "513/locked_candidates~1234562");
+        assert_eq!(remixed.grid.cells[5][5].value(), game.grid.cells[0][0].value());
+        let imported = PuzzleMeta::from_key(&code).unwrap().remix(half_turn).unwrap();
+        assert_eq!(imported.grid.cells[5][5].value(), crate::model::Game::with_seed(5).grid.cells[0][0].value());
+        assert!(PuzzleMeta { variant: Variant::Thermo, ..PuzzleMeta::default() }.remix(half_turn).is_none());
+    }
+}
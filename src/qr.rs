@@ -0,0 +1,310 @@
+// Small QR code encoder for the share screen: byte mode, error correction
+// level L, versions 1-5 (up to 106 bytes), which covers any puzzle share code.
+
+// Data and error-correction codewords for versions 1..=5 at level L (single block)
+const DATA_CODEWORDS: [usize; 5] = [19, 34, 55, 80, 108];
+const EC_CODEWORDS: [usize; 5] = [7, 10, 15, 20, 26];
+
+// A square matrix of modules; `true` is dark
+pub struct QrCode {
+    pub size: usize,
+    modules: Vec<Vec<bool>>,
+    is_function: Vec<Vec<bool>>,
+}
+
+impl QrCode {
+    // Encode `data`, or None if it is too long for version 5
+    pub fn encode(data: &[u8]) -> Option<Self> {
+        let version = (1..=5).find(|&v| 2 + data.len() <= DATA_CODEWORDS[v - 1])?;
+        let codewords = add_error_correction(&encode_data(data, version), version);
+
+        let size = version * 4 + 17;
+        let mut qr = Self { size, modules: vec![vec![false; size]; size], is_function: vec![vec![false; size]; size] };
+        qr.draw_function_patterns(version);
+        qr.draw_codewords(&codewords);
+
+        // Pick the mask with the lowest penalty, as the standard requires
+        let mut best = (u32::MAX, 0);
+        for mask in 0..8 {
+            qr.apply_mask(mask);
+            qr.draw_format_bits(mask);
+            let penalty = qr.penalty();
+            if penalty < best.0 {
+                best = (penalty, mask);
+            }
+            qr.apply_mask(mask); // XOR again to undo
+        }
+        qr.apply_mask(best.1);
+        qr.draw_format_bits(best.1);
+        Some(qr)
+    }
+
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y][x]
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y][x] = dark;
+        self.is_function[y][x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: usize) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+
+        for (cx, cy) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4i32..=4 {
+                for dx in -4i32..=4 {
+                    let (x, y) = (cx as i32 + dx, cy as i32 + dy);
+                    if (0..size as i32).contains(&x) && (0..size as i32).contains(&y) {
+                        let dist = dx.abs().max(dy.abs());
+                        self.set_function(x as usize, y as usize, dist != 2 && dist != 4);
+                    }
+                }
+            }
+        }
+
+        // Versions 2-5 have a single alignment pattern near the bottom-right corner
+        if version >= 2 {
+            let pos = version * 4 + 10;
+            for dy in -2i32..=2 {
+                for dx in -2i32..=2 {
+                    let dist = dx.abs().max(dy.abs());
+                    self.set_function((pos as i32 + dx) as usize, (pos as i32 + dy) as usize, dist != 1);
+                }
+            }
+        }
+
+        // Reserve the format areas; the real bits are drawn once the mask is known
+        self.draw_format_bits(0);
+    }
+
+    fn draw_format_bits(&mut self, mask: u8) {
+        // Level L is format bits 01
+        let data = (1u32 << 3) | mask as u32;
+        let mut rem = data;
+        for _ in 0..10 {
+            rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+        }
+        let bits = ((data << 10) | rem) ^ 0x5412;
+        let bit = |i: u32| (bits >> i) & 1 != 0;
+
+        for i in 0..=5 {
+            self.set_function(8, i, bit(i as u32));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i as u32));
+        }
+
+        let size = self.size;
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i as u32));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i as u32));
+        }
+        // The dark module
+        self.set_function(8, size - 8, true);
+    }
+
+    // Zigzag the codeword bits through every non-function module
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let mut i = 0;
+        let mut right = size as i32 - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vert in 0..size {
+                for j in 0..2 {
+                    let x = (right - j) as usize;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { size - 1 - vert } else { vert };
+                    if !self.is_function[y][x] && i < codewords.len() * 8 {
+                        self.modules[y][x] = (codewords[i >> 3] >> (7 - (i & 7))) & 1 != 0;
+                        i += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u8) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                if invert && !self.is_function[y][x] {
+                    self.modules[y][x] = !self.modules[y][x];
+                }
+            }
+        }
+    }
+
+    // Penalty score from the four rules in the QR specification
+    fn penalty(&self) -> u32 {
+        let size = self.size;
+        let get = |x: usize, y: usize, transpose: bool| if transpose { self.modules[x][y] } else { self.modules[y][x] };
+        let mut score = 0;
+
+        for transpose in [false, true] {
+            for y in 0..size {
+                // Rule 1: runs of five or more same-colored modules
+                let mut run = 1;
+                for x in 1..size {
+                    if get(x, y, transpose) == get(x - 1, y, transpose) {
+                        run += 1;
+                        if run == 5 {
+                            score += 3;
+                        } else if run > 5 {
+                            score += 1;
+                        }
+                    } else {
+                        run = 1;
+                    }
+                }
+                // Rule 3: finder-like 1:1:3:1:1 patterns with four light modules on either side
+                let line: Vec<bool> = (0..size).map(|x| get(x, y, transpose)).collect();
+                const PATTERN: [bool; 11] = [true, false, true, true, true, false, true, false, false, false, false];
+                for window in line.windows(11) {
+                    if window == PATTERN || window.iter().rev().eq(PATTERN.iter()) {
+                        score += 40;
+                    }
+                }
+            }
+        }
+
+        // Rule 2: 2x2 blocks of one color
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let c = self.modules[y][x];
+                if c == self.modules[y][x + 1] && c == self.modules[y + 1][x] && c == self.modules[y + 1][x + 1] {
+                    score += 3;
+                }
+            }
+        }
+
+        // Rule 4: balance of dark and light modules
+        let dark = self.modules.iter().flatten().filter(|&&m| m).count() as i64;
+        let total = (size * size) as i64;
+        let k = ((dark * 20 - total * 10).abs() + total - 1) / total - 1;
+        score + 10 * k.max(0) as u32
+    }
+}
+
+// Byte-mode segment plus terminator and padding, as data codewords
+fn encode_data(data: &[u8], version: usize) -> Vec<u8> {
+    let capacity = DATA_CODEWORDS[version - 1];
+    let mut bits: Vec<bool> = Vec::with_capacity(capacity * 8);
+    let mut push = |value: u32, len: u32| {
+        for i in (0..len).rev() {
+            bits.push((value >> i) & 1 != 0);
+        }
+    };
+    push(0b0100, 4);
+    push(data.len() as u32, 8);
+    for &byte in data {
+        push(byte as u32, 8);
+    }
+
+    let terminator = (capacity * 8 - bits.len()).min(4);
+    bits.extend(std::iter::repeat_n(false, terminator));
+    while !bits.len().is_multiple_of(8) {
+        bits.push(false);
+    }
+
+    let mut codewords: Vec<u8> = bits.chunks(8).map(|byte| byte.iter().fold(0, |acc, &b| (acc << 1) | b as u8)).collect();
+    for pad in [0xEC, 0x11].into_iter().cycle() {
+        if codewords.len() >= capacity {
+            break;
+        }
+        codewords.push(pad);
+    }
+    codewords
+}
+
+fn add_error_correction(data: &[u8], version: usize) -> Vec<u8> {
+    let mut codewords = data.to_vec();
+    codewords.extend(reed_solomon(data, EC_CODEWORDS[version - 1]));
+    codewords
+}
+
+// Reed-Solomon remainder of `data` for `degree` error-correction codewords
+fn reed_solomon(data: &[u8], degree: usize) -> Vec<u8> {
+    let mut divisor = vec![0u8; degree];
+    divisor[degree - 1] = 1;
+    let mut root = 1u8;
+    for _ in 0..degree {
+        for j in 0..degree {
+            divisor[j] = gf_mul(divisor[j], root);
+            if j + 1 < degree {
+                divisor[j] ^= divisor[j + 1];
+            }
+        }
+        root = gf_mul(root, 0x02);
+    }
+
+    let mut result = vec![0u8; degree];
+    for &byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (r, &d) in result.iter_mut().zip(divisor.iter()) {
+            *r ^= gf_mul(d, factor);
+        }
+    }
+    result
+}
+
+// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x^2 + 1
+fn gf_mul(x: u8, y: u8) -> u8 {
+    let mut z: u16 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11D);
+        z ^= ((y as u16 >> i) & 1) * x as u16;
+    }
+    z as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reed_solomon, QrCode};
+
+    #[test]
+    fn reed_solomon_matches_reference_example() {
+        // "HELLO WORLD" at 1-M, from the worked example in the QR specification tutorials
+        let data = [32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17];
+        assert_eq!(reed_solomon(&data, 10), vec![196, 35, 39, 119, 235, 215, 231, 226, 93, 23]);
+    }
+
+    #[test]
+    fn format_bits_match_reference_table() {
+        let mut qr = QrCode::encode(b"x").unwrap();
+        qr.draw_format_bits(0);
+        // Level L, mask 0 is 111011111000100, read along row 8 left of the top-left finder
+        let row: String = [0, 1, 2, 3, 4, 5, 7, 8].iter().map(|&x| if qr.is_dark(x, 8) { '1' } else { '0' }).collect();
+        assert_eq!(row, "11101111");
+    }
+
+    #[test]
+    fn encode_picks_smallest_version() {
+        assert_eq!(QrCode::encode(&[b'a'; 17]).unwrap().size, 21);
+        assert_eq!(QrCode::encode(&[b'a'; 40]).unwrap().size, 29);
+        assert!(QrCode::encode(&[b'a'; 107]).is_none());
+    }
+}
@@ -0,0 +1,123 @@
+// Region map: which region each cell is in, and from that which cells are
+// peers (sharing a row, column or region, so they can't hold the same value).
+// The standard map tiles the board with boxes; variants with other shapes,
+// such as jigsaw puzzles, build their own from a region per cell. Variants
+// whose constraints keep further cells apart, like diagonals or killer cages,
+// add those as extra units.
+
+use crate::{model::BoardSpec, solver::Unit};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegionMap {
+    pub size: usize,
+    // Region of each cell, row by row
+    region: Vec<usize>,
+    // Cells of each region, in reading order
+    members: Vec<Unit>,
+    // Units besides rows, columns and regions, tagged with their kind
+    extra: Vec<(&'static str, Unit)>,
+    // Peers of each cell, row by row, worked out once since they're asked for
+    // on every candidate check
+    peers: Vec<Unit>,
+}
+
+impl RegionMap {
+    // Regions numbered 0..size, given per cell row by row. None unless each
+    // region has exactly `size` cells.
+    pub fn new(size: usize, region: Vec<usize>) -> Option<Self> {
+        if region.len() != size * size || (0..size).any(|i| region.iter().filter(|&&r| r == i).count() != size) {
+            return None;
+        }
+        let mut map = Self { size, region, members: Vec::new(), extra: Vec::new(), peers: Vec::new() };
+        map.members = (0..size).map(|i| map.cells().filter(|&(r, c)| map.region_of(r, c) == i).collect()).collect();
+        map.find_peers();
+        Some(map)
+    }
+
+    // Also keep the cells of `unit` from repeating a value
+    pub fn with_unit(mut self, kind: &'static str, unit: Unit) -> Self {
+        self.extra.push((kind, unit));
+        self.find_peers();
+        self
+    }
+
+    // Boxes of `spec.box_rows` x `spec.box_cols`, numbered left-to-right, top-to-bottom
+    pub fn boxes(spec: BoardSpec) -> Self {
+        let boxes_across = spec.size / spec.box_cols;
+        let region = (0..spec.size * spec.size).map(|i| (i / spec.size / spec.box_rows) * boxes_across + (i % spec.size) / spec.box_cols).collect();
+        Self::new(spec.size, region).expect("boxes tile the board")
+    }
+
+    // Every (row, col), row by row
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> {
+        let size = self.size;
+        (0..size * size).map(move |i| (i / size, i % size))
+    }
+
+    pub fn values(&self) -> std::ops::RangeInclusive<u8> {
+        1..=self.size as u8
+    }
+
+    pub fn region_of(&self, row: usize, col: usize) -> usize {
+        self.region[row * self.size + col]
+    }
+
+    // Cells in region `i`, in reading order
+    pub fn region_cells(&self, i: usize) -> &[(usize, usize)] {
+        &self.members[i]
+    }
+
+    // Every row, then column, then region, then extra unit, tagged with its
+    // kind ("Row", "Column", "Box" or the extra unit's)
+    pub fn units(&self) -> Vec<(&'static str, Unit)> {
+        let size = self.size;
+        let rows = (0..size).map(|r| ("Row", (0..size).map(|c| (r, c)).collect()));
+        let cols = (0..size).map(|c| ("Column", (0..size).map(|r| (r, c)).collect()));
+        let regions = self.members.iter().map(|cells| ("Box", cells.clone()));
+        rows.chain(cols).chain(regions).chain(self.extra.iter().cloned()).collect()
+    }
+
+    // Every cell sharing a unit with (row, col), excluding itself
+    pub fn peers(&self, row: usize, col: usize) -> &[(usize, usize)] {
+        &self.peers[row * self.size + col]
+    }
+
+    fn find_peers(&mut self) {
+        let units = self.units();
+        self.peers = self
+            .cells()
+            .map(|cell| {
+                let mut peers: Unit = units.iter().filter(|(_, unit)| unit.contains(&cell)).flat_map(|(_, unit)| unit.iter().copied()).collect();
+                peers.sort_unstable();
+                peers.dedup();
+                peers.retain(|&peer| peer != cell);
+                peers
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RegionMap;
+    use crate::model::BoardSpec;
+
+    #[test]
+    fn boxes_and_jigsaw_regions_give_peers() {
+        let boxes = RegionMap::boxes(BoardSpec::new(6, 2, 3));
+        assert_eq!((boxes.region_of(0, 3), boxes.region_of(3, 2), boxes.region_of(5, 5)), (1, 2, 5));
+        assert_eq!(boxes.peers(0, 0).len(), 12);
+        assert_eq!(RegionMap::boxes(BoardSpec::new(4, 2, 2)).region_cells(3), vec![(2, 2), (2, 3), (3, 2), (3, 3)]);
+
+        // 4x4 with L-shaped and straight regions
+        let jigsaw = RegionMap::new(4, vec![0, 0, 0, 1, 0, 2, 1, 1, 2, 2, 3, 1, 2, 3, 3, 3]).unwrap();
+        assert_eq!(jigsaw.region_cells(0), vec![(0, 0), (0, 1), (0, 2), (1, 0)]);
+        assert!(jigsaw.peers(1, 0).contains(&(0, 2)));
+        assert!(!jigsaw.peers(1, 0).contains(&(0, 3)));
+        assert_eq!(RegionMap::new(4, vec![0; 16]), None);
+
+        let diagonal = boxes.with_unit("Diagonal", (0..6).map(|i| (i, i)).collect());
+        assert!(diagonal.peers(0, 0).contains(&(5, 5)));
+        assert_eq!(diagonal.units().len(), 19);
+    }
+}
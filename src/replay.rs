@@ -0,0 +1,150 @@
+// Replays: every value entered or cleared over a solve, with the time on the
+// clock, for tools outside the game to chart its tempo. With config
+// `replays`, each solved puzzle is appended to `replays.jsonl` in the data
+// dir as one JSON object a line:
+//
+//   {"puzzle":"513","time_ms":95210,"mistakes":1,"moves":[{"at_ms":1830,"row":2,"col":4,"value":3,"correct":true},...]}
+//
+// Rows and columns count from 1, and a cleared cell has a null value. Undos
+// show up as the entries they take back or put back. `cast` reads a saved
+// solve back to play it over as a recording.
+
+use std::{fmt::Write, io, path::PathBuf, time::Duration};
+
+use crate::storage;
+
+const REPLAYS_FILE: &str = "replays.jsonl";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Move {
+    // Time on the solve clock
+    pub at: Duration,
+    pub row: usize,
+    pub col: usize,
+    // None for a cleared cell
+    pub value: Option<u8>,
+    // Whether the value entered is right, as the game judged it; false for a clear
+    pub correct: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Replay {
+    pub moves: Vec<Move>,
+}
+
+// A solve as saved to the replays file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Solve {
+    // The puzzle's key
+    pub puzzle: String,
+    pub time: Duration,
+    pub mistakes: u32,
+    pub replay: Replay,
+}
+
+impl Solve {
+    // A line of the replays file. Only what `Replay::to_json` writes is read:
+    // no spaces, and keys without quotes or commas.
+    pub fn parse(line: &str) -> Option<Self> {
+        let (head, moves) = line.split_once("\"moves\":[")?;
+        let puzzle = field(head, "puzzle")?.strip_prefix('"')?.strip_suffix('"')?.to_string();
+        let time = Duration::from_millis(field(head, "time_ms")?.parse().ok()?);
+        let mistakes = field(head, "mistakes")?.parse().ok()?;
+        let moves = moves
+            .split('{')
+            .skip(1)
+            .map(|step| {
+                let at = Duration::from_millis(field(step, "at_ms")?.parse().ok()?);
+                let row: usize = field(step, "row")?.parse().ok()?;
+                let col: usize = field(step, "col")?.parse().ok()?;
+                let value = match field(step, "value")? {
+                    "null" => None,
+                    value => Some(value.parse().ok().filter(|value| (1..=6).contains(value))?),
+                };
+                let correct = field(step, "correct")? == "true";
+                if !(1..=6).contains(&row) || !(1..=6).contains(&col) {
+                    return None;
+                }
+                Some(Move { at, row: row - 1, col: col - 1, value, correct })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self { puzzle, time, mistakes, replay: Replay { moves } })
+    }
+
+    // The last solve saved, of `puzzle` if given
+    pub fn last(puzzle: Option<&str>) -> Option<Self> {
+        let text = storage::read(REPLAYS_FILE)?;
+        text.lines().rev().filter_map(Self::parse).find(|solve| puzzle.is_none_or(|puzzle| solve.puzzle == puzzle))
+    }
+}
+
+// The raw value of `"name":` in JSON written by `to_json`
+fn field<'a>(json: &'a str, name: &str) -> Option<&'a str> {
+    let start = json.find(&format!("\"{}\":", name))? + name.len() + 3;
+    let rest = &json[start..];
+    Some(&rest[..rest.find([',', '}', ']']).unwrap_or(rest.len())])
+}
+
+impl Replay {
+    pub fn record(&mut self, at: Duration, (row, col): (usize, usize), value: Option<u8>, correct: bool) {
+        self.moves.push(Move { at, row, col, value, correct });
+    }
+
+    // The solve of `puzzle` as a line of JSON
+    pub fn to_json(&self, puzzle: &str, time: Duration, mistakes: u32) -> String {
+        let mut json = format!("{{\"puzzle\":{},\"time_ms\":{},\"mistakes\":{},\"moves\":[", json_string(puzzle), time.as_millis(), mistakes);
+        for (i, step) in self.moves.iter().enumerate() {
+            let value = step.value.map_or("null".to_string(), |value| value.to_string());
+            let separator = if i == 0 { "" } else { "," };
+            let _ = write!(json, "{}{{\"at_ms\":{},\"row\":{},\"col\":{},\"value\":{},\"correct\":{}}}", separator, step.at.as_millis(), step.row + 1, step.col + 1, value, step.correct);
+        }
+        json + "]}"
+    }
+
+    // Append the finished solve to the replays file
+    pub fn save(&self, puzzle: &str, time: Duration, mistakes: u32) -> io::Result<PathBuf> {
+        storage::append_line(REPLAYS_FILE, &self.to_json(puzzle, time, mistakes))
+    }
+}
+
+pub fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for ch in text.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            ch if ch.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", ch as u32);
+            }
+            ch => quoted.push(ch),
+        }
+    }
+    quoted + "\""
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{json_string, Replay, Solve};
+    use std::time::Duration;
+
+    #[test]
+    fn replays_list_timed_moves_as_json() {
+        let mut replay = Replay::default();
+        replay.record(Duration::from_millis(1830), (1, 3), Some(3), false);
+        replay.record(Duration::from_millis(2500), (1, 3), None, false);
+        assert_eq!(
+            replay.to_json("513~3512462m", Duration::from_secs(95), 1),
+            "{\"puzzle\":\"513~3512462m\",\"time_ms\":95000,\"mistakes\":1,\"moves\":[\
+             {\"at_ms\":1830,\"row\":2,\"col\":4,\"value\":3,\"correct\":false},\
+             {\"at_ms\":2500,\"row\":2,\"col\":4,\"value\":null,\"correct\":false}]}"
+        );
+        assert_eq!(Replay::default().to_json("7", Duration::ZERO, 0), "{\"puzzle\":\"7\",\"time_ms\":0,\"mistakes\":0,\"moves\":[]}");
+        assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\u000a\"");
+
+        let solve = Solve::parse(&replay.to_json("513~3512462m", Duration::from_secs(95), 1)).unwrap();
+        assert_eq!((solve.puzzle.as_str(), solve.time, solve.mistakes), ("513~3512462m", Duration::from_secs(95), 1));
+        assert_eq!(solve.replay, replay);
+        assert_eq!(Solve::parse("{\"puzzle\":\"7\",\"time_ms\":0,\"mistakes\":0,\"moves\":[]}").unwrap().replay, Replay::default());
+        assert!(Solve::parse("{\"puzzle\":\"7\",\"time_ms\":0,\"mistakes\":0,\"moves\":[{\"at_ms\":1,\"row\":7,\"col\":1,\"value\":1,\"correct\":true}]}").is_none());
+    }
+}
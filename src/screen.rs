@@ -0,0 +1,116 @@
+// Which screen is showing. The game's own screen (playing, won or time up) is
+// at the bottom of a stack, and screens opened over it such as About, the
+// pause screen or the history browser are pushed on top. Esc pops back to the
+// screen underneath, so closing one always returns to where it was opened.
+// The overlays that take the player away from the board stop the clock for
+// as long as they're open over play.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Screen {
+    Playing,
+    Won,
+    // The time-attack countdown ran out
+    TimeUp,
+    // The weekly challenge took its last mistake
+    OutOfMistakes,
+    // A puzzle opened with `view`, to look at but not play
+    Viewing,
+    // A new puzzle's details, shown before its clock starts
+    Preview,
+    // Progress of a puzzle being looked for, over the screen it was asked
+    // for from
+    Generating,
+    // Watching the solver play
+    Demo,
+    Paused,
+    About,
+    // Records per kind of puzzle
+    Stats,
+    Share,
+    // Popup explaining why an entry is wrong
+    Explain,
+    // Stepping through earlier boards of the solve
+    History,
+    // How the session went, shown on quitting
+    Summary,
+}
+
+impl Screen {
+    // Opened over another screen, rather than one the game itself is at
+    pub fn is_overlay(self) -> bool {
+        !matches!(self, Screen::Playing | Screen::Won | Screen::TimeUp | Screen::OutOfMistakes | Screen::Viewing | Screen::Preview)
+    }
+
+    // An overlay the clock stops for when opened over play. The history
+    // browser, watch mode and explanations are part of solving, so it runs on.
+    pub fn stops_clock(self) -> bool {
+        matches!(self, Screen::Paused | Screen::About | Screen::Stats | Screen::Share | Screen::Summary)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScreenStack {
+    // Bottom first; never empty
+    screens: Vec<Screen>,
+}
+
+impl ScreenStack {
+    pub fn new(base: Screen) -> Self {
+        Self { screens: vec![base] }
+    }
+
+    // A stack showing `screen`: an overlay is opened over play
+    pub fn showing(screen: Screen) -> Self {
+        match screen.is_overlay() {
+            true => Self { screens: vec![Screen::Playing, screen] },
+            false => Self::new(screen),
+        }
+    }
+
+    // The screen shown
+    pub fn top(&self) -> Screen {
+        *self.screens.last().expect("the screen stack is never empty")
+    }
+
+    // Open `screen` over the one shown, unless it's already shown
+    pub fn push(&mut self, screen: Screen) {
+        if self.top() != screen {
+            self.screens.push(screen);
+        }
+    }
+
+    // Close the screen shown, back to the one under it. The bottom screen
+    // stays; None if that's all there is.
+    pub fn pop(&mut self) -> Option<Screen> {
+        match self.screens.len() {
+            1 => None,
+            _ => self.screens.pop(),
+        }
+    }
+
+    // Bottom first
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = Screen> + '_ {
+        self.screens.iter().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Screen, ScreenStack};
+
+    #[test]
+    fn screens_pop_back_to_where_they_were_opened() {
+        let mut stack = ScreenStack::new(Screen::Won);
+        stack.push(Screen::About);
+        stack.push(Screen::About);
+        assert_eq!(stack.iter().collect::<Vec<_>>(), [Screen::Won, Screen::About]);
+        assert_eq!(stack.pop(), Some(Screen::About));
+        assert_eq!(stack.top(), Screen::Won);
+        assert_eq!(stack.pop(), None);
+        assert_eq!(stack.top(), Screen::Won);
+
+        assert_eq!(ScreenStack::showing(Screen::Paused).iter().collect::<Vec<_>>(), [Screen::Playing, Screen::Paused]);
+        assert_eq!(ScreenStack::showing(Screen::TimeUp), ScreenStack::new(Screen::TimeUp));
+        assert!(Screen::About.stops_clock() && !Screen::History.stops_clock() && !Screen::Won.stops_clock());
+    }
+}
@@ -0,0 +1,111 @@
+// Puzzles looked for on another thread, so the terminal stays live while a
+// profile whose puzzles are rare goes through its thousands of seeds. The
+// search is handed a check to call before each attempt: it counts the
+// attempts for the progress screen, and says to stop once the player has
+// called the search off, so the thread ends at the next attempt rather than
+// running on unseen. A game can't be sent between threads, so the one found
+// comes back as its key and is made again from it.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, TryRecvError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{model::Game, puzzle::PuzzleMeta};
+
+pub struct Search {
+    // What's looked for, e.g. the profile's name
+    pub label: String,
+    // Attempts it may take before giving up
+    pub max_attempts: usize,
+    started: Instant,
+    attempts: Arc<AtomicUsize>,
+    cancelled: Arc<AtomicBool>,
+    found: Receiver<Option<String>>,
+}
+
+impl Search {
+    // Run `find` on another thread, passing it the check to call with each
+    // attempt's number; it returns false once the search is cancelled
+    pub fn start(label: &str, max_attempts: usize, find: impl FnOnce(&mut dyn FnMut(usize) -> bool) -> Option<Game> + Send + 'static) -> Self {
+        let (sender, found) = mpsc::channel();
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (counter, stop) = (attempts.clone(), cancelled.clone());
+        thread::spawn(move || {
+            let game = find(&mut |n| {
+                counter.store(n, Ordering::Relaxed);
+                !stop.load(Ordering::Relaxed)
+            });
+            // Nobody is waiting once the search is dropped
+            let _ = sender.send(game.map(|game| game.meta.key()));
+        });
+        Self { label: label.to_string(), max_attempts, started: Instant::now(), attempts, cancelled, found }
+    }
+
+    // Attempts started so far
+    pub fn attempts(&self) -> usize {
+        self.attempts.load(Ordering::Relaxed)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    // Stop at the next attempt
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    // The outcome once the search is over: the puzzle, or None if nothing
+    // turned up or it was cancelled
+    pub fn poll(&self) -> Option<Option<Game>> {
+        match self.found.try_recv() {
+            Ok(key) => Some(key.and_then(|key| PuzzleMeta::from_key(&key)).and_then(|meta| meta.regenerate())),
+            Err(TryRecvError::Disconnected) => Some(None),
+            Err(TryRecvError::Empty) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::Search;
+    use crate::model::Game;
+
+    fn wait(search: &Search) -> Option<Game> {
+        loop {
+            if let Some(game) = search.poll() {
+                return game;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn searches_count_attempts_and_stop_when_cancelled() {
+        // Gives a puzzle on the third attempt
+        let search = Search::start("third", 10, |attempt| (1..=10).take_while(|&n| attempt(n)).find(|&n| n == 3).map(|n| Game::with_seed(n as u64)));
+        assert_eq!(wait(&search).map(|game| game.meta.seed), Some(3));
+        assert_eq!(search.attempts(), 3);
+
+        // Never finds one, so only cancelling ends it
+        let endless = Search::start("never", usize::MAX, |attempt| {
+            (1..).take_while(|&n| attempt(n)).for_each(|_| thread::sleep(Duration::from_millis(1)));
+            None
+        });
+        while endless.attempts() < 2 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        endless.cancel();
+        assert!(wait(&endless).is_none());
+        assert_eq!(endless.label, "never");
+    }
+}
@@ -0,0 +1,490 @@
+use std::sync::Arc;
+
+use crate::{model::Board, regions::RegionMap};
+use crate::techniques::{HiddenSingle, LockedCandidates, NakedPair, NakedSingle};
+
+// A single logical step: placing `value` at (row, col), justified by `technique`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Deduction {
+    pub row: usize,
+    pub col: usize,
+    pub value: u8,
+    pub technique: &'static str,
+}
+
+// What a technique found: either a value to place, or candidates to rule out.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Step {
+    Place(Deduction),
+    Eliminate(Vec<(usize, usize, u8)>),
+}
+
+// Why a value can't go in a cell, as found by `TechniqueRegistry::refute`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Refutation {
+    // A peer in the same unit ("row", "column" or "box") already holds the value
+    Peer { row: usize, col: usize, unit: &'static str },
+    // Assuming the value, these placements are forced and lead to the contradiction
+    Chain { steps: Vec<Deduction>, contradiction: Contradiction },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Contradiction {
+    // A cell is left without candidates
+    NoCandidates { row: usize, col: usize },
+    // A value has nowhere left to go in a unit, e.g. ("Row", 2, 5)
+    NoPlace { unit: &'static str, index: usize, value: u8 },
+}
+
+// What a solve took, alongside its result: how hard the backtracking search
+// had to look (`Board::solutions_with_stats`) and how the techniques got there
+// (`TechniqueRegistry::grade_with_stats`). Each fills in its own half.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SolveStats {
+    // Values the search tried in a cell
+    pub nodes: usize,
+    // Values tried that led to no solution, each a path it went back from
+    pub backtracks: usize,
+    // Runs of the techniques over the candidates, easiest first, starting
+    // over after each step
+    pub passes: usize,
+    // Steps each technique made, by id, in the order first used
+    pub techniques: Vec<(&'static str, usize)>,
+}
+
+impl SolveStats {
+    fn applied(&mut self, id: &'static str) {
+        match self.techniques.iter_mut().find(|(used, _)| *used == id) {
+            Some((_, count)) => *count += 1,
+            None => self.techniques.push((id, 1)),
+        }
+    }
+
+    // Steps made by all techniques together
+    pub fn steps(&self) -> usize {
+        self.techniques.iter().map(|(_, count)| count).sum()
+    }
+}
+
+// A solving technique that can be registered with the hint/grading engine.
+pub trait Technique {
+    // Stable identifier used in the config file
+    fn id(&self) -> &'static str;
+    // Human-readable name shown in the UI
+    fn name(&self) -> &'static str;
+    // Relative difficulty, higher is harder (singles are 1)
+    fn difficulty(&self) -> u8;
+    // Look for one application of this technique on the current candidates
+    fn find(&self, candidates: &Candidates) -> Option<Step>;
+}
+
+// A row, column or box, as a list of (row, col) coordinates
+pub type Unit = Vec<(usize, usize)>;
+
+// Candidate bitmasks for every cell: bit (v - 1) is set if `v` can legally go there.
+// Filled cells have an empty mask.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidates {
+    pub regions: Arc<RegionMap>,
+    pub masks: Vec<Vec<u16>>,
+}
+
+impl Candidates {
+    pub fn from_grid<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(grid: &Board<N, BOX_ROWS, BOX_COLS>) -> Self {
+        let masks = (0..N).map(|r| (0..N).map(|c| candidates(grid, r, c)).collect()).collect();
+        Self { regions: Arc::clone(&grid.regions), masks }
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> u16 {
+        self.masks[row][col]
+    }
+
+    // Fill (row, col) with `value`, removing it from every peer
+    pub fn place(&mut self, row: usize, col: usize, value: u8) {
+        let bit = 1 << (value - 1);
+        self.masks[row][col] = 0;
+        for &(r, c) in self.regions.peers(row, col) {
+            self.masks[r][c] &= !bit;
+        }
+    }
+
+    // Returns true if the candidate was actually present
+    pub fn remove(&mut self, row: usize, col: usize, value: u8) -> bool {
+        let bit = 1 << (value - 1);
+        let present = self.masks[row][col] & bit != 0;
+        self.masks[row][col] &= !bit;
+        present
+    }
+}
+
+// Candidate bitmask for a single cell of `grid`.
+pub fn candidates<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(grid: &Board<N, BOX_ROWS, BOX_COLS>, row: usize, col: usize) -> u16 {
+    if grid.cells[row][col].value().is_some() {
+        return 0;
+    }
+    let mut mask = 0;
+    for v in grid.regions.values() {
+        if grid.is_valid_move(row, col, v) {
+            mask |= 1 << (v - 1);
+        }
+    }
+    mask
+}
+
+// The unit two peers have in common, "row" first, then "column", then "box"
+pub fn shared_unit(a: (usize, usize), b: (usize, usize)) -> &'static str {
+    if a.0 == b.0 {
+        "row"
+    } else if a.1 == b.1 {
+        "column"
+    } else {
+        "box"
+    }
+}
+
+// The ordered set of techniques used for hints, watch mode and grading.
+pub struct TechniqueRegistry {
+    techniques: Vec<Box<dyn Technique>>,
+    disabled: Vec<&'static str>,
+}
+
+impl TechniqueRegistry {
+    // All built-in techniques, easiest first
+    pub fn builtin() -> Self {
+        let mut registry = Self { techniques: Vec::new(), disabled: Vec::new() };
+        registry.register(Box::new(NakedSingle));
+        registry.register(Box::new(HiddenSingle));
+        registry.register(Box::new(LockedCandidates));
+        registry.register(Box::new(NakedPair));
+        registry
+    }
+
+    // Built-in techniques, minus the ids listed in the config
+    pub fn with_disabled(ids: &[String]) -> Self {
+        let mut registry = Self::builtin();
+        for id in ids {
+            registry.set_enabled(id, false);
+        }
+        registry
+    }
+
+    // Add a technique; techniques are tried in registration order
+    pub fn register(&mut self, technique: Box<dyn Technique>) {
+        self.techniques.push(technique);
+    }
+
+    // Enable or disable a technique by id. Returns false for unknown ids.
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) -> bool {
+        let Some(technique) = self.techniques.iter().find(|t| t.id() == id) else {
+            return false;
+        };
+        let id = technique.id();
+        self.disabled.retain(|&d| d != id);
+        if !enabled {
+            self.disabled.push(id);
+        }
+        true
+    }
+
+    // A registered technique by id, enabled or not
+    pub fn get(&self, id: &str) -> Option<&dyn Technique> {
+        self.techniques.iter().map(|t| t.as_ref()).find(|t| t.id() == id)
+    }
+
+    pub fn enabled(&self) -> impl Iterator<Item = &dyn Technique> {
+        self.techniques.iter().map(|t| t.as_ref()).filter(|t| !self.disabled.contains(&t.id()))
+    }
+
+    // Whether `grid` can be solved with the enabled techniques but not once
+    // technique `id` is left out
+    pub fn requires<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(&mut self, grid: &Board<N, BOX_ROWS, BOX_COLS>, id: &str) -> bool {
+        if self.grade(grid).is_none() || self.disabled.contains(&id) {
+            return false;
+        }
+        self.set_enabled(id, false);
+        let without = self.grade(grid);
+        self.set_enabled(id, true);
+        without.is_none()
+    }
+
+    // Runs enabled techniques (easiest first) until one yields a placement.
+    // Eliminations are applied to `candidates` along the way. Returns the
+    // placement and the hardest difficulty used to reach it, counting the
+    // steps in `stats`.
+    fn next_placement(&self, candidates: &mut Candidates, stats: &mut SolveStats) -> Option<(Deduction, u8)> {
+        let mut hardest = 0;
+        'search: loop {
+            stats.passes += 1;
+            for technique in self.enabled() {
+                match technique.find(candidates) {
+                    Some(Step::Place(deduction)) => {
+                        stats.applied(technique.id());
+                        return Some((deduction, hardest.max(technique.difficulty())));
+                    }
+                    Some(Step::Eliminate(removals)) => {
+                        let mut progressed = false;
+                        for (r, c, v) in removals {
+                            progressed |= candidates.remove(r, c, v);
+                        }
+                        if progressed {
+                            stats.applied(technique.id());
+                            hardest = hardest.max(technique.difficulty());
+                            continue 'search;
+                        }
+                    }
+                    None => {}
+                }
+            }
+            return None;
+        }
+    }
+
+    // The next value the player could logically place on `grid`.
+    pub fn next_deduction<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(&self, grid: &Board<N, BOX_ROWS, BOX_COLS>) -> Option<Deduction> {
+        self.next_placement(&mut Candidates::from_grid(grid), &mut SolveStats::default()).map(|(deduction, _)| deduction)
+    }
+
+    // The placements that solve `grid` logically, in order, as far as the
+    // enabled techniques get
+    pub fn solve_path<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(&self, grid: &Board<N, BOX_ROWS, BOX_COLS>) -> Vec<Deduction> {
+        let mut candidates = Candidates::from_grid(grid);
+        let mut path = Vec::new();
+        while let Some((step, _)) = self.next_placement(&mut candidates, &mut SolveStats::default()) {
+            candidates.place(step.row, step.col, step.value);
+            path.push(step);
+        }
+        path
+    }
+
+    // Explain why `value` can't go at (row, col) on `grid`: either a peer already
+    // holds it, or assuming it forces placements that end in a contradiction.
+    // None when the enabled techniques can't find the contradiction.
+    pub fn refute<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(&self, grid: &Board<N, BOX_ROWS, BOX_COLS>, row: usize, col: usize, value: u8) -> Option<Refutation> {
+        if let Some((r, c)) = grid.conflicting_peer(row, col, value) {
+            return Some(Refutation::Peer { row: r, col: c, unit: shared_unit((row, col), (r, c)) });
+        }
+
+        let mut grid = grid.clone();
+        grid.cells[row][col].set_value(None);
+        let mut candidates = Candidates::from_grid(&grid);
+        grid.cells[row][col].set_value(Some(value));
+        candidates.place(row, col, value);
+        let mut steps = Vec::new();
+        loop {
+            if let Some(contradiction) = contradiction(&grid, &candidates) {
+                return Some(Refutation::Chain { steps, contradiction });
+            }
+            let (step, _) = self.next_placement(&mut candidates, &mut SolveStats::default())?;
+            grid.cells[step.row][step.col].set_value(Some(step.value));
+            candidates.place(step.row, step.col, step.value);
+            steps.push(step);
+        }
+    }
+
+    // The techniques as hard as solving `grid` gets that its solve applied,
+    // by name. None if they can't solve it.
+    pub fn hardest_needed<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(&self, grid: &Board<N, BOX_ROWS, BOX_COLS>) -> Option<Vec<&'static str>> {
+        let (difficulty, stats) = self.grade_with_stats(grid);
+        self.hardest_applied(difficulty, &stats)
+    }
+
+    // `hardest_needed` from a grading already made
+    pub fn hardest_applied(&self, difficulty: Option<u8>, stats: &SolveStats) -> Option<Vec<&'static str>> {
+        let difficulty = difficulty?;
+        let applied = stats.techniques.iter().filter_map(|&(id, _)| self.get(id));
+        Some(applied.filter(|t| t.difficulty() == difficulty).map(|t| t.name()).collect())
+    }
+
+    // Solve `grid` with enabled techniques only. Returns the hardest difficulty
+    // required, or None if the techniques get stuck before the board is full.
+    pub fn grade<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(&self, grid: &Board<N, BOX_ROWS, BOX_COLS>) -> Option<u8> {
+        self.grade_with_stats(grid).0
+    }
+
+    // `grade`, with the passes and technique steps it took, as far as it got
+    pub fn grade_with_stats<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(&self, grid: &Board<N, BOX_ROWS, BOX_COLS>) -> (Option<u8>, SolveStats) {
+        let mut grid = grid.clone();
+        let mut candidates = Candidates::from_grid(&grid);
+        let mut stats = SolveStats::default();
+        let mut hardest = 0;
+        while !grid.is_full() {
+            let Some((step, difficulty)) = self.next_placement(&mut candidates, &mut stats) else {
+                return (None, stats);
+            };
+            grid.cells[step.row][step.col].set_value(Some(step.value));
+            candidates.place(step.row, step.col, step.value);
+            hardest = hardest.max(difficulty);
+        }
+        (Some(hardest), stats)
+    }
+}
+
+// The first empty cell without candidates, or value missing from a unit with nowhere left to go
+pub fn contradiction<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(grid: &Board<N, BOX_ROWS, BOX_COLS>, candidates: &Candidates) -> Option<Contradiction> {
+    let regions = &candidates.regions;
+    for (r, c) in regions.cells() {
+        if grid.cells[r][c].value().is_none() && candidates.get(r, c) == 0 {
+            return Some(Contradiction::NoCandidates { row: r, col: c });
+        }
+    }
+    let units = regions.units();
+    for (i, (unit, cells)) in units.iter().enumerate() {
+        for value in regions.values() {
+            let placed = cells.iter().any(|&(r, c)| grid.cells[r][c].value() == Some(value));
+            let possible = cells.iter().any(|&(r, c)| candidates.get(r, c) & (1 << (value - 1)) != 0);
+            if !placed && !possible {
+                let index = units[..i].iter().filter(|(kind, _)| kind == unit).count();
+                return Some(Contradiction::NoPlace { unit, index, value });
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{candidates, Contradiction, Refutation, TechniqueRegistry};
+    use crate::model::{Board, Grid};
+
+    #[test]
+    fn candidates_exclude_peers() {
+        let mut grid = Grid::new();
+        grid.cells[0][5].set_value(Some(1));
+        grid.cells[5][0].set_value(Some(2));
+        grid.cells[1][1].set_value(Some(3));
+
+        let mask = candidates(&grid, 0, 0);
+        assert_eq!(mask, 0b111000);
+        assert_eq!(candidates(&grid, 1, 1), 0);
+    }
+
+    #[test]
+    fn peers_cover_row_column_and_box() {
+        let cells = Grid::new().regions.peers(0, 0).to_vec();
+        assert_eq!(cells.len(), 12);
+        assert!(cells.contains(&(1, 2)));
+        assert!(!cells.contains(&(0, 0)));
+    }
+
+    #[test]
+    fn next_deduction_finds_naked_single() {
+        let mut grid = Grid::new();
+        for (c, v) in [1, 2, 3, 4, 5].into_iter().enumerate() {
+            grid.cells[0][c].set_value(Some(v));
+        }
+
+        let step = TechniqueRegistry::builtin().next_deduction(&grid).expect("a naked single should be available");
+        assert_eq!((step.row, step.col, step.value), (0, 5, 6));
+        assert_eq!(step.technique, "Naked Single");
+    }
+
+    #[test]
+    fn next_deduction_solves_generated_puzzle_with_correct_values() {
+        let mut grid = Grid::new();
+        assert!(grid.fill_randomly(&mut StdRng::seed_from_u64(2)));
+        let solution = grid.cells;
+        for r in 0..6 {
+            grid.cells[r][r].set_value(None);
+        }
+
+        let registry = TechniqueRegistry::builtin();
+        while let Some(step) = registry.next_deduction(&grid) {
+            assert_eq!(solution[step.row][step.col].value(), Some(step.value));
+            grid.cells[step.row][step.col].set_value(Some(step.value));
+        }
+        assert!(grid.is_solved());
+    }
+
+    #[test]
+    fn disabled_techniques_are_skipped() {
+        let mut grid = Grid::new();
+        for (c, v) in [1, 2, 3, 4, 5].into_iter().enumerate() {
+            grid.cells[0][c].set_value(Some(v));
+        }
+
+        let registry = TechniqueRegistry::with_disabled(&["naked_single".to_string()]);
+        let step = registry.next_deduction(&grid).expect("hidden single should still apply");
+        assert_ne!(step.technique, "Naked Single");
+        assert!(!TechniqueRegistry::builtin().set_enabled("no_such_technique", false));
+    }
+
+    #[test]
+    fn grade_solves_generated_puzzle() {
+        let mut grid = Grid::new();
+        assert!(grid.fill_randomly(&mut StdRng::seed_from_u64(1)));
+        let solution = grid.cells;
+        for r in 0..6 {
+            grid.cells[r][r].set_value(None);
+        }
+
+        let registry = TechniqueRegistry::builtin();
+        assert_eq!(registry.grade(&grid), Some(1));
+        let step = registry.next_deduction(&grid).unwrap();
+        assert_eq!(solution[step.row][step.col].value(), Some(step.value));
+
+        // Each blank has one value left: a single apiece, and no going back
+        let (grade, stats) = registry.grade_with_stats(&grid);
+        assert_eq!(registry.hardest_applied(grade, &stats), Some(vec!["Naked Single"]));
+        assert_eq!((grade, stats.passes, stats.techniques), (Some(1), 6, vec![("naked_single", 6)]));
+        let (solutions, stats) = grid.solutions_with_stats(2);
+        assert_eq!((solutions.len(), stats.nodes, stats.backtracks), (1, 6, 0));
+
+        // Every value tried is on the one solution's path or given up on
+        let givens = crate::model::Game::with_seed(5).givens();
+        let (solutions, stats) = givens.solutions_with_stats(2);
+        assert_eq!(solutions.len(), 1);
+        assert!(stats.backtracks > 0);
+        assert_eq!(stats.backtracks, stats.nodes - (36 - givens.clue_count()));
+    }
+
+    #[test]
+    fn refute_names_peer_or_forced_chain() {
+        let mut grid = Grid::new();
+        assert!(grid.fill_randomly(&mut StdRng::seed_from_u64(2)));
+        let solution = grid.cells;
+        let registry = TechniqueRegistry::builtin();
+
+        // Wrong value shared with a filled peer in the row
+        let wrong = solution[0][1].value().unwrap();
+        grid.cells[0][0].set_value(None);
+        assert_eq!(registry.refute(&grid, 0, 0, wrong), Some(Refutation::Peer { row: 0, col: 1, unit: "row" }));
+
+        // With every peer emptied, the contradiction takes a chain of placements
+        let regions = grid.regions.clone();
+        for &(r, c) in regions.peers(0, 0) {
+            grid.cells[r][c].set_value(None);
+        }
+        let right = solution[0][0].value().unwrap();
+        let wrong = right % 6 + 1;
+        match registry.refute(&grid, 0, 0, wrong) {
+            Some(Refutation::Chain { contradiction, .. }) => {
+                assert!(matches!(contradiction, Contradiction::NoCandidates { .. } | Contradiction::NoPlace { .. }))
+            }
+            other => panic!("expected a chain, got {:?}", other),
+        }
+        assert_eq!(registry.refute(&grid, 0, 0, right), None);
+    }
+
+    #[test]
+    fn other_board_shapes_share_the_solver() {
+        let mut grid = Board::<9, 3, 3>::new();
+        assert!(grid.fill_randomly(&mut StdRng::seed_from_u64(3)));
+        assert!(grid.is_solved());
+        let solution = grid.cells;
+        for i in 0..9 {
+            grid.cells[i][(i * 4) % 9].set_value(None);
+        }
+        assert_eq!(grid.regions.peers(4, 4).len(), 20);
+        assert_eq!(TechniqueRegistry::builtin().grade(&grid), Some(1));
+        let step = TechniqueRegistry::builtin().next_deduction(&grid).unwrap();
+        assert_eq!(solution[step.row][step.col].value(), Some(step.value));
+        assert_eq!(Board::<4, 2, 2>::new().regions.units().len(), 12);
+    }
+
+    #[test]
+    fn grade_fails_on_empty_board() {
+        assert_eq!(TechniqueRegistry::builtin().grade(&Grid::new()), None);
+    }
+}
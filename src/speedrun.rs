@@ -0,0 +1,216 @@
+// Speedrun mode: a split is recorded each time a digit is fully and correctly
+// placed (all six of them), and compared against the personal best run and
+// the best-ever ("gold") time for each segment.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::storage;
+
+const RECORDS_FILE: &str = "speedrun_pb.txt";
+const RUNS_FILE: &str = "speedrun_runs.csv";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Split {
+    pub digit: u8,
+    // Time on the solve clock when the digit was completed
+    pub at: Duration,
+}
+
+#[derive(Debug, Default)]
+pub struct Speedrun {
+    pub splits: Vec<Split>,
+    // Cumulative split times of the best finished run (empty if none yet)
+    pub personal_best: Vec<Duration>,
+    // Best time ever recorded for each segment
+    pub gold: Vec<Duration>,
+    saved: bool,
+}
+
+impl Speedrun {
+    // Start a run against the records stored in the data dir
+    pub fn load() -> Self {
+        storage::read(RECORDS_FILE).map(|text| Self::from_records(&text)).unwrap_or_default()
+    }
+
+    fn from_records(text: &str) -> Self {
+        let mut run = Self::default();
+        for line in text.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                let times = parse_times(value);
+                match key.trim() {
+                    "pb" => run.personal_best = times,
+                    "gold" => run.gold = times,
+                    _ => {}
+                }
+            }
+        }
+        run
+    }
+
+    fn to_records(&self) -> String {
+        format!("pb = {}\ngold = {}\n", format_times(&self.personal_best), format_times(&self.gold))
+    }
+
+    pub fn record(&mut self, digit: u8, at: Duration) {
+        if !self.splits.iter().any(|split| split.digit == digit) {
+            self.splits.push(Split { digit, at });
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.splits.len() == 6
+    }
+
+    // Time spent between the previous split and split `i`
+    pub fn segment(&self, i: usize) -> Duration {
+        let previous = if i == 0 { Duration::ZERO } else { self.splits[i - 1].at };
+        self.splits[i].at.saturating_sub(previous)
+    }
+
+    // Milliseconds ahead (negative) or behind (positive) the personal best at split `i`
+    pub fn delta_ms(&self, i: usize) -> Option<i64> {
+        let pb = self.personal_best.get(i)?;
+        Some(self.splits[i].at.as_millis() as i64 - pb.as_millis() as i64)
+    }
+
+    // Whether split `i` beat the best-ever time for its segment
+    pub fn is_gold(&self, i: usize) -> bool {
+        self.gold.get(i).is_none_or(|&gold| self.segment(i) < gold)
+    }
+
+    // Fold a finished run into the personal best and gold splits
+    fn update_records(&mut self) {
+        let times: Vec<Duration> = self.splits.iter().map(|split| split.at).collect();
+        if self.personal_best.last().is_none_or(|&pb| times[5] < pb) {
+            self.personal_best = times;
+        }
+        for i in 0..self.splits.len() {
+            let segment = self.segment(i);
+            match self.gold.get_mut(i) {
+                Some(gold) => *gold = (*gold).min(segment),
+                None => self.gold.push(segment),
+            }
+        }
+    }
+
+    // Our records and another install's as one: the faster personal best and
+    // the faster gold for each segment
+    fn merged(mut self, other: Speedrun) -> Self {
+        if other.personal_best.last().is_some_and(|&time| self.personal_best.last().is_none_or(|&pb| time < pb)) {
+            self.personal_best = other.personal_best;
+        }
+        for (i, &segment) in other.gold.iter().enumerate() {
+            match self.gold.get_mut(i) {
+                Some(gold) => *gold = (*gold).min(segment),
+                None => self.gold.push(segment),
+            }
+        }
+        self
+    }
+
+    // Once the run is complete, save the new records and export the run.
+    // Returns the export path the first time, None before completion or after saving.
+    pub fn finish(&mut self, puzzle: &str) -> io::Result<Option<PathBuf>> {
+        if !self.is_complete() || self.saved {
+            return Ok(None);
+        }
+        self.saved = true;
+
+        let splits = self.splits.iter().map(|split| format!("{}:{}", split.digit, split.at.as_millis())).collect::<Vec<_>>();
+        let line = format!("{},{},{}", puzzle, self.splits[5].at.as_millis(), splits.join(";"));
+
+        // Deltas shown for the finished run should stay relative to the old records
+        let mut records = Speedrun { splits: self.splits.clone(), personal_best: self.personal_best.clone(), gold: self.gold.clone(), saved: true };
+        records.update_records();
+
+        storage::update(RECORDS_FILE, |text| Speedrun::from_records(text).merged(records).to_records())?;
+        storage::append_line(RUNS_FILE, &line).map(Some)
+    }
+}
+
+// Merge the records and runs kept by another install, in its data dir `from`,
+// into ours: the faster personal best, the faster gold for each segment and
+// every run exported on either. Returns whether there were records to merge.
+pub fn import(from: &Path) -> io::Result<bool> {
+    let runs = storage::merge_from(from, RUNS_FILE, |ours, theirs| {
+        let mut lines: Vec<&str> = ours.lines().collect();
+        for line in theirs.lines() {
+            if !lines.contains(&line) {
+                lines.push(line);
+            }
+        }
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    })?;
+    let records = storage::merge_from(from, RECORDS_FILE, |ours, theirs| Speedrun::from_records(ours).merged(Speedrun::from_records(theirs)).to_records())?;
+    Ok(runs || records)
+}
+
+fn parse_times(value: &str) -> Vec<Duration> {
+    value.split(',').filter_map(|ms| ms.trim().parse().ok()).map(Duration::from_millis).collect()
+}
+
+fn format_times(times: &[Duration]) -> String {
+    times.iter().map(|t| t.as_millis().to_string()).collect::<Vec<_>>().join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Speedrun;
+    use std::time::Duration;
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    #[test]
+    fn records_round_trip() {
+        let run = Speedrun { personal_best: vec![secs(1), secs(3)], gold: vec![secs(1), secs(2)], ..Default::default() };
+        let parsed = Speedrun::from_records(&run.to_records());
+        assert_eq!(parsed.personal_best, run.personal_best);
+        assert_eq!(parsed.gold, run.gold);
+
+        let other = Speedrun { personal_best: vec![secs(2), secs(4)], gold: vec![secs(3), secs(1), secs(2)], ..Default::default() };
+        let merged = parsed.merged(other);
+        assert_eq!(merged.personal_best, run.personal_best);
+        assert_eq!(merged.gold, vec![secs(1), secs(1), secs(2)]);
+    }
+
+    #[test]
+    fn splits_compare_against_records() {
+        let mut run = Speedrun { personal_best: vec![secs(10), secs(20)], gold: vec![secs(8), secs(15)], ..Default::default() };
+        run.record(3, secs(9));
+        run.record(3, secs(11)); // repeated completion is ignored
+        run.record(5, secs(22));
+
+        assert_eq!(run.splits.len(), 2);
+        assert_eq!(run.delta_ms(0), Some(-1000));
+        assert_eq!(run.delta_ms(1), Some(2000));
+        assert!(!run.is_gold(0));
+        assert!(run.is_gold(1));
+    }
+
+    #[test]
+    fn update_records_keeps_best_times() {
+        let mut run = Speedrun { personal_best: vec![secs(5); 6], gold: vec![secs(1); 6], ..Default::default() };
+        for digit in 1..=6 {
+            run.record(digit, secs(digit as u64 * 2));
+        }
+        run.update_records();
+
+        // Slower overall, so the old personal best stays, but no segment beat gold either
+        assert_eq!(run.personal_best, vec![secs(5); 6]);
+        assert_eq!(run.gold, vec![secs(1); 6]);
+
+        let mut first = Speedrun::default();
+        for digit in 1..=6 {
+            first.record(digit, secs(digit as u64));
+        }
+        first.update_records();
+        assert_eq!(first.personal_best.len(), 6);
+        assert_eq!(first.gold, vec![secs(1); 6]);
+    }
+}
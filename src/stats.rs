@@ -0,0 +1,582 @@
+// Records of the games played, kept apart by kind of puzzle: board size,
+// variant and difficulty. A best time is only compared with times on the same
+// kind, so nothing is added up across them. The stats screen has a tab per
+// board size and variant, with a row per difficulty. How each session went
+// is added once it's over, and each weekly challenge goes on a ladder.
+// Races, dailies and timed runs also move a single rating across all kinds.
+
+use std::{
+    collections::BTreeMap,
+    fmt, io,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    bot::Level,
+    model::{Erases, Game},
+    screen::Screen,
+    storage,
+};
+
+const STATS_FILE: &str = "stats.csv";
+
+// Solves of a kind before its own average is taken as the estimate for it
+const OWN_SOLVES: u32 = 3;
+// How many times longer each star takes, when only one difficulty on the tab
+// has been solved
+const STAR_FACTOR: f64 = 1.6;
+// The rating before any rated game, and the most one game moves it
+const START_RATING: f64 = 1200.0;
+const K_FACTOR: f64 = 32.0;
+// What a puzzle is rated, per star above none
+const STAR_RATING: f64 = 100.0;
+const BASE_RATING: f64 = 1000.0;
+// Sessions kept in the stats file, the oldest going first
+const MAX_SESSIONS: usize = 100;
+
+// The kind of puzzle a record is kept for
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Category {
+    pub size: usize,
+    // `Variant::kind`, e.g. "classic"
+    pub variant: String,
+    pub stars: u8,
+}
+
+impl Category {
+    pub fn of(game: &Game) -> Self {
+        Self { size: game.grid.cells.len(), variant: game.meta.variant.kind().to_string(), stars: game.stars() }
+    }
+}
+
+// e.g. "6x6 classic"
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Tab {
+    pub size: usize,
+    pub variant: String,
+}
+
+impl fmt::Display for Tab {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{0}x{0} {1}", self.size, self.variant)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Record {
+    pub played: u32,
+    pub solved: u32,
+    pub best: Option<Duration>,
+    // Time spent on the solved games, for the average
+    pub solve_time: Duration,
+    // Wrong entries taken back, over every game
+    pub erases: Erases,
+}
+
+impl Record {
+    pub fn add(&mut self, solved: bool, time: Duration) {
+        self.played += 1;
+        if solved {
+            self.solved += 1;
+            self.best = Some(self.best.map_or(time, |best| best.min(time)));
+            self.solve_time += time;
+        }
+    }
+
+    pub fn average(&self) -> Option<Duration> {
+        (self.solved > 0).then(|| self.solve_time / self.solved)
+    }
+
+    // Both records as one: counts and times add up, the better best is kept
+    pub fn merge(&mut self, other: &Record) {
+        self.played += other.played;
+        self.solved += other.solved;
+        self.best = match (self.best, other.best) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.solve_time += other.solve_time;
+        self.erases.add(other.erases);
+    }
+}
+
+// The games counted from starting the game to quitting it
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Session {
+    // Seconds since the Unix epoch
+    pub started: u64,
+    pub attempted: u32,
+    pub solved: u32,
+    // On the clock, over every game attempted
+    pub time: Duration,
+    pub best: Option<Duration>,
+}
+
+impl Session {
+    pub fn add(&mut self, solved: bool, time: Duration) {
+        self.attempted += 1;
+        self.time += time;
+        if solved {
+            self.solved += 1;
+            self.best = Some(self.best.map_or(time, |best| best.min(time)));
+        }
+    }
+
+    // e.g. `session,1760400000,3,2,1500000,300000`: start, attempted,
+    // solved, total and best time in ms
+    fn encode(&self) -> String {
+        let best = self.best.map_or("-".to_string(), |best| best.as_millis().to_string());
+        format!("session,{},{},{},{},{}", self.started, self.attempted, self.solved, self.time.as_millis(), best)
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let parts: Vec<&str> = line.split(',').map(str::trim).collect();
+        let ["session", started, attempted, solved, time, best] = parts[..] else {
+            return None;
+        };
+        let best = match best {
+            "-" => None,
+            ms => Some(Duration::from_millis(ms.parse().ok()?)),
+        };
+        Some(Self { started: started.parse().ok()?, attempted: attempted.parse().ok()?, solved: solved.parse().ok()?, time: Duration::from_millis(time.parse().ok()?), best })
+    }
+}
+
+// The attempt at a week's challenge
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WeeklyResult {
+    // The date of its Monday
+    pub week: String,
+    pub solved: bool,
+    pub time: Duration,
+    pub mistakes: u32,
+}
+
+impl WeeklyResult {
+    // e.g. `weekly,2026-10-12,1,312000,2`: week, solved, time in ms and mistakes
+    fn encode(&self) -> String {
+        format!("weekly,{},{},{},{}", self.week, self.solved as u8, self.time.as_millis(), self.mistakes)
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let parts: Vec<&str> = line.split(',').map(str::trim).collect();
+        let ["weekly", week, solved, time, mistakes] = parts[..] else {
+            return None;
+        };
+        let solved = match solved {
+            "0" => false,
+            "1" => true,
+            _ => return None,
+        };
+        Some(Self { week: week.to_string(), solved, time: Duration::from_millis(time.parse().ok()?), mistakes: mistakes.parse().ok()? })
+    }
+}
+
+// An Elo-style rating: each rated game is a match against the puzzle, rated
+// by its stars (and the bot's pace, when racing one). Winning against a
+// stronger puzzle gains more than against a weaker one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rating {
+    pub value: f64,
+    // Rated games so far
+    pub games: u32,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self { value: START_RATING, games: 0 }
+    }
+}
+
+impl Rating {
+    // After a game against `opponent` scoring `score`, from 0 for a loss to 1
+    // for a win
+    pub fn update(&mut self, opponent: f64, score: f64) {
+        let expected = 1.0 / (1.0 + 10f64.powf((opponent - self.value) / 400.0));
+        self.value += K_FACTOR * (score - expected);
+        self.games += 1;
+    }
+
+    // e.g. `rating,1234.5,17`: the rating and the games rated
+    fn encode(&self) -> String {
+        format!("rating,{:.1},{}", self.value, self.games)
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let parts: Vec<&str> = line.split(',').map(str::trim).collect();
+        let ["rating", value, games] = parts[..] else {
+            return None;
+        };
+        Some(Self { value: value.parse().ok().filter(|value: &f64| value.is_finite())?, games: games.parse().ok()? })
+    }
+}
+
+// The rated opponent of a game and how it went, for races against the best
+// solve or a bot, dailies, and time-attack and speedrun runs; None for others.
+// A race scores the share of rivals beaten, and an unsolved game nothing.
+// Otherwise a solve scores 1, less a tenth per mistake down to a half. Games
+// watch mode played in, or mostly played from hints, aren't rated.
+pub fn rated_result(game: &Game, solved: bool) -> Option<(f64, f64)> {
+    let blanks = game.grid.cells.iter().flatten().filter(|cell| !cell.is_fixed()).count() as u32;
+    if game.watched > 0 || game.hinted * 2 >= blanks.max(1) {
+        return None;
+    }
+    let time = game.timer.elapsed();
+    let mut rivals = Vec::new();
+    if let Some(best) = game.ghost.as_ref().and_then(|ghost| ghost.best.as_ref()) {
+        rivals.push(best.last().is_none_or(|&best| time < best));
+    }
+    if let Some(bot) = &game.bot {
+        rivals.push(bot.finish().is_none_or(|finish| time <= finish));
+    }
+    let timed = game.meta.date.is_some() || game.time_attack.is_some() || game.speedrun.is_some();
+    if rivals.is_empty() && !timed {
+        return None;
+    }
+    let pace = match game.bot.as_ref().map(|bot| bot.level) {
+        Some(Level::Relaxed) => -100.0,
+        Some(Level::Quick) => 150.0,
+        Some(Level::Steady) | None => 0.0,
+    };
+    let opponent = BASE_RATING + STAR_RATING * game.stars() as f64 + pace;
+    let score = match solved {
+        false => 0.0,
+        true if !rivals.is_empty() => rivals.iter().filter(|&&beaten| beaten).count() as f64 / rivals.len() as f64,
+        true => (1.0 - 0.1 * game.mistakes as f64).max(0.5),
+    };
+    Some((opponent, score))
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub records: BTreeMap<Category, Record>,
+    // Earlier sessions, oldest first
+    pub sessions: Vec<Session>,
+    // This one, until it's ended
+    pub session: Session,
+    // The weekly ladder, oldest week first
+    pub weekly: Vec<WeeklyResult>,
+    pub rating: Rating,
+}
+
+impl Stats {
+    // The records so far, starting a session now
+    pub fn load() -> Self {
+        let started = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map_or(0, |since| since.as_secs());
+        let stats = storage::read(STATS_FILE).map(|text| Self::parse(&text)).unwrap_or_default();
+        Self { session: Session { started, ..Session::default() }, ..stats }
+    }
+
+    // One line per kind, e.g. `6,classic,2,10,7,312000,2400000,5,1`: size,
+    // variant, stars, played, solved, best and total solve time in ms, then
+    // corrections and errors. A kind never solved has no best, written as
+    // `-`. Lines from before erases were kept end at the solve time. Sessions
+    // follow, one per line, then the weekly results and the rating.
+    pub fn parse(text: &str) -> Self {
+        let sessions = text.lines().filter_map(Session::parse).collect();
+        let rating = text.lines().find_map(Rating::parse).unwrap_or_default();
+        let weekly = text.lines().filter_map(WeeklyResult::parse).collect();
+        let records = text
+            .lines()
+            .filter_map(|line| {
+                let parts: Vec<&str> = line.split(',').map(str::trim).collect();
+                let [size, variant, stars, played, solved, best, solve_time, ref erases @ ..] = parts[..] else {
+                    return None;
+                };
+                let erases = match *erases {
+                    [] => Erases::default(),
+                    [corrections, errors] => Erases { corrections: corrections.parse().ok()?, errors: errors.parse().ok()? },
+                    _ => return None,
+                };
+                let category = Category { size: size.parse().ok()?, variant: variant.to_string(), stars: stars.parse().ok()? };
+                let best = match best {
+                    "-" => None,
+                    ms => Some(Duration::from_millis(ms.parse().ok()?)),
+                };
+                let record = Record { played: played.parse().ok()?, solved: solved.parse().ok()?, best, solve_time: Duration::from_millis(solve_time.parse().ok()?), erases };
+                Some((category, record))
+            })
+            .collect();
+        Self { records, sessions, session: Session::default(), weekly, rating }
+    }
+
+    pub fn to_text(&self) -> String {
+        let records = self.records.iter().map(|(category, record)| {
+            let best = record.best.map_or("-".to_string(), |best| best.as_millis().to_string());
+            let (size, variant, stars) = (category.size, &category.variant, category.stars);
+            let erases = record.erases;
+            format!("{},{},{},{},{},{},{},{},{}\n", size, variant, stars, record.played, record.solved, best, record.solve_time.as_millis(), erases.corrections, erases.errors)
+        });
+        let sessions = self.sessions.iter().map(|session| session.encode() + "\n");
+        let rating = (self.rating.games > 0).then(|| self.rating.encode() + "\n");
+        records.chain(sessions).chain(self.weekly.iter().map(|result| result.encode() + "\n")).chain(rating).collect()
+    }
+
+    // Count how the game went and save. Games quit before entering anything,
+    // and games played with others, aren't counted. Returns whether it was.
+    // The game is added to the records as saved now, so games another
+    // install saved meanwhile are kept.
+    pub fn record(&mut self, game: &Game) -> io::Result<bool> {
+        let solved = game.screen() == Screen::Won;
+        let touched = game.mistakes > 0 || game.grid.cells.iter().flatten().any(|cell| !cell.is_fixed() && cell.value().is_some());
+        if (!solved && !touched) || game.hotseat.is_some() || game.coop_player.is_some() || game.spectating.is_some() {
+            return Ok(false);
+        }
+        let (category, time, erases) = (Category::of(game), game.timer.elapsed(), game.erases);
+        let rated = rated_result(game, solved);
+        self.session.add(solved, time);
+        storage::update(STATS_FILE, |text| {
+            *self = Self { session: self.session, ..Self::parse(text) };
+            let record = self.records.entry(category).or_default();
+            record.add(solved, time);
+            record.erases.add(erases);
+            if let Some(result) = self.weekly.iter_mut().find(|result| game.weekly.as_ref() == Some(&result.week)) {
+                *result = WeeklyResult { week: result.week.clone(), solved, time, mistakes: game.mistakes };
+            }
+            if let Some((opponent, score)) = rated {
+                self.rating.update(opponent, score);
+            }
+            self.to_text()
+        })?;
+        Ok(true)
+    }
+
+    // Use up the attempt at `week`'s challenge, putting it on the ladder as
+    // unsolved until it's recorded. False if it was used already, here or by
+    // an install sharing the data dir.
+    pub fn start_weekly(&mut self, week: &str) -> io::Result<bool> {
+        let mut started = false;
+        storage::update(STATS_FILE, |text| {
+            *self = Self { session: self.session, ..Self::parse(text) };
+            started = !self.weekly.iter().any(|result| result.week == week);
+            if started {
+                self.weekly.push(WeeklyResult { week: week.to_string(), ..WeeklyResult::default() });
+            }
+            self.to_text()
+        })?;
+        Ok(started)
+    }
+
+    // Add this session to the earlier ones and save, unless no game was
+    // counted in it. Returns whether it was added.
+    pub fn end_session(&mut self) -> io::Result<bool> {
+        if self.session.attempted == 0 {
+            return Ok(false);
+        }
+        let session = std::mem::take(&mut self.session);
+        storage::update(STATS_FILE, |text| {
+            *self = Self::parse(text);
+            self.sessions.push(session);
+            self.drop_old_sessions();
+            self.to_text()
+        })?;
+        Ok(true)
+    }
+
+    fn drop_old_sessions(&mut self) {
+        let extra = self.sessions.len().saturating_sub(MAX_SESSIONS);
+        self.sessions.drain(..extra);
+    }
+
+    // Add the records kept by another install. Sessions already listed aren't
+    // added again.
+    pub fn merge(&mut self, other: &Stats) {
+        for (category, record) in &other.records {
+            self.records.entry(category.clone()).or_default().merge(record);
+        }
+        for session in &other.sessions {
+            if !self.sessions.contains(session) {
+                self.sessions.push(*session);
+            }
+        }
+        self.sessions.sort_by_key(|session| session.started);
+        self.drop_old_sessions();
+        for result in &other.weekly {
+            if !self.weekly.iter().any(|ours| ours.week == result.week) {
+                self.weekly.push(result.clone());
+            }
+        }
+        self.weekly.sort_by(|a, b| a.week.cmp(&b.week));
+        // Ratings can't be added up; the one with more games behind it is kept
+        if other.rating.games > self.rating.games {
+            self.rating = other.rating;
+        }
+    }
+
+    // Merge another install's records from its data dir `from` into ours
+    pub fn import(from: &Path) -> io::Result<bool> {
+        storage::merge_from(from, STATS_FILE, |ours, theirs| {
+            let mut stats = Self::parse(ours);
+            stats.merge(&Self::parse(theirs));
+            stats.to_text()
+        })
+    }
+
+    // A tab per board size and variant played, smallest boards first
+    pub fn tabs(&self) -> Vec<Tab> {
+        let mut tabs: Vec<Tab> = self.records.keys().map(|category| Tab { size: category.size, variant: category.variant.clone() }).collect();
+        tabs.dedup();
+        tabs
+    }
+
+    // The records on a tab, easiest first
+    pub fn rows(&self, tab: &Tab) -> Vec<(u8, Record)> {
+        self.records.iter().filter(|(category, _)| category.size == tab.size && category.variant == tab.variant).map(|(category, record)| (category.stars, *record)).collect()
+    }
+
+    // How long a puzzle of `category` typically takes: its own average once
+    // it has a few solves, otherwise the average time its tab's solved
+    // difficulties trend to at its stars. The trend is a straight line through
+    // the log of each difficulty's average, weighted by solves, as each star
+    // multiplies the time rather than adding to it. None with nothing solved
+    // on the tab.
+    pub fn estimate(&self, category: &Category) -> Option<Duration> {
+        let own = self.records.get(category).filter(|record| record.solved >= OWN_SOLVES).and_then(Record::average);
+        if own.is_some() {
+            return own;
+        }
+        let tab = Tab { size: category.size, variant: category.variant.clone() };
+        let points: Vec<(f64, f64, f64)> = self
+            .rows(&tab)
+            .iter()
+            .filter_map(|(stars, record)| Some((*stars as f64, record.average()?.as_secs_f64().max(1.0).ln(), record.solved as f64)))
+            .collect();
+        let weight: f64 = points.iter().map(|&(_, _, w)| w).sum();
+        if weight == 0.0 {
+            return None;
+        }
+        let (mean_x, mean_y) = points.iter().fold((0.0, 0.0), |(x, y), &(px, py, w)| (x + px * w / weight, y + py * w / weight));
+        let spread: f64 = points.iter().map(|&(x, _, w)| w * (x - mean_x).powi(2)).sum();
+        // Harder puzzles aren't expected to go faster, whatever a few lucky solves say
+        let slope = match spread > 0.0 {
+            true => (points.iter().map(|&(x, y, w)| w * (x - mean_x) * (y - mean_y)).sum::<f64>() / spread).max(0.0),
+            false => STAR_FACTOR.ln(),
+        };
+        Some(Duration::from_secs_f64((mean_y + slope * (category.stars as f64 - mean_x)).exp()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rated_result, Category, Rating, Record, Session, Stats, Tab, WeeklyResult, MAX_SESSIONS};
+    use crate::{
+        bot::{Bot, Level},
+        model::{Erases, Game},
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn ratings_move_by_how_unexpected_the_result_was() {
+        let mut rating = Rating::default();
+        rating.update(1200.0, 1.0);
+        assert_eq!((rating.value, rating.games), (1216.0, 1));
+        // Beating a much weaker puzzle gains little, losing to it costs a lot
+        let mut weak = rating;
+        weak.update(800.0, 1.0);
+        assert!(weak.value - rating.value < 3.0);
+        let mut lost = rating;
+        lost.update(800.0, 0.0);
+        assert!(rating.value - lost.value > 29.0);
+
+        // Only races, dailies and timed runs are rated
+        let mut game = Game::with_seed(9);
+        assert_eq!(rated_result(&game, true), None);
+        game.meta.date = Some("2026-10-14".to_string());
+        game.mistakes = 2;
+        let (opponent, score) = rated_result(&game, true).unwrap();
+        assert_eq!((opponent, score), (1000.0 + 100.0 * game.stars() as f64, 0.8));
+        assert_eq!(rated_result(&game, false).map(|(_, score)| score), Some(0.0));
+        // A bot that never finishes is beaten, and a quicker one rates higher
+        game.bot = Some(Bot { level: Level::Quick, times: Vec::new(), total: 1 });
+        assert_eq!(rated_result(&game, true), Some((opponent + 150.0, 1.0)));
+        // Nor are games watch mode played in, or mostly played from hints
+        game.watched = 1;
+        assert!(rated_result(&game, true).is_none());
+        game.watched = 0;
+        let blanks = game.grid.cells.iter().flatten().filter(|cell| !cell.is_fixed()).count() as u32;
+        game.hinted = blanks / 2 - 1;
+        assert!(rated_result(&game, true).is_some());
+        game.hinted = blanks / 2 + 1;
+        assert!(rated_result(&game, true).is_none());
+
+        let stats = Stats { rating, ..Stats::default() };
+        assert!(stats.to_text().ends_with("rating,1216.0,1\n"));
+        assert_eq!(Stats::parse(&stats.to_text()).rating, rating);
+        assert_eq!(Stats::default().to_text(), "");
+    }
+
+    #[test]
+    fn estimates_follow_the_records_across_difficulties() {
+        let mut stats = Stats::default();
+        let category = |stars| Category { size: 6, variant: "classic".to_string(), stars };
+        let secs = Duration::from_secs;
+        assert_eq!(stats.estimate(&category(1)), None);
+
+        // One difficulty solved: the others scale from it
+        stats.records.entry(category(1)).or_default().add(true, secs(100));
+        assert_eq!(stats.estimate(&category(1)).map(|t| t.as_secs()), Some(100));
+        assert_eq!(stats.estimate(&category(2)).map(|t| t.as_secs()), Some(160));
+
+        // Two: a trend through both, which the kind's own average takes over
+        // from after a few solves
+        stats.records.entry(category(3)).or_default().add(true, secs(400));
+        assert_eq!(stats.estimate(&category(2)).map(|t| t.as_secs()), Some(200));
+        for _ in 0..3 {
+            stats.records.entry(category(2)).or_default().add(true, secs(150));
+        }
+        assert_eq!(stats.estimate(&category(2)), Some(secs(150)));
+        assert_eq!(stats.estimate(&Category { variant: "thermo".to_string(), ..category(2) }), None);
+    }
+
+    #[test]
+    fn records_are_kept_per_kind_of_puzzle() {
+        let mut stats = Stats::default();
+        let category = |size, variant: &str, stars| Category { size, variant: variant.to_string(), stars };
+        let secs = Duration::from_secs;
+        stats.records.entry(category(6, "classic", 2)).or_default().add(true, secs(300));
+        stats.records.entry(category(6, "classic", 2)).or_default().add(true, secs(200));
+        stats.records.entry(category(6, "classic", 2)).or_default().add(false, secs(50));
+        stats.records.entry(category(6, "classic", 1)).or_default().add(false, secs(10));
+        stats.records.entry(category(4, "classic", 1)).or_default().add(true, secs(60));
+        stats.records.entry(category(6, "imported", 3)).or_default().add(true, secs(900));
+
+        let record = stats.records[&category(6, "classic", 2)];
+        assert_eq!(record, Record { played: 3, solved: 2, best: Some(secs(200)), solve_time: secs(500), ..Record::default() });
+        assert_eq!(record.average(), Some(secs(250)));
+        assert_eq!(stats.records[&category(6, "classic", 1)].average(), None);
+
+        let tabs = stats.tabs();
+        assert_eq!(tabs.iter().map(Tab::to_string).collect::<Vec<_>>(), ["4x4 classic", "6x6 classic", "6x6 imported"]);
+        assert_eq!(stats.rows(&tabs[1]).iter().map(|(stars, _)| *stars).collect::<Vec<_>>(), [1, 2]);
+
+        stats.session.add(true, secs(200));
+        stats.session.add(false, secs(100));
+        assert_eq!(stats.session, Session { started: 0, attempted: 2, solved: 1, time: secs(300), best: Some(secs(200)) });
+        stats.sessions.push(std::mem::take(&mut stats.session));
+        stats.sessions.push(Session { started: 1760400000, attempted: 1, ..Session::default() });
+
+        stats.weekly.push(WeeklyResult { week: "2026-10-12".to_string(), solved: true, time: secs(400), mistakes: 2 });
+
+        let text = stats.to_text();
+        assert!(text.contains("6,classic,1,1,0,-,0,0,0\n"));
+        assert!(text.ends_with("session,0,2,1,300000,200000\nsession,1760400000,1,0,0,-\nweekly,2026-10-12,1,400000,2\n"));
+        assert_eq!(Stats::parse(&(text.clone() + "garbage\n")), stats);
+
+        // Another install's records add up, keeping the better best. Lines
+        // from before erases were kept still read.
+        let other = Stats::parse("6,classic,2,4,1,150000,150000,5,1\n6,classic,4,1,1,900000,900000\nsession,5,4,1,400000,150000\nsession,0,2,1,300000,200000\nweekly,2026-10-05,0,90000,3\nweekly,2026-10-12,0,0,0\n");
+        stats.merge(&other);
+        assert_eq!(stats.records[&category(6, "classic", 2)], Record { played: 7, solved: 3, best: Some(secs(150)), solve_time: secs(650), erases: Erases { corrections: 5, errors: 1 } });
+        assert_eq!(stats.records[&category(6, "classic", 4)].played, 1);
+        assert_eq!(stats.sessions.iter().map(|session| session.started).collect::<Vec<_>>(), [0, 5, 1760400000]);
+        // A week on both keeps ours
+        assert_eq!(stats.weekly.iter().map(|result| (result.week.as_str(), result.solved)).collect::<Vec<_>>(), [("2026-10-05", false), ("2026-10-12", true)]);
+
+        // Only the latest sessions are kept
+        let many = Stats { sessions: (10..10 + MAX_SESSIONS as u64).map(|started| Session { started, attempted: 1, ..Session::default() }).collect(), ..Stats::default() };
+        stats.merge(&many);
+        assert_eq!(stats.sessions.len(), MAX_SESSIONS);
+        assert_eq!((stats.sessions[0].started, stats.sessions[MAX_SESSIONS - 1].started), (11, 1760400000));
+    }
+}
@@ -0,0 +1,119 @@
+// The status bar under the board, built from sections such as the mode, the
+// clock, the mistakes and the key help. Each section has a priority: on a
+// terminal too narrow for all of them the least important go first, so the
+// clock and any message outlast the key help. Key hints name their key the
+// way the keymap binds it.
+
+use crate::command::{self, Command};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::Style,
+    text::Line,
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+
+const SEPARATOR: &str = " | ";
+
+// How much a section is missed when dropped, least first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    // A key and what it does now, e.g. "h: Hint"
+    Keys,
+    Difficulty,
+    // Figures about the game: mode, mistakes, scores
+    Detail,
+    Clock,
+    // What the player most needs to read: hints, warnings, "YOU WON!"
+    Message,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Section {
+    pub priority: Priority,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct StatusBar {
+    // In the order shown
+    pub sections: Vec<Section>,
+    pub style: Style,
+}
+
+impl StatusBar {
+    pub fn new(style: Style) -> Self {
+        Self { sections: Vec::new(), style }
+    }
+
+    pub fn push(&mut self, priority: Priority, text: impl Into<String>) {
+        let text = text.into();
+        if !text.is_empty() {
+            self.sections.push(Section { priority, text });
+        }
+    }
+
+    // A hint for the key bound to `command`, e.g. `key(Command::Undo, "Undo")` gives "u: Undo"
+    pub fn key(&mut self, command: Command, action: &str) {
+        self.push(Priority::Keys, format!("{}: {}", command::key_label(command), action));
+    }
+
+    // The sections that fit in `width` columns, joined by "|". Of the sections
+    // with the lowest priority, the rightmost is dropped first. If even the
+    // last one left doesn't fit, it's cut short.
+    pub fn text(&self, width: u16) -> String {
+        let width = width as usize;
+        let mut shown: Vec<&Section> = self.sections.iter().collect();
+        loop {
+            let text = shown.iter().map(|section| section.text.as_str()).collect::<Vec<_>>().join(SEPARATOR);
+            if Line::from(text.as_str()).width() <= width {
+                return text;
+            }
+            if shown.len() == 1 {
+                return match width {
+                    0 => String::new(),
+                    _ => text.chars().take(width - 1).chain(Some('…')).collect(),
+                };
+            }
+            let lowest = shown.iter().map(|section| section.priority).min().expect("more than one section is shown");
+            let drop = shown.iter().rposition(|section| section.priority == lowest).expect("some section has the lowest priority");
+            shown.remove(drop);
+        }
+    }
+}
+
+impl Widget for StatusBar {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::default().borders(Borders::ALL);
+        let text = self.text(block.inner(area).width);
+        Paragraph::new(text).alignment(Alignment::Center).style(self.style).block(block).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Priority, StatusBar};
+    use crate::command::Command;
+
+    #[test]
+    fn narrow_bars_drop_the_least_important_sections() {
+        let mut bar = StatusBar::default();
+        bar.push(Priority::Message, "Hint: Naked Single at r1c2");
+        bar.push(Priority::Detail, "Mode: NORMAL (p)");
+        bar.push(Priority::Clock, "Time: 01:02");
+        bar.push(Priority::Difficulty, "★★☆☆");
+        bar.push(Priority::Detail, "");
+        bar.key(Command::Hint, "Hint");
+        bar.key(Command::Quit, "Quit");
+
+        let full = "Hint: Naked Single at r1c2 | Mode: NORMAL (p) | Time: 01:02 | ★★☆☆ | h: Hint | q: Quit";
+        assert_eq!(bar.text(120), full);
+        assert_eq!(bar.text(80), "Hint: Naked Single at r1c2 | Mode: NORMAL (p) | Time: 01:02 | ★★☆☆ | h: Hint");
+        assert_eq!(bar.text(60), "Hint: Naked Single at r1c2 | Mode: NORMAL (p) | Time: 01:02");
+        assert_eq!(bar.text(45), "Hint: Naked Single at r1c2 | Time: 01:02");
+        assert_eq!(bar.text(10), "Hint: Nak…");
+        assert_eq!(bar.text(0), "");
+        assert_eq!(StatusBar::default().text(10), "");
+    }
+}
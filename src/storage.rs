@@ -0,0 +1,208 @@
+// Files kept in the per-user data directory:
+// `$XDG_DATA_HOME/rustdoku6` (or `~/.local/share/rustdoku6`).
+// Disposable files such as logs go in the cache directory instead:
+// `$XDG_CACHE_HOME/rustdoku6` (or `~/.cache/rustdoku6`).
+//
+// The data dir may be kept in a synced folder (Dropbox, Syncthing) and shared
+// by installs on several machines, so files are never written in place: new
+// contents go to a temporary file that is renamed over the old one, and
+// changes are made to the file as it is when saving, not as it was read.
+// Logs that only grow are the exception, a line added in one write at the end.
+// File times are never compared, as sync clients set them as they like.
+
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+pub fn data_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share")))?;
+    Some(base.join("rustdoku6"))
+}
+
+pub fn cache_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("rustdoku6"))
+}
+
+fn path_for(name: &str) -> io::Result<PathBuf> {
+    in_dir(data_dir(), name)
+}
+
+fn in_dir(dir: Option<PathBuf>, name: &str) -> io::Result<PathBuf> {
+    let dir = dir.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory"))?;
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join(name))
+}
+
+// Contents of a data file, or None if it doesn't exist yet
+pub fn read(name: &str) -> Option<String> {
+    fs::read_to_string(path_for(name).ok()?).ok()
+}
+
+pub fn write(name: &str, contents: &str) -> io::Result<PathBuf> {
+    let path = path_for(name)?;
+    replace(&path, contents)?;
+    Ok(path)
+}
+
+// Change a data file from what it holds now, which another install syncing
+// the same folder may have changed since it was read. `change` is given the
+// contents, empty if there's no file yet.
+pub fn update(name: &str, change: impl FnOnce(&str) -> String) -> io::Result<PathBuf> {
+    let path = path_for(name)?;
+    update_file(&path, change)?;
+    Ok(path)
+}
+
+// Add a line to the end of a data file, without rewriting what's there
+pub fn append_line(name: &str, line: &str) -> io::Result<PathBuf> {
+    let path = path_for(name)?;
+    append_to(&path, line)?;
+    Ok(path)
+}
+
+// Where file `name` goes in the cache dir, which is made if need be
+pub fn cached_path(name: &str) -> io::Result<PathBuf> {
+    in_dir(cache_dir(), name)
+}
+
+
+// The data dirs merged in so far, a canonical path a line
+const IMPORTS_FILE: &str = "imports.txt";
+
+// The canonical path of `from`, checked to be another install's data dir, and
+// one not merged in before: merging this game's own data, or the same data
+// twice, would count every game again
+pub fn import_source(from: &Path) -> Result<PathBuf, String> {
+    let source = fs::canonicalize(from).map_err(|err| format!("could not read {}: {}", from.display(), err))?;
+    let own = data_dir().and_then(|dir| fs::canonicalize(dir).ok());
+    check_source(&source, own.as_deref(), &read(IMPORTS_FILE).unwrap_or_default())?;
+    Ok(source)
+}
+
+fn check_source(source: &Path, own: Option<&Path>, imported: &str) -> Result<(), String> {
+    if own == Some(source) {
+        return Err(format!("{} is this game's own data directory", source.display()));
+    }
+    if imported.lines().any(|line| Path::new(line) == source) {
+        return Err(format!("{} was imported already", source.display()));
+    }
+    Ok(())
+}
+
+// Note that `source`, from `import_source`, has been merged in
+pub fn record_import(source: &Path) -> io::Result<PathBuf> {
+    append_line(IMPORTS_FILE, &source.to_string_lossy())
+}
+
+// Merge the data file `name` kept in another install's data dir `from` into
+// ours, with `merge` given our contents and theirs. Returns false if `from`
+// has no such file.
+pub fn merge_from(from: &Path, name: &str, merge: impl Fn(&str, &str) -> String) -> io::Result<bool> {
+    let theirs = match fs::read_to_string(from.join(name)) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(err) => return Err(err),
+    };
+    update(name, |ours| merge(ours, &theirs))?;
+    Ok(true)
+}
+
+// Change the file at `path` from what it holds now, as `update` does for
+// data files. Cache files are changed this way too, as two copies of the game
+// may share them.
+pub(crate) fn update_file(path: &Path, change: impl FnOnce(&str) -> String) -> io::Result<()> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    replace(path, &change(&text))
+}
+
+// A file cut off mid-line (by a crash, or a sync of half a write) gets its
+// newline first, so the new line isn't run into the broken one
+fn append_to(path: &Path, line: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+    let mut last = [b'\n'];
+    if file.metadata()?.len() > 0 {
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last)?;
+    }
+    let text = match last {
+        [b'\n'] => format!("{}\n", line),
+        _ => format!("\n{}\n", line),
+    };
+    file.write_all(text.as_bytes())?;
+    file.sync_all()
+}
+
+// Write `contents` to a hidden file next to `path`, then rename it over
+// `path`, so readers see either the old contents or the new ones in full.
+// The process id keeps two copies of the game from sharing a temporary file.
+fn replace(path: &Path, contents: &str) -> io::Result<()> {
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("data");
+    let temp = path.with_file_name(format!(".{}.{}.tmp", name, process::id()));
+    let written = File::create(&temp).and_then(|mut file| {
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()
+    });
+    match written.and_then(|_| fs::rename(&temp, path)) {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            let _ = fs::remove_file(&temp);
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{append_to, check_source, replace, update_file};
+    use std::{fs, path::Path};
+
+    #[test]
+    fn files_are_replaced_whole_and_updated_from_disk() {
+        let dir = std::env::temp_dir().join(format!("rustdoku6-storage-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("stats.csv");
+
+        update_file(&path, |text| format!("{}a\n", text)).unwrap();
+        // Another install changes the file in between
+        fs::write(&path, "a\nb\n").unwrap();
+        update_file(&path, |text| format!("{}c\n", text)).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "a\nb\nc\n");
+
+        replace(&path, "new\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new\n");
+        // No temporary file is left behind
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        // Appends go on the end, after a newline the file was missing
+        let log = dir.join("runs.csv");
+        append_to(&log, "one").unwrap();
+        assert_eq!(fs::read_to_string(&log).unwrap(), "one\n");
+        fs::write(&log, "one\ntw").unwrap();
+        append_to(&log, "three").unwrap();
+        assert_eq!(fs::read_to_string(&log).unwrap(), "one\ntw\nthree\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn data_is_imported_once_and_never_from_itself() {
+        let (own, other) = (Path::new("/home/a/.local/share/rustdoku6"), Path::new("/mnt/laptop/rustdoku6"));
+        assert!(check_source(other, Some(own), "").is_ok());
+        assert!(check_source(own, Some(own), "").unwrap_err().contains("own data directory"));
+        // Importing the same data again is refused
+        assert!(check_source(other, Some(own), "/mnt/laptop/rustdoku6\n").unwrap_err().contains("imported already"));
+        assert!(check_source(other, None, "/mnt/desktop/rustdoku6\n").is_ok());
+    }
+}
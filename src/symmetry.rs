@@ -0,0 +1,335 @@
+// Symmetries that keep a puzzle valid and just as hard while making it look
+// new: turning the board, mirroring it and relabeling its digits. Boxes of
+// 2x3 only survive a half turn; quarter turns need square boxes. Only cells
+// move, so these are for boards without variant rules, whose sums and paths
+// would stay where they were.
+//
+// The same moves, with rows and columns also swapped within their bands and
+// stacks, give each puzzle a canonical form, shared by every puzzle that is
+// only a disguise of it, so packs can be checked for repeats.
+
+use std::array;
+
+use rand::prelude::*;
+
+use crate::model::Board;
+
+// The cells turned a quarter turn clockwise `turns` times
+pub fn turn_cells<T: Copy, const N: usize>(cells: &[[T; N]; N], turns: u8) -> [[T; N]; N] {
+    let mut turned = *cells;
+    for _ in 0..turns % 4 {
+        let before = turned;
+        turned = array::from_fn(|r| array::from_fn(|c| before[N - 1 - c][r]));
+    }
+    turned
+}
+
+// The cells mirrored left to right
+pub fn mirror_cells<T: Copy, const N: usize>(cells: &[[T; N]; N]) -> [[T; N]; N] {
+    array::from_fn(|r| array::from_fn(|c| cells[r][N - 1 - c]))
+}
+
+// Values with each digit d replaced by digits[d - 1]; 0 (empty) stays
+pub fn relabel_values<const N: usize>(values: &[[u8; N]; N], digits: &[u8; N]) -> [[u8; N]; N] {
+    values.map(|row| row.map(|value| if value == 0 { 0 } else { digits[value as usize - 1] }))
+}
+
+// The board turned a quarter turn clockwise `turns` times. None for an odd
+// number of turns when the boxes aren't square, as they'd no longer be boxes.
+pub fn turn<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(board: &Board<N, BOX_ROWS, BOX_COLS>, turns: u8) -> Option<Board<N, BOX_ROWS, BOX_COLS>> {
+    if turns % 2 == 1 && BOX_ROWS != BOX_COLS {
+        return None;
+    }
+    let mut turned = board.clone();
+    turned.cells = turn_cells(&board.cells, turns);
+    Some(turned)
+}
+
+pub fn mirror<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(board: &Board<N, BOX_ROWS, BOX_COLS>) -> Board<N, BOX_ROWS, BOX_COLS> {
+    let mut mirrored = board.clone();
+    mirrored.cells = mirror_cells(&board.cells);
+    mirrored
+}
+
+// The board with each digit d, values and marks alike, replaced by digits[d - 1]
+pub fn relabel<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(board: &Board<N, BOX_ROWS, BOX_COLS>, digits: &[u8; N]) -> Board<N, BOX_ROWS, BOX_COLS> {
+    let mut relabeled = board.clone();
+    for cell in relabeled.cells.iter_mut().flatten() {
+        cell.set_value(cell.value().map(|value| digits[value as usize - 1]));
+        let marks = cell.marks();
+        cell.clear_marks();
+        for (i, &mark) in marks.iter().enumerate() {
+            cell.set_mark(digits[i], mark);
+        }
+    }
+    relabeled
+}
+
+// A symmetry of the layout of a puzzle's givens
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Symmetry {
+    QuarterTurn,
+    HalfTurn,
+    // Left to right
+    Mirror,
+    // Top to bottom
+    Flip,
+}
+
+impl Symmetry {
+    // As named in generation profiles, e.g. "half_turn"
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "quarter_turn" => Some(Symmetry::QuarterTurn),
+            "half_turn" => Some(Symmetry::HalfTurn),
+            "mirror" => Some(Symmetry::Mirror),
+            "flip" => Some(Symmetry::Flip),
+            _ => None,
+        }
+    }
+
+    // The cells (row, col) of an `n` by `n` board is moved to by the
+    // symmetry, over and over, itself included: the cells that must be given
+    // or empty together
+    pub fn orbit(self, (row, col): (usize, usize), n: usize) -> Vec<(usize, usize)> {
+        let step = |(r, c): (usize, usize)| match self {
+            Symmetry::QuarterTurn => (c, n - 1 - r),
+            Symmetry::HalfTurn => (n - 1 - r, n - 1 - c),
+            Symmetry::Mirror => (r, n - 1 - c),
+            Symmetry::Flip => (n - 1 - r, c),
+        };
+        let mut orbit = vec![(row, col)];
+        let mut next = step((row, col));
+        while next != (row, col) {
+            orbit.push(next);
+            next = step(next);
+        }
+        orbit
+    }
+}
+
+// The symmetries the givens are laid out with, whatever their values
+pub fn clue_symmetries<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(board: &Board<N, BOX_ROWS, BOX_COLS>) -> Vec<Symmetry> {
+    let givens = board.cells.map(|row| row.map(|cell| cell.is_fixed()));
+    [
+        (Symmetry::QuarterTurn, turn_cells(&givens, 1)),
+        (Symmetry::HalfTurn, turn_cells(&givens, 2)),
+        (Symmetry::Mirror, mirror_cells(&givens)),
+        (Symmetry::Flip, mirror_cells(&turn_cells(&givens, 2))),
+    ]
+    .into_iter()
+    .filter(|(_, moved)| *moved == givens)
+    .map(|(symmetry, _)| symmetry)
+    .collect()
+}
+
+// Every order of `n` lines that keeps boxes `size` lines deep whole: the
+// bands in any order, and the lines of each band in any order
+fn line_orders(n: usize, size: usize) -> Vec<Vec<usize>> {
+    let mut orders = Vec::new();
+    for bands in permutations(n / size) {
+        let mut partial = vec![Vec::new()];
+        for &band in &bands {
+            let mut longer = Vec::new();
+            for prefix in &partial {
+                for inner in permutations(size) {
+                    let mut order: Vec<usize> = prefix.clone();
+                    order.extend(inner.iter().map(|i| band * size + i));
+                    longer.push(order);
+                }
+            }
+            partial = longer;
+        }
+        orders.extend(partial);
+    }
+    orders
+}
+
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![Vec::new()];
+    }
+    let mut all = Vec::new();
+    for shorter in permutations(n - 1) {
+        for i in 0..n {
+            let mut longer = shorter.clone();
+            longer.insert(i, n - 1);
+            all.push(longer);
+        }
+    }
+    all
+}
+
+// The givens in canonical form: the least, read row by row with 0 for an
+// empty cell, of all the boards the givens can be moved and relabeled into.
+// Rows move within bands and bands around, columns within stacks and stacks
+// around, and square boxes also let the board be transposed. Each
+// arrangement is relabeled in the order digits first appear, the least
+// relabeling of it.
+pub fn canonical<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(board: &Board<N, BOX_ROWS, BOX_COLS>) -> [[u8; N]; N] {
+    let givens = board.cells.map(|row| row.map(|cell| if cell.is_fixed() { cell.value().unwrap_or(0) } else { 0 }));
+    let mut arrangements = vec![givens];
+    if BOX_ROWS == BOX_COLS {
+        arrangements.push(array::from_fn(|r| array::from_fn(|c| givens[c][r])));
+    }
+    let (rows, cols) = (line_orders(N, BOX_ROWS), line_orders(N, BOX_COLS));
+    let mut least = None;
+    for values in &arrangements {
+        for row_order in &rows {
+            for col_order in &cols {
+                let mut labels = vec![0; N + 1];
+                let mut next = 0;
+                let form: [[u8; N]; N] = array::from_fn(|r| {
+                    array::from_fn(|c| match values[row_order[r]][col_order[c]] {
+                        0 => 0,
+                        value => {
+                            let label = &mut labels[value as usize];
+                            if *label == 0 {
+                                next += 1;
+                                *label = next;
+                            }
+                            *label
+                        }
+                    })
+                });
+                if least.is_none_or(|least| form < least) {
+                    least = Some(form);
+                }
+            }
+        }
+    }
+    least.unwrap_or(givens)
+}
+
+// A number for the canonical form, the same from one build and machine to
+// the next (FNV-1a over its cells), to list and compare puzzles by
+pub fn canonical_hash<const N: usize, const BOX_ROWS: usize, const BOX_COLS: usize>(board: &Board<N, BOX_ROWS, BOX_COLS>) -> u64 {
+    canonical(board).iter().flatten().fold(0xcbf29ce484222325, |hash, &value| (hash ^ value as u64).wrapping_mul(0x100000001b3))
+}
+
+// A turn, then maybe a mirror, then a relabeling
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Transform<const N: usize = 6> {
+    // Quarter turns clockwise
+    pub turns: u8,
+    pub mirror: bool,
+    // What each digit becomes: d is relabeled digits[d - 1]
+    pub digits: [u8; N],
+}
+
+impl<const N: usize> Transform<N> {
+    pub fn identity() -> Self {
+        Self { turns: 0, mirror: false, digits: array::from_fn(|i| i as u8 + 1) }
+    }
+
+    // Any of the symmetries, with quarter turns only for square boxes
+    pub fn random<R: Rng + ?Sized>(rng: &mut R, square_boxes: bool) -> Self {
+        let mut digits = Self::identity().digits;
+        digits.shuffle(rng);
+        let turns = match square_boxes {
+            true => rng.random_range(0..4),
+            false => 2 * rng.random_range(0..2),
+        };
+        Self { turns, mirror: rng.random(), digits }
+    }
+
+    pub fn apply<const BOX_ROWS: usize, const BOX_COLS: usize>(&self, board: &Board<N, BOX_ROWS, BOX_COLS>) -> Option<Board<N, BOX_ROWS, BOX_COLS>> {
+        let turned = turn(board, self.turns)?;
+        let placed = if self.mirror { mirror(&turned) } else { turned };
+        Some(relabel(&placed, &self.digits))
+    }
+
+    // The same transform of a board's values, such as its solution
+    pub fn apply_values(&self, values: &[[u8; N]; N]) -> [[u8; N]; N] {
+        let turned = turn_cells(values, self.turns);
+        let placed = if self.mirror { mirror_cells(&turned) } else { turned };
+        relabel_values(&placed, &self.digits)
+    }
+
+    // As written in puzzle keys: the relabeled digits, the turns and "m" if
+    // mirrored, e.g. "3512462m"
+    pub fn code(&self) -> String {
+        let digits: String = self.digits.iter().filter_map(|&d| char::from_digit(d as u32, 36)).collect();
+        format!("{}{}{}", digits, self.turns, if self.mirror { "m" } else { "" })
+    }
+
+    pub fn parse(code: &str) -> Option<Self> {
+        let (code, mirror) = match code.strip_suffix('m') {
+            Some(code) => (code, true),
+            None => (code, false),
+        };
+        let chars: Vec<char> = code.chars().collect();
+        let (&turns, digits) = chars.split_last()?;
+        let turns = turns.to_digit(4)? as u8;
+        let digits: [u8; N] = digits.iter().map(|ch| ch.to_digit(36).map(|d| d as u8)).collect::<Option<Vec<_>>>()?.try_into().ok()?;
+        let mut sorted = digits;
+        sorted.sort_unstable();
+        (sorted == Self::identity().digits).then_some(Self { turns, mirror, digits })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{canonical, canonical_hash, clue_symmetries, line_orders, turn, Symmetry, Transform};
+    use crate::model::{Board, Game};
+
+    #[test]
+    fn disguised_puzzles_share_a_canonical_form() {
+        assert_eq!((line_orders(6, 2).len(), line_orders(6, 3).len()), (48, 72));
+        let game = Game::with_seed(21);
+        let puzzle = game.givens();
+        let transform = Transform { turns: 2, mirror: true, digits: [3, 5, 1, 2, 4, 6] };
+        let disguised = transform.apply(&puzzle).unwrap();
+        // Swapping the first two bands, and two rows in a band, as well
+        let mut swapped = disguised.clone();
+        swapped.cells = [2, 3, 0, 1, 5, 4].map(|r| disguised.cells[r]);
+        assert_ne!(swapped.cells, puzzle.cells);
+        assert_eq!(canonical(&swapped), canonical(&puzzle));
+        assert_eq!(canonical_hash(&swapped), canonical_hash(&puzzle));
+        // Relabeled in the order digits appear: the first given is a 1
+        assert_eq!(canonical(&puzzle).iter().flatten().find(|&&value| value != 0), Some(&1));
+        // Entries don't count, only the givens
+        let mut entered = puzzle.clone();
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !puzzle.cells[r][c].is_fixed()).unwrap();
+        entered.cells[r][c].set_value(Some(game.solution.unwrap()[r][c]));
+        assert_eq!(canonical(&entered), canonical(&puzzle));
+        assert_ne!(canonical_hash(&Game::with_seed(22).givens()), canonical_hash(&puzzle));
+    }
+
+    #[test]
+    fn transforms_keep_the_puzzle_and_its_solution() {
+        let game = Game::with_seed(21);
+        let transform = Transform { turns: 2, mirror: true, digits: [3, 5, 1, 2, 4, 6] };
+        let board = transform.apply(&game.grid).unwrap();
+        let solution = transform.apply_values(&game.solution.unwrap());
+        // Random clues may leave several solutions; as many as before
+        assert!(board.solutions(50).contains(&solution));
+        assert_eq!(board.solutions(50).len(), game.grid.solutions(50).len());
+        assert_eq!(board.clue_count(), game.grid.clue_count());
+        // The top-left value turns to the bottom-right, then mirrors to the
+        // bottom-left, relabeled
+        let corner = game.solution.unwrap()[0][0];
+        assert_eq!(solution[5][0], transform.digits[corner as usize - 1]);
+
+        assert!(turn(&game.grid, 1).is_none());
+        assert!(turn(&Board::<4, 2, 2>::new(), 1).is_some());
+        assert_eq!(Transform::parse(&transform.code()), Some(transform));
+        assert_eq!(transform.code(), "3512462m");
+        assert_eq!(Transform::<6>::parse("1123452"), None);
+        let random = Transform::<6>::random(&mut StdRng::seed_from_u64(1), false);
+        assert_eq!(random.turns % 2, 0);
+
+        let mut corners = Board::<6, 2, 3>::new();
+        for (r, c) in [(0, 0), (5, 5)] {
+            corners.cells[r][c].set_fixed(true);
+        }
+        assert_eq!(clue_symmetries(&corners), [Symmetry::HalfTurn]);
+        corners.cells[0][5].set_fixed(true);
+        corners.cells[5][0].set_fixed(true);
+        assert_eq!(clue_symmetries(&corners), [Symmetry::QuarterTurn, Symmetry::HalfTurn, Symmetry::Mirror, Symmetry::Flip]);
+        assert_eq!(Symmetry::QuarterTurn.orbit((0, 1), 6), [(0, 1), (1, 5), (5, 4), (4, 0)]);
+        assert_eq!(Symmetry::parse("mirror").map(|mirror| mirror.orbit((2, 2), 6)), Some(vec![(2, 2), (2, 3)]));
+    }
+}
@@ -0,0 +1,208 @@
+// Built-in solving techniques registered with `solver::TechniqueRegistry`.
+
+use crate::solver::{Candidates, Deduction, Step, Technique};
+
+// A cell with exactly one candidate left.
+pub struct NakedSingle;
+
+impl Technique for NakedSingle {
+    fn id(&self) -> &'static str {
+        "naked_single"
+    }
+
+    fn name(&self) -> &'static str {
+        "Naked Single"
+    }
+
+    fn difficulty(&self) -> u8 {
+        1
+    }
+
+    fn find(&self, candidates: &Candidates) -> Option<Step> {
+        for (r, c) in candidates.regions.cells() {
+            let mask = candidates.get(r, c);
+            if mask.count_ones() == 1 {
+                return Some(Step::Place(Deduction {
+                    row: r,
+                    col: c,
+                    value: mask.trailing_zeros() as u8 + 1,
+                    technique: self.name(),
+                }));
+            }
+        }
+        None
+    }
+}
+
+// A value that fits in only one cell of a row, column or box.
+pub struct HiddenSingle;
+
+impl Technique for HiddenSingle {
+    fn id(&self) -> &'static str {
+        "hidden_single"
+    }
+
+    fn name(&self) -> &'static str {
+        "Hidden Single"
+    }
+
+    fn difficulty(&self) -> u8 {
+        1
+    }
+
+    fn find(&self, candidates: &Candidates) -> Option<Step> {
+        for (kind, unit) in candidates.regions.units() {
+            for v in candidates.regions.values() {
+                let bit = 1 << (v - 1);
+                let mut spots = unit.iter().filter(|&&(r, c)| candidates.get(r, c) & bit != 0);
+                if let (Some(&(r, c)), None) = (spots.next(), spots.next()) {
+                    let technique = match kind {
+                        "Row" => "Hidden Single (Row)",
+                        "Column" => "Hidden Single (Column)",
+                        "Diagonal" => "Hidden Single (Diagonal)",
+                        "Cage" => "Hidden Single (Cage)",
+                        _ => "Hidden Single (Box)",
+                    };
+                    return Some(Step::Place(Deduction { row: r, col: c, value: v, technique }));
+                }
+            }
+        }
+        None
+    }
+}
+
+// Pointing / claiming: if a value's candidates in a box all lie in one row or
+// column (or vice versa), it can be removed from the rest of that line (or box).
+pub struct LockedCandidates;
+
+impl Technique for LockedCandidates {
+    fn id(&self) -> &'static str {
+        "locked_candidates"
+    }
+
+    fn name(&self) -> &'static str {
+        "Locked Candidates"
+    }
+
+    fn difficulty(&self) -> u8 {
+        2
+    }
+
+    fn find(&self, candidates: &Candidates) -> Option<Step> {
+        let regions = &candidates.regions;
+        let all_units = regions.units();
+        let lines: Vec<_> = all_units.iter().filter(|(kind, _)| *kind != "Box").map(|(_, cells)| cells).collect();
+
+        for b in 0..regions.size {
+            let box_unit = regions.region_cells(b);
+            for line in &lines {
+                let overlap: Vec<_> = box_unit.iter().filter(|cell| line.contains(cell)).collect();
+                if overlap.len() < 2 {
+                    continue;
+                }
+                for v in regions.values() {
+                    let bit = 1 << (v - 1);
+                    let has = |&(r, c): &(usize, usize)| candidates.get(r, c) & bit != 0;
+                    if !overlap.iter().any(|&&cell| has(&cell)) {
+                        continue;
+                    }
+                    let in_box_outside = box_unit.iter().any(|cell| !line.contains(cell) && has(cell));
+                    let in_line_outside = line.iter().any(|cell| !box_unit.contains(cell) && has(cell));
+
+                    // Pointing: confined to the line within the box, so clear the rest of the line
+                    if !in_box_outside && in_line_outside {
+                        let removals = line.iter().filter(|cell| !box_unit.contains(cell) && has(cell)).map(|&(r, c)| (r, c, v)).collect();
+                        return Some(Step::Eliminate(removals));
+                    }
+                    // Claiming: confined to the box within the line, so clear the rest of the box
+                    if !in_line_outside && in_box_outside {
+                        let removals = box_unit.iter().filter(|cell| !line.contains(cell) && has(cell)).map(|&(r, c)| (r, c, v)).collect();
+                        return Some(Step::Eliminate(removals));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+// Two cells in a unit sharing the same two candidates: those values can be
+// removed from every other cell in the unit.
+pub struct NakedPair;
+
+impl Technique for NakedPair {
+    fn id(&self) -> &'static str {
+        "naked_pair"
+    }
+
+    fn name(&self) -> &'static str {
+        "Naked Pair"
+    }
+
+    fn difficulty(&self) -> u8 {
+        3
+    }
+
+    fn find(&self, candidates: &Candidates) -> Option<Step> {
+        for (_, unit) in candidates.regions.units() {
+            for (i, &(r1, c1)) in unit.iter().enumerate() {
+                let pair = candidates.get(r1, c1);
+                if pair.count_ones() != 2 {
+                    continue;
+                }
+                for &(r2, c2) in &unit[i + 1..] {
+                    if candidates.get(r2, c2) != pair {
+                        continue;
+                    }
+                    let mut removals = Vec::new();
+                    for &(r, c) in &unit {
+                        if (r, c) == (r1, c1) || (r, c) == (r2, c2) {
+                            continue;
+                        }
+                        for v in candidates.regions.values() {
+                            if pair & candidates.get(r, c) & (1 << (v - 1)) != 0 {
+                                removals.push((r, c, v));
+                            }
+                        }
+                    }
+                    if !removals.is_empty() {
+                        return Some(Step::Eliminate(removals));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LockedCandidates, NakedPair};
+    use crate::{
+        model::Grid,
+        solver::{Candidates, Step, Technique},
+    };
+
+    #[test]
+    fn locked_candidates_clears_rest_of_line() {
+        // Value 1 only possible in row 0 within box 0, and also elsewhere in row 0
+        let mut candidates = Candidates { regions: Grid::new().regions, masks: vec![vec![0b111110; 6]; 6] };
+        candidates.masks[0][0] |= 1;
+        candidates.masks[0][1] |= 1;
+        candidates.masks[0][4] |= 1;
+
+        let step = LockedCandidates.find(&candidates).expect("pointing pair should be found");
+        assert_eq!(step, Step::Eliminate(vec![(0, 4, 1)]));
+    }
+
+    #[test]
+    fn naked_pair_clears_rest_of_unit() {
+        let mut candidates = Candidates { regions: Grid::new().regions, masks: vec![vec![0; 6]; 6] };
+        candidates.masks[0][0] = 0b000011;
+        candidates.masks[0][1] = 0b000011;
+        candidates.masks[0][2] = 0b000111;
+
+        let step = NakedPair.find(&candidates).expect("naked pair should be found");
+        assert_eq!(step, Step::Eliminate(vec![(0, 2, 1), (0, 2, 2)]));
+    }
+}
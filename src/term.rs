@@ -0,0 +1,26 @@
+// The terminal backend, chosen at compile time: crossterm by default, or
+// termwiz with `--no-default-features --features termwiz`. Each backend
+// provides the same small set of items:
+//
+// - `TermBackend`, the ratatui backend type
+// - `setup` / `restore` to enter and leave fullscreen mode, `setup` also
+//   asking the terminal for its background color if told to, and
+//   `Session::enhanced_keys` telling whether it marks held keys as repeats
+// - `set_title`, `cell_pixels` and `raw_output` (for graphics escapes)
+// - `Input`, which turns the backend's input into `AppEvent`s
+//
+// There's no termion backend: termion only runs on unix, and termwiz
+// already stands in for crossterm there as well as on Windows.
+
+#[cfg(not(any(feature = "crossterm", feature = "termwiz")))]
+compile_error!("enable a terminal backend: the `crossterm` (default) or `termwiz` feature");
+
+#[cfg(not(feature = "termwiz"))]
+mod with_crossterm;
+#[cfg(not(feature = "termwiz"))]
+pub use with_crossterm::*;
+
+#[cfg(feature = "termwiz")]
+mod with_termwiz;
+#[cfg(feature = "termwiz")]
+pub use with_termwiz::*;
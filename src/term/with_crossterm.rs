@@ -0,0 +1,194 @@
+// Crossterm backend. Input is read on its own thread and reaches the game loop
+// over a channel, so the loop never blocks in the terminal.
+
+use std::{
+    io::{self, Stdout, Write},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    event::{
+        self, DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture, Event, KeyEvent,
+        KeyEventKind, KeyModifiers, KeyboardEnhancementFlags, MouseButton, MouseEventKind, PopKeyboardEnhancementFlags,
+        PushKeyboardEnhancementFlags,
+    },
+    execute,
+    terminal::{
+        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, window_size, EnterAlternateScreen,
+        LeaveAlternateScreen, SetTitle,
+    },
+};
+use ratatui::{backend::CrosstermBackend, Terminal};
+
+use crate::{
+    background::{self, Background},
+    events::{AppEvent, Key, KeyCode, Mouse, MouseKind},
+};
+
+pub type TermBackend = CrosstermBackend<Stdout>;
+
+// What `setup` changed, so `restore` can undo it
+pub struct Session {
+    // The terminal marks held keys as repeats
+    pub enhanced_keys: bool,
+    mouse: bool,
+    // What the terminal said its background is, if asked
+    pub background: Option<Background>,
+    // Keys typed while it was asked, for `Input` to hand out first
+    pub typed: Vec<Key>,
+}
+
+// Mouse capture is optional because it takes over the terminal's own text selection
+pub fn setup(mouse: bool, probe_background: bool) -> io::Result<(Terminal<TermBackend>, Session)> {
+    enable_raw_mode()?;
+    // Before crossterm starts reading input, which would take the reply for keys
+    let probe = probe_background.then(background::detect).unwrap_or_default();
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableFocusChange)?;
+    if mouse {
+        execute!(stdout, EnableMouseCapture)?;
+    }
+    // Where supported, have the terminal mark held keys as repeats
+    let enhanced_keys = supports_keyboard_enhancement().unwrap_or(false);
+    if enhanced_keys {
+        execute!(stdout, PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES))?;
+    }
+    Ok((Terminal::new(CrosstermBackend::new(stdout))?, Session { enhanced_keys, mouse, background: probe.background, typed: probe.typed }))
+}
+
+pub fn restore(terminal: &mut Terminal<TermBackend>, session: Session) -> io::Result<()> {
+    if session.enhanced_keys {
+        execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags)?;
+    }
+    disable_raw_mode()?;
+    if session.mouse {
+        execute!(terminal.backend_mut(), DisableMouseCapture)?;
+    }
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableFocusChange)?;
+    terminal.show_cursor()
+}
+
+pub fn set_title(terminal: &mut Terminal<TermBackend>, title: &str) -> io::Result<()> {
+    execute!(terminal.backend_mut(), SetTitle(title))
+}
+
+// Pixel size of one cell, if the terminal reports it
+pub fn cell_pixels(_terminal: &mut Terminal<TermBackend>) -> Option<(u16, u16)> {
+    window_size()
+        .ok()
+        .filter(|size| size.width > 0 && size.columns > 0 && size.height > 0 && size.rows > 0)
+        .map(|size| (size.width / size.columns, size.height / size.rows))
+}
+
+// Where to write escape sequences that bypass ratatui
+pub fn raw_output(terminal: &mut Terminal<TermBackend>) -> impl Write + '_ {
+    terminal.backend_mut()
+}
+
+pub struct Input {
+    events: Receiver<io::Result<AppEvent>>,
+}
+
+impl Input {
+    // Start the input thread, after the keys `typed` earlier. It stops once
+    // `Input` is dropped, and forwards any terminal error before stopping.
+    pub fn new(tick_rate: Duration, typed: Vec<Key>) -> Self {
+        let (tx, rx) = mpsc::channel();
+        for key in typed {
+            let _ = tx.send(Ok(AppEvent::Key(key)));
+        }
+        thread::spawn(move || {
+            let mut last_tick = Instant::now();
+            loop {
+                let timeout = tick_rate.saturating_sub(last_tick.elapsed());
+                let event = match event::poll(timeout) {
+                    Ok(true) => match event::read() {
+                        Ok(event) => translate(event).map(Ok),
+                        Err(e) => Some(Err(e)),
+                    },
+                    Ok(false) => None,
+                    Err(e) => Some(Err(e)),
+                };
+                let failed = matches!(event, Some(Err(_)));
+                if let Some(event) = event {
+                    if tx.send(event).is_err() || failed {
+                        return;
+                    }
+                }
+
+                if last_tick.elapsed() >= tick_rate {
+                    last_tick = Instant::now();
+                    if tx.send(Ok(AppEvent::Tick)).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        Self { events: rx }
+    }
+
+    // Wait for the next event
+    pub fn next(&mut self, _terminal: &mut Terminal<TermBackend>) -> io::Result<AppEvent> {
+        self.events.recv().map_err(|_| io::Error::other("input thread stopped"))?
+    }
+}
+
+// Keep only the events the game reacts to; key releases are dropped
+fn translate(event: Event) -> Option<AppEvent> {
+    let event = match event {
+        Event::Key(key) if key.kind != KeyEventKind::Release => AppEvent::Key(translate_key(key)),
+        Event::Mouse(mouse) => {
+            let kind = match mouse.kind {
+                MouseEventKind::Moved => MouseKind::Moved,
+                MouseEventKind::Down(MouseButton::Left) => MouseKind::LeftDown,
+                _ => MouseKind::Other,
+            };
+            AppEvent::Mouse(Mouse { column: mouse.column, row: mouse.row, kind })
+        }
+        Event::Resize(..) => AppEvent::Resize,
+        Event::FocusLost => AppEvent::FocusLost,
+        Event::FocusGained => AppEvent::FocusGained,
+        _ => return None,
+    };
+    Some(event)
+}
+
+fn translate_key(key: KeyEvent) -> Key {
+    use crossterm::event::KeyCode as K;
+    let code = match key.code {
+        K::Char(ch) => KeyCode::Char(ch),
+        K::F(n) => KeyCode::F(n),
+        K::Up => KeyCode::Up,
+        K::Down => KeyCode::Down,
+        K::Left => KeyCode::Left,
+        K::Right => KeyCode::Right,
+        K::Esc => KeyCode::Esc,
+        K::Enter => KeyCode::Enter,
+        K::Backspace => KeyCode::Backspace,
+        K::Delete => KeyCode::Delete,
+        _ => KeyCode::Other,
+    };
+    Key { code, repeat: key.kind == KeyEventKind::Repeat, shift: key.modifiers.contains(KeyModifiers::SHIFT) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::translate;
+    use crate::events::{AppEvent, Key, KeyCode};
+    use crossterm::event::{Event, KeyCode as K, KeyEvent, KeyEventKind, KeyModifiers};
+
+    #[test]
+    fn translate_drops_releases_and_marks_repeats() {
+        let press = KeyEvent::new(K::Char('q'), KeyModifiers::NONE);
+        let release = KeyEvent { kind: KeyEventKind::Release, ..press };
+        let repeat = KeyEvent { kind: KeyEventKind::Repeat, ..press };
+
+        assert_eq!(translate(Event::Key(press)), Some(AppEvent::Key(Key::new(KeyCode::Char('q')))));
+        assert_eq!(translate(Event::Key(release)), None);
+        assert_eq!(translate(Event::Key(repeat)), Some(AppEvent::Key(Key { code: KeyCode::Char('q'), repeat: true, shift: false })));
+        assert_eq!(translate(Event::Resize(80, 24)), Some(AppEvent::Resize));
+        assert_eq!(translate(Event::Paste("x".to_string())), None);
+    }
+}
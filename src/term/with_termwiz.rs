@@ -0,0 +1,151 @@
+// Termwiz backend. Termwiz reads input through the same terminal object that
+// draws, so instead of a separate thread `Input::next` polls it from the game
+// loop with a timeout that lands on the next tick. The terminal doesn't report
+// focus changes, so the idle timeout is what pauses the clock here.
+
+use std::{
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use ratatui::{backend::TermwizBackend, Terminal};
+use termwiz::{
+    caps::{Capabilities, ProbeHints},
+    input::{InputEvent, KeyCode as K, Modifiers, MouseButtons},
+    surface::Change,
+    terminal::{buffered::BufferedTerminal, SystemTerminal, Terminal as _},
+};
+
+use crate::{
+    background::{self, Background},
+    events::{AppEvent, Key, KeyCode, Mouse, MouseKind},
+};
+
+pub type TermBackend = TermwizBackend;
+
+// Termwiz keeps its own restore state
+pub struct Session {
+    // Always false: termwiz doesn't mark held keys as repeats
+    pub enhanced_keys: bool,
+    // What the terminal said its background is, if asked
+    pub background: Option<Background>,
+    // Keys typed while it was asked, for `Input` to hand out first
+    pub typed: Vec<Key>,
+}
+
+// Mouse reporting is optional because it takes over the terminal's own text selection
+pub fn setup(mouse: bool, probe_background: bool) -> io::Result<(Terminal<TermBackend>, Session)> {
+    let hints = ProbeHints::new_from_env().mouse_reporting(Some(mouse));
+    let caps = Capabilities::new_with_hints(hints).map_err(other)?;
+    let mut buffered = BufferedTerminal::new(SystemTerminal::new(caps).map_err(other)?).map_err(other)?;
+    buffered.terminal().set_raw_mode().map_err(other)?;
+    // Termwiz only reads input when polled, so the reply is there to take
+    let probe = probe_background.then(background::detect).unwrap_or_default();
+    buffered.terminal().enter_alternate_screen().map_err(other)?;
+    Ok((Terminal::new(TermwizBackend::with_buffered_terminal(buffered))?, Session { enhanced_keys: false, background: probe.background, typed: probe.typed }))
+}
+
+pub fn restore(terminal: &mut Terminal<TermBackend>, _session: Session) -> io::Result<()> {
+    terminal.show_cursor()?;
+    let system = terminal.backend_mut().buffered_terminal_mut().terminal();
+    system.exit_alternate_screen().map_err(other)?;
+    system.set_cooked_mode().map_err(other)
+}
+
+pub fn set_title(terminal: &mut Terminal<TermBackend>, title: &str) -> io::Result<()> {
+    let buffered = terminal.backend_mut().buffered_terminal_mut();
+    buffered.add_change(Change::Title(title.to_string()));
+    buffered.flush().map_err(other)
+}
+
+// Pixel size of one cell, if the terminal reports it
+pub fn cell_pixels(terminal: &mut Terminal<TermBackend>) -> Option<(u16, u16)> {
+    let size = terminal.backend_mut().buffered_terminal_mut().terminal().get_screen_size().ok()?;
+    if size.xpixel == 0 || size.ypixel == 0 || size.cols == 0 || size.rows == 0 {
+        return None;
+    }
+    Some(((size.xpixel / size.cols) as u16, (size.ypixel / size.rows) as u16))
+}
+
+// Where to write escape sequences that bypass ratatui. Frames are flushed by the
+// time graphics are drawn, so writing straight to stdout keeps the order.
+pub fn raw_output(_terminal: &mut Terminal<TermBackend>) -> impl Write + '_ {
+    io::stdout()
+}
+
+pub struct Input {
+    tick_rate: Duration,
+    last_tick: Instant,
+    // Keys typed before, handed out first
+    typed: Vec<Key>,
+}
+
+impl Input {
+    pub fn new(tick_rate: Duration, mut typed: Vec<Key>) -> Self {
+        typed.reverse();
+        Self { tick_rate, last_tick: Instant::now(), typed }
+    }
+
+    // Wait for the next event, or the next tick
+    pub fn next(&mut self, terminal: &mut Terminal<TermBackend>) -> io::Result<AppEvent> {
+        if let Some(key) = self.typed.pop() {
+            return Ok(AppEvent::Key(key));
+        }
+        loop {
+            let timeout = self.tick_rate.saturating_sub(self.last_tick.elapsed());
+            if timeout.is_zero() {
+                self.last_tick = Instant::now();
+                return Ok(AppEvent::Tick);
+            }
+            let buffered = terminal.backend_mut().buffered_terminal_mut();
+            let Some(event) = buffered.terminal().poll_input(Some(timeout)).map_err(other)? else {
+                continue;
+            };
+            if let InputEvent::Resized { cols, rows } = event {
+                buffered.resize(cols, rows);
+            }
+            if let Some(event) = translate(event) {
+                return Ok(event);
+            }
+        }
+    }
+}
+
+fn other(err: termwiz::Error) -> io::Error {
+    io::Error::other(err.to_string())
+}
+
+fn translate(event: InputEvent) -> Option<AppEvent> {
+    let event = match event {
+        InputEvent::Key(key) => {
+            let code = match key.key {
+                K::Char(ch) => KeyCode::Char(ch),
+                K::Function(n) => KeyCode::F(n),
+                K::UpArrow => KeyCode::Up,
+                K::DownArrow => KeyCode::Down,
+                K::LeftArrow => KeyCode::Left,
+                K::RightArrow => KeyCode::Right,
+                K::Escape => KeyCode::Esc,
+                K::Enter => KeyCode::Enter,
+                K::Backspace => KeyCode::Backspace,
+                K::Delete => KeyCode::Delete,
+                _ => KeyCode::Other,
+            };
+            AppEvent::Key(Key { shift: key.modifiers.contains(Modifiers::SHIFT), ..Key::new(code) })
+        }
+        InputEvent::Mouse(mouse) => {
+            let kind = if mouse.mouse_buttons.contains(MouseButtons::LEFT) {
+                MouseKind::LeftDown
+            } else if mouse.mouse_buttons == MouseButtons::NONE {
+                MouseKind::Moved
+            } else {
+                MouseKind::Other
+            };
+            // Termwiz reports mouse positions 1-based
+            AppEvent::Mouse(Mouse { column: mouse.x.saturating_sub(1), row: mouse.y.saturating_sub(1), kind })
+        }
+        InputEvent::Resized { .. } => AppEvent::Resize,
+        _ => return None,
+    };
+    Some(event)
+}
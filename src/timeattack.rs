@@ -0,0 +1,106 @@
+// Time-attack mode: every puzzle comes with a countdown scaled by its
+// difficulty, and each cell's first correct placement buys a few more
+// seconds; clearing it and placing it again doesn't. Solving in
+// time loads the next puzzle and extends the streak; running out ends the
+// run. The longest streak is kept in the data dir.
+
+use std::{io, path::Path, time::Duration};
+
+use crate::storage;
+
+const RECORDS_FILE: &str = "time_attack.txt";
+// Countdown for a puzzle: a base plus a share per difficulty star
+const BASE_TIME: Duration = Duration::from_secs(60);
+const TIME_PER_STAR: Duration = Duration::from_secs(90);
+// Added for every correct placement
+pub const BONUS: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Default)]
+pub struct TimeAttack {
+    // Countdown for the current puzzle, including bonuses earned so far
+    pub allowance: Duration,
+    // Puzzles solved in time during this run
+    pub streak: u32,
+    pub best_streak: u32,
+    // Cells of the current puzzle that have earned their bonus
+    rewarded: Vec<(usize, usize)>,
+    saved: bool,
+}
+
+impl TimeAttack {
+    // Start a run against the best streak stored in the data dir
+    pub fn load(stars: u8) -> Self {
+        let best_streak = storage::read(RECORDS_FILE).map_or(0, |text| parse_best(&text));
+        Self { allowance: allowance(stars), best_streak, ..Self::default() }
+    }
+
+    pub fn remaining(&self, elapsed: Duration) -> Duration {
+        self.allowance.saturating_sub(elapsed)
+    }
+
+    // The bonus for a correct placement at `cell`, once per cell and puzzle
+    pub fn bonus(&mut self, cell: (usize, usize)) {
+        if !self.rewarded.contains(&cell) {
+            self.rewarded.push(cell);
+            self.allowance += BONUS;
+        }
+    }
+
+    // Count a puzzle solved in time and set the countdown for the next one
+    pub fn solved(&mut self, next_stars: u8) {
+        self.streak += 1;
+        self.best_streak = self.best_streak.max(self.streak);
+        self.allowance = allowance(next_stars);
+        self.rewarded.clear();
+    }
+
+    // Save the best streak once the run is over
+    pub fn finish(&mut self) -> io::Result<()> {
+        if self.saved {
+            return Ok(());
+        }
+        self.saved = true;
+        storage::update(RECORDS_FILE, |text| format!("best_streak = {}\n", parse_best(text).max(self.best_streak))).map(|_| ())
+    }
+}
+
+pub fn allowance(stars: u8) -> Duration {
+    BASE_TIME + TIME_PER_STAR * stars as u32
+}
+
+// Keep the longer of our best streak and the one kept by another install, in
+// its data dir `from`
+pub fn import(from: &Path) -> io::Result<bool> {
+    storage::merge_from(from, RECORDS_FILE, |ours, theirs| format!("best_streak = {}\n", parse_best(ours).max(parse_best(theirs))))
+}
+
+fn parse_best(text: &str) -> u32 {
+    text.lines()
+        .filter_map(|line| line.split_once('='))
+        .find(|(key, _)| key.trim() == "best_streak")
+        .and_then(|(_, value)| value.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{allowance, parse_best, TimeAttack, BONUS};
+    use std::time::Duration;
+
+    #[test]
+    fn countdown_grows_with_bonuses_and_streaks_are_kept() {
+        let mut run = TimeAttack { allowance: allowance(1), ..TimeAttack::default() };
+        assert_eq!(run.remaining(Duration::from_secs(30)), Duration::from_secs(120));
+        run.bonus((0, 0));
+        run.bonus((0, 0));
+        assert_eq!(run.remaining(Duration::from_secs(30)), Duration::from_secs(120) + BONUS);
+        assert_eq!(run.remaining(Duration::from_secs(900)), Duration::ZERO);
+
+        run.solved(3);
+        run.solved(3);
+        assert_eq!((run.streak, run.best_streak, run.allowance), (2, 2, allowance(3)));
+
+        assert_eq!(parse_best("best_streak = 7\n"), 7);
+        assert_eq!(parse_best("garbage"), 0);
+    }
+}
@@ -1,63 +1,1149 @@
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect, Alignment},
+    backend::TestBackend,
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Margin, Rect, Alignment},
     style::{Color, Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
-//     text::Span,
-    Frame,
+    Frame, Terminal,
 };
 
-use crate::model::{Game, GameState};
+use std::time::Duration;
+
+use crate::{
+    config::Config,
+    constraints::Clues,
+    hotseat::HotSeat,
+    model::{Erases, Explanation, Game},
+    stats::Stats,
+    screen::Screen,
+    puzzle::Variant,
+    qr::QrCode,
+    solver::{Contradiction, Refutation},
+    about,
+    command::Command,
+    dialog::{Dialog, Kind},
+    i18n::{t, tf},
+    macros::Pending,
+    speedrun::Speedrun,
+    statusbar::{Priority, StatusBar},
+    symmetry::{clue_symmetries, Symmetry},
+    weekly,
+};
+
+// How long the points for the previous endless puzzle stay in the status line
+const ENDLESS_POINTS_SHOWN: Duration = Duration::from_secs(5);
+// Frames of the spinner on the search's progress screen, a tenth of a second each
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+// Timings measured by the game loop, shown in the debug overlay
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameStats {
+    // Time the previous frame took to draw and flush
+    pub render_time: Duration,
+    pub frames: u64,
+}
+
+// Returns where the board was drawn, if it is visible and not covered by a popup
+pub fn draw(f: &mut Frame, game: &Game, config: &Config, stats: &FrameStats) -> Option<Rect> {
+    let board_area = draw_screen(f, game, config, stats);
+    if game.light_background {
+        for_light_background(f);
+    }
+    board_area
+}
+
+// Text on the terminal's own background is colored for a dark one; on a
+// light one each color is swapped for a darker one of its hue. Anything with
+// a background of its own, like the board and the popups, keeps its colors.
+fn for_light_background(f: &mut Frame) {
+    for cell in f.buffer_mut().content.iter_mut().filter(|cell| cell.bg == Color::Reset) {
+        cell.fg = match cell.fg {
+            Color::White | Color::Gray => Color::Black,
+            Color::DarkGray => Color::Rgb(90, 90, 90),
+            Color::Yellow | Color::LightYellow => Color::Rgb(140, 90, 0),
+            Color::Cyan | Color::LightCyan => Color::Rgb(0, 100, 140),
+            Color::Green | Color::LightGreen => Color::Rgb(0, 120, 0),
+            Color::LightRed => Color::Red,
+            Color::LightBlue => Color::Blue,
+            Color::LightMagenta => Color::Magenta,
+            color => color,
+        };
+    }
+}
+
+fn draw_screen(f: &mut Frame, game: &Game, config: &Config, stats: &FrameStats) -> Option<Rect> {
+    if is_compact(f.area()) {
+        let board_area = draw_compact(f, game, config);
+        return draw_overlays(f, game, stats, board_area, vec![("screen", f.area())]);
+    }
 
-pub fn draw(f: &mut Frame, game: &Game) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
             [
                 Constraint::Length(3), // Title
                 Constraint::Min(0),    // Game Board
+                Constraint::Length(race_rows(game)),
                 Constraint::Length(3), // Instructions
             ]
             .as_ref(),
         )
         .split(f.area());
+    let mut rects = vec![("screen", f.area()), ("title", chunks[0]), ("status", chunks[3])];
 
     // Title
-    let title = Paragraph::new("RustDoku6")
+    let title = Paragraph::new(title_text(game))
         .alignment(Alignment::Center)
         .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(title, chunks[0]);
     
     // Game Board Area
-    draw_board(f, game, chunks[1]);
+    let board_area = if game.screen() == Screen::Paused && config.blank_on_pause {
+        let paused = Paragraph::new(t("title.paused"))
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+        f.render_widget(paused, chunks[1]);
+        None
+    } else if matches!(game.screen(), Screen::Preview | Screen::Generating) {
+        draw_preview(f, game, chunks[1]);
+        None
+    } else if game.screen() == Screen::History {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(30)])
+            .split(chunks[1]);
+        draw_undo_tree(f, game, columns[1]);
+        rects.push(("history", columns[1]));
+        Some(draw_board(f, game, columns[0]))
+    } else if !legend(game).is_empty() || game.speedrun.is_some() {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(30)])
+            .split(chunks[1]);
+        // A variant speedrun stacks its splits over the legend
+        let (splits, rules) = match (&game.speedrun, legend(game).is_empty()) {
+            (Some(_), false) => {
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(8), Constraint::Min(0)])
+                    .split(columns[1]);
+                (Some(rows[0]), Some(rows[1]))
+            }
+            (Some(_), true) => (Some(columns[1]), None),
+            (None, _) => (None, Some(columns[1])),
+        };
+        if let (Some(run), Some(area)) = (&game.speedrun, splits) {
+            draw_splits(f, run, area);
+            rects.push(("splits", area));
+        }
+        if let Some(area) = rules {
+            draw_legend(f, game, area);
+            rects.push(("legend", area));
+        }
+        Some(draw_board(f, game, columns[0]))
+    } else {
+        Some(draw_board(f, game, chunks[1]))
+    };
 
     // Instructions
-    // Instructions
-    let status_text = match game.state {
-        GameState::Playing => {
-            let mode_str = match game.mode {
-                crate::model::InputMode::Normal => "NORMAL",
-                crate::model::InputMode::Pencil => "PENCIL",
+    f.render_widget(status_bar(game), chunks[3]);
+    if race_rows(game) > 0 {
+        draw_race(f, game, chunks[2]);
+        rects.push(("race", chunks[2]));
+    }
+
+    draw_overlays(f, game, stats, board_area, rects)
+}
+
+// The status bar's sections for the current state
+fn status_bar(game: &Game) -> StatusBar {
+    let mut bar = StatusBar::new(Style::default().fg(status_color(game.screen())));
+    let time = clock_text(game);
+    let mistakes = match game.weekly {
+        Some(_) => tf("status.mistakes_of", &[&game.mistakes, &weekly::MAX_MISTAKES]),
+        None => tf("status.mistakes", &[&game.mistakes]),
+    };
+    if let Some((_, dialog)) = &game.dialog {
+        match dialog.kind {
+            Kind::Message => bar.key(Command::Dismiss, t("key.ok")),
+            Kind::Confirm => {
+                bar.push(Priority::Keys, format!("y: {}", t("key.yes")));
+                bar.push(Priority::Keys, format!("n: {}", t("key.no")));
+            }
+            Kind::TextInput(_) => {
+                bar.key(Command::Confirm, t("key.ok"));
+                bar.key(Command::Back, t("key.cancel"));
+            }
+            Kind::ListPicker { .. } => {
+                bar.key(Command::MoveCursor(1, 0), t("key.choose"));
+                bar.key(Command::Confirm, t("key.ok"));
+                bar.key(Command::Back, t("key.cancel"));
+            }
+        }
+        return bar;
+    }
+    if let Some(addr) = &game.spectating {
+        match game.screen() {
+            Screen::Won => {
+                bar.push(Priority::Message, tf("status.watching", &[addr]));
+                bar.push(Priority::Clock, tf("status.solved_in", &[&time]));
+            }
+            state => {
+                bar.push(Priority::Message, tf("status.watching", &[addr]));
+                bar.push(Priority::Detail, screen_label(state).to_string());
+                bar.push(Priority::Clock, tf("status.time", &[&time]));
+            }
+        }
+        bar.push(Priority::Detail, mistakes);
+        bar.key(Command::Quit, t("key.quit"));
+        return bar;
+    }
+    if let Some(pending) = game.macros.pending {
+        let message = match pending {
+            Pending::Record => t("status.record_macro"),
+            Pending::Play => t("status.play_macro"),
+        };
+        bar.push(Priority::Message, message);
+        bar.key(Command::EnterDigit(1), t("key.register"));
+        bar.key(Command::Back, t("key.cancel"));
+        return bar;
+    }
+    if let Some(row) = game.go_to {
+        bar.push(Priority::Message, tf("status.go_to", &[&go_to_text(row)]));
+        bar.key(Command::EnterDigit(1), if row.is_none() { t("key.row") } else { t("key.column") });
+        bar.key(Command::Back, t("key.cancel"));
+        return bar;
+    }
+    match game.screen() {
+        Screen::Playing => {
+            if game.hypothesis.is_some() {
+                bar.push(Priority::Message, t("status.what_if"));
+            }
+            if let Some((register, _)) = &game.macros.recording {
+                bar.push(Priority::Message, tf("status.recording", &[register]));
+                bar.key(Command::RecordMacro, t("key.stop"));
+            }
+            match (&game.hotseat, game.coop_player) {
+                (Some(seat), _) => {
+                    bar.push(Priority::Message, tf("status.player_to_play", &[&(seat.turn + 1)]));
+                    bar.push(Priority::Detail, hotseat_scores(seat));
+                }
+                (None, Some(player)) => bar.push(Priority::Detail, tf("status.coop", &[&(player + 1), &game.peer_cursors.len()])),
+                (None, None) => {
+                    // The points for the previous puzzle show for its first few seconds
+                    match game.endless.as_ref().and_then(|session| session.last_points) {
+                        Some(points) if game.timer.elapsed() < ENDLESS_POINTS_SHOWN => bar.push(Priority::Message, tf("status.solved_points", &[&points])),
+                        _ => {}
+                    }
+                    if let Some(attack) = &game.time_attack {
+                        bar.push(Priority::Detail, tf("status.streak", &[&attack.streak]));
+                    }
+                }
+            }
+            let notice = match (game.progress_check, game.rejected) {
+                (Some(0), _) => t("status.on_track").to_string(),
+                (Some(1), _) => t("status.off_track_one").to_string(),
+                (Some(wrong), _) => tf("status.off_track", &[&wrong]),
+                (None, Some(rejected)) => {
+                    let (r, c) = rejected.peer;
+                    tf("status.blocked", &[&rejected.value, &unit_name(rejected.unit), &(r + 1), &(c + 1)])
+                }
+                // Which entry is wrong stays hidden until the player asks for a hint
+                _ if game.hint.is_some() => String::new(),
+                _ if game.dead_end().is_some() => tf("status.dead_end", &[&crate::command::key_label(Command::Hint)]),
+                _ if game.wrong_at_cursor().is_some() => tf("status.wrong_entry", &[&crate::command::key_label(Command::Explain)]),
+                _ => String::new(),
             };
-            format!("Mode: {} (p) | Mistakes: {} | Arrows/1-6/BS | i: About | q: Quit", mode_str, game.mistakes)
+            bar.push(Priority::Message, notice);
+            match &game.hint {
+                Some(step) if step.value == 0 => bar.push(Priority::Message, tf("status.hint_wrong", &[&(step.row + 1), &(step.col + 1)])),
+                Some(step) => bar.push(Priority::Message, tf("status.hint", &[&step.technique, &(step.row + 1), &(step.col + 1)])),
+                None => {}
+            }
+            let mode = match game.mode {
+                crate::model::InputMode::Normal => t("mode.normal"),
+                crate::model::InputMode::Pencil => t("mode.pencil"),
+            };
+            bar.push(Priority::Detail, tf("status.mode", &[&mode, &crate::command::key_label(Command::ToggleMode)]));
+            bar.push(Priority::Clock, tf("status.time", &[&time]));
+            // Players' mistakes are in their own scores
+            let shared = game.hotseat.is_some() || game.coop_player.is_some();
+            if !shared {
+                bar.push(Priority::Detail, mistakes);
+                if let (Some(estimate), Some(pace)) = (game.estimate(), game.pace()) {
+                    bar.push(Priority::Detail, tf("status.typical", &[&format_duration(estimate), &format!("{:+}", pace)]));
+                }
+            }
+            bar.push(Priority::Difficulty, stars_text(game.stars()));
+            playing_keys(&mut bar, game);
+        }
+        Screen::Won if game.hotseat.is_some() => {
+            bar.push(Priority::Message, t("status.solved"));
+            bar.push(Priority::Clock, tf("status.time", &[&time]));
+            bar.key(Command::Quit, t("key.quit"));
+        }
+        Screen::Won if game.coop_player.is_some() => {
+            bar.push(Priority::Message, t("status.won"));
+            bar.push(Priority::Clock, tf("status.time", &[&time]));
+            bar.push(Priority::Detail, mistakes);
+            bar.key(Command::Quit, t("key.quit"));
+        }
+        Screen::Won => match &game.heatmap {
+            Some(counts) => {
+                let (total, cells) = counts.iter().flatten().fold((0, 0), |(total, cells), &n| (total + n, cells + (n > 0) as u32));
+                bar.push(Priority::Message, tf("status.heatmap", &[&total, &cells]));
+                bar.push(Priority::Detail, erases_text(game.erases));
+                bar.key(Command::ToggleHeatmap, t("key.hide"));
+                bar.key(Command::NewGame, t("key.new_game"));
+                bar.key(Command::Quit, t("key.quit"));
+            }
+            None => {
+                bar.push(Priority::Message, t("status.won"));
+                bar.push(Priority::Clock, tf("status.time", &[&time]));
+                bar.push(Priority::Detail, mistakes);
+                bar.push(Priority::Message, review_text(game));
+                if let Some(result) = bot_result(game) {
+                    bar.push(Priority::Message, result);
+                }
+                bar.key(Command::EnterDigit(1), t("key.highlight"));
+                bar.key(Command::ToggleHeatmap, t("key.heatmap"));
+                bar.key(Command::NewGame, t("key.new_game"));
+                bar.key(Command::ToggleStats, t("key.stats"));
+                bar.key(Command::Quit, t("key.quit"));
+            }
         },
-        GameState::Won => format!("YOU WON! Mistakes: {} | Press 'q' to quit.", game.mistakes),
-        GameState::About => "Press 'i' or 'Esc' to return to game.".to_string(),
+        Screen::Viewing => {
+            let (index, count) = game.viewing.unwrap_or((0, 1));
+            bar.push(Priority::Message, tf("status.viewing", &[&(index + 1), &count]));
+            bar.push(Priority::Difficulty, stars_text(game.stars()));
+            bar.key(Command::EnterDigit(1), t("key.highlight"));
+            if count > 1 {
+                bar.key(Command::NextPuzzle, t("key.next"));
+                bar.key(Command::PreviousPuzzle, t("key.previous"));
+            }
+            bar.key(Command::Quit, t("key.quit"));
+        }
+        Screen::Preview => {
+            bar.push(Priority::Message, t("status.preview"));
+            bar.push(Priority::Difficulty, stars_text(game.stars()));
+            bar.key(Command::Confirm, t("key.start"));
+            bar.key(Command::NewGame, t("key.reroll"));
+            bar.key(Command::Quit, t("key.quit"));
+        }
+        Screen::Generating => {
+            bar.push(Priority::Message, t("status.generating"));
+            bar.key(Command::Back, t("key.cancel"));
+        }
+        Screen::OutOfMistakes => {
+            bar.push(Priority::Message, tf("status.out_of_mistakes", &[&game.mistakes]));
+            bar.push(Priority::Clock, tf("status.time", &[&time]));
+            bar.key(Command::Quit, t("key.quit"));
+        }
+        Screen::TimeUp => {
+            let (streak, best) = game.time_attack.as_ref().map_or((0, 0), |attack| (attack.streak, attack.best_streak));
+            bar.push(Priority::Message, tf("status.time_up", &[&streak, &best]));
+            bar.key(Command::Quit, t("key.quit"));
+        }
+        Screen::Paused => {
+            bar.push(Priority::Message, tf("status.paused", &[&time]));
+            bar.key(Command::Dismiss, t("key.resume"));
+            bar.key(Command::Quit, t("key.quit"));
+        }
+        Screen::About => {
+            bar.key(Command::MoveCursor(1, 0), t("key.scroll"));
+            bar.key(Command::Back, t("key.back"));
+        }
+        Screen::Stats => {
+            bar.key(Command::MoveCursor(0, 1), t("key.tab"));
+            bar.key(Command::Back, t("key.back"));
+        }
+        Screen::Summary => {
+            bar.push(Priority::Message, t("status.thanks"));
+            bar.key(Command::Dismiss, t("key.quit"));
+        }
+        Screen::Share => {
+            bar.push(Priority::Message, t("status.scan"));
+            bar.key(Command::Back, t("key.back"));
+        }
+        Screen::Explain => bar.key(Command::Back, t("key.back")),
+        Screen::History => {
+            let (entry, branch, branches) = history_position(game);
+            bar.push(Priority::Message, tf("status.history", &[&entry, &branch, &branches]));
+            bar.key(Command::Confirm, t("key.resume"));
+            bar.key(Command::Back, t("key.back"));
+            bar.key(Command::MoveCursor(0, 1), t("key.step"));
+            bar.key(Command::MoveCursor(1, 0), t("key.branch"));
+        }
+        Screen::Demo => {
+            match &game.demo_last {
+                Some(step) if step.value == 0 => bar.push(Priority::Message, tf("status.watch_wrong", &[&step.technique, &(step.row + 1), &(step.col + 1)])),
+                Some(step) => bar.push(Priority::Message, tf("status.watch_step", &[&step.technique, &(step.row + 1), &(step.col + 1), &step.value])),
+                None => bar.push(Priority::Message, t("status.watch_thinking")),
+            }
+            bar.key(Command::ToggleDemo, t("key.stop"));
+            bar.key(Command::Quit, t("key.quit"));
+        }
+    }
+    bar
+}
+
+// Keys that do something on the board as it is, most useful first
+fn playing_keys(bar: &mut StatusBar, game: &Game) {
+    if game.hypothesis.is_some() {
+        bar.key(Command::Hypothesis, t("key.keep"));
+        bar.key(Command::DiscardHypothesis, t("key.discard"));
+    }
+    let (r, c) = game.cursor;
+    let cell = &game.grid.cells[r][c];
+    // The weekly challenge is played without assists
+    let assisted = game.weekly.is_none();
+    if assisted && game.wrong_at_cursor().is_some() {
+        bar.key(Command::Explain, t("key.why"));
+    }
+    match game.mode {
+        crate::model::InputMode::Normal => {
+            bar.key(Command::EnterDigit(1), t("key.enter"));
+            bar.key(Command::EnterAlternate(1), t("key.mark"));
+        }
+        crate::model::InputMode::Pencil => {
+            bar.key(Command::EnterDigit(1), t("key.mark"));
+            bar.key(Command::EnterAlternate(1), t("key.enter"));
+        }
+    }
+    if !cell.is_fixed() && cell.value().is_some() {
+        bar.key(Command::ToggleLock, if cell.locked() { t("key.unlock") } else { t("key.lock") });
+        if !cell.locked() {
+            bar.key(Command::ClearCell, t("key.clear"));
+        }
+    }
+    // Undo is off with others on the board
+    if game.hotseat.is_none() && game.coop_player.is_none() {
+        bar.key(Command::Undo, t("key.undo"));
+    }
+    if game.last_entry.is_some() {
+        bar.key(Command::Repeat, t("key.repeat"));
+    }
+    if assisted {
+        bar.key(Command::Hint, t("key.hint"));
+        bar.key(Command::ToggleCounts, if game.show_counts { t("key.hide_counts") } else { t("key.counts") });
+        bar.key(Command::ToggleLastCells, if game.show_last_cells { t("key.hide_last_cells") } else { t("key.last_cells") });
+    }
+    bar.key(Command::ToggleCoordinates, if game.show_coordinates { t("key.hide_coordinates") } else { t("key.coordinates") });
+    if game.hypothesis.is_none() {
+        bar.key(Command::Hypothesis, t("key.what_if"));
+    }
+    if assisted {
+        bar.key(Command::ToggleDemo, t("key.watch"));
+    }
+    bar.key(Command::Share, t("key.share"));
+    bar.key(Command::ToggleAbout, t("key.about"));
+    bar.key(Command::Quit, t("key.quit"));
+}
+
+// Who the solve races: the best earlier solve, once there is one, and the bot,
+// each with its entries by now on the clock
+fn rivals(game: &Game) -> Vec<(&'static str, usize)> {
+    let elapsed = game.timer.elapsed();
+    let mut rivals = Vec::new();
+    if let Some(best) = game.ghost.as_ref().and_then(|ghost| ghost.progress(elapsed)) {
+        rivals.push((t("race.best"), best));
+    }
+    if let Some(bot) = &game.bot {
+        rivals.push((t("race.bot"), bot.progress(elapsed)));
+    }
+    rivals
+}
+
+// A line for the player's bar and one for each rival's, or none without rivals
+fn race_rows(game: &Game) -> u16 {
+    match rivals(game).len() {
+        _ if game.spectating.is_some() => 0,
+        0 => 0,
+        n => n as u16 + 1,
+    }
+}
+
+// One bar for this solve's correct entries and one for each rival's at the
+// same time on the clock
+fn draw_race(f: &mut Frame, game: &Game, area: Rect) {
+    let rivals = rivals(game);
+    let total = 36 - game.grid.clue_count();
+    let you = game.correct_entries();
+    let width = (area.width as usize).saturating_sub(16).min(48);
+    let bar = |done: usize| {
+        let filled = (done * width).checked_div(total).unwrap_or(0).min(width);
+        format!("{}{} {:>2}/{}", "█".repeat(filled), "░".repeat(width - filled), done, total)
     };
-    
-    let instructions = Paragraph::new(status_text)
-        .alignment(Alignment::Center)
-        .style(Style::default().fg(if let GameState::Won = game.state { Color::Green } else { Color::White }))
-        .block(Block::default().borders(Borders::ALL));
-    f.render_widget(instructions, chunks[2]);
+    let ahead = if rivals.iter().all(|&(_, done)| you >= done) { Color::Green } else { Color::LightRed };
+    // The names padded alike, so the bars line up
+    let pad = rivals.iter().map(|(name, _)| name).chain([&t("race.you")]).map(|name| name.chars().count()).max().unwrap_or(0).max(5) + 1;
+    let name = |name: &str| Span::raw(format!("{:<pad$}", name));
+    let mut lines = vec![Line::from(vec![name(t("race.you")), Span::styled(bar(you), Style::default().fg(ahead))])];
+    for (rival, done) in rivals {
+        lines.push(Line::from(vec![name(rival), Span::styled(bar(done), Style::default().fg(Color::Gray))]));
+    }
+    f.render_widget(Paragraph::new(lines).alignment(Alignment::Center), area);
+}
+
+// How the race against the bot ended, for the review
+fn bot_result(game: &Game) -> Option<String> {
+    let bot = game.bot.as_ref()?;
+    let time = game.timer.elapsed();
+    Some(match bot.finish() {
+        Some(finish) if finish < time => tf("status.bot_won", &[&format_duration(time - finish)]),
+        _ => t("status.bot_beaten").to_string(),
+    })
+}
+
+// e.g. "5 corrections, 1 logical error"
+fn erases_text(erases: Erases) -> String {
+    let count = |n: u32, one: &str, many: &str| tf(if n == 1 { one } else { many }, &[&n]);
+    format!("{}, {}", count(erases.corrections, "review.correction", "review.corrections"), count(erases.errors, "review.logical_error", "review.logical_errors"))
+}
+
+// How the review says the cell under the cursor gets solved
+fn review_text(game: &Game) -> String {
+    let (r, c) = game.cursor;
+    if game.grid.cells[r][c].is_fixed() {
+        return tf("review.given", &[&(r + 1), &(c + 1)]);
+    }
+    match game.review_step(r, c) {
+        Some((n, step)) => tf("review.step", &[&(r + 1), &(c + 1), &n, &game.review.len(), &step.technique]),
+        None => tf("review.guessing", &[&(r + 1), &(c + 1)]),
+    }
+}
+
+// What a spectator is told the player is at
+fn screen_label(screen: Screen) -> &'static str {
+    t(match screen {
+        Screen::Playing => "screen.playing",
+        Screen::Won => "status.solved",
+        Screen::TimeUp => "screen.time_up",
+        Screen::OutOfMistakes => "screen.out_of_mistakes",
+        Screen::Viewing => "screen.viewing",
+        Screen::Preview => "screen.preview",
+        Screen::Generating => "screen.generating",
+        Screen::Demo => "screen.demo",
+        Screen::Paused => "screen.paused",
+        Screen::About | Screen::Stats | Screen::Share | Screen::Explain | Screen::History | Screen::Summary => "screen.menus",
+    })
+}
+
+// Shorten a status line that doesn't fit in `width` by dropping "|"-separated
+// parts from the end, where the key help is, and cutting the first part short
+// if even that is too long
+fn fit_status(text: &str, width: u16) -> String {
+    let width = width as usize;
+    let fits = |line: &str| Line::from(line).width() <= width;
+    if fits(text) {
+        return text.to_string();
+    }
+    let parts: Vec<&str> = text.split(" | ").collect();
+    for n in (1..parts.len()).rev() {
+        let shortened = format!("{} | …", parts[..n].join(" | "));
+        if fits(&shortened) {
+            return shortened;
+        }
+    }
+    if width == 0 {
+        return String::new();
+    }
+    parts[0].chars().take(width - 1).chain(Some('…')).collect()
+}
+
+// The entry shown in the history browser, and which of its siblings it is
+fn history_position(game: &Game) -> (usize, usize, usize) {
+    let tree = &game.undo_tree;
+    let node = game.history_node;
+    let siblings = tree.node(node).parent.map_or(&[][..], |parent| &tree.node(parent).children[..]);
+    let branch = siblings.iter().position(|&s| s == node).map_or(1, |i| i + 1);
+    (tree.depth(node), branch, siblings.len().max(1))
+}
+
+// The screen `draw` gives on a `width` x `height` terminal, as plain text:
+// one line per row, trailing spaces trimmed, colors and styles dropped
+pub fn snapshot(game: &Game, config: &Config, width: u16, height: u16) -> String {
+    let buffer = rendered(game, config, width, height);
+    let lines: Vec<String> = (0..height).map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect::<String>().trim_end().to_string()).collect();
+    lines.join("\n") + "\n"
+}
+
+// The screen drawn off screen, at the size given
+fn rendered(game: &Game, config: &Config, width: u16, height: u16) -> Buffer {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).expect("the test backend never fails");
+    terminal
+        .draw(|f| {
+            draw(f, game, config, &FrameStats::default());
+        })
+        .expect("the test backend never fails");
+    terminal.backend().buffer().clone()
+}
+
+// The screen as `snapshot` draws it, but with its colors and bold kept as
+// ANSI escapes, for a terminal of that size
+pub fn ansi_snapshot(game: &Game, config: &Config, width: u16, height: u16) -> String {
+    let buffer = rendered(game, config, width, height);
+    let mut text = String::new();
+    for y in 0..height {
+        let mut style = None;
+        for x in 0..width {
+            let cell = &buffer[(x, y)];
+            let cell_style = (cell.fg, cell.bg, cell.modifier.contains(Modifier::BOLD));
+            if style != Some(cell_style) {
+                let bold = if cell_style.2 { "1" } else { "22" };
+                text.push_str(&format!("\x1b[{};{};{}m", bold, sgr(cell.fg, false), sgr(cell.bg, true)));
+                style = Some(cell_style);
+            }
+            text.push_str(cell.symbol());
+        }
+        text.push_str("\x1b[0m");
+        // No newline after the last line, which would scroll the screen
+        if y + 1 < height {
+            text.push_str("\r\n");
+        }
+    }
+    text
+}
+
+// The SGR parameters for a foreground or background color
+fn sgr(color: Color, background: bool) -> String {
+    let base = if background { 40 } else { 30 };
+    match color {
+        Color::Reset => (base + 9).to_string(),
+        Color::Black => base.to_string(),
+        Color::Red => (base + 1).to_string(),
+        Color::Green => (base + 2).to_string(),
+        Color::Yellow => (base + 3).to_string(),
+        Color::Blue => (base + 4).to_string(),
+        Color::Magenta => (base + 5).to_string(),
+        Color::Cyan => (base + 6).to_string(),
+        Color::Gray => (base + 7).to_string(),
+        Color::DarkGray => (base + 60).to_string(),
+        Color::LightRed => (base + 61).to_string(),
+        Color::LightGreen => (base + 62).to_string(),
+        Color::LightYellow => (base + 63).to_string(),
+        Color::LightBlue => (base + 64).to_string(),
+        Color::LightMagenta => (base + 65).to_string(),
+        Color::LightCyan => (base + 66).to_string(),
+        Color::White => (base + 67).to_string(),
+        Color::Indexed(n) => format!("{};5;{}", base + 8, n),
+        Color::Rgb(r, g, b) => format!("{};2;{};{};{}", base + 8, r, g, b),
+    }
+}
 
+// A centered one-line message on an otherwise empty screen
+#[cfg_attr(not(feature = "net"), allow(dead_code))]
+pub fn draw_waiting(f: &mut Frame, text: &str, light_background: bool) {
+    let area = f.area();
+    let line = Rect::new(area.x, area.y + area.height / 2, area.width, 1.min(area.height));
+    f.render_widget(Paragraph::new(text).alignment(Alignment::Center).style(Style::default().fg(Color::Yellow)), line);
+    if light_background {
+        for_light_background(f);
+    }
+}
+
+// Popups and the debug overlay, drawn over either layout. Popups hide the board
+// from the graphics renderer.
+fn draw_overlays(f: &mut Frame, game: &Game, stats: &FrameStats, board_area: Option<Rect>, mut rects: Vec<(&str, Rect)>) -> Option<Rect> {
     // Render About Popup if needed
-    if let GameState::About = game.state {
-        draw_about_popup(f);
+    if let Screen::About = game.screen() {
+        draw_about_popup(f, game);
+    }
+    if let Screen::Stats = game.screen() {
+        draw_stats_popup(f, game);
+    }
+    if let Screen::Summary = game.screen() {
+        draw_summary_popup(f, game);
+    }
+    if let Screen::Share = game.screen() {
+        draw_share_popup(f, game);
+    }
+    if let (Screen::Explain, Some(explanation)) = (game.screen(), &game.explanation) {
+        draw_explain_popup(f, explanation);
+    }
+    let results = match (&game.hotseat, game.screen()) {
+        (Some(seat), Screen::Won) => Some(seat),
+        _ => None,
+    };
+    if let Some(seat) = results {
+        draw_hotseat_results(f, seat, game.timer.elapsed());
+    }
+    if let Some((_, dialog)) = &game.dialog {
+        draw_dialog(f, dialog);
+    }
+    if game.show_debug {
+        rects.extend(board_area.map(|area| ("board", area)));
+        draw_debug_overlay(f, game, stats, &rects);
+    }
+
+    match game.screen() {
+        Screen::About | Screen::Stats | Screen::Summary | Screen::Share | Screen::Explain => None,
+        _ if results.is_some() || game.dialog.is_some() => None,
+        _ => board_area,
+    }
+}
+
+// Below this size the regular layout shrinks cells to a single row and pencil marks stop fitting
+const COMPACT_MIN_WIDTH: u16 = 60;
+const COMPACT_MIN_HEIGHT: u16 = 25;
+
+fn is_compact(area: Rect) -> bool {
+    area.width < COMPACT_MIN_WIDTH || area.height < COMPACT_MIN_HEIGHT
+}
+
+// Compact layout for small terminals: one-line title and status, and a board
+// framed with half-block characters that fits in 21x10 cells.
+fn draw_compact(f: &mut Frame, game: &Game, config: &Config) -> Option<Rect> {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)])
+        .split(f.area());
+
+    let title = Paragraph::new(compact_title_text(game))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD));
+    f.render_widget(title, chunks[0]);
+
+    let board_area = if game.screen() == Screen::Paused && config.blank_on_pause {
+        let paused = Paragraph::new(t("title.paused"))
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+        f.render_widget(paused, chunks[1]);
+        None
+    } else if matches!(game.screen(), Screen::Preview | Screen::Generating) {
+        draw_preview(f, game, chunks[1]);
+        None
+    } else {
+        Some(draw_compact_board(f, game, chunks[1]))
+    };
+
+    let status = Paragraph::new(fit_status(&compact_status_text(game), chunks[2].width))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(status_color(game.screen())));
+    f.render_widget(status, chunks[2]);
+
+    board_area
+}
+
+// The search's progress, with a spinner that turns while it runs
+fn generating_lines(game: &Game) -> Vec<String> {
+    let Some(search) = &game.search else {
+        return Vec::new();
+    };
+    let elapsed = search.elapsed();
+    let spinner = SPINNER[(elapsed.as_millis() / 100) as usize % SPINNER.len()];
+    vec![
+        format!("{} {}", spinner, tf("generating.profile", &[&search.label])),
+        tf("generating.attempts", &[&search.attempts(), &search.max_attempts, &elapsed.as_secs()]),
+    ]
+}
+
+// What a new puzzle is like, shown in place of its board until it's started
+fn preview_lines(game: &Game) -> Vec<String> {
+    let symmetries: Vec<&str> = clue_symmetries(&game.grid)
+        .into_iter()
+        .map(|symmetry| match symmetry {
+            Symmetry::QuarterTurn => t("symmetry.quarter_turn"),
+            Symmetry::HalfTurn => t("symmetry.half_turn"),
+            Symmetry::Mirror => t("symmetry.mirror"),
+            Symmetry::Flip => t("symmetry.flip"),
+        })
+        .collect();
+    let symmetry = if symmetries.is_empty() { t("symmetry.none").to_string() } else { symmetries.join(", ") };
+    let needs = match &game.preview_needs {
+        Some(names) => names.join(&format!(" {} ", t("preview.or"))),
+        None => t("preview.beyond").to_string(),
+    };
+    let mut lines = vec![
+        t("preview.title").to_string(),
+        String::new(),
+        tf("preview.clues", &[&game.grid.clue_count()]),
+        tf("preview.symmetry", &[&symmetry]),
+        tf("preview.needs", &[&needs]),
+        stars_text(game.stars()),
+    ];
+    // The digit keys switch profile; the one followed is in brackets
+    if !game.profiles.is_empty() {
+        let profiles: Vec<String> = game
+            .profiles
+            .iter()
+            .enumerate()
+            .map(|(i, profile)| match game.profile == Some(i) {
+                true => format!("[{} {}]", i + 1, profile.name),
+                false => format!("{} {}", i + 1, profile.name),
+            })
+            .collect();
+        lines.extend([String::new(), tf("preview.profiles", &[&profiles.join("  ")])]);
+    }
+    lines
+}
+
+// The preview, or the progress of the search for the next puzzle, in place
+// of the board
+fn draw_preview(f: &mut Frame, game: &Game, area: Rect) {
+    let lines = match game.screen() {
+        Screen::Generating => generating_lines(game),
+        _ => preview_lines(game),
+    };
+    let top = area.height.saturating_sub(lines.len() as u16) / 2;
+    let area = Rect { y: area.y + top, height: area.height - top, ..area };
+    let preview = Paragraph::new(lines.join("\n"))
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+    f.render_widget(preview, area);
+}
+
+fn compact_title_text(game: &Game) -> String {
+    let daily = game.meta.date.as_ref().map_or(String::new(), |date| format!(" {}", date));
+    let endless = game.endless.as_ref().map_or(String::new(), |session| format!(" E{}:{}", session.solved + 1, session.score));
+    let recording = game.macros.recording.as_ref().map_or(String::new(), |(register, _)| format!(" rec{}", register));
+    format!(
+        "RD6{}{}{} {} {}c #{}",
+        daily,
+        endless,
+        recording,
+        stars_text(game.stars()),
+        game.grid.clue_count(),
+        game.meta.seed
+    )
+}
+
+// Abbreviated status line; the full key list is in the regular layout and the README
+// Entries ahead of (+) or behind (-) the best earlier solve, and the bot's
+// marked b, for the compact status
+fn ghost_lead(game: &Game) -> String {
+    let elapsed = game.timer.elapsed();
+    let lead = |done: usize| game.correct_entries() as i64 - done as i64;
+    let ghost = game.ghost.as_ref().and_then(|ghost| ghost.progress(elapsed)).map_or(String::new(), |best| format!(" {:+}", lead(best)));
+    let bot = game.bot.as_ref().map_or(String::new(), |bot| format!(" b{:+}", lead(bot.progress(elapsed))));
+    ghost + &bot
+}
+
+fn compact_status_text(game: &Game) -> String {
+    let time = clock_text(game);
+    let splits = game.speedrun.as_ref().map_or(String::new(), |run| format!(" S{}/6", run.splits.len()));
+    let turn = match (&game.hotseat, game.coop_player) {
+        (Some(seat), _) => format!("P{} {} ", seat.turn + 1, hotseat_scores(seat)),
+        (None, Some(player)) => format!("P{}+{} ", player + 1, game.peer_cursors.len()),
+        (None, None) => String::new(),
+    };
+    match game.screen() {
+        _ if game.dialog.as_ref().is_some_and(|(_, dialog)| dialog.kind == Kind::Confirm) => "y/n".to_string(),
+        _ if game.dialog.is_some() => "⏎ ok | esc".to_string(),
+        _ if game.spectating.is_some() => format!("{} | {}", tf("compact.watch", &[&time, &game.mistakes]), crate::command::key_label(Command::Quit)),
+        _ if game.go_to.is_some() => format!("{} | esc", go_to_text(game.go_to.flatten())),
+        _ if game.macros.pending.is_some() => {
+            let key = match game.macros.pending {
+                Some(Pending::Record) => Command::RecordMacro,
+                _ => Command::PlayMacro,
+            };
+            format!("{} _ | esc", crate::command::key_label(key))
+        }
+        Screen::Playing => {
+            let mode = match game.mode {
+                crate::model::InputMode::Normal => 'N',
+                crate::model::InputMode::Pencil => 'P',
+            };
+            match game.progress_check {
+                Some(0) => return t("compact.on_track").to_string(),
+                Some(wrong) => return tf("compact.off_track", &[&wrong]),
+                None => {}
+            }
+            if let Some(rejected) = game.rejected {
+                return tf("compact.blocked", &[&rejected.value, &(rejected.peer.0 + 1), &(rejected.peer.1 + 1)]);
+            }
+            if game.hint.is_none() && game.dead_end().is_some() {
+                return tf("status.dead_end", &[&crate::command::key_label(Command::Hint)]);
+            }
+            match &game.hint {
+                Some(step) if step.value == 0 => tf("compact.hint_wrong", &[&(step.row + 1), &(step.col + 1)]),
+                Some(step) => format!("r{}c{}: {}", step.row + 1, step.col + 1, step.technique),
+                None if game.hotseat.is_some() || game.coop_player.is_some() => {
+                    format!("{}{} {} | {}", turn, mode, time, key_hints(&[Command::ToggleMode, Command::Hint, Command::ToggleDemo, Command::Share, Command::ToggleAbout, Command::Quit]))
+                }
+                None if game.hypothesis.is_some() => format!(
+                    "{} | {}",
+                    tf("compact.what_if", &[&time, &game.mistakes]),
+                    tf("compact.what_if_keys", &[&crate::command::key_label(Command::Hypothesis), &crate::command::key_label(Command::DiscardHypothesis)])
+                ),
+                None if game.weekly.is_some() => format!(
+                    "{} {} ✗{}{} | {}",
+                    mode,
+                    time,
+                    game.mistakes,
+                    splits,
+                    key_hints(&[Command::ToggleMode, Command::Undo, Command::Share, Command::ToggleAbout, Command::Quit])
+                ),
+                None => format!(
+                    "{} {} ✗{}{}{} | {}",
+                    mode,
+                    time,
+                    game.mistakes,
+                    splits,
+                    ghost_lead(game),
+                    key_hints(&[Command::ToggleMode, Command::Undo, Command::Hint, Command::ToggleDemo, Command::Share, Command::ToggleAbout, Command::Quit])
+                ),
+            }
+        }
+        Screen::Won if game.hotseat.is_some() || game.coop_player.is_some() => format!("{} | {}", tf("compact.won", &[&time, &game.mistakes]), crate::command::key_label(Command::Quit)),
+        Screen::Won => format!(
+            "{} | {}",
+            tf("compact.won", &[&time, &game.mistakes]),
+            key_hints(&[Command::EnterDigit(1), Command::ToggleHeatmap, Command::NewGame, Command::Quit])
+        ),
+        Screen::Viewing => {
+            let (index, count) = game.viewing.unwrap_or((0, 1));
+            format!(
+                "{} | {}",
+                tf("compact.viewing", &[&(index + 1), &count]),
+                key_hints(&[Command::EnterDigit(1), Command::PreviousPuzzle, Command::NextPuzzle, Command::Quit])
+            )
+        }
+        Screen::Preview => format!(
+            "{} | ⏎ {}",
+            tf("compact.preview", &[&game.grid.clue_count(), &stars_text(game.stars())]),
+            key_hints(&[Command::NewGame, Command::Quit])
+        ),
+        Screen::Generating => format!("{} | esc", tf("compact.searching", &[&game.search.as_ref().map_or(0, |search| search.attempts())])),
+        Screen::TimeUp => format!("{} | {}", tf("compact.time_up", &[&game.time_attack.as_ref().map_or(0, |attack| attack.streak)]), crate::command::key_label(Command::Quit)),
+        Screen::OutOfMistakes => format!("{} | {}", tf("compact.out_of_mistakes", &[&time]), crate::command::key_label(Command::Quit)),
+        Screen::Paused => format!("{} | {}", tf("compact.paused", &[&time]), t("compact.any_key")),
+        Screen::About => "↑↓ | esc".to_string(),
+        Screen::Stats => "←→ | esc".to_string(),
+        Screen::Summary => t("compact.any_key_quit").to_string(),
+        Screen::Share | Screen::Explain => "esc".to_string(),
+        Screen::History => {
+            let (entry, branch, branches) = history_position(game);
+            format!("{} ←→↑↓ ⏎ esc", tf("compact.history", &[&entry, &branch, &branches]))
+        }
+        Screen::Demo => {
+            let keys = key_hints(&[Command::ToggleDemo, Command::Quit]);
+            match &game.demo_last {
+                Some(step) if step.value == 0 => format!("r{}c{} {} | {}", step.row + 1, step.col + 1, step.technique, keys),
+                Some(step) => format!("r{}c{}={} {} | {}", step.row + 1, step.col + 1, step.value, step.technique, keys),
+                None => format!("{} | {}", t("compact.watch_solver"), keys),
+            }
+        }
+    }
+}
+
+// A pending go-to chord as typed so far, e.g. "g B _"
+fn go_to_text(row: Option<usize>) -> String {
+    let row = row.map_or('_', row_letter);
+    format!("{} {} _", crate::command::key_label(Command::GoTo), row)
+}
+
+// The keys the keymap in use binds to `commands`, for the compact status
+fn key_hints(commands: &[Command]) -> String {
+    commands.iter().map(|&command| crate::command::key_label(command)).collect::<Vec<_>>().join(" ")
+}
+
+// Each cell is one row by three columns. Box bands are split by a half-height line,
+// drawn as upper half blocks in the color of the cell above over the grid color.
+fn draw_compact_board(f: &mut Frame, game: &Game, area: Rect) -> Rect {
+    let grid_color = Color::Blue;
+    let line = |s: &'static str| Span::styled(s, Style::default().fg(grid_color));
+
+    let mut lines = Vec::with_capacity(10);
+    let mut top = vec![line("▗")];
+    top.extend((0..19).map(|_| line("▄")));
+    top.push(line("▖"));
+    lines.push(Line::from(top));
+
+    for r in 0..6 {
+        let cells: Vec<(String, Style)> = (0..6).map(|c| cell_appearance(game, r, c)).collect();
+        let mut row = vec![line("▐")];
+        for (c, (text, style)) in cells.iter().enumerate() {
+            if c == 3 {
+                row.push(Span::styled(" ", Style::default().bg(grid_color)));
+            }
+            row.push(Span::styled(compact_cell_text(text), *style));
+        }
+        row.push(line("▌"));
+        lines.push(Line::from(row));
+
+        if r == 1 || r == 3 {
+            let mut separator = vec![line("▐")];
+            for (c, (_, style)) in cells.iter().enumerate() {
+                if c == 3 {
+                    separator.push(Span::styled(" ", Style::default().bg(grid_color)));
+                }
+                let fg = style.bg.unwrap_or(grid_color);
+                separator.push(Span::styled("▀▀▀", Style::default().fg(fg).bg(grid_color)));
+            }
+            separator.push(line("▌"));
+            lines.push(Line::from(separator));
+        }
+    }
+
+    let mut bottom = vec![line("▝")];
+    bottom.extend((0..19).map(|_| line("▀")));
+    bottom.push(line("▘"));
+    lines.push(Line::from(bottom));
+
+    // Room above the board for clues outside the grid or the column numbers
+    let area = match outside_clues(game).is_some() || game.show_coordinates {
+        true => Rect { y: area.y + 1, height: area.height.saturating_sub(1), ..area },
+        false => area,
+    };
+    let (width, height) = (COMPACT_BOARD_WIDTH, lines.len() as u16);
+    let board_area = Rect::new(
+        area.x + area.width.saturating_sub(width) / 2,
+        area.y + area.height.saturating_sub(height) / 2,
+        width.min(area.width),
+        height.min(area.height),
+    );
+    f.render_widget(Paragraph::new(lines), board_area);
+    let cols = [0, 1, 2, 3, 4, 5].map(|c| (board_area.x + 1 + 3 * c + (c >= 3) as u16, 3));
+    let rows = [1, 2, 4, 5, 7, 8].map(|y| board_area.y + y);
+    draw_outside_clues(f, game, board_area, cols, rows);
+    draw_coordinates(f, game, board_area, cols, rows);
+    board_area
+}
+
+// Clues the board's constraints show outside the grid, such as sandwich sums
+fn outside_clues(game: &Game) -> Option<(&Clues, &Clues)> {
+    game.grid.constraints.iter().find_map(|rule| rule.outside_clues())
+}
+
+// Column clues go on the line above the board and row clues just left of it,
+// lined up with the columns (x and width) and rows (y) given
+fn draw_outside_clues(f: &mut Frame, game: &Game, board: Rect, cols: [(u16, u16); 6], rows: [u16; 6]) {
+    let Some((row_clues, col_clues)) = outside_clues(game) else {
+        return;
+    };
+    let style = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    let screen = f.area();
+    for (&(x, width), clue) in cols.iter().zip(col_clues) {
+        if let (Some(clue), Some(y)) = (clue, board.y.checked_sub(1)) {
+            let area = Rect::new(x, y, width, 1).intersection(screen);
+            f.render_widget(Paragraph::new(clue.to_string()).style(style).alignment(Alignment::Center), area);
+        }
+    }
+    for (&y, clue) in rows.iter().zip(row_clues) {
+        if let (Some(clue), Some(x)) = (clue, board.x.checked_sub(3)) {
+            let area = Rect::new(x, y, 3, 1).intersection(screen);
+            f.render_widget(Paragraph::new(format!("{} ", clue)).style(style).alignment(Alignment::Right), area);
+        }
+    }
+}
+
+// Row letters and column numbers, lined up like the outside clues. They go
+// above and left of the board, or below and right of it where clues are.
+fn draw_coordinates(f: &mut Frame, game: &Game, board: Rect, cols: [(u16, u16); 6], rows: [u16; 6]) {
+    if !game.show_coordinates {
+        return;
+    }
+    let style = Style::default().fg(Color::Gray);
+    let screen = f.area();
+    let (y, x) = match outside_clues(game) {
+        Some(_) => (Some(board.y + board.height), Some(board.x + board.width)),
+        None => (board.y.checked_sub(1), board.x.checked_sub(2)),
+    };
+    for (c, &(col_x, width)) in cols.iter().enumerate() {
+        if let Some(y) = y {
+            let area = Rect::new(col_x, y, width, 1).intersection(screen);
+            f.render_widget(Paragraph::new((c + 1).to_string()).style(style).alignment(Alignment::Center), area);
+        }
+    }
+    for (r, &row_y) in rows.iter().enumerate() {
+        if let Some(x) = x {
+            let area = Rect::new(x, row_y, 2, 1).intersection(screen);
+            f.render_widget(Paragraph::new(format!(" {}", row_letter(r))).style(style), area);
+        }
+    }
+}
+
+// A, B, ... for the rows, top down
+fn row_letter(r: usize) -> char {
+    (b'A' + r as u8) as char
+}
+
+const COMPACT_BOARD_WIDTH: u16 = 21;
+
+// Fit a cell's text into three columns: a centered digit, or up to three pencil marks
+fn compact_cell_text(text: &str) -> String {
+    let marks: String = text.chars().filter(|ch| !ch.is_whitespace()).collect();
+    match marks.chars().count() {
+        0 => "   ".to_string(),
+        1 => format!(" {} ", marks),
+        2 => format!("{} ", marks),
+        3 => marks,
+        _ => format!("{}+", marks.chars().take(2).collect::<String>()),
+    }
+}
+
+// Identifies the puzzle: difficulty stars, clue count, generation seed and
+// variant, and its author if known. Also used for the terminal window title.
+pub fn title_text(game: &Game) -> String {
+    let meta = &game.meta;
+    let daily = match (&meta.date, &game.weekly) {
+        (_, Some(week)) => format!(" | {}", tf("title.weekly", &[week])),
+        (Some(date), None) => format!(" | {}", tf("title.daily", &[date])),
+        (None, None) => String::new(),
+    };
+    let endless = game.endless.as_ref().map_or(String::new(), |session| {
+        format!(" | {}", tf("title.endless", &[&(session.solved + 1), &session.score]))
+    });
+    format!(
+        "RustDoku6{}{} | {} | {} | {}{}{}{}",
+        daily,
+        endless,
+        stars_text(game.stars()),
+        tf("title.clues", &[&game.grid.clue_count()]),
+        // With the transform, as in the puzzle's key
+        tf("title.seed", &[&match meta.transform {
+            Some(transform) => format!("{}~{}", meta.seed, transform.code()),
+            None => meta.seed.to_string(),
+        }]),
+        match &meta.variant {
+            Variant::Classic => String::new(),
+            variant => format!(" ({})", variant),
+        },
+        meta.author.as_ref().map_or(String::new(), |author| format!(" | {}", tf("title.by", &[author]))),
+        match (game.is_solving(), meta.unique) {
+            (true, _) => format!(" | {}", t("title.solving")),
+            (false, Some(false)) => format!(" | {}", t("title.several_solutions")),
+            _ => String::new(),
+        }
+    )
+}
+
+// Difficulty as filled and empty stars, e.g. "★★☆☆"
+fn stars_text(stars: u8) -> String {
+    format!("{}{}", "★".repeat(stars as usize), "☆".repeat((crate::model::MAX_STARS - stars) as usize))
+}
+
+// The solve clock, or what is left of the countdown in time-attack mode
+fn clock_text(game: &Game) -> String {
+    let elapsed = game.timer.elapsed();
+    match &game.time_attack {
+        Some(attack) => tf("status.time_left", &[&format_duration(attack.remaining(elapsed))]),
+        None => format_duration(elapsed),
+    }
+}
+
+fn status_color(screen: Screen) -> Color {
+    match screen {
+        Screen::Won => Color::Green,
+        Screen::TimeUp | Screen::OutOfMistakes => Color::Red,
+        _ => Color::White,
+    }
+}
+
+// mm:ss, or h:mm:ss past the hour
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60)
+    } else {
+        format!("{:02}:{:02}", secs / 60, secs % 60)
     }
 }
 
-fn draw_board(f: &mut Frame, game: &Game, area: Rect) {
+fn draw_board(f: &mut Frame, game: &Game, area: Rect) -> Rect {
     // Inverted Grid Lines:
     // 1. Render a background color on the whole board area. This will show through the gaps.
     // 2. Use Layout with spacing to create gaps.
@@ -68,6 +1154,12 @@ fn draw_board(f: &mut Frame, game: &Game, area: Rect) {
     // So for a square board, Width (chars) should be ~2x Height (rows).
     // Limit width to 60% of screen to prevent stretching.
     
+    // Clues outside the grid and coordinates need a line above the board and
+    // a few columns beside it
+    let area = match outside_clues(game).is_some() || game.show_coordinates {
+        true => area.inner(Margin::new(4, 1)),
+        false => area,
+    };
     let (board_area, s) = calculate_board_rect(area, 60);
 
     // 2. Background (The "Lines" + Border)
@@ -99,166 +1191,12 @@ fn draw_board(f: &mut Frame, game: &Game, area: Rect) {
             .split(rows_layout[r]);
             
         for c in 0..6 {
-            let cell = &game.grid.cells[r][c];
-            // Determine content to render
-            // If value is present, show it.
-            // If value is None:
-            //   If Pencil Mode: show marks.
-            //   If Normal Mode: checks marks count. If 1, show it with validation color.
-            
-            // Region Coloring - More vibrant colors for better visibility
-            let region_idx = (r / 2) * 2 + (c / 3);
-            let region_bg = match region_idx {
-                0 => Color::Rgb(30, 30, 80),   // Deeper blue
-                1 => Color::Rgb(30, 80, 30),   // Richer green
-                2 => Color::Rgb(80, 30, 30),   // Warmer red
-                3 => Color::Rgb(80, 80, 30),   // Olive
-                4 => Color::Rgb(30, 80, 80),   // Teal
-                5 => Color::Rgb(80, 30, 80),   // Magenta
-                _ => Color::Black,
-            };
+            let (rendered_text, style) = cell_appearance(game, r, c);
 
-            // Cell Style Base
-            let mut style = Style::default().bg(region_bg).add_modifier(Modifier::BOLD);
+            let cell_area = cols_layout[c];
             
-            // Cursor Highlight
-            if (r, c) == game.cursor {
-                style = style.bg(Color::Yellow).fg(Color::Black);
-            } else if cell.is_fixed {
-                style = style.fg(Color::Cyan); 
-            } else {
-                style = style.fg(Color::White);
-            }
-            
-            // let cell_area = cols_layout[c]; (Moved down)
-
-            // Content determination
-            let mut rendered_text = String::new();
-            let mut use_validation_style = false;
-            let mut validation_valid = true;
-            
-            if let Some(v) = cell.value {
-                rendered_text = v.to_string();
-                
-                // If it's a user-entered number (not fixed), check validity
-                if !cell.is_fixed {
-                    use_validation_style = true;
-                    validation_valid = game.is_correct_move(r, c, v);
-                }
-            } else {
-                // Check if exactly one mark is set (common logic for both modes now if we want validation)
-                let _mark_count = cell.marks.iter().filter(|&&m| m).count();
-                
-                // Construct text based on mode, but we can reuse validation logic if count == 1
-                match game.mode {
-                    crate::model::InputMode::Pencil => {
-                         for i in 0..6 {
-                            if cell.marks[i] {
-                                rendered_text.push_str(&format!("{}", i + 1));
-                            } else {
-                                rendered_text.push(' ');
-                            }
-                        }
-                    }
-                    crate::model::InputMode::Normal => {
-                        // Check marks count. If 1, show it with validation color.
-                        let mark_count = cell.marks.iter().filter(|&&m| m).count();
-                        if mark_count == 1 {
-                            // Safe to unwrap because mark_count == 1 guarantees at least one mark exists
-                            let mark_idx = cell.marks.iter().position(|&m| m).expect("Mark should exist when count is 1");
-                            let mark_val = (mark_idx + 1) as u8;
-                            rendered_text = mark_val.to_string();
-                            use_validation_style = true;
-                            validation_valid = game.is_correct_move(r, c, mark_val);
-                        }
-                    }
-                }
-            }
-
-            // Determine final background and foreground colors
-            let _bg_color = region_bg;
-            let _fg_color = Color::White;
-            let _is_bold = true;
-            
-            // Determine final background and foreground colors
-            let mut bg_color = region_bg;
-            let mut fg_color = Color::White;
-            let mut is_bold = true;
-            
-            if cell.is_fixed {
-                fg_color = Color::Cyan;
-            }
-
-            // Validation Styling
-            if use_validation_style {
-                 if cell.value.is_some() {
-                     // Explicit Value: Use Background Color
-                     if validation_valid {
-                        bg_color = Color::Green;
-                        fg_color = Color::Black; 
-                    } else {
-                        bg_color = Color::Red;
-                        fg_color = Color::White; 
-                    }
-                 } else {
-                     // Implicit Value (Single Mark): Use Foreground Color only
-                     // Keep the region background (or cursor background)
-                     // But change text color to Green/Red
-                     if validation_valid {
-                         fg_color = Color::Green;
-                     } else {
-                         fg_color = Color::LightRed; // LightRed is brighter against dark backgrounds
-                     }
-                     // Maybe add Underline to indicate it's not final?
-                     style = style.add_modifier(Modifier::UNDERLINED);
-                 }
-            } else if cell.value.is_none() && game.mode == crate::model::InputMode::Pencil {
-                fg_color = Color::Gray;
-                is_bold = false;
-            }
-
-            // Cursor Handling
-            if (r, c) == game.cursor {
-                bg_color = Color::Yellow;
-                fg_color = Color::Black;
-                
-                // If validation is active, we need to ensure contrast or visibility on top of Yellow.
-                if use_validation_style {
-                     if cell.value.is_some() {
-                        // Explicit: Background takes precedence over Cursor Yellow?
-                        // Or Cursor Yellow takes precedence?
-                        // If we want to show validation, we must modify Cursor color.
-                        if validation_valid {
-                             bg_color = Color::LightGreen; // Cursor on Valid
-                        } else {
-                             bg_color = Color::LightRed; // Cursor on Invalid
-                        }
-                     } else {
-                        // Implicit: Foreground was Green/Red.
-                        // On Yellow BG, Green text is hard to read. Red text is okay.
-                        // Let's force Black/Dark Blue for contrast if it's Green?
-                        // Or maybe use Blue for Valid on Yellow?
-                        if validation_valid {
-                            fg_color = Color::Rgb(0, 100, 0);
-                        } else {
-                            fg_color = Color::Red;
-                        }
-                     }
-                }
-            }
-            
-            let mut style = Style::default().bg(bg_color).fg(fg_color);
-            if is_bold {
-                style = style.add_modifier(Modifier::BOLD);
-            }
-            if cell.value.is_none() && game.mode == crate::model::InputMode::Pencil {
-                 style = style.add_modifier(Modifier::ITALIC);
-            }
-
-            let cell_area = cols_layout[c];
-            
-            // Ensure full background coverage for the cell
-            f.render_widget(Block::default().style(style), cell_area);
+            // Ensure full background coverage for the cell
+            f.render_widget(Block::default().style(style), cell_area);
             
             // Render text
             if !rendered_text.trim().is_empty() {
@@ -282,10 +1220,329 @@ fn draw_board(f: &mut Frame, game: &Game, area: Rect) {
             }
         }
     }
+    let cols = [0, 1, 2, 3, 4, 5].map(|c| (inner_area.x + c * (2 * s + 1), 2 * s));
+    let rows = [0, 1, 2, 3, 4, 5].map(|r| inner_area.y + r * (s + 1) + s.saturating_sub(1) / 2);
+    if game.subtle_regions {
+        draw_region_lines(f, game, inner_area, s);
+    }
+    draw_outside_clues(f, game, board_area, cols, rows);
+    draw_coordinates(f, game, board_area, cols, rows);
+
+    // Thermometer lines run on across the gaps between their cells
+    for path in game.grid.constraints.iter().filter_map(|rule| rule.path()) {
+        for pair in path.windows(2) {
+            let ((r, c), next) = (pair[0].min(pair[1]), pair[0].max(pair[1]));
+            let (x, y, symbol) = match next.0 == r {
+                true => (cols[c].0 + 2 * s, rows[r], "━"),
+                false => (cols[c].0 + s.saturating_sub(1), inner_area.y + r as u16 * (s + 1) + s, "┃"),
+            };
+            if let Some(cell) = f.buffer_mut().cell_mut((x, y)) {
+                cell.set_symbol(symbol).set_fg(Color::Gray);
+            }
+        }
+    }
+
+    board_area
 }
 
 
 
+// With subtle regions, the lines between cells of one region are dimmed, so
+// the full-weight lines left mark out the regions
+fn draw_region_lines(f: &mut Frame, game: &Game, inner_area: Rect, s: u16) {
+    let region = |r: usize, c: usize| game.grid.regions.region_of(r.min(5), c.min(5));
+    let buffer = f.buffer_mut();
+    for r in 0..6 {
+        for c in 0..6 {
+            let (x, y) = (inner_area.x + c as u16 * (2 * s + 1), inner_area.y + r as u16 * (s + 1));
+            let right = c < 5 && region(r, c) == region(r, c + 1);
+            let below = r < 5 && region(r, c) == region(r + 1, c);
+            let corner = right && below && region(r, c) == region(r + 1, c + 1);
+            let mut dim = Vec::new();
+            if right {
+                dim.extend((0..s).map(|dy| (x + 2 * s, y + dy)));
+            }
+            if below {
+                dim.extend((0..2 * s).map(|dx| (x + dx, y + s)));
+            }
+            if corner {
+                dim.push((x + 2 * s, y + s));
+            }
+            for at in dim {
+                if let Some(cell) = buffer.cell_mut(at) {
+                    cell.set_bg(Color::Rgb(45, 45, 55));
+                }
+            }
+        }
+    }
+}
+
+// Text and style for cell (r, c), shared by the character-cell and raster renderers
+pub fn cell_appearance(game: &Game, r: usize, c: usize) -> (String, Style) {
+    let cell = &game.grid.cells[r][c];
+    // Determine content to render
+    // If value is present, show it.
+    // If value is None:
+    //   If Pencil Mode: show marks.
+    //   If Normal Mode: checks marks count. If 1, show it with validation color.
+    
+    // Region Coloring - More vibrant colors for better visibility
+    let region_idx = game.grid.regions.region_of(r, c);
+    let region_bg = match region_idx {
+        0 => Color::Rgb(30, 30, 80),   // Deeper blue
+        1 => Color::Rgb(30, 80, 30),   // Richer green
+        2 => Color::Rgb(80, 30, 30),   // Warmer red
+        3 => Color::Rgb(80, 80, 30),   // Olive
+        4 => Color::Rgb(30, 80, 80),   // Teal
+        5 => Color::Rgb(80, 30, 80),   // Magenta
+        _ => Color::Black,
+    };
+    // Subtle: the same hues, barely off the grey
+    let region_bg = match region_bg {
+        Color::Rgb(red, green, blue) if game.subtle_regions => Color::Rgb(red / 3 + 14, green / 3 + 14, blue / 3 + 14),
+        color => color,
+    };
+    // Thermometers are lighter tubes through the region colors, the bulb brightest
+    let tube = game.grid.constraints.iter().filter_map(|rule| rule.path()).find_map(|path| path.iter().position(|&cell| cell == (r, c)));
+    let region_bg = match (tube, region_bg) {
+        (Some(at), Color::Rgb(red, green, blue)) => {
+            let lift = if at == 0 { 70 } else { 40 };
+            Color::Rgb(red.saturating_add(lift), green.saturating_add(lift), blue.saturating_add(lift))
+        }
+        (_, color) => color,
+    };
+    // Candidate counts: the fewer digits an empty cell can take, the
+    // brighter its region color, so the most constrained cells stand out
+    let count = game.candidate_count(r, c).filter(|_| game.show_counts && game.screen() == Screen::Playing);
+    let region_bg = match (count, region_bg) {
+        (Some(n), Color::Rgb(red, green, blue)) => {
+            let lift = 12 * (6 - n.min(6)) as u8;
+            Color::Rgb(red.saturating_add(lift), green.saturating_add(lift), blue.saturating_add(lift))
+        }
+        (_, color) => color,
+    };
+    // Units one cell short of full light up, and the free cell in them most
+    let last_units: Vec<_> = match game.show_last_cells && game.screen() == Screen::Playing {
+        true => game.last_cells().into_iter().filter(|unit| unit.contains(&(r, c))).collect(),
+        false => Vec::new(),
+    };
+    let region_bg = match region_bg {
+        _ if !last_units.is_empty() && cell.value().is_none() => Color::Rgb(150, 120, 20),
+        Color::Rgb(red, green, blue) if !last_units.is_empty() => Color::Rgb(red.saturating_add(30), green.saturating_add(30), blue.saturating_add(10)),
+        color => color,
+    };
+
+    // Content determination
+    let mut rendered_text = String::new();
+    let mut use_validation_style = false;
+    let mut validation_valid = true;
+    
+    if let Some(v) = cell.value() {
+        rendered_text = v.to_string();
+        
+        // If it's a user-entered number (not fixed), check validity. What-if
+        // entries aren't judged until the branch is kept.
+        if !cell.is_fixed() && !game.is_hypothetical(r, c) {
+            use_validation_style = true;
+            validation_valid = game.is_correct_move(r, c, v);
+        }
+    } else {
+        // Check if exactly one mark is set (common logic for both modes now if we want validation)
+        let _mark_count = cell.marks().iter().filter(|&&m| m).count();
+        
+        // Construct text based on mode, but we can reuse validation logic if count == 1
+        match game.mode {
+            crate::model::InputMode::Pencil => {
+                 for i in 0..6 {
+                    if cell.mark(i as u8 + 1) {
+                        rendered_text.push_str(&format!("{}", i + 1));
+                    } else {
+                        rendered_text.push(' ');
+                    }
+                }
+            }
+            crate::model::InputMode::Normal => {
+                // Check marks count. If 1, show it with validation color.
+                let mark_count = cell.marks().iter().filter(|&&m| m).count();
+                if mark_count == 1 {
+                    // Safe to unwrap because mark_count == 1 guarantees at least one mark exists
+                    let mark_idx = cell.marks().iter().position(|&m| m).expect("Mark should exist when count is 1");
+                    let mark_val = (mark_idx + 1) as u8;
+                    rendered_text = mark_val.to_string();
+                    use_validation_style = true;
+                    validation_valid = game.is_correct_move(r, c, mark_val);
+                }
+            }
+        }
+    }
+
+    // Determine final background and foreground colors
+    let _bg_color = region_bg;
+    let _fg_color = Color::White;
+    let _is_bold = true;
+    
+    // Determine final background and foreground colors
+    let mut bg_color = region_bg;
+    let mut fg_color = Color::White;
+    let mut is_bold = true;
+    let mut underline = false;
+    
+    if cell.is_fixed() {
+        fg_color = Color::Cyan;
+    } else if game.is_hypothetical(r, c) {
+        fg_color = Color::LightMagenta;
+    }
+
+    // Validation Styling
+    if use_validation_style {
+         if cell.value().is_some() {
+             // Explicit Value: Use Background Color
+             if validation_valid {
+                bg_color = Color::Green;
+                fg_color = Color::Black; 
+            } else {
+                bg_color = Color::Red;
+                fg_color = Color::White; 
+            }
+         } else {
+             // Implicit Value (Single Mark): Use Foreground Color only
+             // Keep the region background (or cursor background)
+             // But change text color to Green/Red
+             if validation_valid {
+                 fg_color = Color::Green;
+             } else {
+                 fg_color = Color::LightRed; // LightRed is brighter against dark backgrounds
+             }
+             // Maybe add Underline to indicate it's not final?
+             underline = true;
+         }
+    } else if cell.value().is_none() && game.mode == crate::model::InputMode::Pencil {
+        fg_color = Color::Gray;
+        is_bold = false;
+    }
+
+    // With no marks to show, the count itself goes in the cell, dimmed so it
+    // isn't taken for an entry; a cell with none left is a dead end
+    if let Some(n) = count.filter(|_| rendered_text.trim().is_empty()) {
+        rendered_text = n.to_string();
+        fg_color = if n == 0 { Color::LightRed } else { Color::DarkGray };
+        is_bold = false;
+    }
+
+    // The digit picked out in the review of a finished board
+    if matches!(game.screen(), Screen::Won | Screen::Viewing) && game.highlight.is_some() && cell.value() == game.highlight {
+        bg_color = Color::LightBlue;
+        fg_color = Color::Black;
+    }
+
+    // The mistake heatmap replaces the validation colors: the more wrong
+    // entries a cell took, the redder
+    if let Some(counts) = game.heatmap.filter(|_| game.screen() == Screen::Won) {
+        bg_color = match counts[r][c] {
+            0 => region_bg,
+            1 => Color::Rgb(150, 100, 0),
+            2 => Color::Rgb(190, 60, 0),
+            _ => Color::Rgb(230, 20, 20),
+        };
+        if counts[r][c] > 0 || !cell.is_fixed() {
+            fg_color = Color::White;
+        }
+    }
+
+    // A row, column or box the digit just placed completed glows with the flash
+    let glowing = !game.completed.is_empty() && game.flash_until.is_some();
+    if glowing && game.completed.iter().any(|&i| game.grid.regions.units()[i].1.contains(&(r, c))) {
+        bg_color = Color::Rgb(200, 230, 120);
+        fg_color = Color::Black;
+    }
+
+    // The row, column and box of the digit just placed light up while the flash lasts
+    let placed = game.placed.filter(|_| game.flash_until.is_some());
+    if placed.is_some_and(|(pr, pc)| game.grid.regions.peers(pr, pc).contains(&(r, c))) {
+        if let Color::Rgb(red, green, blue) = bg_color {
+            bg_color = Color::Rgb(red.saturating_add(25), green.saturating_add(25), blue.saturating_add(25));
+        }
+    }
+
+    // Hover: a lighter region color, so the cell a click would pick stands out
+    // without being mistaken for the cursor
+    if game.hover == Some((r, c)) && bg_color == region_bg {
+        if let Color::Rgb(red, green, blue) = region_bg {
+            bg_color = Color::Rgb(red.saturating_add(40), green.saturating_add(40), blue.saturating_add(40));
+        }
+    }
+
+    // Other co-op players' cursors, each in its player's color
+    if let Some(&(player, _)) = game.peer_cursors.iter().find(|&&(_, at)| at == (r, c)) {
+        bg_color = player_color(player);
+        fg_color = Color::Black;
+    }
+
+    // A refused entry flashes the clashing peer, and the cursor below
+    let flashing = game.rejected.filter(|_| game.flash_until.is_some());
+    if flashing.is_some_and(|rejected| rejected.peer == (r, c)) {
+        bg_color = Color::Magenta;
+        fg_color = Color::White;
+    }
+
+    // Cursor Handling
+    if (r, c) == game.cursor {
+        bg_color = Color::Yellow;
+        fg_color = Color::Black;
+        
+        // If validation is active, we need to ensure contrast or visibility on top of Yellow.
+        if use_validation_style {
+             if cell.value().is_some() {
+                // Explicit: Background takes precedence over Cursor Yellow?
+                // Or Cursor Yellow takes precedence?
+                // If we want to show validation, we must modify Cursor color.
+                if validation_valid {
+                     bg_color = Color::LightGreen; // Cursor on Valid
+                } else {
+                     bg_color = Color::LightRed; // Cursor on Invalid
+                }
+             } else {
+                // Implicit: Foreground was Green/Red.
+                // On Yellow BG, Green text is hard to read. Red text is okay.
+                // Let's force Black/Dark Blue for contrast if it's Green?
+                // Or maybe use Blue for Valid on Yellow?
+                if validation_valid {
+                    fg_color = Color::Rgb(0, 100, 0);
+                } else {
+                    fg_color = Color::Red;
+                }
+             }
+        }
+        if game.is_hypothetical(r, c) {
+            fg_color = Color::Magenta;
+        }
+        if flashing.is_some() {
+            bg_color = Color::LightMagenta;
+        }
+    }
+    
+    let mut style = Style::default().bg(bg_color).fg(fg_color);
+    if is_bold {
+        style = style.add_modifier(Modifier::BOLD);
+    }
+    if cell.value().is_none() && game.mode == crate::model::InputMode::Pencil {
+         style = style.add_modifier(Modifier::ITALIC);
+    }
+    // Locked entries are underlined too; they always hold a value, so they
+    // can't be taken for a single mark
+    if underline || cell.locked() {
+        style = style.add_modifier(Modifier::UNDERLINED);
+    }
+
+    (rendered_text, style)
+}
+
+// Cursor color of a co-op player; our own cursor stays yellow
+pub fn player_color(player: u8) -> Color {
+    const COLORS: [Color; 5] = [Color::LightMagenta, Color::LightCyan, Color::LightGreen, Color::LightBlue, Color::LightRed];
+    COLORS[player as usize % COLORS.len()]
+}
+
 // Calculates a board size that guarantees perfectly uniform cells
 // Formula: Total_Size = (6 * Cell_Size) + 5 gaps
 // This ensures Integer Division by 6 has 0 remainder.
@@ -319,41 +1576,914 @@ fn calculate_board_rect(available: Rect, max_width_percent: u16) -> (Rect, u16)
     (Rect::new(x, y, board_w, board_h), s)
 }
 
-// Helper to draw the About popup centered on screen
-fn draw_about_popup(f: &mut Frame) {
+// The cell at terminal position (column, row) on a board drawn at `board`, in
+// either layout. Gaps between cells and the frame belong to no cell.
+pub fn cell_at(board: Rect, column: u16, row: u16) -> Option<(usize, usize)> {
+    if !board.contains((column, row).into()) {
+        return None;
+    }
+    let (x, y) = (column - board.x, row - board.y);
+    if board.width == COMPACT_BOARD_WIDTH {
+        // Frame, then three-column cells with a one-column gap after the third;
+        // rows are one line each, with a separator line after rows 2 and 4
+        let c = match x {
+            1..=9 => (x - 1) / 3,
+            11..=19 => 3 + (x - 11) / 3,
+            _ => return None,
+        };
+        let r = match y {
+            1 | 2 => y - 1,
+            4 | 5 => y - 2,
+            7 | 8 => y - 3,
+            _ => return None,
+        };
+        return Some((r as usize, c as usize));
+    }
+    // Regular layout: a one-cell frame, and cells of 2s x s with one-cell gaps
+    let s = board.height.saturating_sub(7) / 6;
+    let (x, y) = (x.checked_sub(1)?, y.checked_sub(1)?);
+    let (c, r) = (x / (2 * s + 1), y / (s + 1));
+    if s == 0 || c > 5 || r > 5 || x % (2 * s + 1) == 2 * s || y % (s + 1) == s {
+        return None;
+    }
+    Some((r as usize, c as usize))
+}
+
+// Speedrun side panel: one line per completed digit with its delta to the personal best.
+// Green is ahead, red is behind, gold marks a best-ever segment.
+fn draw_splits(f: &mut Frame, run: &Speedrun, area: Rect) {
+    let mut lines = Vec::with_capacity(7);
+    for (i, split) in run.splits.iter().enumerate() {
+        let (delta, delta_color) = match run.delta_ms(i) {
+            Some(ms) => {
+                let sign = if ms < 0 { '-' } else { '+' };
+                let text = format!("{}{:.1}", sign, ms.unsigned_abs() as f64 / 1000.0);
+                (text, if ms < 0 { Color::Green } else { Color::Red })
+            }
+            None => ("-".to_string(), Color::Gray),
+        };
+        let delta_color = if run.is_gold(i) { Color::Yellow } else { delta_color };
+        lines.push(Line::from(vec![
+            Span::raw(format!("{}. {}  {}  ", i + 1, tf("splits.digit", &[&split.digit]), format_duration(split.at))),
+            Span::styled(delta, Style::default().fg(delta_color)),
+        ]));
+    }
+    for i in run.splits.len()..6 {
+        let pb = run.personal_best.get(i).map_or("--:--".to_string(), |&t| format_duration(t));
+        lines.push(Line::styled(format!("{}. ...      {}", i + 1, pb), Style::default().fg(Color::DarkGray)));
+    }
+
+    let panel = Paragraph::new(lines).block(Block::default().title(format!(" {} ", t("popup.splits"))).borders(Borders::ALL));
+    f.render_widget(panel, area);
+}
+
+// The variant rules in play, one entry per kind of constraint however many
+// the board has, e.g. a single one for all its cages
+pub fn legend(game: &Game) -> Vec<(&'static str, &'static str)> {
+    let mut entries: Vec<_> = Vec::new();
+    for (name, text) in game.grid.constraints.iter().filter_map(|rule| rule.legend().map(|text| (rule.name(), text))) {
+        if !entries.iter().any(|&(seen, _)| seen == name) {
+            entries.push((name, text));
+        }
+    }
+    entries
+}
+
+// Variant panel beside the board: each extra rule, named as in the debug
+// overlay's rules line, with what it asks
+fn draw_legend(f: &mut Frame, game: &Game, area: Rect) {
+    let mut lines = Vec::new();
+    for (name, text) in legend(game) {
+        let key = format!("rule.{}", name.to_lowercase());
+        lines.push(Line::styled(t_or(&key, name), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)));
+        lines.push(Line::raw(t_or(&format!("{}_text", key), text)));
+    }
+    // The cage under the cursor, and what its empty cells can still take
+    if let Some(cage) = game.cage_at(game.cursor.0, game.cursor.1) {
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(tf("legend.cage", &[&cage.sum, &cage.so_far]), Style::default().fg(Color::Yellow)));
+        if cage.empty > 0 {
+            let sets: Vec<String> = cage.combinations.iter().map(|set| set.iter().map(u8::to_string).collect::<Vec<_>>().join("+")).collect();
+            let left = if sets.is_empty() { t("legend.nothing_fits").to_string() } else { sets.join(" ") };
+            let key = if cage.empty == 1 { "legend.cell_left" } else { "legend.cells_left" };
+            lines.push(Line::raw(tf(key, &[&cage.empty, &left])));
+        }
+    }
+    let title = format!(" {} ", t("legend.rules"));
+    let panel = Paragraph::new(lines).wrap(ratatui::widgets::Wrap { trim: true }).block(Block::default().title(title).borders(Borders::ALL));
+    f.render_widget(panel, area);
+}
+
+// The undo tree next to the board while browsing the history, scrolled to
+// keep the board shown in view
+fn draw_undo_tree(f: &mut Frame, game: &Game, area: Rect) {
+    let lines = game.undo_tree.lines();
+    let selected = lines.iter().position(|&(node, _)| node == game.history_node).unwrap_or(0);
+    let visible = area.height.saturating_sub(2) as usize;
+    let first = selected.saturating_sub(visible / 2).min(lines.len().saturating_sub(visible));
+    let rows: Vec<Line> = lines
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(visible)
+        .map(|(i, (node, text))| {
+            let style = if i == selected {
+                Style::default().fg(Color::Black).bg(Color::Yellow)
+            } else if *node == game.undo_tree.current {
+                Style::default().fg(Color::Cyan)
+            } else {
+                Style::default()
+            };
+            Line::styled(text.clone(), style)
+        })
+        .collect();
+    let block = Block::default().title(format!(" {} ", t("popup.history"))).borders(Borders::ALL);
+    f.render_widget(Paragraph::new(rows).block(block), area);
+}
+
+// Share screen: the puzzle's share code as a scannable QR code.
+// Each text row packs two module rows using half-block characters.
+fn draw_share_popup(f: &mut Frame, game: &Game) {
+    let code = game.grid.share_code();
+    let Some(qr) = QrCode::encode(code.as_bytes()) else {
+        return;
+    };
+
+    // Scanners need a light quiet zone of four modules around the symbol
+    let quiet = 4;
+    let span = qr.size + 2 * quiet;
+    let dark_at = |x: usize, y: usize| {
+        x >= quiet && y >= quiet && x < quiet + qr.size && y < quiet + qr.size && qr.is_dark(x - quiet, y - quiet)
+    };
+    let module_color = |dark: bool| if dark { Color::Black } else { Color::White };
+
+    let mut lines = Vec::with_capacity(span / 2 + 3);
+    for y in (0..span).step_by(2) {
+        let spans: Vec<Span> = (0..span)
+            .map(|x| {
+                let top = module_color(dark_at(x, y));
+                let bottom = module_color(y + 1 < span && dark_at(x, y + 1));
+                Span::styled("▀", Style::default().fg(top).bg(bottom))
+            })
+            .collect();
+        lines.push(Line::from(spans));
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::raw(code));
+
+    let width = (span as u16 + 4).max(44).min(f.area().width);
+    let height = (lines.len() as u16 + 2).min(f.area().height);
     let area = f.area();
-    
-    // Calculate a centered popup area (approx 60% width, 40% height)
-    let popup_width_percent = 60;
-    let popup_height_percent = 30; // Shorter height for minimal text
-    
-    let popup_width = (area.width as f32 * (popup_width_percent as f32 / 100.0)) as u16;
-    let popup_height = (area.height as f32 * (popup_height_percent as f32 / 100.0)) as u16;
-    
+    let popup_area = Rect::new(
+        area.x + area.width.saturating_sub(width) / 2,
+        area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    );
+
+    f.render_widget(ratatui::widgets::Clear, popup_area);
+    let block = Block::default()
+        .title(format!(" {} ", t("popup.share")))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::DarkGray).fg(Color::White))
+        .title_alignment(Alignment::Center);
+    f.render_widget(Paragraph::new(lines).block(block).alignment(Alignment::Center), popup_area);
+}
+
+// Placed and mistakes per player, e.g. "P1 ✓4 ✗1 · P2 ✓3 ✗0"
+fn hotseat_scores(seat: &HotSeat) -> String {
+    let scores: Vec<String> = seat
+        .players
+        .iter()
+        .enumerate()
+        .map(|(i, player)| format!("P{} ✓{} ✗{}", i + 1, player.placed, player.mistakes))
+        .collect();
+    scores.join(" · ")
+}
+
+// The message for `key`, or `english` for a rule or unit the catalog doesn't know
+fn t_or<'a>(key: &str, english: &'a str) -> &'a str {
+    Some(t(key)).filter(|message| !message.is_empty()).unwrap_or(english)
+}
+
+// A unit's kind as the solver names it ("Row", "column", "Diagonal"), in the
+// running text of messages
+fn unit_name(unit: &str) -> String {
+    let unit = unit.to_lowercase();
+    t_or(&format!("unit.{}", unit), &unit).to_string()
+}
+
+// Steps of a forced chain shown before the rest are summed up
+const MAX_CHAIN_LINES: usize = 8;
+
+// The solver's case against a wrong entry, in rule terms
+fn explanation_lines(explanation: &Explanation) -> Vec<String> {
+    let Explanation { row, col, value, refutation } = explanation;
+    let cell = format!("r{}c{}", row + 1, col + 1);
+    match refutation {
+        Some(Refutation::Peer { row: r, col: c, unit }) => {
+            vec![tf("explain.clash", &[&cell, &value, &value, &(r + 1), &(c + 1), &unit_name(unit)])]
+        }
+        Some(Refutation::Chain { steps, contradiction }) => {
+            let outcome = match contradiction {
+                Contradiction::NoCandidates { row: r, col: c } => tf("explain.no_value", &[&(r + 1), &(c + 1)]),
+                Contradiction::NoPlace { unit, index, value: v } => tf("explain.no_place", &[&v, &unit_name(unit), &(index + 1)]),
+            };
+            if steps.is_empty() {
+                return vec![tf("explain.if_then", &[&cell, &value, &outcome])];
+            }
+            let mut lines = vec![tf("explain.if", &[&cell, &value])];
+            for step in steps.iter().take(MAX_CHAIN_LINES) {
+                lines.push(tf("explain.forced", &[&(step.row + 1), &(step.col + 1), &step.value, &step.technique]));
+            }
+            if steps.len() > MAX_CHAIN_LINES {
+                lines.push(tf("explain.more", &[&(steps.len() - MAX_CHAIN_LINES)]));
+            }
+            lines.push(tf("explain.so", &[&outcome]));
+            lines
+        }
+        None => vec![tf("explain.unknown", &[&cell, &value]), t("explain.unknown_why").to_string()],
+    }
+}
+
+fn draw_explain_popup(f: &mut Frame, explanation: &Explanation) {
+    let mut lines: Vec<Line> = vec![Line::raw("")];
+    lines.extend(explanation_lines(explanation).into_iter().map(Line::raw));
+
+    let area = f.area();
+    let width = 58.min(area.width);
+    let height = (lines.len() as u16 + 3).min(area.height);
+    let popup_area = Rect::new(
+        area.x + area.width.saturating_sub(width) / 2,
+        area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    );
+    f.render_widget(ratatui::widgets::Clear, popup_area);
+    let block = Block::default()
+        .title(format!(" {} ", t("popup.explain")))
+        .borders(Borders::ALL)
+        .padding(ratatui::widgets::Padding::horizontal(1))
+        .style(Style::default().bg(Color::DarkGray).fg(Color::White))
+        .title_alignment(Alignment::Center);
+    let paragraph = Paragraph::new(lines).block(block).wrap(ratatui::widgets::Wrap { trim: false });
+    f.render_widget(paragraph, popup_area);
+}
+
+// Any open dialog, centered over the rest: its text, then the text typed so
+// far or the list to pick from
+fn draw_dialog(f: &mut Frame, dialog: &Dialog) {
+    let mut lines: Vec<Line> = dialog.text.lines().map(Line::raw).collect();
+    match &dialog.kind {
+        Kind::Message | Kind::Confirm => {}
+        Kind::TextInput(value) => {
+            lines.push(Line::raw(""));
+            lines.push(Line::from(vec![Span::raw("> "), Span::raw(value.as_str()), Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK))]));
+        }
+        Kind::ListPicker { items, selected } => {
+            lines.push(Line::raw(""));
+            lines.extend(items.iter().enumerate().map(|(i, item)| match i == *selected {
+                true => Line::styled(format!("> {}", item), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                false => Line::raw(format!("  {}", item)),
+            }));
+        }
+    }
+
+    let area = f.area();
+    let width = 58.min(area.width);
+    // Text wraps, so allow a line or two more than it has
+    let height = (lines.len() as u16 + 4).min(area.height);
+    let popup_area = Rect::new(area.x + area.width.saturating_sub(width) / 2, area.y + area.height.saturating_sub(height) / 2, width, height);
+    f.render_widget(ratatui::widgets::Clear, popup_area);
+    let block = Block::default()
+        .title(format!(" {} ", dialog.title))
+        .borders(Borders::ALL)
+        .padding(ratatui::widgets::Padding::new(1, 1, 1, 0))
+        .style(Style::default().bg(Color::DarkGray).fg(Color::White))
+        .title_alignment(Alignment::Center);
+    f.render_widget(Paragraph::new(lines).block(block).wrap(ratatui::widgets::Wrap { trim: false }), popup_area);
+}
+
+// Hot-seat results once the board is solved: the winner and each player's counts
+fn draw_hotseat_results(f: &mut Frame, seat: &HotSeat, time: Duration) {
+    let headline = match seat.winner() {
+        Some(i) => tf("hotseat.wins", &[&(i + 1)]),
+        None => t("hotseat.draw").to_string(),
+    };
+    let mut lines = vec![
+        Line::styled(headline, Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+        Line::raw(""),
+    ];
+    for (i, player) in seat.players.iter().enumerate() {
+        lines.push(Line::raw(tf("hotseat.player", &[&(i + 1), &player.placed, &player.mistakes, &player.score()])));
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::raw(tf("hotseat.together", &[&format_duration(time)])));
+
+    let area = f.area();
+    let width = 48.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup_area = Rect::new(
+        area.x + area.width.saturating_sub(width) / 2,
+        area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    );
+    f.render_widget(ratatui::widgets::Clear, popup_area);
+    let block = Block::default()
+        .title(format!(" {} ", t("popup.results")))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::DarkGray).fg(Color::White))
+        .title_alignment(Alignment::Center);
+    f.render_widget(Paragraph::new(lines).block(block).alignment(Alignment::Center), popup_area);
+}
+
+// F12 overlay in the top-left corner: frame timing, puzzle generation time,
+// the board's rules, what the solver sees on the current board, and the
+// layout rects in use
+fn draw_debug_overlay(f: &mut Frame, game: &Game, stats: &FrameStats, rects: &[(&str, Rect)]) {
+    let solve_started = std::time::Instant::now();
+    let next = game.techniques.next_deduction(&game.grid);
+    let solve_time = solve_started.elapsed();
+    let candidates: u32 = (0..36)
+        .map(|i| (i / 6, i % 6))
+        .filter(|&(r, c)| game.grid.cells[r][c].value().is_none())
+        .map(|(r, c)| crate::solver::candidates(&game.grid, r, c).count_ones())
+        .sum();
+
+    let mut lines = vec![
+        Line::raw(format!("frame #{}: {:.2?}", stats.frames, stats.render_time)),
+        Line::raw(format!("generated in {:.2?}, grade {:?}", game.generation_time, game.meta.difficulty)),
+        Line::raw(format!("techniques: {}", game.techniques.enabled().count())),
+        Line::raw(format!("rules: {}", game.grid.constraints.iter().map(|rule| rule.name()).collect::<Vec<_>>().join(", "))),
+        Line::raw(format!("candidates left: {}", candidates)),
+        Line::raw(match next {
+            Some(step) => format!("next: {} r{}c{} ({:.2?})", step.technique, step.row + 1, step.col + 1, solve_time),
+            None => format!("next: stuck ({:.2?})", solve_time),
+        }),
+    ];
+    if let Some((_, search, logic)) = &game.debug_solve {
+        lines.push(Line::raw(format!("search: {} nodes, {} backtracks", search.nodes, search.backtracks)));
+        lines.push(Line::raw(format!("logic: {} passes, {} steps", logic.passes, logic.steps())));
+        for (id, count) in &logic.techniques {
+            lines.push(Line::raw(format!("  {:<18}{}", id, count)));
+        }
+    }
+    for (name, rect) in rects {
+        lines.push(Line::raw(format!("{:<7}{}x{} at {},{}", name, rect.width, rect.height, rect.x, rect.y)));
+    }
+
+    let area = f.area();
+    let width = 44.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup_area = Rect::new(area.x, area.y, width, height);
+    f.render_widget(ratatui::widgets::Clear, popup_area);
+    let block = Block::default()
+        .title(" Debug (F12) ")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::Black).fg(Color::LightMagenta));
+    f.render_widget(Paragraph::new(lines).block(block), popup_area);
+}
+
+// The About popup's height on a terminal `height` rows high
+fn about_height(height: u16) -> u16 {
+    (about::lines().len() as u16 + 2).min(height * 2 / 3).max(3.min(height))
+}
+
+// Lines of the About text its popup has room for
+pub fn about_rows(height: u16) -> usize {
+    about_height(height).saturating_sub(2) as usize
+}
+
+// The About popup, centered and scrolled to `game.about_scroll`. Changelog
+// headings stand out.
+fn draw_about_popup(f: &mut Frame, game: &Game) {
+    let area = f.area();
+    let lines = about::lines();
+    let popup_width = (lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) as u16 + 4).min(area.width);
+    let popup_height = about_height(area.height);
     let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
     let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
-    
     let popup_area = Rect::new(x, y, popup_width, popup_height);
-    
+
     // Clear the background of the popup area so grid doesn't show through
     f.render_widget(ratatui::widgets::Clear, popup_area);
-    
+
+    let shown = popup_height.saturating_sub(2) as usize;
+    // Scrolled no further than fills the popup, should it have grown since
+    let scroll = game.about_scroll.min(lines.len().saturating_sub(shown));
+    let position = if lines.len() > shown { format!(" {}/{} ", scroll + shown, lines.len()) } else { String::new() };
     let block = Block::default()
-        .title(" About RustDoku6 ")
+        .title(format!(" {} ", t("popup.about")))
+        .title_bottom(Line::from(position).right_aligned())
         .borders(Borders::ALL)
         .style(Style::default().bg(Color::DarkGray).fg(Color::White))
         .title_alignment(Alignment::Center);
 
-    let text = "RustDoku6\n\n\
-                A 6x6 Sudoku TUI game written in Rust.\n\n\
-                Author: jxmullins\n\
-                Built with: Rust, Ratatui, Antigravity\n\n\
-                Press 'i' or 'Esc' to close.";
+    let text: Vec<Line> = lines
+        .into_iter()
+        .skip(scroll)
+        .map(|line| match line.strip_prefix("## ").or_else(|| line.strip_prefix("# ")) {
+            Some(heading) => Line::styled(heading.to_string(), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            None => Line::raw(line),
+        })
+        .collect();
+    f.render_widget(Paragraph::new(text).block(block), popup_area);
+}
 
-    let paragraph = Paragraph::new(text)
-        .block(block)
-        .alignment(Alignment::Center)
-        .wrap(ratatui::widgets::Wrap { trim: true });
+// The stats screen's lines: the tabs, the shown one picked out, then a row
+// per difficulty of the shown tab
+fn stats_lines(game: &Game) -> Vec<Line<'static>> {
+    let stats = game.stats.clone().unwrap_or_default();
+    let tabs = stats.tabs();
+    let Some(shown) = tabs.get(game.stats_tab.min(tabs.len().saturating_sub(1))) else {
+        return [vec![Line::raw(t("stats.none"))], rating_lines(&stats), weekly_lines(&stats)].concat();
+    };
+    let mut spans = Vec::new();
+    for tab in &tabs {
+        let style = if tab == shown { Style::default().fg(Color::Yellow).add_modifier(Modifier::REVERSED) } else { Style::default() };
+        spans.push(Span::styled(format!(" {} ", tab), style));
+    }
+    let mut lines = vec![Line::from(spans), Line::raw("")];
+    let header = format!(
+        "{:<8} {:>6} {:>6} {:>8} {:>8} {:>11} {:>6}",
+        t("stats.stars"),
+        t("stats.played"),
+        t("stats.solved"),
+        t("stats.best"),
+        t("stats.average"),
+        t("stats.corrections"),
+        t("stats.errors")
+    );
+    lines.push(Line::styled(header, Style::default().add_modifier(Modifier::BOLD)));
+    for (stars, record) in stats.rows(shown) {
+        let time = |time: Option<Duration>| time.map_or("-".to_string(), format_duration);
+        let (corrections, errors) = (record.erases.corrections, record.erases.errors);
+        lines.push(Line::raw(format!(
+            "{:<8} {:>6} {:>6} {:>8} {:>8} {:>11} {:>6}",
+            stars_text(stars),
+            record.played,
+            record.solved,
+            time(record.best),
+            time(record.average()),
+            corrections,
+            errors
+        )));
+    }
+    lines.extend(rating_lines(&stats));
+    lines.extend(weekly_lines(&stats));
+    lines
+}
 
-    f.render_widget(paragraph, popup_area);
+// The rating, once a rated game has moved it
+fn rating_lines(stats: &Stats) -> Vec<Line<'static>> {
+    if stats.rating.games == 0 {
+        return Vec::new();
+    }
+    let rating = tf("stats.rating", &[&format!("{:.0}", stats.rating.value), &stats.rating.games]);
+    vec![Line::raw(""), Line::styled(rating, Style::default().add_modifier(Modifier::BOLD))]
+}
+
+// The weekly challenge ladder, newest week first
+fn weekly_lines(stats: &Stats) -> Vec<Line<'static>> {
+    const WEEKS_SHOWN: usize = 8;
+    if stats.weekly.is_empty() {
+        return Vec::new();
+    }
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let mut lines = vec![Line::raw(""), Line::styled(t("stats.weekly"), bold)];
+    let header = format!("{:<10} {:>8} {:>8} {:>8}", t("stats.week"), t("stats.result"), t("stats.time"), t("stats.mistakes"));
+    lines.push(Line::styled(header, bold));
+    for result in stats.weekly.iter().rev().take(WEEKS_SHOWN) {
+        let outcome = if result.solved { t("stats.solved") } else { "-" };
+        lines.push(Line::raw(format!("{:<10} {:>8} {:>8} {:>8}", result.week, outcome, format_duration(result.time), result.mistakes)));
+    }
+    lines
+}
+
+fn draw_stats_popup(f: &mut Frame, game: &Game) {
+    let area = f.area();
+    let lines = stats_lines(game);
+    let popup_width = (lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4).max(24).min(area.width);
+    let popup_height = (lines.len() as u16 + 2).min(area.height);
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    f.render_widget(ratatui::widgets::Clear, popup_area);
+    let block = Block::default()
+        .title(format!(" {} ", t("popup.stats")))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::DarkGray).fg(Color::White))
+        .title_alignment(Alignment::Center);
+    f.render_widget(Paragraph::new(lines).block(block), popup_area);
+}
+
+// What the session summary says about the games counted since starting
+fn summary_lines(game: &Game) -> Vec<String> {
+    let session = game.stats.as_ref().map(|stats| stats.session).unwrap_or_default();
+    vec![
+        tf("summary.attempted", &[&session.attempted]),
+        tf("summary.solved", &[&session.solved]),
+        tf("summary.total_time", &[&format_duration(session.time)]),
+        tf("summary.best", &[&session.best.map_or("-".to_string(), format_duration)]),
+    ]
+}
+
+fn draw_summary_popup(f: &mut Frame, game: &Game) {
+    let area = f.area();
+    let lines = summary_lines(game);
+    let popup_width = (lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) as u16 + 4).max(24).min(area.width);
+    let popup_height = (lines.len() as u16 + 2).min(area.height);
+    let x = area.x + (area.width.saturating_sub(popup_width)) / 2;
+    let y = area.y + (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(x, y, popup_width, popup_height);
+
+    f.render_widget(ratatui::widgets::Clear, popup_area);
+    let block = Block::default()
+        .title(format!(" {} ", t("popup.session")))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::DarkGray).fg(Color::White))
+        .title_alignment(Alignment::Center);
+    f.render_widget(Paragraph::new(lines.join("\n")).block(block), popup_area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{about_rows, cell_appearance, cell_at, compact_cell_text, compact_status_text, draw, erases_text, explanation_lines, fit_status, format_duration, is_compact, legend, snapshot, status_bar, title_text, FrameStats};
+    use crate::{
+        command::Command,
+        config::Config,
+        model::{Erases, Explanation, Game, Grid, Timer},
+        solver::{Contradiction, Deduction, Refutation},
+    };
+    use ratatui::{backend::TestBackend, layout::Rect, style::Color, Terminal};
+    use rustdoku6::{
+        constraints::{Diagonal, KillerCage, Sandwich, Thermometer},
+        generator::PuzzleSpec,
+        puzzle::Variant,
+        speedrun::Speedrun,
+        symmetry::Transform,
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn status_drops_trailing_parts_to_fit() {
+        let status = "Mode: NORMAL (p) | Time: 01:02 | h: Hint | q: Quit";
+        assert_eq!(fit_status(status, 80), status);
+        assert_eq!(fit_status(status, 40), "Mode: NORMAL (p) | Time: 01:02 | …");
+        assert_eq!(fit_status(status, 10), "Mode: NOR…");
+        assert_eq!(fit_status(status, 0), "");
+    }
+
+    #[test]
+    fn explanations_read_in_rule_terms() {
+        let peer = Explanation { row: 0, col: 0, value: 4, refutation: Some(Refutation::Peer { row: 0, col: 3, unit: "row" }) };
+        assert_eq!(explanation_lines(&peer), vec!["r1c1 = 4 clashes with the 4 at r1c4 in the same row."]);
+
+        let step = Deduction { row: 1, col: 2, value: 5, technique: "Naked Single" };
+        let contradiction = Contradiction::NoPlace { unit: "Box", index: 2, value: 6 };
+        let chain = Explanation { row: 0, col: 0, value: 4, refutation: Some(Refutation::Chain { steps: vec![step; 10], contradiction }) };
+        let lines = explanation_lines(&chain);
+        assert_eq!(lines[0], "If r1c1 were 4:");
+        assert_eq!(lines[1], "  r2c3 must be 5 (Naked Single)");
+        assert_eq!(lines[9], "  ...and 2 more forced placements");
+        assert_eq!(lines[10], "so 6 would have nowhere to go in box 3.");
+
+        let contradiction = Contradiction::NoCandidates { row: 0, col: 4 };
+        let direct = Explanation { row: 0, col: 5, value: 3, refutation: Some(Refutation::Chain { steps: Vec::new(), contradiction }) };
+        assert_eq!(explanation_lines(&direct), vec!["If r1c6 were 3, r1c5 would have no value left."]);
+    }
+
+    #[test]
+    fn erase_counts_read_as_one_or_many() {
+        assert_eq!(erases_text(Erases { corrections: 1, errors: 0 }), "1 correction, 0 logical errors");
+        assert_eq!(erases_text(Erases { corrections: 5, errors: 1 }), "5 corrections, 1 logical error");
+    }
+
+    #[test]
+    fn compact_layout_kicks_in_on_small_terminals() {
+        assert!(is_compact(Rect::new(0, 0, 80, 24)));
+        assert!(is_compact(Rect::new(0, 0, 50, 40)));
+        assert!(!is_compact(Rect::new(0, 0, 100, 30)));
+    }
+
+    #[test]
+    fn placement_lights_up_its_peers() {
+        let mut game = Game::with_seed(13);
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        game.apply(Command::SelectCell(r, c));
+        game.apply(Command::EnterDigit(game.solution.unwrap()[r][c]));
+        let (_, lit) = cell_appearance(&game, r, (c + 1) % 6);
+        game.flash_until = Some(std::time::Instant::now());
+        game.end_flash();
+        let (_, unlit) = cell_appearance(&game, r, (c + 1) % 6);
+        assert_ne!(lit.bg, unlit.bg);
+    }
+
+    #[test]
+    fn candidate_counts_show_in_empty_cells() {
+        let mut game = Game::with_seed(13);
+        // Away from the cursor, whose color hides the shading
+        let empty: Vec<_> = (0..36).map(|i| (i / 6, i % 6)).filter(|&(r, c)| !game.grid.cells[r][c].is_fixed() && (r, c) != (0, 0)).collect();
+        let plain: Vec<_> = empty.iter().map(|&(r, c)| cell_appearance(&game, r, c)).collect();
+        game.apply(Command::ToggleCounts);
+        for (&(r, c), (text, style)) in empty.iter().zip(&plain) {
+            let n = game.candidate_count(r, c).unwrap();
+            let (shown, shaded) = cell_appearance(&game, r, c);
+            assert_eq!((text.as_str(), shown), ("", n.to_string()));
+            // Fewer candidates, brighter cell
+            let lift = 12 * (6 - n as u8);
+            let (Some(Color::Rgb(red, green, blue)), Some(lifted)) = (style.bg, shaded.bg) else { panic!("region colors are RGB") };
+            assert_eq!(lifted, Color::Rgb(red + lift, green + lift, blue + lift));
+        }
+        game.apply(Command::ToggleCounts);
+        assert_eq!(cell_appearance(&game, empty[0].0, empty[0].1), plain[0]);
+    }
+
+    #[test]
+    fn title_shows_the_transform_with_the_seed() {
+        let mut game = Game::with_seed(513);
+        assert!(!title_text(&game).contains("513~"));
+        game.meta.transform = Transform::parse("6543212");
+        assert!(title_text(&game).contains("513~6543212"));
+    }
+
+    #[test]
+    fn snapshots_are_plain_text_of_the_screen() {
+        let mut game = Game::with_seed(13);
+        game.timer = Timer::stopped_at(Duration::from_secs(75));
+        let config = Config::default();
+        let text = snapshot(&game, &config, 100, 30);
+        assert_eq!(text, snapshot(&game, &config, 100, 30));
+        assert_eq!(text.lines().count(), 30);
+        assert!(text.lines().all(|line| line.chars().count() <= 100 && !line.ends_with(' ')));
+        assert!(text.contains(&format_duration(Duration::from_secs(75))));
+        assert!(text.contains(&game.meta.seed.to_string()));
+
+        // A bot to race adds the bars under the board
+        game.bot = Some(rustdoku6::bot::Bot::new(rustdoku6::bot::Level::Quick, &game.givens(), &game.techniques));
+        let text = snapshot(&game, &config, 100, 30);
+        assert!(text.contains("You   ") && text.contains("Bot   ") && !text.contains("Best  "));
+        // A minute in, the bot is ahead of a player yet to make an entry
+        assert!(snapshot(&game, &config, 80, 24).contains(" b-"));
+        game.bot = None;
+
+        // Coordinates label the rows and columns, in either layout
+        game.apply(Command::ToggleCoordinates);
+        for (width, height) in [(100, 30), (80, 24)] {
+            let text = snapshot(&game, &config, width, height);
+            assert!(text.lines().any(|line| line.split_whitespace().collect::<Vec<_>>() == ["1", "2", "3", "4", "5", "6"]), "{}x{}", width, height);
+            assert!(text.lines().any(|line| line.trim_start().starts_with('F')), "{}x{}", width, height);
+        }
+        game.apply(Command::ToggleCoordinates);
+
+        // The preview hides the board behind what the puzzle is like
+        game.show_preview();
+        let text = snapshot(&game, &config, 100, 30);
+        assert!(text.contains("NEW PUZZLE") && text.contains(&format!("Clues: {}", game.grid.clue_count())));
+        assert!(text.contains("Enter: Start") && text.contains("n: Reroll"));
+    }
+
+    #[test]
+    fn variant_rules_are_listed_beside_the_board() {
+        let mut game = Game::with_seed(13);
+        assert!(legend(&game).is_empty());
+        assert!(!snapshot(&game, &Config::default(), 100, 30).contains("Rules"));
+
+        game.grid = Grid::with_constraints(vec![
+            Box::new(Diagonal),
+            Box::new(KillerCage { cells: vec![(0, 0), (0, 1)], sum: 3 }),
+            Box::new(KillerCage { cells: vec![(5, 4), (5, 5)], sum: 11 }),
+        ]);
+        assert_eq!(legend(&game).iter().map(|&(name, _)| name).collect::<Vec<_>>(), ["Diagonal", "Cage"]);
+        let text = snapshot(&game, &Config::default(), 100, 30);
+        assert!(text.contains("Rules") && text.contains("Diagonal") && text.contains("Cage"));
+
+        // The cage under the cursor shows how it's adding up
+        game.cursor = (0, 1);
+        let text = snapshot(&game, &Config::default(), 100, 30);
+        assert!(text.contains("Cage 3: 0 so far") && text.contains("2 cells left: 1+2"));
+
+        // A speedrun's splits stack above the rules
+        game.start_speedrun(Speedrun::default());
+        let text = snapshot(&game, &Config::default(), 100, 30);
+        assert!(text.contains("Splits") && text.contains("Rules") && text.contains("Cage"));
+    }
+
+    #[test]
+    fn sandwich_sums_line_up_outside_the_grid() {
+        let spec = PuzzleSpec { variant: Variant::Sandwich, ..PuzzleSpec::default() };
+        let mut game = Game::generate(42, &spec).unwrap();
+        game.timer = Timer::stopped_at(Duration::ZERO);
+        let sums = Sandwich::of(&game.solution.unwrap());
+        let clues = |sums: &[Option<u32>]| sums.iter().map(|sum| sum.unwrap().to_string()).collect::<Vec<_>>();
+        for (width, height) in [(100, 30), (80, 24)] {
+            let text = snapshot(&game, &Config::default(), width, height);
+            let lines: Vec<&str> = text.lines().collect();
+            let top = lines.iter().position(|line| line.split_whitespace().take(6).collect::<Vec<_>>() == clues(&sums.cols)).unwrap();
+            // Each row's sum starts the line the row is drawn on
+            let firsts: Vec<&str> = lines[top + 1..].iter().filter_map(|line| line.split_whitespace().next()).filter(|first| first.parse::<u32>().is_ok()).take(6).collect();
+            assert_eq!(firsts, clues(&sums.rows), "{}x{}", width, height);
+        }
+    }
+
+    #[test]
+    fn thermometers_are_drawn_across_the_gaps() {
+        let mut game = Game::unsolved(Grid::with_constraints(vec![Box::new(Thermometer { cells: vec![(0, 0), (0, 1), (1, 1)] })]));
+        game.cursor = (5, 5);
+        let text = snapshot(&game, &Config::default(), 100, 30);
+        assert_eq!((text.matches('━').count(), text.matches('┃').count()), (1, 1));
+        // The tube lightens the region colors under it, keeping their hue
+        let mut plain = Game::unsolved(Grid::new());
+        plain.cursor = game.cursor;
+        let lifted = |at: (usize, usize), lift: u8| match cell_appearance(&plain, at.0, at.1).1.bg {
+            Some(Color::Rgb(red, green, blue)) => Some(Color::Rgb(red + lift, green + lift, blue + lift)),
+            color => color,
+        };
+        assert_eq!(cell_appearance(&game, 0, 0).1.bg, lifted((0, 0), 70));
+        assert_eq!(cell_appearance(&game, 1, 1).1.bg, lifted((1, 1), 40));
+        assert_ne!(cell_appearance(&game, 0, 0).1.bg, cell_appearance(&game, 1, 1).1.bg);
+    }
+
+    #[test]
+    fn subtle_regions_shade_faintly_and_dim_the_lines_inside() {
+        let mut game = Game::with_seed(13);
+        game.cursor = (5, 5);
+        let dimmed = |game: &Game| {
+            let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+            terminal
+                .draw(|f| {
+                    draw(f, game, &Config::default(), &FrameStats::default());
+                })
+                .unwrap();
+            terminal.backend().buffer().content().iter().filter(|cell| cell.bg == Color::Rgb(45, 45, 55)).count()
+        };
+        assert_eq!(cell_appearance(&game, 0, 0).1.bg, Some(Color::Rgb(30, 30, 80)));
+        assert_eq!(dimmed(&game), 0);
+
+        game.subtle_regions = true;
+        assert_eq!(cell_appearance(&game, 0, 0).1.bg, Some(Color::Rgb(24, 24, 40)));
+        assert!(dimmed(&game) > 0);
+    }
+
+    #[test]
+    fn about_screen_scrolls_through_the_changelog() {
+        let mut game = Game::with_seed(13);
+        game.apply(Command::ToggleAbout);
+        let text = snapshot(&game, &Config::default(), 100, 30);
+        assert!(text.contains(&format!("RustDoku6 {}", env!("CARGO_PKG_VERSION"))));
+        assert!(!text.contains("Pencil marks, undo"));
+
+        // Scrolling stops with the last line at the bottom of the popup
+        game.about_rows = about_rows(30);
+        for _ in 0..100 {
+            game.apply(Command::MoveCursor(1, 0));
+        }
+        let last = crate::about::lines().len() - about_rows(30);
+        assert_eq!(game.about_scroll, last);
+        let text = snapshot(&game, &Config::default(), 100, 30);
+        assert!(text.contains("Pencil marks, undo") && !text.contains("Author:"));
+        game.apply(Command::MoveCursor(-1, 0));
+        assert_eq!(game.about_scroll, last - 1);
+
+        game.apply(Command::Dismiss);
+        game.apply(Command::ToggleAbout);
+        assert_eq!(game.about_scroll, 0);
+    }
+
+    #[test]
+    fn stats_screens_show_records_and_the_session() {
+        let mut game = Game::with_seed(13);
+        game.apply(Command::ToggleStats);
+        assert!(snapshot(&game, &Config::default(), 100, 30).contains("No games recorded yet"));
+
+        let mut stats = crate::stats::Stats::default();
+        let category = |variant: &str, stars| crate::stats::Category { size: 6, variant: variant.to_string(), stars };
+        stats.records.entry(category("classic", 2)).or_default().add(true, Duration::from_secs(135));
+        stats.records.entry(category("imported", 3)).or_default().add(false, Duration::from_secs(20));
+        game.stats = Some(stats);
+        let text = snapshot(&game, &Config::default(), 100, 30);
+        assert!(text.contains("6x6 classic") && text.contains("6x6 imported") && text.contains("02:15") && text.contains("Corrections"));
+
+        game.apply(Command::MoveCursor(0, 1));
+        let text = snapshot(&game, &Config::default(), 100, 30);
+        assert!(text.contains("★★★☆") && !text.contains("02:15"));
+        game.apply(Command::MoveCursor(0, 1));
+        assert_eq!(game.stats_tab, 0);
+        game.apply(Command::Back);
+        assert_eq!(game.screen(), crate::screen::Screen::Playing);
+
+        let stats = game.stats.as_mut().unwrap();
+        stats.session.add(true, Duration::from_secs(135));
+        stats.session.add(false, Duration::from_secs(40));
+        game.screens.push(crate::screen::Screen::Summary);
+        let text = snapshot(&game, &Config::default(), 100, 30);
+        assert!(text.contains("Puzzles attempted: 2") && text.contains("Total time: 02:55") && text.contains("Best solve: 02:15"));
+        assert!(!game.apply(Command::Dismiss));
+    }
+
+    #[test]
+    fn key_hints_follow_the_state() {
+        let mut game = Game::with_seed(13);
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !game.grid.cells[r][c].is_fixed()).unwrap();
+        game.apply(Command::SelectCell(r, c));
+        let text = status_bar(&game).text(300);
+        assert!(text.contains("1-6: Enter | Shift+1-6: Mark") && !text.contains("l: Lock"));
+
+        game.apply(Command::EnterDigit(game.solution.unwrap()[r][c]));
+        game.apply(Command::ToggleMode);
+        let text = status_bar(&game).text(300);
+        assert!(text.contains("Mode: PENCIL (p)") && text.contains("1-6: Mark | Shift+1-6: Enter | l: Lock | BS: Clear"));
+
+        let mut stats = crate::stats::Stats::default();
+        stats.records.entry(crate::stats::Category::of(&game)).or_default().add(true, Duration::from_secs(100));
+        game.stats = Some(stats);
+        assert!(status_bar(&game).text(300).contains("Typical: 01:40, pace +"));
+
+        game.apply(Command::GoTo);
+        game.apply(Command::EnterDigit(3));
+        assert_eq!(status_bar(&game).text(300), "Go to: g C _ | 1-6: Column | Esc: Cancel");
+        game.apply(Command::Back);
+
+        game.apply(Command::Hypothesis);
+        assert!(status_bar(&game).text(300).starts_with("WHAT-IF | Mode: PENCIL (p) | Time: "));
+        assert!(status_bar(&game).text(300).contains("b: Keep | x: Discard"));
+
+        game.apply(Command::ToggleAbout);
+        assert_eq!(status_bar(&game).text(300), "Up/Down: Scroll | Esc: Back");
+        game.apply(Command::Back);
+        game.apply(Command::Hypothesis);
+
+        // The weekly challenge offers no assists
+        assert!(status_bar(&game).text(300).contains("h: Hint"));
+        game.weekly = Some("2026-10-12".to_string());
+        let text = status_bar(&game).text(300);
+        assert!(!text.contains("h: Hint") && !text.contains(": Watch") && !text.contains(": Counts"));
+        game.weekly = None;
+
+        let viewer = crate::viewer::Viewer::parse(&format!("{}\n{}", game.grid.share_code(), game.grid.share_code())).unwrap();
+        assert!(status_bar(viewer.game()).text(300).ends_with("1-6: Highlight | ]: Next | [: Previous | q: Quit"));
+    }
+
+    #[test]
+    fn notices_name_the_keys_from_the_keymap() {
+        let mut game = Game::with_seed(13);
+        let solution = game.solution.unwrap();
+        let mut seen = (false, false);
+        let open: Vec<_> = (0..36).map(|i| (i / 6, i % 6)).filter(|&(r, c)| !game.grid.cells[r][c].is_fixed()).collect();
+        for (r, c) in open {
+            for wrong in (1..=6).filter(|&v| v != solution[r][c]) {
+                game.apply(Command::SelectCell(r, c));
+                game.apply(Command::EnterDigit(wrong));
+                let text = status_bar(&game).text(300);
+                if game.dead_end().is_some() {
+                    assert!(text.contains("Dead end: an entry must be wrong (h: find it)"));
+                    assert_eq!(compact_status_text(&game), "Dead end: an entry must be wrong (h: find it)");
+                    seen.0 = true;
+                } else {
+                    assert!(text.contains("Wrong entry, e: Why?"));
+                    seen.1 = true;
+                }
+                game.apply(Command::ClearCell);
+            }
+        }
+        assert_eq!(seen, (true, true));
+
+        game.apply(Command::Hypothesis);
+        assert!(compact_status_text(&game).starts_with("what-if ") && compact_status_text(&game).ends_with(" | b: keep x: discard"));
+        game.apply(Command::DiscardHypothesis);
+
+        game.screens.push(crate::screen::Screen::Won);
+        assert!(compact_status_text(&game).ends_with(" | 1-6 m n q"));
+    }
+
+    #[test]
+    fn compact_cell_text_fits_three_columns() {
+        assert_eq!(compact_cell_text("5"), " 5 ");
+        assert_eq!(compact_cell_text("1  4  "), "14 ");
+        assert_eq!(compact_cell_text("12 45 "), "12+");
+        assert_eq!(compact_cell_text(""), "   ");
+    }
+
+    #[test]
+    fn cell_at_maps_pointer_to_cells() {
+        // Regular board with 4x2 cells at (10, 5)
+        let board = Rect::new(10, 5, 31, 19);
+        assert_eq!(cell_at(board, 11, 6), Some((0, 0)));
+        assert_eq!(cell_at(board, 14, 7), Some((0, 0)));
+        assert_eq!(cell_at(board, 15, 6), None); // gap
+        assert_eq!(cell_at(board, 16, 9), Some((1, 1)));
+        assert_eq!(cell_at(board, 39, 22), Some((5, 5)));
+        assert_eq!(cell_at(board, 40, 22), None); // frame
+        assert_eq!(cell_at(board, 5, 5), None);
+
+        // Compact board
+        let board = Rect::new(0, 0, 21, 10);
+        assert_eq!(cell_at(board, 1, 1), Some((0, 0)));
+        assert_eq!(cell_at(board, 10, 1), None);
+        assert_eq!(cell_at(board, 11, 4), Some((2, 3)));
+        assert_eq!(cell_at(board, 19, 8), Some((5, 5)));
+        assert_eq!(cell_at(board, 5, 3), None); // band separator
+    }
 }
@@ -0,0 +1,268 @@
+// Undo tree: every board the solve has been through, so undoing an entry and
+// making a different one starts a new branch instead of throwing the old one
+// away. Node 0 is the starting board; each entry adds a child of the current
+// node. Nodes keep only the cells their entry changed, and the tree one
+// board, the current node's: moving to another node undoes the changes on the
+// way up to where the branches meet and makes those on the way down, so a
+// long solve doesn't hold a board per keystroke.
+
+use crate::{
+    i18n::{t, tf},
+    model::{Cell, Grid},
+};
+
+// The cells of a board, all a node's board differs from another's by
+pub type Cells = [[Cell; 6]; 6];
+
+// A cell an entry changed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Change {
+    pub row: usize,
+    pub col: usize,
+    pub before: Cell,
+    pub after: Cell,
+}
+
+pub struct Node {
+    // What the entry changed from the parent's board; nothing for the start
+    pub changes: Vec<Change>,
+    // Where the entry leading to this board was made
+    pub cursor: (usize, usize),
+    pub parent: Option<usize>,
+    // Oldest first
+    pub children: Vec<usize>,
+    // The child last visited, followed when stepping forward
+    pub last_child: Option<usize>,
+    // What the entry changed, e.g. "r2c3=5"
+    pub label: String,
+}
+
+pub struct UndoTree {
+    pub nodes: Vec<Node>,
+    pub current: usize,
+    // The current node's board
+    cells: Cells,
+}
+
+impl UndoTree {
+    pub fn new(grid: &Grid) -> Self {
+        let root = Node { changes: Vec::new(), cursor: (0, 0), parent: None, children: Vec::new(), last_child: None, label: t("history.start").to_string() };
+        Self { nodes: vec![root], current: 0, cells: grid.cells }
+    }
+
+    // The current node's board
+    pub fn cells(&self) -> &Cells {
+        &self.cells
+    }
+
+    // The board at node `id`, worked out from the current one
+    pub fn cells_at(&self, id: usize) -> Cells {
+        let mut cells = self.cells;
+        self.walk(&mut cells, self.current, id);
+        cells
+    }
+
+    pub fn node(&self, id: usize) -> &Node {
+        &self.nodes[id]
+    }
+
+    // Add the board after an entry as a new child of the current node. Entries
+    // that changed nothing aren't kept.
+    pub fn record(&mut self, grid: &Grid, cursor: (usize, usize)) {
+        let changes: Vec<Change> = (0..36)
+            .map(|i| (i / 6, i % 6))
+            .filter(|&(r, c)| self.cells[r][c] != grid.cells[r][c])
+            .map(|(row, col)| Change { row, col, before: self.cells[row][col], after: grid.cells[row][col] })
+            .collect();
+        if changes.is_empty() {
+            return;
+        }
+        let label = describe(&changes, cursor);
+        let id = self.nodes.len();
+        self.nodes.push(Node { changes, cursor, parent: Some(self.current), children: Vec::new(), last_child: None, label });
+        let parent = &mut self.nodes[self.current];
+        parent.children.push(id);
+        parent.last_child = Some(id);
+        self.current = id;
+        self.cells = grid.cells;
+    }
+
+    // Step back to the parent, returning its board and where the undone entry was made
+    pub fn undo(&mut self) -> Option<(&Cells, (usize, usize))> {
+        let node = &self.nodes[self.current];
+        let parent = node.parent?;
+        for change in &node.changes {
+            self.cells[change.row][change.col] = change.before;
+        }
+        let cursor = node.cursor;
+        self.current = parent;
+        Some((&self.cells, cursor))
+    }
+
+    // Make `id` the current node, so later entries branch from it
+    pub fn jump(&mut self, id: usize) {
+        let mut cells = self.cells;
+        self.walk(&mut cells, self.current, id);
+        self.cells = cells;
+        self.current = id;
+        let mut child = id;
+        while let Some(parent) = self.nodes[child].parent {
+            self.nodes[parent].last_child = Some(child);
+            child = parent;
+        }
+    }
+
+    // Turn `cells` from node `from`'s board into node `to`'s: up from `from`
+    // undoing entries until reaching a node `to` descends from, then down
+    // making the entries on the way to `to`
+    fn walk(&self, cells: &mut Cells, from: usize, to: usize) {
+        // `to` and the nodes above it, up to the start
+        let mut down = vec![to];
+        let mut node = to;
+        while let Some(parent) = self.nodes[node].parent {
+            down.push(parent);
+            node = parent;
+        }
+        let mut node = from;
+        while !down.contains(&node) {
+            for change in &self.nodes[node].changes {
+                cells[change.row][change.col] = change.before;
+            }
+            node = self.nodes[node].parent.unwrap_or(0);
+        }
+        let meet = down.iter().position(|&id| id == node).unwrap_or(down.len());
+        for &id in down[..meet].iter().rev() {
+            for change in &self.nodes[id].changes {
+                cells[change.row][change.col] = change.after;
+            }
+        }
+    }
+
+    // Entries on the way from the start to `id`
+    pub fn depth(&self, id: usize) -> usize {
+        let mut depth = 0;
+        let mut node = id;
+        while let Some(parent) = self.nodes[node].parent {
+            depth += 1;
+            node = parent;
+        }
+        depth
+    }
+
+    // The sibling `delta` places over from `id`, staying put at either end
+    pub fn sibling(&self, id: usize, delta: i8) -> usize {
+        let Some(parent) = self.nodes[id].parent else {
+            return id;
+        };
+        let siblings = &self.nodes[parent].children;
+        let i = siblings.iter().position(|&s| s == id).unwrap_or(0) as isize + delta as isize;
+        siblings[i.clamp(0, siblings.len() as isize - 1) as usize]
+    }
+
+    // How many times each cell was given a value for which `counts` holds,
+    // across every branch
+    pub fn entry_counts(&self, counts: impl Fn(usize, usize, u8) -> bool) -> [[u32; 6]; 6] {
+        let mut totals = [[0; 6]; 6];
+        for change in self.nodes.iter().flat_map(|node| &node.changes) {
+            match change.after.value() {
+                Some(v) if change.before.value() != Some(v) && counts(change.row, change.col, v) => totals[change.row][change.col] += 1,
+                _ => {}
+            }
+        }
+        totals
+    }
+
+    // One line per node, depth first, indented where branches split:
+    //   start
+    //   r1c1=2
+    //   ├ r1c2=3
+    //   │ r1c3=4
+    //   └ r1c2=5
+    pub fn lines(&self) -> Vec<(usize, String)> {
+        let mut lines = Vec::with_capacity(self.nodes.len());
+        // (node, prefix of its own line, prefix of the lines below it)
+        let mut stack = vec![(0, String::new(), String::new())];
+        while let Some((id, first, rest)) = stack.pop() {
+            let node = &self.nodes[id];
+            lines.push((id, format!("{}{}", first, node.label)));
+            match node.children[..] {
+                [] => {}
+                [only] => stack.push((only, rest.clone(), rest)),
+                ref children => {
+                    for (i, &child) in children.iter().enumerate().rev() {
+                        if i + 1 == children.len() {
+                            stack.push((child, format!("{}└ ", rest), format!("{}  ", rest)));
+                        } else {
+                            stack.push((child, format!("{}├ ", rest), format!("{}│ ", rest)));
+                        }
+                    }
+                }
+            }
+        }
+        lines
+    }
+}
+
+// What an entry changed, as "r2c3=5", "r2c3 marks", "r2c3 cleared" or
+// "r2c3 locked".
+// That's the cell at `cursor`, or the first cell changed if it didn't change.
+fn describe(changes: &[Change], cursor: (usize, usize)) -> String {
+    let Some(change) = changes.iter().find(|change| (change.row, change.col) == cursor).or(changes.first()) else {
+        return String::new();
+    };
+    let (r, c, old, new) = (change.row, change.col, change.before, change.after);
+    let (row, col) = (r + 1, c + 1);
+    match new.value() {
+        Some(v) if new.value() != old.value() => format!("r{}c{}={}", row, col, v),
+        None if old.value().is_some() => tf("history.cleared", &[&row, &col]),
+        _ if new.locked() != old.locked() => tf(if new.locked() { "history.locked" } else { "history.unlocked" }, &[&row, &col]),
+        _ => tf("history.marks", &[&row, &col]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UndoTree;
+    use crate::model::Grid;
+
+    fn with(grid: &Grid, r: usize, c: usize, v: u8) -> Grid {
+        let mut grid = grid.clone();
+        grid.cells[r][c].set_value(Some(v));
+        grid
+    }
+
+    #[test]
+    fn undone_branches_survive_new_entries() {
+        let start = Grid::new();
+        let mut tree = UndoTree::new(&start);
+        let a = with(&start, 0, 0, 2);
+        tree.record(&a, (0, 0));
+        tree.record(&with(&a, 0, 1, 3), (0, 1));
+        tree.record(&with(&a, 0, 1, 3), (0, 1)); // no change, no node
+
+        let (cells, cursor) = tree.undo().unwrap();
+        assert_eq!((cells[0][1].value(), cursor), (None, (0, 1)));
+        tree.record(&with(&a, 0, 1, 5), (0, 1));
+
+        assert_eq!(tree.nodes.len(), 4);
+        assert_eq!(tree.nodes[1].children, vec![2, 3]);
+        assert_eq!((tree.depth(3), tree.sibling(3, -1), tree.sibling(3, 1)), (2, 2, 3));
+        let lines: Vec<String> = tree.lines().into_iter().map(|(_, line)| line).collect();
+        assert_eq!(lines, vec!["start", "r1c1=2", "├ r1c2=3", "└ r1c2=5"]);
+
+        let fives = tree.entry_counts(|_, _, v| v != 3);
+        assert_eq!((fives[0][0], fives[0][1]), (1, 1));
+
+        // Nodes keep only their entry; boards on other branches are worked out
+        assert_eq!(tree.nodes[3].changes.len(), 1);
+        assert_eq!((tree.cells_at(2)[0][1].value(), tree.cells_at(0)[0][0].value()), (Some(3), None));
+        assert_eq!(tree.cells_at(3), with(&a, 0, 1, 5).cells);
+
+        tree.jump(2);
+        assert_eq!((tree.current, tree.nodes[1].last_child), (2, Some(2)));
+        assert_eq!(tree.cells(), &with(&a, 0, 1, 3).cells);
+        tree.undo();
+        tree.undo();
+        assert!(tree.undo().is_none());
+    }
+}
@@ -0,0 +1,193 @@
+// Read-only viewing of the puzzles in a file (`rustdoku6 view <file>`), e.g.
+// to look over a pack before playing it. The cursor moves and digits can be
+// picked out as on a finished board, but nothing can be entered.
+//
+// A file holds one or more puzzles, separated by blank lines: share codes,
+// one per line, or boards in `Grid` text, whose entries show as well, so a
+// solve saved as text looks as it was left. Lines of `replays.jsonl` show
+// each saved solve as it ended. Lines starting with `#` are comments.
+
+use std::{error::Error, fmt, time::Duration};
+
+use crate::{
+    command::Command,
+    generator::ImportError,
+    model::{Game, Grid, ParseError, Timer},
+    puzzle::PuzzleMeta,
+    replay::Solve,
+    screen::{Screen, ScreenStack},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ViewError {
+    // No puzzle in the file
+    Empty,
+    // Puzzles count from 0, in file order
+    Board { puzzle: usize, error: ParseError },
+    Puzzle { puzzle: usize, error: ImportError },
+    // A line that isn't a saved solve, or whose puzzle can't be generated again
+    Replay { puzzle: usize },
+}
+
+impl fmt::Display for ViewError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ViewError::Empty => write!(f, "no puzzles in the file"),
+            ViewError::Board { puzzle, error } => write!(f, "puzzle {}: {}", puzzle + 1, error),
+            ViewError::Puzzle { puzzle, error } => write!(f, "puzzle {}: {}", puzzle + 1, error),
+            ViewError::Replay { puzzle } => write!(f, "puzzle {}: not a saved solve of a puzzle the game can make again", puzzle + 1),
+        }
+    }
+}
+
+impl Error for ViewError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ViewError::Empty | ViewError::Replay { .. } => None,
+            ViewError::Board { error, .. } => Some(error),
+            ViewError::Puzzle { error, .. } => Some(error),
+        }
+    }
+}
+
+pub struct Viewer {
+    pub games: Vec<Game>,
+    // The puzzle shown
+    pub index: usize,
+}
+
+impl Viewer {
+    pub fn parse(text: &str) -> Result<Self, ViewError> {
+        let lines: Vec<&str> = text.lines().filter(|line| !line.trim_start().starts_with('#')).collect();
+        let mut games = Vec::new();
+        for block in lines.split(|line| line.trim().is_empty()).filter(|block| !block.is_empty()) {
+            if block.iter().all(|line| line.trim().starts_with("RD6:")) {
+                for line in block {
+                    let puzzle = games.len();
+                    let grid = Grid::from_share_code(line.trim()).ok_or(ViewError::Puzzle { puzzle, error: ImportError::ShareCode })?;
+                    games.push(viewing(&grid).map_err(|error| ViewError::Puzzle { puzzle, error })?);
+                }
+            } else if block.iter().all(|line| line.trim().starts_with('{')) {
+                for line in block {
+                    let puzzle = games.len();
+                    games.push(Solve::parse(line.trim()).and_then(|solve| solved(&solve)).ok_or(ViewError::Replay { puzzle })?);
+                }
+            } else {
+                let puzzle = games.len();
+                let grid = Grid::parse(&block.join("\n")).map_err(|error| ViewError::Board { puzzle, error })?;
+                games.push(viewing(&grid).map_err(|error| ViewError::Puzzle { puzzle, error })?);
+            }
+        }
+        if games.is_empty() {
+            return Err(ViewError::Empty);
+        }
+        let count = games.len();
+        for (i, game) in games.iter_mut().enumerate() {
+            game.viewing = Some((i, count));
+        }
+        Ok(Self { games, index: 0 })
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.games[self.index]
+    }
+
+    // Carry out a command on the puzzle shown, where it doesn't change the
+    // board. Returns false when the command leaves the viewer.
+    pub fn apply(&mut self, command: Command) -> bool {
+        let count = self.games.len();
+        let game = &mut self.games[self.index];
+        match command {
+            Command::Quit | Command::Back => return false,
+            Command::MoveCursor(dr, dc) => game.move_cursor(dr, dc),
+            Command::SelectCell(r, c) => game.cursor = (r.min(5), c.min(5)),
+            Command::EnterDigit(num) => game.highlight = if game.highlight == Some(num) { None } else { Some(num) },
+            Command::NextPuzzle => self.index = (self.index + 1) % count,
+            Command::PreviousPuzzle => self.index = (self.index + count - 1) % count,
+            // The labels stay on or off from one puzzle to the next
+            Command::ToggleCoordinates => {
+                let shown = !game.show_coordinates;
+                self.games.iter_mut().for_each(|game| game.show_coordinates = shown);
+            }
+            _ => {}
+        }
+        self.games[self.index].mark_dirty();
+        true
+    }
+}
+
+// A game showing `grid` as it is, entries included. It isn't solved, so a
+// big pack opens at once; entries are checked against the rules.
+fn viewing(grid: &Grid) -> Result<Game, ImportError> {
+    grid.invariants().map_err(ImportError::Invalid)?;
+    let mut game = Game::unsolved(grid.clone());
+    game.screens = ScreenStack::new(Screen::Viewing);
+    game.timer = Timer::stopped_at(Duration::ZERO);
+    Ok(game)
+}
+
+// A saved solve, its puzzle generated again from the key and the moves
+// played over it, with the time and mistakes it ended on
+fn solved(solve: &Solve) -> Option<Game> {
+    let mut game = PuzzleMeta::from_key(&solve.puzzle)?.regenerate()?;
+    for step in &solve.replay.moves {
+        let cell = &mut game.grid.cells[step.row][step.col];
+        cell.set_value(step.value);
+        cell.clear_marks();
+    }
+    game.screens = ScreenStack::new(Screen::Viewing);
+    game.timer = Timer::stopped_at(solve.time);
+    game.mistakes = solve.mistakes;
+    Some(game)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ViewError, Viewer};
+    use crate::{command::Command, generator::ImportError, model::Game, replay::Replay};
+    use std::time::Duration;
+
+    #[test]
+    fn packs_are_browsed_without_changing_them() {
+        let first = Game::with_seed(3);
+        let mut second = Game::with_seed(4);
+        let (r, c) = (0..36).map(|i| (i / 6, i % 6)).find(|&(r, c)| !second.grid.cells[r][c].is_fixed()).unwrap();
+        second.grid.cells[r][c].set_value(Some(second.solution.unwrap()[r][c]));
+        let text = format!("# A pack\n{}\n\n{}", first.grid.share_code(), second.grid);
+
+        let mut viewer = Viewer::parse(&text).unwrap();
+        assert_eq!(viewer.games.len(), 2);
+        assert_eq!(viewer.games[1].viewing, Some((1, 2)));
+        assert_eq!(viewer.game().grid.cells, first.grid.cells);
+        viewer.apply(Command::EnterDigit(2));
+        viewer.apply(Command::ClearCell);
+        viewer.apply(Command::Hint);
+        assert_eq!(viewer.game().grid.cells, first.grid.cells);
+        assert_eq!(viewer.game().highlight, Some(2));
+
+        viewer.apply(Command::PreviousPuzzle);
+        assert_eq!(viewer.game().grid.cells, second.grid.cells);
+        viewer.apply(Command::NextPuzzle);
+        assert_eq!(viewer.index, 0);
+        assert!(!viewer.apply(Command::Quit));
+
+        assert_eq!(Viewer::parse("# nothing\n\n").err(), Some(ViewError::Empty));
+        assert_eq!(Viewer::parse("RD6:12").err(), Some(ViewError::Puzzle { puzzle: 0, error: ImportError::ShareCode }));
+        assert!(matches!(Viewer::parse(&format!("{}\n\n......", first.grid.share_code())), Err(ViewError::Board { puzzle: 1, .. })));
+    }
+
+    #[test]
+    fn saved_solves_show_as_they_ended() {
+        let game = Game::with_seed(3);
+        let mut replay = Replay::default();
+        for (r, c) in (0..36).map(|i| (i / 6, i % 6)).filter(|&(r, c)| !game.grid.cells[r][c].is_fixed()) {
+            replay.record(Duration::from_secs(1), (r, c), Some(game.solution.unwrap()[r][c]), true);
+        }
+        let line = replay.to_json(&game.meta.key(), Duration::from_secs(80), 2);
+
+        let viewer = Viewer::parse(&format!("# replays.jsonl\n{}\n", line)).unwrap();
+        assert!(viewer.game().is_won());
+        assert_eq!((viewer.game().timer.elapsed(), viewer.game().mistakes), (Duration::from_secs(80), 2));
+        assert_eq!(Viewer::parse("{\"puzzle\":\"7\"}").err(), Some(ViewError::Replay { puzzle: 0 }));
+    }
+}
@@ -0,0 +1,48 @@
+// Puzzle of the week (`--weekly`): everyone playing in the same week gets
+// the same hard puzzle, found from a seed derived from the week. There's one
+// attempt per week, counted as soon as it starts, with no hints or other
+// assists, and the game is lost at MAX_MISTAKES. Each week's result goes on
+// a ladder with the stats.
+
+use crate::model::{Game, MAX_STARS};
+
+pub const MAX_MISTAKES: u32 = 3;
+// Expert: beyond what the built-in techniques solve
+const STARS: u8 = MAX_STARS;
+// Seeds from the week's on tried for a puzzle that hard
+const MAX_TRIES: u64 = 200;
+
+// Stable seed for a week, named by the date of its Monday (FNV-1a)
+pub fn seed(week: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in "weekly ".bytes().chain(week.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash % (u32::MAX as u64 + 1)
+}
+
+// The week's puzzle: the first seed from the week's on with the top grade,
+// or an error when none of MAX_TRIES is that hard
+pub fn puzzle(week: &str) -> Result<Game, String> {
+    let start = seed(week);
+    let found = (start..start + MAX_TRIES).map(Game::with_seed).find(|game| game.stars() == STARS);
+    let mut game = found.ok_or_else(|| format!("no expert puzzle turned up for the week of {} in {} seeds", week, MAX_TRIES))?;
+    game.weekly = Some(week.to_string());
+    Ok(game)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{puzzle, seed, STARS};
+
+    #[test]
+    fn each_week_has_one_hard_puzzle() {
+        assert_eq!(seed("2026-10-12"), seed("2026-10-12"));
+        assert_ne!(seed("2026-10-12"), seed("2026-10-19"));
+        let game = puzzle("2026-10-12").unwrap();
+        assert_eq!(game.stars(), STARS);
+        assert_eq!(game.weekly.as_deref(), Some("2026-10-12"));
+        assert_eq!(puzzle("2026-10-12").unwrap().grid.to_string(), game.grid.to_string());
+    }
+}